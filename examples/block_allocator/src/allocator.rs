@@ -0,0 +1,173 @@
+//! Allocator implementation.
+
+use std::collections::{BTreeMap, BTreeSet};
+use std::fs::{self, File, OpenOptions};
+use std::io::{self, BufRead, BufReader, Write};
+use std::path::{Path, PathBuf};
+
+use first::crash_point;
+
+/// Block number within the pool.
+pub type BlockId = u32;
+
+/// How [`Allocator::open()`] rebuilds the free list after a restart.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Recovery {
+    /// Derive the free list from the allocation log: every block without
+    /// a live owner is free. Always consistent.
+    RebuildFromLog,
+    /// Load the last flushed free list as is.
+    ///
+    /// BUG: the flushed list is only a snapshot. Blocks allocated since
+    /// the flush come back as free (double allocation) and blocks freed
+    /// since the flush are lost (leak).
+    TrustFreeList,
+}
+
+/// Allocator over a fixed pool of blocks.
+///
+/// On disk:
+/// - `alloc.log`: one `ALLOC <block> <owner>` or `FREE <block>` line per
+///   operation, synced before the operation returns. This is the source
+///   of truth for ownership.
+/// - `freelist`: free block numbers, one per line, replaced atomically by
+///   [`flush_free_list()`](Self::flush_free_list).
+pub struct Allocator {
+    /// Directory holding the allocator files.
+    dir: PathBuf,
+    /// Handle to the allocation log.
+    log: File,
+    /// Number of blocks in the pool.
+    capacity: BlockId,
+    /// Free blocks, lowest first.
+    free: BTreeSet<BlockId>,
+    /// Owner of each allocated block.
+    owners: BTreeMap<BlockId, String>,
+}
+
+impl Allocator {
+    /// Open or create an allocator over `capacity` blocks in `dir`.
+    pub fn open(dir: &Path, capacity: BlockId, recovery: Recovery) -> io::Result<Self> {
+        fs::create_dir_all(dir)?;
+        let owners = Self::replay_log(&dir.join("alloc.log"))?;
+
+        let free = match recovery {
+            Recovery::RebuildFromLog => (0..capacity)
+                .filter(|block| !owners.contains_key(block))
+                .collect(),
+            Recovery::TrustFreeList => match fs::read_to_string(dir.join("freelist")) {
+                Ok(text) => text
+                    .lines()
+                    .map(|l| l.parse().expect("invalid block in free list"))
+                    .collect(),
+                Err(e) if e.kind() == io::ErrorKind::NotFound => (0..capacity).collect(),
+                Err(e) => return Err(e),
+            },
+        };
+
+        let log = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(dir.join("alloc.log"))?;
+
+        Ok(Self {
+            dir: dir.to_path_buf(),
+            log,
+            capacity,
+            free,
+            owners,
+        })
+    }
+
+    /// Replay the allocation log into the owner of each live block.
+    ///
+    /// A torn last line is ignored: its operation never returned.
+    fn replay_log(path: &Path) -> io::Result<BTreeMap<BlockId, String>> {
+        let mut owners = BTreeMap::new();
+        let file = match File::open(path) {
+            Ok(file) => file,
+            Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(owners),
+            Err(e) => return Err(e),
+        };
+        for line in BufReader::new(file).lines() {
+            let line = line?;
+            let parts: Vec<&str> = line.splitn(3, ' ').collect();
+            match parts.as_slice() {
+                ["ALLOC", block, owner] => {
+                    if let Ok(block) = block.parse() {
+                        owners.insert(block, owner.to_string());
+                    }
+                }
+                ["FREE", block] => {
+                    if let Ok(block) = block.parse::<BlockId>() {
+                        owners.remove(&block);
+                    }
+                }
+                _ => {}
+            }
+        }
+        Ok(owners)
+    }
+
+    /// Allocate the lowest free block to `owner`.
+    ///
+    /// Returns `None` if the pool is exhausted.
+    pub fn allocate(&mut self, owner: &str) -> Option<BlockId> {
+        let block = self.free.pop_first()?;
+        writeln!(self.log, "ALLOC {} {}", block, owner).expect("failed to write ALLOC");
+        crash_point("after_alloc_write");
+        self.log.sync_all().expect("failed to fsync after ALLOC");
+        crash_point("after_alloc_fsync");
+        self.owners.insert(block, owner.to_string());
+        Some(block)
+    }
+
+    /// Return `block` to the free list.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `block` is not allocated.
+    pub fn deallocate(&mut self, block: BlockId) {
+        assert!(
+            self.owners.contains_key(&block),
+            "block {} is not allocated",
+            block
+        );
+        writeln!(self.log, "FREE {}", block).expect("failed to write FREE");
+        crash_point("after_free_write");
+        self.log.sync_all().expect("failed to fsync after FREE");
+        crash_point("after_free_fsync");
+        self.owners.remove(&block);
+        self.free.insert(block);
+    }
+
+    /// Persist the free list, replacing the previous one atomically.
+    pub fn flush_free_list(&mut self) -> io::Result<()> {
+        let tmp = self.dir.join("freelist.tmp");
+        let mut file = File::create(&tmp)?;
+        for block in &self.free {
+            writeln!(file, "{}", block)?;
+        }
+        file.sync_all()?;
+        crash_point("after_freelist_write");
+        fs::rename(&tmp, self.dir.join("freelist"))?;
+        File::open(&self.dir)?.sync_all()?;
+        crash_point("after_freelist_flush");
+        Ok(())
+    }
+
+    /// Number of blocks in the pool.
+    pub fn capacity(&self) -> BlockId {
+        self.capacity
+    }
+
+    /// Free blocks, lowest first.
+    pub fn free_blocks(&self) -> impl Iterator<Item = BlockId> + '_ {
+        self.free.iter().copied()
+    }
+
+    /// Owner of `block`, if it is allocated.
+    pub fn owner(&self, block: BlockId) -> Option<&str> {
+        self.owners.get(&block).map(|s| s.as_str())
+    }
+}
@@ -0,0 +1,5 @@
+//! Block allocator: fixed pool of blocks with an on-disk free list.
+
+mod allocator;
+
+pub use allocator::{Allocator, BlockId, Recovery};
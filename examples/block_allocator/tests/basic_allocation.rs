@@ -0,0 +1,34 @@
+//! Basic allocation tests for the block allocator.
+//!
+//! These tests verify clean-restart semantics.
+
+use block_allocator::{Allocator, Recovery};
+use tempfile::tempdir;
+
+/// Test that allocations survive a clean restart.
+#[test]
+fn allocations_survive_restart() {
+    let dir = tempdir().unwrap();
+
+    {
+        let mut alloc = Allocator::open(dir.path(), 4, Recovery::RebuildFromLog).unwrap();
+        assert_eq!(alloc.allocate("a"), Some(0));
+        assert_eq!(alloc.allocate("b"), Some(1));
+        alloc.deallocate(0);
+    }
+
+    let alloc = Allocator::open(dir.path(), 4, Recovery::RebuildFromLog).unwrap();
+    assert_eq!(alloc.owner(0), None);
+    assert_eq!(alloc.owner(1), Some("b"));
+    assert_eq!(alloc.free_blocks().collect::<Vec<_>>(), [0, 2, 3]);
+}
+
+/// Test that the pool runs out after `capacity` allocations.
+#[test]
+fn pool_exhaustion() {
+    let dir = tempdir().unwrap();
+    let mut alloc = Allocator::open(dir.path(), 2, Recovery::RebuildFromLog).unwrap();
+    assert!(alloc.allocate("a").is_some());
+    assert!(alloc.allocate("b").is_some());
+    assert_eq!(alloc.allocate("c"), None);
+}
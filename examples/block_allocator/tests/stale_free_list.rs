@@ -0,0 +1,47 @@
+//! The buggy allocator variant: recovery trusts the last flushed free list.
+//!
+//! FIRST reports a double allocation and fails, so this test only runs
+//! with `BLOCK_ALLOCATOR_BUGGY=1` (see `docs/proof/block_allocator.md`).
+
+use block_allocator::{Allocator, Recovery};
+
+/// Enables the test; inherited by the FIRST child processes.
+const ENV_BUGGY: &str = "BLOCK_ALLOCATOR_BUGGY";
+
+#[test]
+fn free_list_trusted_after_crash() {
+    if std::env::var_os(ENV_BUGGY).is_none() {
+        return;
+    }
+    first::test()
+        .run(|env| {
+            let mut alloc = Allocator::open(&env.path("alloc"), 4, Recovery::TrustFreeList).unwrap();
+            alloc.flush_free_list().unwrap();
+            let a = alloc.allocate("a").unwrap();
+            alloc.allocate("b").unwrap();
+            alloc.flush_free_list().unwrap();
+            alloc.deallocate(a);
+            alloc.allocate("c").unwrap();
+        })
+        .verify(|env, crash_info| {
+            let alloc = Allocator::open(&env.path("alloc"), 4, Recovery::TrustFreeList).unwrap();
+            let free: Vec<u32> = alloc.free_blocks().collect();
+            for &block in &free {
+                if let Some(owner) = alloc.owner(block) {
+                    panic!(
+                        "double allocation at crash point '{}': block {} is on the free list but owned by {:?}",
+                        crash_info.label, block, owner
+                    );
+                }
+            }
+            for block in 0..alloc.capacity() {
+                assert!(
+                    free.contains(&block) || alloc.owner(block).is_some(),
+                    "leak at crash point '{}': block {} is neither free nor allocated",
+                    crash_info.label,
+                    block
+                );
+            }
+        })
+        .execute();
+}
@@ -2,4 +2,4 @@
 
 mod wal;
 
-pub use wal::{TxId, Wal};
+pub use wal::{CheckpointOrder, TxId, Wal};
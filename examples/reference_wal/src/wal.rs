@@ -10,7 +10,27 @@ use first::crash_point;
 /// Transaction identifier.
 pub type TxId = u64;
 
+/// How [`Wal::checkpoint()`] orders the checkpoint and the WAL truncation.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CheckpointOrder {
+    /// Make the checkpoint durable (write, fsync, rename, fsync the
+    /// directory), then truncate the WAL. A crash in between leaves both,
+    /// and replaying the WAL over the checkpoint is idempotent.
+    DurableThenTruncate,
+    /// Truncate the WAL, then write the checkpoint.
+    ///
+    /// BUG: between the truncation and the durable checkpoint, committed
+    /// transactions exist only in memory; a crash there loses them.
+    TruncateFirst,
+}
+
 /// Minimal append-only write-ahead log.
+///
+/// On disk:
+/// - `wal.log`: `BEGIN`, `PUT` and `COMMIT` records, one per line.
+/// - `checkpoint`: the committed state up to some transaction, replaced
+///   atomically by [`checkpoint()`](Self::checkpoint), after which the WAL
+///   only holds later transactions.
 pub struct Wal {
     /// Path to the WAL directory.
     dir: PathBuf,
@@ -33,12 +53,8 @@ impl Wal {
 
         let wal_path = path.join("wal.log");
 
-        // Recover state from existing WAL
-        let (state, max_txid) = if wal_path.exists() {
-            Self::recover_from_file(&wal_path)?
-        } else {
-            (HashMap::new(), 0)
-        };
+        // Recover state from the checkpoint and the WAL
+        let (state, max_txid) = Self::recover(path)?;
 
         // Open file in append mode
         let file = OpenOptions::new()
@@ -54,19 +70,54 @@ impl Wal {
         })
     }
 
-    /// Recover state from WAL file.
+    /// Recover state from the checkpoint, if any, and the WAL on top.
     ///
     /// Returns the recovered state and the maximum transaction ID seen.
-    fn recover_from_file(wal_path: &Path) -> io::Result<(HashMap<String, String>, TxId)> {
+    fn recover(dir: &Path) -> io::Result<(HashMap<String, String>, TxId)> {
+        let (state, max_txid) = Self::load_checkpoint(&dir.join("checkpoint"))?;
+        let wal_path = dir.join("wal.log");
+        if !wal_path.exists() {
+            return Ok((state, max_txid));
+        }
+        Self::recover_from_file(&wal_path, state, max_txid)
+    }
+
+    /// Load a checkpoint: a `TXID <n>` line, then one `KV <key> <value>`
+    /// line per key.
+    fn load_checkpoint(path: &Path) -> io::Result<(HashMap<String, String>, TxId)> {
+        let mut state = HashMap::new();
+        let mut max_txid: TxId = 0;
+        let text = match fs::read_to_string(path) {
+            Ok(text) => text,
+            Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok((state, max_txid)),
+            Err(e) => return Err(e),
+        };
+        for line in text.lines() {
+            let parts: Vec<&str> = line.splitn(3, ' ').collect();
+            match parts.as_slice() {
+                ["TXID", txid] => max_txid = txid.parse().expect("invalid txid in checkpoint"),
+                ["KV", key, value] => {
+                    state.insert(key.to_string(), value.to_string());
+                }
+                _ => panic!("malformed checkpoint record: {}", line),
+            }
+        }
+        Ok((state, max_txid))
+    }
+
+    /// Replay the WAL file over `state`, the state as of `max_txid`.
+    ///
+    /// Returns the recovered state and the maximum transaction ID seen.
+    fn recover_from_file(
+        wal_path: &Path,
+        mut state: HashMap<String, String>,
+        mut max_txid: TxId,
+    ) -> io::Result<(HashMap<String, String>, TxId)> {
         let file = File::open(wal_path)?;
         let reader = BufReader::new(file);
 
         // Pending transactions: txid -> Vec<(key, value)>
         let mut pending: HashMap<TxId, Vec<(String, String)>> = HashMap::new();
-        // Committed state
-        let mut state: HashMap<String, String> = HashMap::new();
-        // Track max txid seen
-        let mut max_txid: TxId = 0;
 
         for line in reader.lines() {
             let line = line?;
@@ -152,11 +203,44 @@ impl Wal {
         self.file.sync_all().expect("failed to fsync after COMMIT");
         crash_point("after_wal_fsync");
 
-        let wal_path = self.dir.join("wal.log");
-        let (state, _) = Self::recover_from_file(&wal_path).expect("failed to recover after commit");
+        let (state, _) = Self::recover(&self.dir).expect("failed to recover after commit");
         self.state = state;
     }
 
+    /// Persist the committed state as a checkpoint and empty the WAL.
+    pub fn checkpoint(&mut self, order: CheckpointOrder) -> io::Result<()> {
+        if order == CheckpointOrder::TruncateFirst {
+            self.truncate_wal()?;
+        }
+
+        let tmp = self.dir.join("checkpoint.tmp");
+        let mut file = File::create(&tmp)?;
+        writeln!(file, "TXID {}", self.next_txid - 1)?;
+        let mut keys: Vec<&String> = self.state.keys().collect();
+        keys.sort();
+        for key in keys {
+            writeln!(file, "KV {} {}", key, self.state[key])?;
+        }
+        file.sync_all()?;
+        crash_point("after_checkpoint_write");
+        fs::rename(&tmp, self.dir.join("checkpoint"))?;
+        File::open(&self.dir)?.sync_all()?;
+        crash_point("after_checkpoint_durable");
+
+        if order == CheckpointOrder::DurableThenTruncate {
+            self.truncate_wal()?;
+        }
+        Ok(())
+    }
+
+    /// Empty the WAL file.
+    fn truncate_wal(&mut self) -> io::Result<()> {
+        self.file.set_len(0)?;
+        crash_point("after_wal_truncate");
+        self.file.sync_all()?;
+        crash_point("after_truncate_fsync");
+        Ok(())
+    }
 
     /// Get a value by key from committed state.
     pub fn get(&self, key: &str) -> Option<&str> {
@@ -2,7 +2,7 @@
 //!
 //! These tests verify clean-restart recovery semantics.
 
-use reference_wal::Wal;
+use reference_wal::{CheckpointOrder, Wal};
 use tempfile::tempdir;
 
 /// Test that committed data survives a clean restart.
@@ -42,3 +42,29 @@ fn uncommitted_data_not_visible_after_restart() {
     let wal = Wal::open(dir.path()).unwrap();
     assert_eq!(wal.get("key"), None);
 }
+
+/// Test that data committed before and after a checkpoint survives a
+/// clean restart.
+#[test]
+fn checkpointed_data_survives_restart() {
+    let dir = tempdir().unwrap();
+
+    {
+        let mut wal = Wal::open(dir.path()).unwrap();
+        let tx = wal.begin();
+        wal.put(tx, "key1", "value1");
+        wal.commit(tx);
+        wal.checkpoint(CheckpointOrder::DurableThenTruncate)
+            .unwrap();
+        let tx = wal.begin();
+        wal.put(tx, "key2", "value2");
+        wal.commit(tx);
+        assert_eq!(wal.get("key1"), Some("value1"));
+    }
+
+    let mut wal = Wal::open(dir.path()).unwrap();
+    assert_eq!(wal.get("key1"), Some("value1"));
+    assert_eq!(wal.get("key2"), Some("value2"));
+    // Transaction IDs continue past the checkpoint
+    assert_eq!(wal.begin(), 3);
+}
@@ -0,0 +1,40 @@
+//! The buggy checkpoint variant: the WAL is truncated before the
+//! checkpoint is durable.
+//!
+//! FIRST reports lost committed data and fails, so this test only runs
+//! with `REFERENCE_WAL_BUGGY=1` (see `docs/proof/checkpoint_truncate.md`).
+
+use std::fs;
+
+use reference_wal::{CheckpointOrder, Wal};
+
+/// Enables the test; inherited by the FIRST child processes.
+const ENV_BUGGY: &str = "REFERENCE_WAL_BUGGY";
+
+#[test]
+fn wal_truncated_before_checkpoint() {
+    if std::env::var_os(ENV_BUGGY).is_none() {
+        return;
+    }
+    first::test()
+        .run(|env| {
+            let mut wal = Wal::open(&env.path("wal")).unwrap();
+            let tx = wal.begin();
+            wal.put(tx, "a", "1");
+            wal.commit(tx);
+            fs::write(env.path("acked"), "a 1\n").unwrap();
+            wal.checkpoint(CheckpointOrder::TruncateFirst).unwrap();
+        })
+        .verify(|env, crash_info| {
+            let wal = Wal::open(&env.path("wal")).unwrap();
+            if env.path("acked").exists() {
+                assert_eq!(
+                    wal.get("a"),
+                    Some("1"),
+                    "committed data lost at crash point '{}': a=1 was acknowledged but not recovered",
+                    crash_info.label
+                );
+            }
+        })
+        .execute();
+}
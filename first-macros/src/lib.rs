@@ -0,0 +1,278 @@
+//! Attribute macros for FIRST, re-exported by the `first` crate as
+//! `first::crash_test`, `first::run` and `first::verify`.
+//!
+//! The macros only rewrite a function into the builder form
+//! `first::test().run(..).verify(..).execute()` inside a plain `#[test]`;
+//! everything else is done by `execute()`. They are written against
+//! `proc_macro` alone, so this crate has no dependencies.
+//!
+//! # Phase routing
+//!
+//! The generated `#[test]` runs in the orchestrator and again in every
+//! child process, like a hand-written one. `execute()` reads the phase and
+//! calls the workload in EXECUTION children and the verify function in
+//! VERIFY children; in the orchestrator it calls neither.
+//!
+//! # How panics propagate
+//!
+//! - A panic in the workload fails its EXECUTION child, and the
+//!   orchestrator reports the crash point as failed with the child's exit
+//!   code.
+//! - A panic in verify, e.g. a failed `assert!`, fails that crash point,
+//!   and the orchestrator prints the panic message with the reproduction
+//!   command.
+//! - The orchestrator fails the test by exiting the process with status 1,
+//!   not by panicking, so `#[should_panic]` on a generated test never
+//!   passes. Use the builder's `expect_violation()` for tests that must
+//!   find a bug.
+
+use proc_macro::{Delimiter, Group, Ident, Literal, Punct, Spacing, Span, TokenStream, TokenTree};
+
+/// Turn a function taking the workspace and an optional crash into a FIRST
+/// test.
+///
+/// The function is called as the workload with `crash` set to `None`, and
+/// as verify with the crash that was injected:
+///
+/// ```ignore
+/// #[first::crash_test]
+/// fn wal_commit(env: &first::Env, crash: Option<&first::CrashInfo>) {
+///     let mut wal = Wal::open(&env.path("wal")).unwrap();
+///     match crash {
+///         None => {
+///             wal.append(b"record");
+///             first::crash_point("after_append");
+///             wal.commit();
+///         }
+///         Some(crash) => assert!(wal.is_consistent(), "after {}", crash.label),
+///     }
+/// }
+/// ```
+///
+/// The function becomes a `#[test]` with the same name; do not add
+/// `#[test]` yourself. Other attributes, such as `#[ignore]`, are kept on
+/// the test. For builder options, write the builder form instead.
+#[proc_macro_attribute]
+pub fn crash_test(attr: TokenStream, item: TokenStream) -> TokenStream {
+    let function = match parse(attr, item) {
+        Ok(function) => function,
+        Err(e) => return e,
+    };
+    let body_fn = Ident::new("__first_crash_test", function.name.span());
+    let mut body = function.renamed(body_fn);
+    body.extend(function.builder());
+    body.extend(code(
+        ".run(|env| __first_crash_test(env, ::core::option::Option::None))
+            .verify(|env, crash| __first_crash_test(env, ::core::option::Option::Some(crash)))
+            .execute();",
+    ));
+    function.test(body)
+}
+
+/// Mark the workload of a FIRST test; the verify function is the
+/// [`macro@verify`] function of the same name in the same module.
+///
+/// ```ignore
+/// #[first::run]
+/// fn wal_commit(env: &first::Env) {
+///     let mut wal = Wal::open(&env.path("wal")).unwrap();
+///     wal.append(b"record");
+///     first::crash_point("after_append");
+///     wal.commit();
+/// }
+///
+/// #[first::verify]
+/// fn wal_commit(env: &first::Env, crash: &first::CrashInfo) {
+///     let wal = Wal::open(&env.path("wal")).unwrap();
+///     assert!(wal.is_consistent(), "after {}", crash.label);
+/// }
+/// ```
+///
+/// The workload function becomes a `#[test]` with its name; do not add
+/// `#[test]` yourself. A missing verify function is reported as an
+/// unresolved `__first_verify_<name>`.
+#[proc_macro_attribute]
+pub fn run(attr: TokenStream, item: TokenStream) -> TokenStream {
+    let function = match parse(attr, item) {
+        Ok(function) => function,
+        Err(e) => return e,
+    };
+    let mut body = function.renamed(Ident::new("__first_run", function.name.span()));
+    body.extend(function.builder());
+    body.extend(code(".run(__first_run).verify"));
+    body.extend([TokenTree::Group(Group::new(
+        Delimiter::Parenthesis,
+        TokenStream::from(TokenTree::Ident(verify_name(&function.name))),
+    ))]);
+    body.extend(code(".execute();"));
+    function.test(body)
+}
+
+/// Mark the verify function of the [`macro@run`] workload of the same
+/// name.
+///
+/// The function is renamed out of the way, so both can share the test's
+/// name; it is only called through the test.
+#[proc_macro_attribute]
+pub fn verify(attr: TokenStream, item: TokenStream) -> TokenStream {
+    let function = match parse(attr, item) {
+        Ok(function) => function,
+        Err(e) => return e,
+    };
+    let mut out: TokenStream = function.attrs.iter().cloned().collect();
+    out.extend(function.vis.iter().cloned());
+    out.extend(function.renamed(verify_name(&function.name)));
+    out
+}
+
+/// A function item, split around its name.
+struct Function {
+    /// Outer attributes, kept on the generated item.
+    attrs: Vec<TokenTree>,
+    /// Visibility, kept on the generated item.
+    vis: Vec<TokenTree>,
+    /// Qualifiers and the `fn` keyword.
+    head: Vec<TokenTree>,
+    name: Ident,
+    /// Generics, parameters, return type and body.
+    tail: Vec<TokenTree>,
+}
+
+impl Function {
+    /// The function without attributes and visibility, renamed to `name`.
+    fn renamed(&self, name: Ident) -> TokenStream {
+        let mut out: TokenStream = self.head.iter().cloned().collect();
+        out.extend([TokenTree::Ident(name)]);
+        out.extend(self.tail.iter().cloned());
+        out
+    }
+
+    /// `::first::test()` naming the generated test, so children are
+    /// filtered on its exact name.
+    fn builder(&self) -> TokenStream {
+        code(&format!(
+            "::first::test().__test_fn(::core::module_path!(), {:?})",
+            self.name.to_string().trim_start_matches("r#")
+        ))
+    }
+
+    /// A `#[test]` with the function's name, attributes and visibility,
+    /// running `body`.
+    fn test(&self, body: TokenStream) -> TokenStream {
+        let mut out: TokenStream = self.attrs.iter().cloned().collect();
+        out.extend(code("#[test]"));
+        out.extend(self.vis.iter().cloned());
+        out.extend(code("fn"));
+        out.extend([
+            TokenTree::Ident(self.name.clone()),
+            TokenTree::Group(Group::new(Delimiter::Parenthesis, TokenStream::new())),
+            TokenTree::Group(Group::new(Delimiter::Brace, body)),
+        ]);
+        out
+    }
+}
+
+/// Split a function item, or return a `compile_error!` explaining why the
+/// attribute does not apply.
+fn parse(attr: TokenStream, item: TokenStream) -> Result<Function, TokenStream> {
+    if let Some(token) = attr.into_iter().next() {
+        return Err(error("this attribute takes no arguments", token.span()));
+    }
+    let mut tokens = item.into_iter().peekable();
+
+    let mut attrs = Vec::new();
+    while let Some(TokenTree::Punct(p)) = tokens.peek()
+        && p.as_char() == '#'
+    {
+        let pound = tokens.next().unwrap();
+        let Some(TokenTree::Group(group)) = tokens.next() else {
+            return Err(error("expected an attribute", pound.span()));
+        };
+        if group.stream().to_string() == "test" {
+            return Err(error(
+                "remove `#[test]`: the FIRST attribute generates the test",
+                group.span(),
+            ));
+        }
+        attrs.push(pound);
+        attrs.push(TokenTree::Group(group));
+    }
+
+    let mut vis = Vec::new();
+    if let Some(TokenTree::Ident(ident)) = tokens.peek()
+        && ident.to_string() == "pub"
+    {
+        vis.push(tokens.next().unwrap());
+        if let Some(TokenTree::Group(group)) = tokens.peek()
+            && group.delimiter() == Delimiter::Parenthesis
+        {
+            vis.push(tokens.next().unwrap());
+        }
+    }
+
+    let mut head = Vec::new();
+    loop {
+        match tokens.next() {
+            Some(TokenTree::Ident(ident)) if ident.to_string() == "async" => {
+                return Err(error("async tests are not supported", ident.span()));
+            }
+            Some(TokenTree::Ident(ident)) if ident.to_string() == "fn" => {
+                head.push(TokenTree::Ident(ident));
+                break;
+            }
+            Some(token) => head.push(token),
+            None => return Err(error("expected a function", Span::call_site())),
+        }
+    }
+    let Some(TokenTree::Ident(name)) = tokens.next() else {
+        return Err(error("expected a function name", Span::call_site()));
+    };
+
+    let tail: Vec<TokenTree> = tokens.collect();
+    match tail.last() {
+        Some(TokenTree::Group(body)) if body.delimiter() == Delimiter::Brace => {}
+        _ => return Err(error("expected a function body", name.span())),
+    }
+
+    Ok(Function {
+        attrs,
+        vis,
+        head,
+        name,
+        tail,
+    })
+}
+
+/// Name under which `#[first::verify]` keeps the verify function of test
+/// `name`.
+fn verify_name(name: &Ident) -> Ident {
+    Ident::new(&format!("__first_verify_{}", name), name.span())
+}
+
+/// Parse generated code.
+fn code(source: &str) -> TokenStream {
+    source.parse().expect("generated code is valid")
+}
+
+/// `::core::compile_error!("message")`, reported at `span`.
+fn error(message: &str, span: Span) -> TokenStream {
+    let mut tokens = Vec::new();
+    for segment in ["core", "compile_error"] {
+        tokens.push(TokenTree::Punct(Punct::new(':', Spacing::Joint)));
+        tokens.push(TokenTree::Punct(Punct::new(':', Spacing::Alone)));
+        tokens.push(TokenTree::Ident(Ident::new(segment, span)));
+    }
+    tokens.push(TokenTree::Punct(Punct::new('!', Spacing::Alone)));
+    tokens.push(TokenTree::Group(Group::new(
+        Delimiter::Parenthesis,
+        TokenStream::from(TokenTree::Literal(Literal::string(message))),
+    )));
+    tokens.push(TokenTree::Punct(Punct::new(';', Spacing::Alone)));
+    tokens
+        .into_iter()
+        .map(|mut token| {
+            token.set_span(span);
+            token
+        })
+        .collect()
+}
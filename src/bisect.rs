@@ -0,0 +1,222 @@
+//! Bisection to the first failing crash point.
+//!
+//! With `TestBuilder::bisect()`, the orchestrator does not sweep every
+//! point. It discovers the points, runs the last one, and if that one
+//! fails, binary-searches for the smallest failing point ID, running
+//! about `log2(n)` points instead of `n`.
+//!
+//! This assumes failures are monotonic: once a point fails, every later
+//! point fails too, as when a step corrupts state that later steps keep.
+//! A bug visible only at isolated points can be missed or misplaced;
+//! setting [`ENV_NO_BISECT`] falls back to the linear sweep.
+
+use std::collections::BTreeMap;
+use std::path::Path;
+
+use crate::env::CrashInfo;
+use crate::orchestrator::{self, ChildResult, FIRST_BASE_DIR, VerifyRef, cleanup_work_dir};
+use crate::test::Options;
+
+/// Set to `1` to sweep every crash point even when `bisect()` is set.
+pub(crate) const ENV_NO_BISECT: &str = "FIRST_NO_BISECT";
+
+/// A crash point that failed, with what to report about it.
+struct Failure {
+    crash_info: CrashInfo,
+    reason: String,
+}
+
+/// Find and report the first failing crash point.
+///
+/// Exits the process with status 1 if a point fails, unless
+/// `expect_violation()` is set, in which case finding none fails.
+pub(crate) fn run(
+    exe: &Path,
+    test_name: &Option<String>,
+    metadata_dir: &Path,
+    options: &Options,
+    in_process: Option<VerifyRef<'_>>,
+) {
+    let tag = crate::report::tag_suffix(crate::report::run_tag(options).as_deref());
+    let Some(points) =
+        crate::discover::discover(exe, test_name, Path::new(FIRST_BASE_DIR), options)
+    else {
+        eprintln!("[first] error: bisect() requires crash point discovery, which failed");
+        std::process::exit(1);
+    };
+    let total = points.len();
+    eprintln!(
+        "[first] bisecting {} crash points (assumes a failing point is followed only by failing points; {}=1 sweeps them all)",
+        total, ENV_NO_BISECT
+    );
+
+    // Each point runs at most once
+    let mut results: BTreeMap<usize, Option<Failure>> = BTreeMap::new();
+    let mut check = |target: usize| -> bool {
+        let result = results.entry(target).or_insert_with(|| {
+            let label = &points[target - 1].label;
+            check_point(
+                exe,
+                test_name,
+                metadata_dir,
+                target,
+                label,
+                total,
+                options,
+                in_process,
+            )
+        });
+        result.is_some()
+    };
+
+    let first_failing = if total > 0 && check(total) {
+        let (mut low, mut high) = (1, total);
+        while low < high {
+            let mid = low + (high - low) / 2;
+            if check(mid) {
+                high = mid;
+            } else {
+                low = mid + 1;
+            }
+        }
+        Some(low)
+    } else {
+        None
+    };
+
+    let run = results.len();
+    let Some(target) = first_failing else {
+        if options.expect_violation {
+            eprintln!(
+                "[first] FAILED: expected a crash-consistency violation but the last crash point passed{}",
+                tag
+            );
+            std::process::exit(1);
+        }
+        eprintln!(
+            "[first] bisect: last crash point {} passed, so all {} crash points are assumed to pass ({} run){}",
+            total, total, run, tag
+        );
+        return;
+    };
+    let Some(Some(failure)) = results.remove(&target) else {
+        unreachable!("the first failing point was checked");
+    };
+    let work_dir = crate::invocation::run_dir(target, options.loopback_fs.as_ref());
+    orchestrator::print_failure_info(
+        target,
+        &work_dir,
+        &failure.crash_info,
+        test_name,
+        &failure.reason,
+    );
+    eprintln!(
+        "[first] bisect: first failing crash point is {} (\"{}\"), found after running {} of {} crash points{}",
+        target, failure.crash_info.label, run, total, tag
+    );
+    if !options.expect_violation {
+        std::process::exit(1);
+    }
+}
+
+/// Crash at `target` and verify, returning the failure if any.
+///
+/// Passing points leave no work dir behind; failing ones keep theirs.
+#[allow(clippy::too_many_arguments)]
+fn check_point(
+    exe: &Path,
+    test_name: &Option<String>,
+    metadata_dir: &Path,
+    target: usize,
+    label: &str,
+    total: usize,
+    options: &Options,
+    in_process: Option<VerifyRef<'_>>,
+) -> Option<Failure> {
+    let work_dir = crate::invocation::run_dir(target, options.loopback_fs.as_ref());
+    cleanup_work_dir(&work_dir);
+    if let Err(e) = orchestrator::create_work_dir(&work_dir, options) {
+        eprintln!(
+            "[first] error: {}",
+            orchestrator::io_failure("create", &work_dir, &e)
+        );
+        std::process::exit(1);
+    }
+
+    let exec_result = orchestrator::spawn_child(
+        exe,
+        test_name,
+        "EXECUTION",
+        target,
+        None,
+        &work_dir,
+        metadata_dir,
+        options.coverage_dir.as_deref(),
+        options.resource_limits.as_ref(),
+    );
+    let (crash_info, reason) = match exec_result {
+        ChildResult::Crashed(mut crash_info) => {
+            crash_info.total_points = Some(total);
+            let in_process_result = in_process.and_then(|verify| {
+                orchestrator::verify_in_process(
+                    verify,
+                    target,
+                    &work_dir,
+                    metadata_dir,
+                    &crash_info,
+                )
+            });
+            let verify_result = match in_process_result {
+                Some(result) => result,
+                None => orchestrator::spawn_child_with_crash_info(
+                    exe,
+                    test_name,
+                    target,
+                    &work_dir,
+                    metadata_dir,
+                    &crash_info,
+                    None,
+                    options.coverage_dir.as_deref(),
+                    options.resource_limits.as_ref(),
+                    None,
+                ),
+            };
+            let reason = match verify_result {
+                ChildResult::Success => None,
+                ChildResult::Failed(code) => {
+                    Some(format!("verification failed with exit code {}", code))
+                }
+                ChildResult::Crashed(_) | ChildResult::Skipped(_) => {
+                    Some("verify phase crashed unexpectedly".to_string())
+                }
+                ChildResult::TimedOut(limit) => {
+                    Some(format!("verify phase timed out after {:?}", limit))
+                }
+            };
+            (crash_info, reason)
+        }
+        // A disabled label is never crashed at, so it cannot fail
+        ChildResult::Skipped(crash_info) => (crash_info, None),
+        ChildResult::Success => (
+            CrashInfo::new(target, label.to_string()),
+            Some("crash point was never reached (is the workload deterministic?)".to_string()),
+        ),
+        ChildResult::Failed(code) => (
+            CrashInfo::new(target, label.to_string()),
+            Some(format!("execution failed with exit code {}", code)),
+        ),
+        ChildResult::TimedOut(limit) => (
+            CrashInfo::new(target, label.to_string()),
+            Some(format!("workload timed out after {:?}", limit)),
+        ),
+    };
+
+    match &reason {
+        Some(_) => eprintln!("[first] bisect: crash point {}: FAILED", target),
+        None => {
+            eprintln!("[first] bisect: crash point {}: OK", target);
+            cleanup_work_dir(&work_dir);
+        }
+    }
+    reason.map(|reason| Failure { crash_info, reason })
+}
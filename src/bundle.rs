@@ -0,0 +1,438 @@
+//! Failure report bundles.
+//!
+//! Packages everything needed to report a verify failure into one artifact:
+//! the post-crash workspace, the crash info, the repro command, the verify
+//! child's captured output and a description of the environment.
+//!
+//! # Layout
+//!
+//! ```text
+//! <bundle>/
+//!   README.txt        failure summary and repro command
+//!   crash_info.txt    every CrashInfo field, one per line
+//!   environment.txt   platform, executable and FIRST_* variables
+//!   verify.stdout     captured verify child stdout
+//!   verify.stderr     captured verify child stderr
+//!   workspace/        copy of the post-crash workspace
+//!   replay/
+//!     target          crash point targeted by the sweep
+//!     crash_info.bin  the CrashInfo, in the binary metadata format
+//!     metadata/       the crash point's metadata sidecars (durability
+//!                     manifest, memory checkpoint, synced lengths)
+//! ```
+//!
+//! The `replay/` directory makes a bundle self-contained: with
+//! `TestBuilder::replay_bundle()`, [`replay()`] runs verify again on a
+//! copy of the workspace, with the captured crash info and sidecars,
+//! without running the workload.
+
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+use crate::env::CrashInfo;
+use crate::metadata;
+use crate::orchestrator::{
+    ChildResult, FIRST_BASE_DIR, cleanup_work_dir, io_failure, spawn_child_with_crash_info,
+};
+use crate::test::Options;
+
+/// Output captured from a child process.
+#[derive(Debug, Default)]
+pub(crate) struct CapturedOutput {
+    pub(crate) stdout: Vec<u8>,
+    pub(crate) stderr: Vec<u8>,
+}
+
+/// A failed crash point, as described in a bundle.
+pub(crate) struct Failure<'a> {
+    pub(crate) target: usize,
+    pub(crate) reason: &'a str,
+    pub(crate) repro: &'a str,
+    pub(crate) test_name: &'a Option<String>,
+    pub(crate) work_dir: &'a Path,
+    pub(crate) metadata_dir: &'a Path,
+    pub(crate) crash_info: &'a CrashInfo,
+    pub(crate) output: &'a CapturedOutput,
+}
+
+/// Write a failure bundle to `path`, replacing any previous bundle there.
+///
+/// A path ending in `.tar.gz` or `.tgz` produces a gzipped tarball (via the
+/// system `tar`); any other path produces a directory.
+pub(crate) fn write(path: &Path, failure: &Failure) -> io::Result<()> {
+    let Some(stem) = archive_stem(path) else {
+        remove_existing(path)?;
+        return write_dir(path, failure);
+    };
+
+    // Assemble next to the archive so the tarball holds a single named dir.
+    let staging = path.with_file_name(stem);
+    remove_existing(&staging)?;
+    write_dir(&staging, failure)?;
+    remove_existing(path)?;
+
+    let status = Command::new("tar")
+        .arg("-czf")
+        .arg(path)
+        .arg("-C")
+        .arg(staging.parent().unwrap_or(Path::new(".")))
+        .arg(stem)
+        .status();
+    let _ = fs::remove_dir_all(&staging);
+
+    match status {
+        Ok(status) if status.success() => Ok(()),
+        Ok(status) => Err(io::Error::other(format!("tar exited with {}", status))),
+        Err(e) => Err(io::Error::new(e.kind(), format!("cannot run tar: {}", e))),
+    }
+}
+
+/// Bundle path for crash point `target`, when several bundles are written.
+///
+/// `out/bug.tar.gz` becomes `out/bug_point_3.tar.gz`, `out/bug` becomes
+/// `out/bug_point_3`.
+pub(crate) fn path_for_point(path: &Path, target: usize) -> PathBuf {
+    let name = path
+        .file_name()
+        .map(|n| n.to_string_lossy().into_owned())
+        .unwrap_or_default();
+    let (stem, ext) = match archive_stem(path) {
+        Some(stem) => (stem.to_string(), &name[stem.len()..]),
+        None => (name.clone(), ""),
+    };
+    path.with_file_name(format!("{}_point_{}{}", stem, target, ext))
+}
+
+/// Bundle directory name inside an archive, or `None` for a plain directory.
+fn archive_stem(path: &Path) -> Option<&str> {
+    let name = path.file_name()?.to_str()?;
+    name.strip_suffix(".tar.gz")
+        .or_else(|| name.strip_suffix(".tgz"))
+        .filter(|stem| !stem.is_empty())
+}
+
+/// Remove a previous bundle at `path`, if any.
+fn remove_existing(path: &Path) -> io::Result<()> {
+    match fs::symlink_metadata(path) {
+        Ok(meta) if meta.is_dir() => fs::remove_dir_all(path),
+        Ok(_) => fs::remove_file(path),
+        Err(e) if e.kind() == io::ErrorKind::NotFound => Ok(()),
+        Err(e) => Err(e),
+    }
+}
+
+/// Write the bundle contents into directory `dir`.
+fn write_dir(dir: &Path, failure: &Failure) -> io::Result<()> {
+    fs::create_dir_all(dir)?;
+
+    let test = failure.test_name.as_deref().unwrap_or("<all tests>");
+    fs::write(
+        dir.join("README.txt"),
+        format!(
+            "test: {}\ncrash point: {}\nreason: {}\n\nto reproduce:\n  {}\n",
+            test, failure.target, failure.reason, failure.repro
+        ),
+    )?;
+    fs::write(
+        dir.join("crash_info.txt"),
+        describe_crash_info(failure.crash_info),
+    )?;
+    fs::write(dir.join("environment.txt"), describe_environment())?;
+    fs::write(dir.join("verify.stdout"), &failure.output.stdout)?;
+    fs::write(dir.join("verify.stderr"), &failure.output.stderr)?;
+    copy_tree(failure.work_dir, &dir.join("workspace"))?;
+
+    let replay = dir.join("replay");
+    fs::create_dir_all(replay.join("metadata"))?;
+    fs::write(replay.join("target"), format!("{}\n", failure.target))?;
+    fs::write(
+        replay.join("crash_info.bin"),
+        metadata::encode(failure.crash_info),
+    )?;
+    let suffix = format!("_{}", failure.target);
+    for entry in fs::read_dir(failure.metadata_dir)? {
+        let entry = entry?;
+        let name = entry.file_name();
+        let name = name.to_string_lossy();
+        // Sidecars of this point only; dotfiles are FIRST's own records
+        if entry.file_type()?.is_file() && name.ends_with(&suffix) && !name.starts_with('.') {
+            fs::copy(entry.path(), replay.join("metadata").join(&*name))?;
+        }
+    }
+    Ok(())
+}
+
+/// Run verify again on the failure captured in the bundle at `path`.
+///
+/// The workload does not run: the VERIFY child gets a copy of the
+/// bundled workspace, the captured crash info and the captured sidecars,
+/// and runs the verify closures of the current source. Exits the process
+/// with status 1 if the bundle cannot be read or verify fails.
+pub(crate) fn replay(exe: &Path, test_name: &Option<String>, path: &Path, options: &Options) {
+    let base = PathBuf::from(FIRST_BASE_DIR).join("bundle_replay");
+    cleanup_work_dir(&base);
+    let fail = |message: String| -> ! {
+        eprintln!("[first] error: {}", message);
+        std::process::exit(1);
+    };
+
+    let bundle = match archive_stem(path) {
+        Some(stem) => {
+            if let Err(e) = extract(path, &base) {
+                fail(io_failure("extract bundle", path, &e));
+            }
+            base.join(stem)
+        }
+        None => path.to_path_buf(),
+    };
+    let replay = bundle.join("replay");
+    let target = fs::read_to_string(replay.join("target"))
+        .ok()
+        .and_then(|s| s.trim().parse::<usize>().ok());
+    let crash_info = fs::read(replay.join("crash_info.bin"))
+        .ok()
+        .and_then(|bytes| metadata::decode(&bytes));
+    let (Some(target), Some(crash_info)) = (target, crash_info) else {
+        fail(format!(
+            "{} is not a replayable bundle (missing or unreadable replay/ directory; bundles written by older versions of FIRST cannot be replayed)",
+            path.display()
+        ));
+    };
+
+    // Verify may modify its workspace: keep the bundle itself pristine
+    let work_dir = base.join("workspace");
+    let metadata_dir = base.join("meta");
+    if let Err(e) = copy_tree(&bundle.join("workspace"), &work_dir) {
+        fail(io_failure("copy bundled workspace to", &work_dir, &e));
+    }
+    // The failed run's log is not part of the crashed state
+    crate::verify_log::remove(&work_dir);
+    if let Err(e) = copy_tree(&replay.join("metadata"), &metadata_dir) {
+        fail(io_failure("copy bundled metadata to", &metadata_dir, &e));
+    }
+
+    eprintln!(
+        "[first] replaying crash point {} (\"{}\") from bundle {}",
+        target,
+        crash_info.label,
+        path.display()
+    );
+    let reason = match spawn_child_with_crash_info(
+        exe,
+        test_name,
+        target,
+        &work_dir,
+        &metadata_dir,
+        &crash_info,
+        None,
+        options.coverage_dir.as_deref(),
+        options.resource_limits.as_ref(),
+        None,
+    ) {
+        ChildResult::Success => None,
+        ChildResult::Failed(code) => Some(format!("verification failed with exit code {}", code)),
+        ChildResult::Crashed(_) | ChildResult::Skipped(_) => {
+            Some("verify phase crashed unexpectedly".to_string())
+        }
+        ChildResult::TimedOut(limit) => Some(format!("verify phase timed out after {:?}", limit)),
+    };
+    if let Some(reason) = reason {
+        eprintln!("[first] bundle replay: FAILED (see {})", work_dir.display());
+        eprintln!("[first] reason: {}", reason);
+        crate::verify_log::print_tail(&work_dir);
+        std::process::exit(1);
+    }
+
+    eprintln!("[first] bundle replay: OK");
+    if std::env::var("FIRST_KEEP_ARTIFACTS").is_err() {
+        cleanup_work_dir(&base);
+    }
+}
+
+/// Unpack the bundle archive at `path` into `dir`.
+fn extract(path: &Path, dir: &Path) -> io::Result<()> {
+    fs::create_dir_all(dir)?;
+    let status = Command::new("tar")
+        .arg("-xzf")
+        .arg(path)
+        .arg("-C")
+        .arg(dir)
+        .status()
+        .map_err(|e| io::Error::new(e.kind(), format!("cannot run tar: {}", e)))?;
+    if !status.success() {
+        return Err(io::Error::other(format!("tar exited with {}", status)));
+    }
+    Ok(())
+}
+
+/// Render every `CrashInfo` field as a `key: value` line.
+fn describe_crash_info(info: &CrashInfo) -> String {
+    let mut out = format!(
+        "point_id: {}\nlabel: {}\nfsync_count: {}\neintr_count: {}\n",
+        info.point_id, info.label, info.fsync_count, info.eintr_count
+    );
+    if let Some(fds) = info.max_fds {
+        out.push_str(&format!("max_fds: {}\n", fds));
+    }
+    if let Some(total) = info.total_points {
+        out.push_str(&format!("total_points: {}\n", total));
+    }
+    if let Some(partial) = &info.partial_write {
+        out.push_str(&format!("partial_write: {}\n", partial.to_env()));
+    }
+    if let Some(injected) = &info.dir_fsync_fault {
+        out.push_str(&format!("dir_fsync_fault: {}\n", injected.to_env()));
+    }
+    for flush in &info.partial_flush {
+        out.push_str(&format!("partial_flush: {}\n", flush.to_env()));
+    }
+    out
+}
+
+/// Describe the platform, executable and FIRST configuration.
+fn describe_environment() -> String {
+    let mut out = format!(
+        "first: {}\nos: {}\narch: {}\n",
+        env!("CARGO_PKG_VERSION"),
+        std::env::consts::OS,
+        std::env::consts::ARCH
+    );
+    if let Ok(exe) = std::env::current_exe() {
+        out.push_str(&format!("exe: {}\n", exe.display()));
+    }
+    if let Ok(cwd) = std::env::current_dir() {
+        out.push_str(&format!("cwd: {}\n", cwd.display()));
+    }
+    out.push_str(&format!(
+        "args: {:?}\n",
+        std::env::args().collect::<Vec<_>>()
+    ));
+
+    let mut vars: Vec<_> = std::env::vars()
+        .filter(|(key, _)| key.starts_with("FIRST_"))
+        .collect();
+    vars.sort();
+    for (key, value) in vars {
+        out.push_str(&format!("{}={}\n", key, value));
+    }
+    out
+}
+
+/// Recursively copy `src` to `dst`, recreating symlinks rather than
+/// following them.
+pub(crate) fn copy_tree(src: &Path, dst: &Path) -> io::Result<()> {
+    copy_tree_with(src, dst, |from, to| fs::copy(from, to).map(drop))
+}
+
+/// Like [`copy_tree()`], copying each regular file with `copy_file`.
+pub(crate) fn copy_tree_with(
+    src: &Path,
+    dst: &Path,
+    copy_file: impl Fn(&Path, &Path) -> io::Result<()>,
+) -> io::Result<()> {
+    fs::create_dir_all(dst)?;
+    let mut stack: Vec<(PathBuf, PathBuf)> = vec![(src.to_path_buf(), dst.to_path_buf())];
+    while let Some((from_dir, to_dir)) = stack.pop() {
+        for entry in fs::read_dir(&from_dir)? {
+            let entry = entry?;
+            let file_type = entry.file_type()?;
+            let to = to_dir.join(entry.file_name());
+            if file_type.is_dir() {
+                fs::create_dir(&to)?;
+                stack.push((entry.path(), to));
+            } else if file_type.is_symlink() {
+                std::os::unix::fs::symlink(fs::read_link(entry.path())?, &to)?;
+            } else if file_type.is_file() {
+                copy_file(&entry.path(), &to)?;
+            }
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_archive_stem() {
+        assert_eq!(archive_stem(Path::new("out/bug.tar.gz")), Some("bug"));
+        assert_eq!(archive_stem(Path::new("bug.tgz")), Some("bug"));
+        assert_eq!(archive_stem(Path::new("out/bug")), None);
+        assert_eq!(archive_stem(Path::new(".tar.gz")), None);
+    }
+
+    #[test]
+    fn test_path_for_point() {
+        assert_eq!(
+            path_for_point(Path::new("out/bug.tar.gz"), 3),
+            PathBuf::from("out/bug_point_3.tar.gz")
+        );
+        assert_eq!(
+            path_for_point(Path::new("out/bug"), 12),
+            PathBuf::from("out/bug_point_12")
+        );
+    }
+
+    #[test]
+    fn test_write_dir_bundle() {
+        let dir = tempfile::tempdir().unwrap();
+        let work_dir = dir.path().join("run_3");
+        fs::create_dir_all(work_dir.join("log")).unwrap();
+        fs::write(work_dir.join("log/wal"), b"entry").unwrap();
+        std::os::unix::fs::symlink("log/wal", work_dir.join("current")).unwrap();
+
+        let metadata_dir = dir.path().join("meta");
+        fs::create_dir_all(&metadata_dir).unwrap();
+        fs::write(metadata_dir.join("durability_manifest_3"), b"m3").unwrap();
+        fs::write(metadata_dir.join("durability_manifest_13"), b"m13").unwrap();
+        fs::write(metadata_dir.join(".crash_execution_3"), b"").unwrap();
+
+        let crash_info = CrashInfo::new(3, "after_commit".to_string());
+        let output = CapturedOutput {
+            stdout: b"out".to_vec(),
+            stderr: b"err".to_vec(),
+        };
+        let failure = Failure {
+            target: 3,
+            reason: "verification failed with exit code 101",
+            repro: "cargo test",
+            test_name: &Some("my_test".to_string()),
+            work_dir: &work_dir,
+            metadata_dir: &metadata_dir,
+            crash_info: &crash_info,
+            output: &output,
+        };
+        let bundle = dir.path().join("bundle");
+        write(&bundle, &failure).unwrap();
+        // Rewriting replaces the previous bundle
+        write(&bundle, &failure).unwrap();
+
+        assert_eq!(
+            fs::read(bundle.join("workspace/log/wal")).unwrap(),
+            b"entry"
+        );
+        assert_eq!(
+            fs::read_link(bundle.join("workspace/current")).unwrap(),
+            PathBuf::from("log/wal")
+        );
+        assert_eq!(fs::read(bundle.join("verify.stderr")).unwrap(), b"err");
+        let info = fs::read_to_string(bundle.join("crash_info.txt")).unwrap();
+        assert!(info.contains("label: after_commit"));
+        let readme = fs::read_to_string(bundle.join("README.txt")).unwrap();
+        assert!(readme.contains("test: my_test"));
+
+        let replay = bundle.join("replay");
+        assert_eq!(fs::read_to_string(replay.join("target")).unwrap(), "3\n");
+        let bytes = fs::read(replay.join("crash_info.bin")).unwrap();
+        assert_eq!(metadata::decode(&bytes).unwrap().label, "after_commit");
+        let mut sidecars: Vec<_> = fs::read_dir(replay.join("metadata"))
+            .unwrap()
+            .map(|e| e.unwrap().file_name())
+            .collect();
+        sidecars.sort();
+        assert_eq!(sidecars, ["durability_manifest_3"]);
+    }
+}
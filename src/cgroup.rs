@@ -0,0 +1,262 @@
+//! Resource limits for child processes (Linux cgroup v2).
+//!
+//! With `TestBuilder::resource_limits()`, every EXECUTION and VERIFY child
+//! runs in its own transient cgroup, created below the orchestrator's
+//! cgroup with the configured `memory.max` and `cpu.max`. The child joins
+//! it between `fork` and `exec`, so the limits apply from its first
+//! instruction. After the child exits, the cgroup's `memory.events` tells
+//! an OOM kill apart from an injected crash, and the cgroup is removed.
+//!
+//! The first sweep with limits moves the orchestrator into a leaf cgroup
+//! of its own, removed again when it exits. Where cgroup v2 is not
+//! available, or the orchestrator may not create cgroups, every sweep with
+//! limits prints a warning and its children run without them.
+
+use std::fs::{self, File, OpenOptions};
+use std::io;
+use std::os::unix::io::AsRawFd;
+use std::os::unix::process::CommandExt;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+use std::sync::OnceLock;
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+/// Root of the unified cgroup hierarchy.
+const CGROUP_ROOT: &str = "/sys/fs/cgroup";
+
+/// CPU accounting period for `cpu.max`, in microseconds.
+const CPU_PERIOD_US: u64 = 100_000;
+
+/// Limits applied to each EXECUTION and VERIFY child.
+///
+/// # Example
+///
+/// ```ignore
+/// first::test()
+///     .resource_limits(
+///         first::ResourceLimits::new()
+///             .memory_max(64 * 1024 * 1024)
+///             .cpu_percent(50),
+///     )
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ResourceLimits {
+    memory_max: Option<u64>,
+    cpu_percent: Option<u32>,
+}
+
+impl ResourceLimits {
+    /// No limits.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Limit each child to `bytes` of memory (`memory.max`). A child over
+    /// the limit is OOM-killed, which FIRST reports as such rather than as
+    /// an injected crash.
+    pub fn memory_max(mut self, bytes: u64) -> Self {
+        self.memory_max = Some(bytes);
+        self
+    }
+
+    /// Limit each child to `percent` of one CPU (`cpu.max`); above 100
+    /// allows several CPUs.
+    pub fn cpu_percent(mut self, percent: u32) -> Self {
+        self.cpu_percent = Some(percent.max(1));
+        self
+    }
+}
+
+/// The orchestrator's cgroup, prepared by the first sweep with limits to
+/// hold child cgroups, or why it cannot.
+static PARENT: OnceLock<Result<Parent, String>> = OnceLock::new();
+
+/// Source of unique child cgroup names.
+static NEXT_ID: AtomicUsize = AtomicUsize::new(0);
+
+#[derive(Debug)]
+struct Parent {
+    /// The orchestrator's original cgroup, holding the child cgroups.
+    dir: PathBuf,
+    /// The leaf the orchestrator moved itself into.
+    leaf: PathBuf,
+}
+
+/// Prepare child cgroups for a sweep with `limits`.
+///
+/// Called by the orchestrator at the start of every sweep; prints a
+/// warning if a sweep asks for limits but cgroup v2 cannot be used, and
+/// its children then run without them.
+pub(crate) fn configure(limits: Option<&ResourceLimits>) {
+    if limits.is_none() {
+        return;
+    }
+    if let Err(e) = PARENT.get_or_init(|| parent_dir().map_err(|e| e.to_string())) {
+        eprintln!(
+            "[first] warning: resource limits unavailable ({}); running children without them",
+            e
+        );
+    }
+}
+
+/// Prepare the orchestrator's own cgroup to hold child cgroups.
+///
+/// cgroup v2 only lets a cgroup without processes of its own distribute
+/// controllers to children, so the orchestrator first moves itself into
+/// a leaf below its cgroup, then enables the memory and cpu controllers
+/// there. Child cgroups become siblings of that leaf.
+///
+/// The leaf is removed when the orchestrator exits.
+fn parent_dir() -> io::Result<Parent> {
+    if !Path::new(CGROUP_ROOT).join("cgroup.controllers").exists() {
+        return Err(io::Error::new(
+            io::ErrorKind::Unsupported,
+            "cgroup v2 is not mounted at /sys/fs/cgroup",
+        ));
+    }
+    let membership = fs::read_to_string("/proc/self/cgroup")?;
+    let path = membership
+        .lines()
+        .find_map(|l| l.strip_prefix("0::"))
+        .ok_or_else(|| io::Error::new(io::ErrorKind::NotFound, "no cgroup v2 membership"))?;
+    let dir = Path::new(CGROUP_ROOT).join(path.trim_start_matches('/'));
+
+    let leaf = dir.join(format!("first_orchestrator_{}", std::process::id()));
+    fs::create_dir(&leaf)?;
+    fs::write(leaf.join("cgroup.procs"), "0")?;
+    fs::write(dir.join("cgroup.subtree_control"), "+memory +cpu")?;
+    // SAFETY: registers a function without arguments; atexit has no
+    // other preconditions.
+    if unsafe { libc::atexit(remove_leaf_at_exit) } != 0 {
+        eprintln!(
+            "[first] warning: cannot register removal of cgroup {}",
+            leaf.display()
+        );
+    }
+    Ok(Parent { dir, leaf })
+}
+
+/// Move the orchestrator back into its original cgroup and remove the
+/// leaf.
+extern "C" fn remove_leaf_at_exit() {
+    let Some(Ok(parent)) = PARENT.get() else {
+        return;
+    };
+    // A non-root cgroup distributing controllers may not hold processes
+    if fs::write(parent.dir.join("cgroup.procs"), "0").is_err() {
+        let _ = fs::write(parent.dir.join("cgroup.subtree_control"), "-memory -cpu");
+        let _ = fs::write(parent.dir.join("cgroup.procs"), "0");
+    }
+    let _ = fs::remove_dir(&parent.leaf);
+}
+
+/// A transient cgroup holding one child.
+pub(crate) struct Cgroup {
+    dir: PathBuf,
+    /// The memory limit of the child, for reports.
+    memory_max: Option<u64>,
+    /// `cgroup.procs`, opened before `fork` so the child only has to write.
+    _procs: File,
+}
+
+impl Cgroup {
+    /// Create a cgroup with `limits` for the next child and make `cmd`
+    /// join it.
+    ///
+    /// Returns `None` without limits, if cgroup v2 is unavailable, or (with
+    /// a warning) if the cgroup cannot be set up.
+    pub(crate) fn attach(cmd: &mut Command, limits: Option<&ResourceLimits>) -> Option<Self> {
+        let limits = limits?;
+        let parent = PARENT.get()?.as_ref().ok()?;
+        let id = NEXT_ID.fetch_add(1, Ordering::SeqCst);
+        let dir = parent
+            .dir
+            .join(format!("first_{}_{}", std::process::id(), id));
+        match Self::create(&dir, limits) {
+            Ok(procs) => {
+                let fd = procs.as_raw_fd();
+                // SAFETY: only write(2), which is async-signal-safe, runs
+                // between fork and exec, on an fd owned by the parent.
+                unsafe {
+                    cmd.pre_exec(move || {
+                        // "0" moves the writing process itself
+                        if libc::write(fd, b"0".as_ptr() as *const libc::c_void, 1) < 0 {
+                            return Err(io::Error::last_os_error());
+                        }
+                        Ok(())
+                    });
+                }
+                Some(Self {
+                    dir,
+                    memory_max: limits.memory_max,
+                    _procs: procs,
+                })
+            }
+            Err(e) => {
+                eprintln!(
+                    "[first] warning: cannot create cgroup {}: {}",
+                    dir.display(),
+                    e
+                );
+                let _ = fs::remove_dir(&dir);
+                None
+            }
+        }
+    }
+
+    fn create(dir: &Path, limits: &ResourceLimits) -> io::Result<File> {
+        fs::create_dir(dir)?;
+        if let Some(bytes) = limits.memory_max {
+            fs::write(dir.join("memory.max"), bytes.to_string())?;
+            // Keep the limit strict: no swapping instead of the OOM kill
+            let _ = fs::write(dir.join("memory.swap.max"), "0");
+        }
+        if let Some(percent) = limits.cpu_percent {
+            let quota = CPU_PERIOD_US * u64::from(percent) / 100;
+            fs::write(dir.join("cpu.max"), format!("{} {}", quota, CPU_PERIOD_US))?;
+        }
+        OpenOptions::new()
+            .write(true)
+            .open(dir.join("cgroup.procs"))
+    }
+
+    /// Whether the kernel OOM-killed a process in this cgroup.
+    pub(crate) fn oom_killed(&self) -> bool {
+        fs::read_to_string(self.dir.join("memory.events"))
+            .map(|events| oom_kills(&events) > 0)
+            .unwrap_or(false)
+    }
+
+    /// The configured memory limit, for reports.
+    pub(crate) fn memory_max(&self) -> Option<u64> {
+        self.memory_max
+    }
+}
+
+impl Drop for Cgroup {
+    fn drop(&mut self) {
+        // The child has exited; an empty cgroup can be removed
+        let _ = fs::remove_dir(&self.dir);
+    }
+}
+
+/// The `oom_kill` counter of a `memory.events` file.
+fn oom_kills(events: &str) -> u64 {
+    events
+        .lines()
+        .find_map(|l| l.strip_prefix("oom_kill "))
+        .and_then(|n| n.trim().parse().ok())
+        .unwrap_or(0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_oom_kills() {
+        let events = "low 0\nhigh 0\nmax 3\noom 1\noom_kill 1\noom_group_kill 0\n";
+        assert_eq!(oom_kills(events), 1);
+        assert_eq!(oom_kills("low 0\n"), 0);
+    }
+}
@@ -0,0 +1,308 @@
+//! In-memory state checkpoints.
+//!
+//! For engines whose in-memory state is the ground truth of what recovery
+//! must reconstruct, the workload serializes that state with
+//! [`checkpoint_memory()`] each time a commit is about to return. The last
+//! checkpoint before the crash is kept in a sidecar file in the metadata
+//! directory, where verify loads it with [`memory_checkpoint()`] and
+//! compares it against the recovered state.
+//!
+//! With the `serde` feature, `Env::checkpoint()` and `Env::restore()` do
+//! the same for typed values under a key, kept as JSON.
+//!
+//! `Env::expect()` records the keys the workload has durably committed,
+//! and `Env::expected()` hands verify the [`Checkpoint`] of those recorded
+//! before the crash: the keys recovery must not lose.
+
+use std::fs::{self, OpenOptions};
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+
+use crate::env::Env;
+use crate::rt::{self, Phase};
+
+/// Sidecar holding the checkpoint of crash point `target`.
+fn sidecar_name(target: &str) -> String {
+    format!("memory_checkpoint_{}", target)
+}
+
+/// Record `state` as the in-memory state recovery must reconstruct if the
+/// process crashes before the next checkpoint.
+///
+/// Call it right before a commit returns, once the commit is durable, with
+/// the engine's state serialized in any format the verify closure can
+/// reproduce from the recovered engine. Each call replaces the previous
+/// checkpoint; the one in place at the crash reaches verify through
+/// [`memory_checkpoint()`].
+///
+/// A no-op outside the EXECUTION phase.
+///
+/// # Example
+///
+/// ```ignore
+/// .run(|env| {
+///     let mut db = Db::open(env.path("db"));
+///     db.put("k", "v");
+///     db.commit();
+///     first::checkpoint_memory(db.dump());
+///     first::crash_point("after_commit");
+/// })
+/// .verify(|env, crash_info| {
+///     if crash_info.label == "after_commit" {
+///         let db = Db::open(env.path("db"));
+///         first::memory_checkpoint(env)
+///             .expect("checkpoint taken before the crash")
+///             .assert_matches(db.dump());
+///     }
+/// })
+/// ```
+pub fn checkpoint_memory(state: impl AsRef<[u8]>) {
+    if rt::runtime().phase != Phase::Execution {
+        return;
+    }
+    let (Ok(metadata_dir), Ok(target)) = (
+        std::env::var("FIRST_METADATA_DIR"),
+        std::env::var("FIRST_CRASH_TARGET"),
+    ) else {
+        return;
+    };
+    let path = Path::new(&metadata_dir).join(sidecar_name(&target));
+    let mut contents = format!("{}\n", rt::points_passed()).into_bytes();
+    contents.extend_from_slice(state.as_ref());
+
+    // Replace atomically, so the sidecar never holds a torn checkpoint
+    let tmp = PathBuf::from(format!("{}.tmp", path.display()));
+    if let Err(e) = fs::write(&tmp, &contents).and_then(|()| fs::rename(&tmp, &path)) {
+        panic!("cannot write memory checkpoint {}: {}", path.display(), e);
+    }
+}
+
+/// In-memory state recorded by [`checkpoint_memory()`] before a crash.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MemoryCheckpoint {
+    /// Number of crash points passed when the checkpoint was taken.
+    pub after_point: usize,
+    /// Serialized state, as passed to [`checkpoint_memory()`].
+    pub state: Vec<u8>,
+}
+
+impl MemoryCheckpoint {
+    /// Assert that `recovered`, serialized like the checkpoint, equals the
+    /// checkpointed state.
+    ///
+    /// # Panics
+    ///
+    /// Panics, showing both states, if they differ.
+    #[track_caller]
+    pub fn assert_matches(&self, recovered: impl AsRef<[u8]>) {
+        let recovered = recovered.as_ref();
+        if recovered == self.state {
+            return;
+        }
+        panic!(
+            "recovered state does not match the memory checkpoint taken after point {}\n  checkpoint: {}\n  recovered:  {}",
+            self.after_point,
+            String::from_utf8_lossy(&self.state).escape_debug(),
+            String::from_utf8_lossy(recovered).escape_debug()
+        );
+    }
+
+    /// Parse the contents of a sidecar file.
+    fn parse(contents: &[u8]) -> Option<Self> {
+        let newline = contents.iter().position(|&b| b == b'\n')?;
+        let after_point = std::str::from_utf8(&contents[..newline])
+            .ok()?
+            .parse()
+            .ok()?;
+        Some(Self {
+            after_point,
+            state: contents[newline + 1..].to_vec(),
+        })
+    }
+}
+
+/// Load the last memory checkpoint taken before the current crash point.
+///
+/// Returns `None` if the workload crashed before its first
+/// [`checkpoint_memory()`] call.
+pub fn memory_checkpoint(env: &Env) -> Option<MemoryCheckpoint> {
+    let target = env.crash_target()?;
+    let contents = fs::read(env.metadata_path(sidecar_name(&target))).ok()?;
+    MemoryCheckpoint::parse(&contents)
+}
+
+/// Sidecar holding the typed checkpoints of crash point `target`.
+#[cfg(feature = "serde")]
+fn values_sidecar_name(target: &str) -> String {
+    format!("checkpoints_{}.json", target)
+}
+
+/// Store `value` under `key` in the typed checkpoints of this EXECUTION
+/// child (`Env::checkpoint()`), replacing the sidecar atomically.
+#[cfg(feature = "serde")]
+pub(crate) fn store_value(env: &Env, key: &str, value: serde_json::Value) -> std::io::Result<()> {
+    if rt::runtime().phase != Phase::Execution {
+        return Ok(());
+    }
+    let Ok(target) = std::env::var("FIRST_CRASH_TARGET") else {
+        return Ok(());
+    };
+    let path = env.metadata_path(values_sidecar_name(&target));
+    let mut values = load_values(&path).unwrap_or_default();
+    values.insert(key.to_string(), value);
+    let json = serde_json::to_vec(&values).map_err(std::io::Error::other)?;
+    let tmp = PathBuf::from(format!("{}.tmp", path.display()));
+    fs::write(&tmp, json).and_then(|()| fs::rename(&tmp, &path))
+}
+
+/// The value last stored under `key` before the current crash point
+/// (`Env::restore()`).
+#[cfg(feature = "serde")]
+pub(crate) fn load_value(env: &Env, key: &str) -> Option<serde_json::Value> {
+    let target = env.crash_target()?;
+    load_values(&env.metadata_path(values_sidecar_name(&target)))?.remove(key)
+}
+
+/// Every typed checkpoint in a sidecar file.
+#[cfg(feature = "serde")]
+fn load_values(path: &Path) -> Option<serde_json::Map<String, serde_json::Value>> {
+    serde_json::from_slice(&fs::read(path).ok()?).ok()
+}
+
+/// Sidecar holding the keys `Env::expect()` recorded for crash point
+/// `target`.
+fn expected_sidecar_name(target: &str) -> String {
+    format!("expected_{}", target)
+}
+
+/// Keys the workload declared durably committed before the crash, with
+/// `Env::expect()`.
+///
+/// Returned by `Env::expected()` in verify. Every key in it was written
+/// and synced before the crash point, so recovery must keep all of them;
+/// keys committed later may or may not have survived.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Checkpoint {
+    /// Committed keys, in the order they were first recorded.
+    pub committed: Vec<String>,
+    /// Number of crash points passed at the last `Env::expect()` call, or
+    /// 0 if the workload crashed before its first one.
+    pub after_point: usize,
+}
+
+impl Checkpoint {
+    /// Whether `key` was committed before the crash.
+    pub fn contains(&self, key: &str) -> bool {
+        self.committed.iter().any(|k| k == key)
+    }
+
+    /// Assert that recovery kept every committed key, `present` telling
+    /// whether the recovered state has a key.
+    ///
+    /// # Panics
+    ///
+    /// Panics, listing them, if any committed key is missing.
+    #[track_caller]
+    pub fn assert_present(&self, present: impl Fn(&str) -> bool) {
+        let missing: Vec<&String> = self.committed.iter().filter(|k| !present(k)).collect();
+        assert!(
+            missing.is_empty(),
+            "{} of {} keys committed before the crash (last Env::expect() after point {}) are missing after recovery: {:?}",
+            missing.len(),
+            self.committed.len(),
+            self.after_point,
+            missing
+        );
+    }
+
+    /// Parse the contents of a sidecar file: one `after_point\tkey` line
+    /// per recorded key.
+    fn parse(contents: &str) -> Self {
+        let mut checkpoint = Self::default();
+        for line in contents.lines() {
+            let Some((after_point, key)) = line.split_once('\t') else {
+                continue;
+            };
+            let Ok(after_point) = after_point.parse() else {
+                continue;
+            };
+            checkpoint.after_point = after_point;
+            if !checkpoint.contains(key) {
+                checkpoint.committed.push(key.to_string());
+            }
+        }
+        checkpoint
+    }
+}
+
+/// Append `committed` to the expected keys of this EXECUTION child
+/// (`Env::expect()`).
+pub(crate) fn record_expected(env: &Env, committed: &[&str]) -> io::Result<()> {
+    if rt::runtime().phase != Phase::Execution {
+        return Ok(());
+    }
+    let Ok(target) = std::env::var("FIRST_CRASH_TARGET") else {
+        return Ok(());
+    };
+    if let Some(key) = committed.iter().find(|k| k.contains('\n')) {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidInput,
+            format!("expected key {:?} contains a newline", key),
+        ));
+    }
+    let after_point = rt::points_passed();
+    let lines: String = committed
+        .iter()
+        .map(|key| format!("{}\t{}\n", after_point, key))
+        .collect();
+    // One append, so a crash never leaves part of a call recorded
+    OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(env.metadata_path(expected_sidecar_name(&target)))?
+        .write_all(lines.as_bytes())
+}
+
+/// The keys recorded by `Env::expect()` before the current crash point
+/// (`Env::expected()`).
+pub(crate) fn load_expected(env: &Env) -> Checkpoint {
+    let Some(target) = env.crash_target() else {
+        return Checkpoint::default();
+    };
+    let contents =
+        fs::read_to_string(env.metadata_path(expected_sidecar_name(&target))).unwrap_or_default();
+    Checkpoint::parse(&contents)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_checkpoint() {
+        let checkpoint = MemoryCheckpoint::parse(b"3\nk=v\nk2=v2").unwrap();
+        assert_eq!(checkpoint.after_point, 3);
+        assert_eq!(checkpoint.state, b"k=v\nk2=v2");
+        checkpoint.assert_matches("k=v\nk2=v2");
+
+        assert_eq!(MemoryCheckpoint::parse(b"0\n").unwrap().state, b"");
+        assert!(MemoryCheckpoint::parse(b"no header").is_none());
+    }
+
+    #[test]
+    fn test_parse_expected() {
+        let checkpoint = Checkpoint::parse("1\tkey1\n1\tkey2\n3\tkey1\n3\tkey3\n");
+        assert_eq!(checkpoint.committed, ["key1", "key2", "key3"]);
+        assert_eq!(checkpoint.after_point, 3);
+        assert!(checkpoint.contains("key2"));
+        checkpoint.assert_present(|_| true);
+
+        assert_eq!(Checkpoint::parse(""), Checkpoint::default());
+    }
+
+    #[test]
+    #[should_panic(expected = "1 of 3 keys committed before the crash")]
+    fn test_assert_present_reports_missing() {
+        Checkpoint::parse("2\ta\n2\tb\n2\tc\n").assert_present(|key| key != "b");
+    }
+}
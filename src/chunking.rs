@@ -0,0 +1,147 @@
+//! Recovery under varying read granularity.
+//!
+//! With `TestBuilder::vary_read_chunking()`, every workspace left by a
+//! crash is verified once more per configured chunk size, each time on a
+//! copy. In those VERIFY children, readers opened with
+//! [`Env::open_chunked()`](crate::Env::open_chunked) return at most that
+//! many bytes per `read`, so record parsing that assumes whole records per
+//! read fails there. The original workspace is left untouched for the
+//! regular VERIFY child, where chunked readers read without a limit.
+
+use std::fs::File;
+use std::io::{self, Read};
+use std::path::{Path, PathBuf};
+
+use crate::bundle::copy_tree;
+use crate::cgroup::ResourceLimits;
+use crate::env::CrashInfo;
+use crate::orchestrator::{
+    ChildResult, FIRST_BASE_DIR, cleanup_work_dir, io_failure, spawn_child_with_crash_info,
+};
+
+/// Maximum bytes per `read` of every [`ChunkedReader`] in a VERIFY child.
+pub(crate) const ENV_READ_CHUNK: &str = "FIRST_READ_CHUNK";
+
+/// A file reader that returns data in chunks of a fixed maximum size.
+///
+/// Returned by [`Env::open_chunked()`](crate::Env::open_chunked). Each
+/// [`read()`](Read::read) returns at most the chunk size FIRST is currently
+/// verifying with, even if the caller's buffer is larger; outside those
+/// runs it reads like the underlying [`File`].
+#[derive(Debug)]
+pub struct ChunkedReader {
+    file: File,
+    chunk: Option<usize>,
+}
+
+impl ChunkedReader {
+    pub(crate) fn new(file: File) -> Self {
+        let chunk = std::env::var(ENV_READ_CHUNK)
+            .ok()
+            .and_then(|s| s.parse().ok())
+            .filter(|&n| n > 0);
+        Self { file, chunk }
+    }
+
+    /// Chunk size in effect, or `None` when reads are not split.
+    pub fn chunk_size(&self) -> Option<usize> {
+        self.chunk
+    }
+
+    /// The underlying file.
+    pub fn into_inner(self) -> File {
+        self.file
+    }
+}
+
+impl Read for ChunkedReader {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let len = self.chunk.map_or(buf.len(), |chunk| chunk.min(buf.len()));
+        self.file.read(&mut buf[..len])
+    }
+}
+
+/// Verify copies of the workspace of crash point `target`, once per size
+/// in `sizes`, with reads split into chunks of that size.
+///
+/// Returns why the crash point fails, if it does. The copy of a failing
+/// size is kept for inspection.
+#[allow(clippy::too_many_arguments)]
+pub(crate) fn check(
+    exe: &Path,
+    test_name: &Option<String>,
+    target: usize,
+    work_dir: &Path,
+    metadata_dir: &Path,
+    crash_info: &CrashInfo,
+    sizes: &[usize],
+    coverage_dir: Option<&Path>,
+    limits: Option<&ResourceLimits>,
+) -> Option<String> {
+    let base = PathBuf::from(FIRST_BASE_DIR)
+        .join("chunked")
+        .join(format!("point_{}", target));
+    cleanup_work_dir(&base);
+
+    for &size in sizes {
+        let dir = base.join(format!("chunk_{}", size));
+        if let Err(e) = copy_tree(work_dir, &dir) {
+            return Some(io_failure("copy workspace to", &dir, &e));
+        }
+        let failure = match spawn_child_with_crash_info(
+            exe,
+            test_name,
+            target,
+            &dir,
+            metadata_dir,
+            crash_info,
+            Some(size),
+            coverage_dir,
+            limits,
+            None,
+        ) {
+            ChildResult::Success => None,
+            ChildResult::Failed(code) => Some(format!("failed with exit code {}", code)),
+            ChildResult::Crashed(_) | ChildResult::Skipped(_) => {
+                Some("crashed unexpectedly".to_string())
+            }
+            ChildResult::TimedOut(limit) => Some(format!("timed out after {:?}", limit)),
+        };
+        if let Some(failure) = failure {
+            return Some(format!(
+                "verification with read chunk size {} {} (see {})",
+                size,
+                failure,
+                dir.display()
+            ));
+        }
+        cleanup_work_dir(&dir);
+    }
+
+    cleanup_work_dir(&base);
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_reads_are_split() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("log");
+        std::fs::write(&path, b"abcdefg").unwrap();
+
+        let mut reader = ChunkedReader {
+            file: File::open(&path).unwrap(),
+            chunk: Some(3),
+        };
+        let mut buf = [0u8; 16];
+        assert_eq!(reader.read(&mut buf).unwrap(), 3);
+        assert_eq!(&buf[..3], b"abc");
+        assert_eq!(reader.read(&mut buf[..2]).unwrap(), 2);
+        let mut rest = Vec::new();
+        reader.read_to_end(&mut rest).unwrap();
+        assert_eq!(rest, b"fg");
+    }
+}
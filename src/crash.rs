@@ -0,0 +1,326 @@
+//! Crash-time filesystem effects.
+//!
+//! Applied by the EXECUTION child to its workspace immediately before
+//! `SIGKILL`, to model artifacts a real crash can leave behind.
+
+use std::fs::{self, OpenOptions};
+use std::io::{self, Write};
+use std::ops::Range;
+use std::os::unix::fs::{FileExt, MetadataExt};
+use std::os::unix::io::AsRawFd;
+use std::path::{Path, PathBuf};
+
+use crate::env::{PartialFlush, PartialWrite};
+use crate::journal;
+use crate::test::Options;
+
+/// Unit in which a crash inside a barrier persists pending data.
+///
+/// The usual page size, fixed so that a seed persists the same pages on
+/// every platform.
+const FLUSH_PAGE: u64 = 4096;
+
+/// Apply every crash effect enabled in `options` to the workspace.
+///
+/// Errors are ignored: the process is about to be killed, and a failed
+/// effect must never prevent the crash itself.
+pub(crate) fn apply_effects(options: &Options, work_dir: &Path) {
+    // Before the unsynced tails are lost: a partially flushed file keeps
+    // what reached the disk
+    let _ = apply_partial_flush(work_dir);
+    if options.drop_unsynced {
+        let _ = restore_synced_contents(work_dir);
+    } else if options.lose_unsynced_writes {
+        let _ = discard_unsynced_tails(work_dir);
+    }
+    if let Some(fill) = options.block_padding {
+        let _ = pad_to_block_boundary(work_dir, fill);
+    }
+    if options.lose_unsynced_mmap {
+        crate::mmap::discard_unsynced();
+    }
+}
+
+/// Record every regular file already in `work_dir` as synced at its
+/// current length.
+///
+/// A RECOVER child starts from what an earlier crash left on disk, which
+/// is durable by definition; only its own unsynced writes may be lost.
+pub(crate) fn mark_durable(work_dir: &Path) {
+    for path in regular_files(work_dir).unwrap_or_default() {
+        if let Ok(file) = fs::File::open(&path) {
+            journal::record_synced(&file);
+        }
+    }
+}
+
+/// Tear the most recent unsynced write logged by the `Env` write shims
+/// (`TestBuilder::torn_last_write()`).
+///
+/// The first half of the write is kept. A write that ends the file is
+/// cut there, leaving a short tail; one in the middle of the file has
+/// its second half zeroed. The tear is journaled as the partial write,
+/// unless the crash point already interrupted one, and is called before
+/// the crash metadata is written so that verify sees it.
+pub(crate) fn tear_last_write(work_dir: &Path) {
+    let Some(write) = journal::with(|j| j.writes.last().copied()) else {
+        return;
+    };
+    let Some(path) = regular_files(work_dir)
+        .unwrap_or_default()
+        .into_iter()
+        .find(|path| {
+            fs::metadata(path).is_ok_and(|m| m.dev() == write.dev && m.ino() == write.ino)
+        })
+    else {
+        return;
+    };
+    let Ok(file) = OpenOptions::new().write(true).open(&path) else {
+        return;
+    };
+    let Ok(len) = file.metadata().map(|m| m.len()) else {
+        return;
+    };
+    let kept = write.len / 2;
+    let end = write.offset + write.len;
+    let torn = if end >= len {
+        file.set_len(write.offset + kept)
+    } else {
+        file.write_all_at(&vec![0; (write.len - kept) as usize], write.offset + kept)
+    };
+    if torn.is_err() {
+        return;
+    }
+    journal::with(|j| {
+        j.partial_write.get_or_insert_with(|| PartialWrite {
+            file: path.strip_prefix(work_dir).unwrap_or(&path).to_path_buf(),
+            offset: write.offset,
+            written: kept as usize,
+            len: write.len as usize,
+        });
+    });
+}
+
+/// Choose which pending pages of `files` a crash inside a barrier
+/// persists.
+///
+/// A file's pending data is everything past its last synced length, in
+/// pages aligned to [`FLUSH_PAGE`]; each page is persisted or not by a
+/// SplitMix64 stream seeded with `seed`. Files without pending data are
+/// left out.
+pub(crate) fn plan_partial_flush(
+    work_dir: &Path,
+    files: &[PathBuf],
+    seed: u64,
+) -> Vec<PartialFlush> {
+    let synced = journal::with(|j| j.synced.clone());
+    let mut state = seed;
+    let mut coin = || {
+        state = state.wrapping_add(0x9e37_79b9_7f4a_7c15);
+        let mut z = state;
+        z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+        (z ^ (z >> 31)) & 1 == 1
+    };
+
+    let mut flushes = Vec::new();
+    for file in files {
+        let Ok(meta) = fs::metadata(work_dir.join(file)) else {
+            continue;
+        };
+        let synced_len = synced
+            .iter()
+            .find(|s| s.dev == meta.dev() && s.ino == meta.ino())
+            .map_or(0, |s| s.len);
+        if meta.len() <= synced_len {
+            continue;
+        }
+        let persisted = pending_pages(synced_len, meta.len())
+            .filter(|_| coin())
+            .fold(Vec::<Range<u64>>::new(), |mut ranges, page| {
+                match ranges.last_mut() {
+                    Some(last) if last.end == page.start => last.end = page.end,
+                    _ => ranges.push(page),
+                }
+                ranges
+            });
+        flushes.push(PartialFlush {
+            file: file.clone(),
+            synced_len,
+            len: meta.len(),
+            persisted,
+        });
+    }
+    flushes
+}
+
+/// The pages of `synced_len..len`, the first and last clipped to it.
+fn pending_pages(synced_len: u64, len: u64) -> impl Iterator<Item = Range<u64>> {
+    let mut start = synced_len;
+    std::iter::from_fn(move || {
+        if start >= len {
+            return None;
+        }
+        let end = ((start / FLUSH_PAGE + 1) * FLUSH_PAGE).min(len);
+        let page = start..end;
+        start = end;
+        Some(page)
+    })
+}
+
+/// Apply the partial flush journaled by a crash inside a barrier.
+///
+/// Each file ends after its last persisted page, and the pending pages
+/// before it that were not persisted are zeroed. The result counts as
+/// synced, so `lose_unsynced_writes()` keeps it.
+fn apply_partial_flush(work_dir: &Path) -> io::Result<()> {
+    let flushes = journal::with(|j| j.partial_flush.clone());
+    for flush in flushes {
+        let file = OpenOptions::new()
+            .write(true)
+            .open(work_dir.join(&flush.file))?;
+        let end = flush.persisted.last().map_or(flush.synced_len, |r| r.end);
+        let mut offset = flush.synced_len;
+        for persisted in flush.persisted.iter().chain([&(end..end)]) {
+            if persisted.start > offset {
+                file.write_all_at(&vec![0; (persisted.start - offset) as usize], offset)?;
+            }
+            offset = persisted.end;
+        }
+        file.set_len(end)?;
+        journal::record_synced(&file);
+    }
+    Ok(())
+}
+
+/// Truncate every regular file in `work_dir` to its last synced length.
+///
+/// Sync lengths come from the journal, keyed by inode, so a file synced
+/// under one name and renamed afterwards keeps its data. Files never
+/// synced are truncated to zero bytes.
+fn discard_unsynced_tails(work_dir: &Path) -> io::Result<()> {
+    let synced = journal::with(|j| j.synced.clone());
+    for path in regular_files(work_dir)? {
+        let Ok(meta) = fs::metadata(&path) else {
+            continue;
+        };
+        let durable = synced
+            .iter()
+            .find(|s| s.dev == meta.dev() && s.ino == meta.ino())
+            .map_or(0, |s| s.len);
+        if meta.len() > durable {
+            let _ = OpenOptions::new()
+                .write(true)
+                .open(&path)
+                .and_then(|f| f.set_len(durable));
+        }
+    }
+    Ok(())
+}
+
+/// Roll every regular file in `work_dir` back to its contents at its
+/// last sync.
+///
+/// Contents come from the journal, keyed by inode like the synced
+/// lengths. Files never synced are truncated to zero bytes.
+fn restore_synced_contents(work_dir: &Path) -> io::Result<()> {
+    let synced = journal::with(|j| j.synced_data.clone());
+    for path in regular_files(work_dir)? {
+        let Ok(meta) = fs::metadata(&path) else {
+            continue;
+        };
+        let durable = synced
+            .iter()
+            .find(|s| s.dev == meta.dev() && s.ino == meta.ino())
+            .map_or(&[][..], |s| &s.data);
+        if fs::read(&path).is_ok_and(|current| current == durable) {
+            continue;
+        }
+        let _ = OpenOptions::new().write(true).open(&path).and_then(|f| {
+            f.write_all_at(durable, 0)?;
+            f.set_len(durable.len() as u64)
+        });
+    }
+    Ok(())
+}
+
+/// Extend every regular file in `work_dir` to its next block boundary.
+///
+/// Filesystems may expose a crashed file whose size was rounded up to the
+/// block size, with zeroes or stale data after the last real write. The
+/// block size is taken from each file's `st_blksize`.
+fn pad_to_block_boundary(work_dir: &Path, fill: u8) -> io::Result<()> {
+    for path in regular_files(work_dir)? {
+        let meta = fs::metadata(&path)?;
+        let block = meta.blksize().max(1);
+        let rem = meta.len() % block;
+        if rem == 0 {
+            continue;
+        }
+        let padding = vec![fill; (block - rem) as usize];
+        OpenOptions::new()
+            .append(true)
+            .open(&path)?
+            .write_all(&padding)?;
+    }
+    Ok(())
+}
+
+/// Evict the workspace's cached pages so verify reads from the disk.
+///
+/// Called by the orchestrator between the crash and verify. Each regular
+/// file gets `posix_fadvise(POSIX_FADV_DONTNEED)`; if that is refused, a
+/// system-wide `/proc/sys/vm/drop_caches` is attempted instead, which
+/// requires root.
+///
+/// Only clean pages can be evicted. Data the workload wrote but never
+/// synced is still dirty and stays cached until the kernel writes it back.
+pub(crate) fn drop_page_cache(work_dir: &Path) -> io::Result<()> {
+    let mut refused = None;
+    for path in regular_files(work_dir)? {
+        let file = fs::File::open(&path)?;
+        let rc = unsafe { libc::posix_fadvise(file.as_raw_fd(), 0, 0, libc::POSIX_FADV_DONTNEED) };
+        if rc != 0 {
+            refused = Some(io::Error::from_raw_os_error(rc));
+        }
+    }
+    match refused {
+        Some(err) => fs::write("/proc/sys/vm/drop_caches", b"1").map_err(|_| err),
+        None => Ok(()),
+    }
+}
+
+/// Recursively list regular files under `dir`, without following symlinks.
+pub(crate) fn regular_files(dir: &Path) -> io::Result<Vec<PathBuf>> {
+    let mut files = Vec::new();
+    let mut stack = vec![dir.to_path_buf()];
+    while let Some(dir) = stack.pop() {
+        for entry in fs::read_dir(&dir)? {
+            let entry = entry?;
+            let file_type = entry.file_type()?;
+            if file_type.is_dir() {
+                stack.push(entry.path());
+            } else if file_type.is_file() {
+                files.push(entry.path());
+            }
+        }
+    }
+    files.sort();
+    Ok(files)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_pending_pages() {
+        let pages: Vec<_> = pending_pages(100, 9000).collect();
+        assert_eq!(pages, vec![100..4096, 4096..8192, 8192..9000]);
+        assert_eq!(
+            pending_pages(4096, 8192).collect::<Vec<_>>(),
+            vec![4096..8192]
+        );
+        assert_eq!(pending_pages(10, 10).count(), 0);
+    }
+}
@@ -0,0 +1,125 @@
+//! Crash metadata encoded with serde.
+//!
+//! With the `serde` feature, the EXECUTION child reports a crash (or a
+//! skipped point) as one `{"event":"crash",...}` line on stderr that holds
+//! the whole [`CrashInfo`] under `info`, and the orchestrator decodes it
+//! with `serde_json`. Labels, paths and sites may then contain any
+//! character, including quotes and braces. Barriers and partial flushes
+//! travel inside `info` instead of as separate events.
+//!
+//! Without the feature, the hand-rolled encoding of `rt` and the parser
+//! in `orchestrator` are used; the orchestrator also falls back to that
+//! parser for any line this module cannot decode.
+
+use serde::{Deserialize, Serialize};
+
+use crate::env::CrashInfo;
+
+/// A crash or skip event as written by the child.
+#[derive(Serialize)]
+struct Event<'a> {
+    /// `"crash"` or `"skipped"`, first so the line keeps its prefix.
+    event: &'a str,
+    info: &'a CrashInfo,
+    seed: Option<u64>,
+    work_dir: Option<&'a str>,
+    run_tag: Option<&'a str>,
+}
+
+/// The part of an event the orchestrator reads back.
+#[derive(Deserialize)]
+struct ParsedEvent {
+    info: CrashInfo,
+}
+
+/// Encode `info` as a one-line `event` (`"crash"` or `"skipped"`).
+pub(crate) fn encode(
+    event: &str,
+    info: &CrashInfo,
+    seed: Option<u64>,
+    work_dir: Option<&str>,
+    run_tag: Option<&str>,
+) -> Option<String> {
+    serde_json::to_string(&Event {
+        event,
+        info,
+        seed,
+        work_dir,
+        run_tag,
+    })
+    .ok()
+}
+
+/// Decode a line written by [`encode()`].
+pub(crate) fn decode(line: &str) -> Option<CrashInfo> {
+    serde_json::from_str::<ParsedEvent>(line)
+        .ok()
+        .map(|event| event.info)
+}
+
+/// Serde adapter for [`CrashInfo::site`], whose file name is `'static`.
+///
+/// Decoding leaks the file name, as `CrashInfo::set_site()` does; this
+/// happens at most once per decoded crash.
+pub(crate) mod site {
+    use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+    pub(crate) fn serialize<S: Serializer>(
+        site: &Option<(&'static str, u32)>,
+        serializer: S,
+    ) -> Result<S::Ok, S::Error> {
+        site.serialize(serializer)
+    }
+
+    pub(crate) fn deserialize<'de, D: Deserializer<'de>>(
+        deserializer: D,
+    ) -> Result<Option<(&'static str, u32)>, D::Error> {
+        let site = Option::<(String, u32)>::deserialize(deserializer)?;
+        Ok(site.map(|(file, line)| (&*Box::leak(file.into_boxed_str()), line)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::env::{BarrierKind, BarrierRecord, PartialWrite};
+    use std::path::PathBuf;
+
+    #[test]
+    fn test_round_trip() {
+        let mut info = CrashInfo::new(7, r#"commit "a,{b}" \ done"#.to_string());
+        info.site = Some(("src/wal.rs", 42));
+        info.history = vec![3, 7];
+        info.elapsed = Some(std::time::Duration::from_micros(1500));
+        info.partial_write = Some(PartialWrite {
+            file: PathBuf::from("wal,{1}.log"),
+            offset: 8,
+            written: 4,
+            len: 8,
+        });
+        info.barriers = vec![BarrierRecord {
+            kind: BarrierKind::Fdatasync,
+            file: Some(PathBuf::from("wal")),
+            after_point: 2,
+            range: Some(0..4096),
+        }];
+
+        let line = encode("crash", &info, Some(1), Some("/tmp/first/run_7"), None).unwrap();
+        assert!(line.starts_with(r#"{"event":"crash""#));
+        assert!(!line.contains('\n'));
+
+        let decoded = decode(&line).unwrap();
+        assert_eq!(decoded.point_id, 7);
+        assert_eq!(decoded.label, info.label);
+        assert_eq!(decoded.site, info.site);
+        assert_eq!(decoded.history, info.history);
+        assert_eq!(decoded.elapsed, info.elapsed);
+        assert_eq!(decoded.partial_write, info.partial_write);
+        assert_eq!(decoded.barriers, info.barriers);
+    }
+
+    #[test]
+    fn test_decode_rejects_legacy_line() {
+        assert!(decode(r#"{"event":"crash","point_id":5,"label":"a"}"#).is_none());
+    }
+}
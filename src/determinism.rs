@@ -0,0 +1,130 @@
+//! Crash schedule determinism checks.
+//!
+//! Crash point IDs only identify the same point in every child if the
+//! workload reaches its crash points in the same order each time. With
+//! `TestBuilder::assert_deterministic()`, the labels of the DISCOVER run
+//! become the reference schedule, kept in the metadata directory, and
+//! every EXECUTION child checks each crash point it reaches against it.
+//! A workload whose control flow depends on the clock or on randomness
+//! then fails at the first divergence, instead of crashing at a point
+//! other than the one reported and reproduced.
+
+use std::fs;
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+use std::sync::OnceLock;
+
+use crate::discover::DiscoveredPoint;
+
+/// Reference schedule of the sweep in `metadata_dir`.
+fn schedule_path(metadata_dir: &Path) -> PathBuf {
+    metadata_dir.join("schedule")
+}
+
+/// Write the discovered labels as the reference schedule.
+pub(crate) fn write_reference(metadata_dir: &Path, points: &[DiscoveredPoint]) -> io::Result<()> {
+    let schedule: String = points
+        .iter()
+        .map(|point| format!("{}\n", escape(&point.label)))
+        .collect();
+    fs::write(schedule_path(metadata_dir), schedule)
+}
+
+/// Check that crash point `point_id` of an EXECUTION child has the label
+/// of the reference schedule, failing the workload if not.
+///
+/// A no-op without a reference schedule.
+pub(crate) fn check(point_id: usize, label: &str) {
+    static REFERENCE: OnceLock<Option<Vec<String>>> = OnceLock::new();
+    let reference = REFERENCE.get_or_init(|| {
+        let dir = std::env::var_os("FIRST_METADATA_DIR")?;
+        let schedule = fs::read_to_string(schedule_path(Path::new(&dir))).ok()?;
+        Some(schedule.lines().map(unescape).collect())
+    });
+    let Some(reference) = reference else {
+        return;
+    };
+    if let Some(message) = divergence(reference, point_id, label) {
+        report(&message);
+    }
+}
+
+/// Describe how crash point `point_id` labelled `label` departs from
+/// `reference`, if it does.
+fn divergence(reference: &[String], point_id: usize, label: &str) -> Option<String> {
+    match reference.get(point_id - 1) {
+        Some(expected) if expected == label => None,
+        Some(expected) => Some(format!(
+            "nondeterministic crash schedule: expected label \"{}\" at point {}, saw \"{}\"",
+            expected, point_id, label
+        )),
+        None => Some(format!(
+            "nondeterministic crash schedule: expected {} crash points, saw \"{}\" at point {}",
+            reference.len(),
+            label,
+            point_id
+        )),
+    }
+}
+
+/// Fail the workload with `message`, like an expected-unreachable point.
+pub(crate) fn report(message: &str) -> ! {
+    let message = format!("[first] error: {}", message);
+    // Raw stderr: libtest captures eprintln! output in the child
+    let mut stderr = io::stderr().lock();
+    let _ = stderr.write_all(message.as_bytes());
+    let _ = stderr.write_all(b"\n");
+    let _ = stderr.flush();
+    drop(stderr);
+    panic!("{}", message);
+}
+
+/// Keep a label on one line of the schedule.
+fn escape(label: &str) -> String {
+    label.replace('\\', "\\\\").replace('\n', "\\n")
+}
+
+/// Reverse [`escape()`].
+fn unescape(line: &str) -> String {
+    let mut label = String::with_capacity(line.len());
+    let mut chars = line.chars();
+    while let Some(c) = chars.next() {
+        if c != '\\' {
+            label.push(c);
+            continue;
+        }
+        match chars.next() {
+            Some('n') => label.push('\n'),
+            Some(other) => label.push(other),
+            None => label.push('\\'),
+        }
+    }
+    label
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_divergence() {
+        let reference = vec!["open".to_string(), "write".to_string()];
+        assert_eq!(divergence(&reference, 1, "open"), None);
+        assert_eq!(divergence(&reference, 2, "write"), None);
+        assert_eq!(
+            divergence(&reference, 2, "sync").unwrap(),
+            "nondeterministic crash schedule: expected label \"write\" at point 2, saw \"sync\""
+        );
+        assert_eq!(
+            divergence(&reference, 3, "sync").unwrap(),
+            "nondeterministic crash schedule: expected 2 crash points, saw \"sync\" at point 3"
+        );
+    }
+
+    #[test]
+    fn test_escape_round_trip() {
+        for label in ["plain", "two\nlines", "back\\slash\\n"] {
+            assert_eq!(unescape(&escape(label)), label);
+        }
+    }
+}
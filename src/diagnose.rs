@@ -0,0 +1,211 @@
+//! Verify panic diagnosis.
+//!
+//! Classifies panics raised by verify closures so failure reports can tell
+//! an invariant violation apart from a crash-damaged file tripping up the
+//! verifier's own read or parse code.
+
+use std::backtrace::Backtrace;
+use std::io::Write;
+use std::panic::{self, AssertUnwindSafe, PanicHookInfo};
+use std::sync::{Arc, Mutex};
+use std::thread;
+
+use crate::env::CrashInfo;
+
+/// What a verify panic most likely means.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum PanicKind {
+    /// An explicit `assert!`-family failure: the invariant was violated.
+    Invariant,
+    /// Reading or decoding a file failed, typically because the crash left
+    /// it truncated or torn (e.g. `read_to_string().unwrap()` on bad UTF-8).
+    DamagedRead,
+    /// Any other panic.
+    Other,
+}
+
+impl PanicKind {
+    fn describe(self) -> &'static str {
+        match self {
+            PanicKind::Invariant => "invariant assertion failed",
+            PanicKind::DamagedRead => {
+                "reading or parsing a file failed; the crash may have left it torn or \
+                 truncated. If partial data is a legal post-crash state, recovery must \
+                 tolerate it instead of unwrapping"
+            }
+            PanicKind::Other => "verify closure panicked",
+        }
+    }
+}
+
+/// Panic captured by the hook: message, location, and classification.
+struct CapturedPanic {
+    message: String,
+    location: Option<String>,
+    kind: PanicKind,
+}
+
+/// A panic hook, as returned by `panic::take_hook()`.
+type PanicHook = dyn Fn(&PanicHookInfo<'_>) + Sync + Send + 'static;
+
+/// Most recent panic captured while running a verify closure.
+static LAST_PANIC: Mutex<Option<CapturedPanic>> = Mutex::new(None);
+
+/// Run a verify closure, reporting a classified diagnosis if it panics.
+///
+/// The panic is re-raised after reporting so the VERIFY phase still fails
+/// exactly as it would without the wrapper.
+pub(crate) fn run_verify<F: FnOnce()>(crash_info: &CrashInfo, f: F) {
+    let previous_hook: Arc<PanicHook> = panic::take_hook().into();
+    let hook = Arc::clone(&previous_hook);
+    let verify_thread = thread::current().id();
+    panic::set_hook(Box::new(move |info| {
+        // Panics of other threads are not the verify closure's
+        if thread::current().id() == verify_thread {
+            let message = payload_message(info.payload());
+            let location = info
+                .location()
+                .map(|l| format!("{}:{}", l.file(), l.line()));
+            let backtrace = Backtrace::force_capture().to_string();
+            let kind = classify(&message, &backtrace);
+            if let Ok(mut last) = LAST_PANIC.lock() {
+                *last = Some(CapturedPanic {
+                    message,
+                    location,
+                    kind,
+                });
+            }
+        }
+        hook(info);
+    }));
+
+    let result = panic::catch_unwind(AssertUnwindSafe(f));
+
+    // Reinstate the hook that was installed before, e.g. the user's own.
+    let _ = panic::take_hook();
+    panic::set_hook(Box::new(move |info| previous_hook(info)));
+
+    if let Err(payload) = result {
+        if let Some(captured) = LAST_PANIC.lock().ok().and_then(|mut l| l.take()) {
+            report(crash_info, &captured);
+        }
+        panic::resume_unwind(payload);
+    }
+}
+
+/// Like [`run_verify()`], for verify run in the orchestrator by
+/// `verify_in_process()`.
+///
+/// The orchestrator's panic hook is left alone; the panic is classified
+/// from its payload alone, without its location or backtrace.
+pub(crate) fn run_verify_in_process<F: FnOnce()>(crash_info: &CrashInfo, f: F) {
+    if let Err(payload) = panic::catch_unwind(AssertUnwindSafe(f)) {
+        let message = payload_message(payload.as_ref());
+        let captured = CapturedPanic {
+            kind: classify(&message, ""),
+            message,
+            location: None,
+        };
+        report(crash_info, &captured);
+        panic::resume_unwind(payload);
+    }
+}
+
+/// Report a classified verify panic.
+fn report(crash_info: &CrashInfo, captured: &CapturedPanic) {
+    // Write to the raw stderr handle: eprintln! would be swallowed by
+    // libtest's output capture in the child process.
+    let report = format!(
+        "[first] verify panic at crash point {} (\"{}\"): {}\n[first] panic: {}{}\n",
+        crash_info.point_id,
+        crash_info.label,
+        captured.kind.describe(),
+        captured.message,
+        captured
+            .location
+            .as_ref()
+            .map_or(String::new(), |location| format!(" at {}", location))
+    );
+    let mut stderr = std::io::stderr().lock();
+    let _ = stderr.write_all(report.as_bytes());
+    let _ = stderr.flush();
+}
+
+/// Classify a panic from its message and captured backtrace.
+pub(crate) fn classify(message: &str, backtrace: &str) -> PanicKind {
+    const DAMAGED_MESSAGES: &[&str] = &[
+        "did not contain valid UTF-8",
+        "invalid utf-8",
+        "Utf8Error",
+        "UnexpectedEof",
+        "failed to fill whole buffer",
+        "InvalidData",
+        "ParseIntError",
+        "ParseFloatError",
+        "invalid digit",
+    ];
+    const DAMAGED_FRAMES: &[&str] = &[
+        "std::fs::read",
+        "std::io::Read",
+        "std::io::BufRead",
+        "core::str::converts::from_utf8",
+        "core::str::<impl str>::parse",
+    ];
+
+    if message.starts_with("assertion") {
+        return PanicKind::Invariant;
+    }
+    if DAMAGED_MESSAGES.iter().any(|m| message.contains(m)) {
+        return PanicKind::DamagedRead;
+    }
+    if message.contains("called `Result::unwrap()` on an `Err` value")
+        && DAMAGED_FRAMES.iter().any(|f| backtrace.contains(f))
+    {
+        return PanicKind::DamagedRead;
+    }
+    if message.to_lowercase().contains("invariant violation") {
+        return PanicKind::Invariant;
+    }
+    PanicKind::Other
+}
+
+/// Extract the message from a panic payload.
+pub(crate) fn payload_message(payload: &(dyn std::any::Any + Send)) -> String {
+    if let Some(s) = payload.downcast_ref::<&str>() {
+        s.to_string()
+    } else if let Some(s) = payload.downcast_ref::<String>() {
+        s.clone()
+    } else {
+        "<non-string panic payload>".to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_classify_assertion() {
+        let kind = classify("assertion `left == right` failed\n  left: 1\n right: 2", "");
+        assert_eq!(kind, PanicKind::Invariant);
+    }
+
+    #[test]
+    fn test_classify_bad_utf8() {
+        let message = "called `Result::unwrap()` on an `Err` value: Error { kind: InvalidData, \
+                       message: \"stream did not contain valid UTF-8\" }";
+        assert_eq!(classify(message, ""), PanicKind::DamagedRead);
+    }
+
+    #[test]
+    fn test_classify_unwrap_in_read_frame() {
+        let message = "called `Result::unwrap()` on an `Err` value: Custom";
+        let backtrace = "   3: std::fs::read_to_string::inner\n   4: my_test::verify";
+        assert_eq!(classify(message, backtrace), PanicKind::DamagedRead);
+    }
+
+    #[test]
+    fn test_classify_other() {
+        assert_eq!(classify("boom", ""), PanicKind::Other);
+    }
+}
@@ -0,0 +1,464 @@
+//! Crash point discovery.
+//!
+//! Runs the workload once in the DISCOVER phase, without crashing, to
+//! enumerate every crash point before the sweep starts. Results are cached
+//! per test binary so repeated invocations skip the extra run.
+
+use std::collections::hash_map::DefaultHasher;
+use std::fs;
+use std::hash::{Hash, Hasher};
+use std::io::{BufRead, BufReader, Write};
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
+use std::time::UNIX_EPOCH;
+
+use crate::orchestrator;
+use crate::test::Options;
+use crate::timeout::Watchdog;
+
+/// Forces rediscovery even when a valid cache entry exists.
+const ENV_REDISCOVER: &str = "FIRST_REDISCOVER";
+
+/// Set to `1` to list the crash points instead of sweeping them.
+pub(crate) const ENV_LIST_POINTS: &str = "FIRST_LIST_POINTS";
+
+/// A crash point enumerated by the DISCOVER phase.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct DiscoveredPoint {
+    /// The 1-indexed crash point ID.
+    pub(crate) point_id: usize,
+    /// The label passed to `crash_point()`.
+    pub(crate) label: String,
+    /// The `file:line` call site, for `crash_point!` points.
+    pub(crate) site: Option<String>,
+}
+
+/// Enumerate the crash points of `test_name`, using the cache when valid.
+///
+/// The cache lives under `base_dir` and is keyed by the executable path and
+/// test name. It is invalidated whenever the executable's size or
+/// modification time changes, i.e. whenever the test binary is rebuilt.
+/// Set `FIRST_REDISCOVER=1` to ignore it.
+///
+/// Returns `None` if the DISCOVER child could not be run or failed.
+pub(crate) fn discover(
+    exe: &Path,
+    test_name: &Option<String>,
+    base_dir: &Path,
+    options: &Options,
+) -> Option<Vec<DiscoveredPoint>> {
+    let cache_path = cache_path(exe, test_name, base_dir);
+    let fingerprint = binary_fingerprint(exe);
+
+    if std::env::var(ENV_REDISCOVER).is_err()
+        && let Some(fingerprint) = &fingerprint
+        && let Some(points) = read_cache(&cache_path, fingerprint)
+    {
+        return Some(points);
+    }
+
+    let points = points_of(&run_discover_child(exe, test_name, base_dir, options)?);
+
+    if let Some(fingerprint) = &fingerprint {
+        // Caching is best-effort; a failed write only costs a rerun.
+        let _ = write_cache(&cache_path, fingerprint, &points);
+    }
+
+    Some(points)
+}
+
+/// An entry of the DISCOVER timeline: a crash point or an instrumented
+/// filesystem operation, in execution order.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) enum TimelineEntry {
+    Point(DiscoveredPoint),
+    /// An `Env` / `atomic_write()` operation: kind (`write`, `fsync`,
+    /// `fdatasync`, `rename`) and its workspace-relative target.
+    Op {
+        kind: String,
+        target: String,
+    },
+}
+
+/// Run the workload in DISCOVER phase and return its full timeline.
+///
+/// Unlike [`discover()`], never uses the cache: operations are not cached.
+pub(crate) fn timeline(
+    exe: &Path,
+    test_name: &Option<String>,
+    base_dir: &Path,
+    options: &Options,
+) -> Option<Vec<TimelineEntry>> {
+    run_discover_child(exe, test_name, base_dir, options)
+}
+
+/// The crash points of a timeline.
+pub(crate) fn points_of(timeline: &[TimelineEntry]) -> Vec<DiscoveredPoint> {
+    timeline
+        .iter()
+        .filter_map(|entry| match entry {
+            TimelineEntry::Point(point) => Some(point.clone()),
+            TimelineEntry::Op { .. } => None,
+        })
+        .collect()
+}
+
+/// Print the crash points of a `list_points()` dry run.
+pub(crate) fn print_points(points: &[DiscoveredPoint]) {
+    // Raw stderr: eprint! is captured by libtest on the test thread.
+    let mut stderr = std::io::stderr().lock();
+    let _ = stderr.write_all(format_points(points).as_bytes());
+    let _ = stderr.flush();
+}
+
+/// One line per crash point: its ID, label and call site, if known.
+///
+/// The format is kept stable so listings can be diffed between builds.
+fn format_points(points: &[DiscoveredPoint]) -> String {
+    let mut out = format!("[first] {} crash points:\n", points.len());
+    for point in points {
+        out.push_str(&format!("  {} \"{}\"", point.point_id, point.label));
+        if let Some(site) = &point.site {
+            out.push_str(&format!(" {}", site));
+        }
+        out.push('\n');
+    }
+    out
+}
+
+/// Distinct `crash_point!` call sites, in order of first occurrence.
+pub(crate) fn distinct_sites(points: &[DiscoveredPoint]) -> Vec<String> {
+    let mut sites: Vec<String> = Vec::new();
+    for site in points.iter().filter_map(|p| p.site.as_ref()) {
+        if !sites.contains(site) {
+            sites.push(site.clone());
+        }
+    }
+    sites
+}
+
+/// Distinct crash point labels, in order of first occurrence.
+pub(crate) fn distinct_labels(points: &[DiscoveredPoint]) -> Vec<String> {
+    let mut labels: Vec<String> = Vec::new();
+    for point in points {
+        if !labels.contains(&point.label) {
+            labels.push(point.label.clone());
+        }
+    }
+    labels
+}
+
+/// Spawn the DISCOVER child and collect its timeline events.
+fn run_discover_child(
+    exe: &Path,
+    test_name: &Option<String>,
+    base_dir: &Path,
+    options: &Options,
+) -> Option<Vec<TimelineEntry>> {
+    let work_dir = base_dir.join("discover");
+    let _ = fs::remove_dir_all(&work_dir);
+    if let Err(e) = orchestrator::create_work_dir(&work_dir, options) {
+        eprintln!(
+            "[first] error: {}",
+            orchestrator::io_failure("create", &work_dir, &e)
+        );
+        return None;
+    }
+
+    let mut cmd = Command::new(exe);
+    cmd.env("FIRST_PHASE", "DISCOVER");
+    if let Some(seed) = crate::rt::run_seed() {
+        cmd.env(crate::rt::ENV_SEED, seed.to_string());
+    }
+    cmd.env(
+        crate::invocation::ENV_INVOCATION,
+        crate::invocation::env_value(),
+    );
+    cmd.env("FIRST_WORK_DIR", work_dir.to_string_lossy().to_string());
+
+    orchestrator::harness_args(&mut cmd, test_name);
+
+    cmd.stderr(Stdio::piped());
+    cmd.stdout(Stdio::null());
+
+    let mut child = match orchestrator::spawn_with_retry(&mut cmd) {
+        Ok(c) => c,
+        Err(e) => {
+            eprintln!("[first] error: cannot spawn discover child: {}", e);
+            return None;
+        }
+    };
+
+    let watchdog = Watchdog::start(child.id(), crate::timeout::limit());
+    let points = child
+        .stderr
+        .take()
+        .map(parse_timeline_events)
+        .unwrap_or_default();
+
+    let status = child.wait().ok()?;
+    let _ = fs::remove_dir_all(&work_dir);
+
+    if watchdog.is_some_and(Watchdog::finish) {
+        eprintln!(
+            "[first] error: discover run timed out after {:?}",
+            crate::timeout::limit().unwrap_or_default()
+        );
+        return None;
+    }
+
+    if !status.success() {
+        eprintln!(
+            "[first] error: discover run failed with exit code {}",
+            status.code().unwrap_or(-1)
+        );
+        return None;
+    }
+
+    Some(points)
+}
+
+/// Parse `{"event":"point",...}` and `{"event":"op",...}` lines from the
+/// DISCOVER child's stderr.
+fn parse_timeline_events(stderr: impl std::io::Read) -> Vec<TimelineEntry> {
+    let reader = BufReader::new(stderr);
+    reader
+        .lines()
+        .map_while(Result::ok)
+        .filter_map(|line| {
+            if line.starts_with(r#"{"event":"point""#) {
+                parse_point_json(&line).map(TimelineEntry::Point)
+            } else if line.starts_with(r#"{"event":"op""#) {
+                parse_op_json(&line)
+            } else {
+                None
+            }
+        })
+        .collect()
+}
+
+/// Parse a single operation event.
+fn parse_op_json(json: &str) -> Option<TimelineEntry> {
+    // Format: {"event":"op","kind":"...","target":"..."}
+    let kind = json.find(r#""kind":""#).and_then(|i| {
+        let start = i + 8;
+        let end = json[start..].find('"')?;
+        Some(json[start..start + end].to_string())
+    })?;
+    let target = json.find(r#""target":""#).and_then(|i| {
+        let start = i + 10;
+        let end = json[start..].rfind('"')?;
+        Some(json[start..start + end].to_string())
+    })?;
+    Some(TimelineEntry::Op { kind, target })
+}
+
+/// Parse a single point event.
+fn parse_point_json(json: &str) -> Option<DiscoveredPoint> {
+    // Format: {"event":"point","point_id":N[,"site_file":"...","site_line":N],"label":"..."}
+    let point_id = json.find(r#""point_id":"#).and_then(|i| {
+        let start = i + 11;
+        let end = json[start..].find(',')?;
+        json[start..start + end].parse().ok()
+    })?;
+
+    let site = json.find(r#""site_file":""#).and_then(|i| {
+        let start = i + 13;
+        let end = json[start..].find('"')?;
+        let file = &json[start..start + end];
+        let rest = &json[start + end..];
+        let line_start = rest.find(r#""site_line":"#)? + 12;
+        let line_end = rest[line_start..].find(',')?;
+        let line: u32 = rest[line_start..line_start + line_end].parse().ok()?;
+        Some(format!("{}:{}", file, line))
+    });
+
+    let label = json.find(r#""label":""#).and_then(|i| {
+        let start = i + 9;
+        let end = json[start..].rfind('"')?;
+        Some(json[start..start + end].to_string())
+    })?;
+
+    Some(DiscoveredPoint {
+        point_id,
+        label,
+        site,
+    })
+}
+
+/// Cache file for a given executable and test.
+fn cache_path(exe: &Path, test_name: &Option<String>, base_dir: &Path) -> PathBuf {
+    let mut hasher = DefaultHasher::new();
+    exe.hash(&mut hasher);
+    test_name.hash(&mut hasher);
+    crate::invocation::env_value().hash(&mut hasher);
+    base_dir
+        .join("discovery")
+        .join(format!("{:016x}.txt", hasher.finish()))
+}
+
+/// Identify a build of the executable by size and modification time.
+fn binary_fingerprint(exe: &Path) -> Option<String> {
+    let meta = fs::metadata(exe).ok()?;
+    let mtime = meta.modified().ok()?.duration_since(UNIX_EPOCH).ok()?;
+    Some(format!("{} {}", meta.len(), mtime.as_nanos()))
+}
+
+/// Read cached points if the cache matches `fingerprint`.
+///
+/// Cache format: the fingerprint on the first line, then one
+/// `<point_id>\t<site>\t<label>` line per crash point, with an empty
+/// `<site>` for points without a call site.
+fn read_cache(path: &Path, fingerprint: &str) -> Option<Vec<DiscoveredPoint>> {
+    let contents = fs::read_to_string(path).ok()?;
+    let mut lines = contents.lines();
+    if lines.next()? != fingerprint {
+        return None;
+    }
+    lines
+        .map(|line| {
+            let (id, rest) = line.split_once('\t')?;
+            let (site, label) = rest.split_once('\t')?;
+            Some(DiscoveredPoint {
+                point_id: id.parse().ok()?,
+                label: label.to_string(),
+                site: Some(site.to_string()).filter(|s| !s.is_empty()),
+            })
+        })
+        .collect()
+}
+
+/// Write points to the cache. Labels containing newlines, and sites
+/// containing tabs or newlines, are not cached.
+fn write_cache(path: &Path, fingerprint: &str, points: &[DiscoveredPoint]) -> std::io::Result<()> {
+    if points.iter().any(|p| {
+        p.label.contains('\n') || p.site.as_ref().is_some_and(|s| s.contains(['\t', '\n']))
+    }) {
+        return Ok(());
+    }
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    let mut contents = format!("{}\n", fingerprint);
+    for point in points {
+        contents.push_str(&format!(
+            "{}\t{}\t{}\n",
+            point.point_id,
+            point.site.as_deref().unwrap_or(""),
+            point.label
+        ));
+    }
+    fs::write(path, contents)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_cache_round_trip() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("cache.txt");
+        let points = vec![
+            DiscoveredPoint {
+                point_id: 1,
+                label: "after_write".to_string(),
+                site: Some("tests/wal.rs:12".to_string()),
+            },
+            DiscoveredPoint {
+                point_id: 2,
+                label: "after\tfsync".to_string(),
+                site: None,
+            },
+        ];
+        write_cache(&path, "10 20", &points).unwrap();
+        assert_eq!(read_cache(&path, "10 20"), Some(points));
+        assert_eq!(read_cache(&path, "10 21"), None);
+    }
+
+    #[test]
+    fn test_distinct_sites() {
+        let point = |point_id, site: Option<&str>| DiscoveredPoint {
+            point_id,
+            label: "p".to_string(),
+            site: site.map(str::to_string),
+        };
+        let points = vec![
+            point(1, Some("a.rs:3")),
+            point(2, None),
+            point(3, Some("a.rs:7")),
+            point(4, Some("a.rs:3")),
+        ];
+        assert_eq!(distinct_sites(&points), vec!["a.rs:3", "a.rs:7"]);
+    }
+
+    #[test]
+    fn test_format_points() {
+        let points = vec![
+            DiscoveredPoint {
+                point_id: 1,
+                label: "open".to_string(),
+                site: None,
+            },
+            DiscoveredPoint {
+                point_id: 2,
+                label: "write".to_string(),
+                site: Some("src/wal.rs:12".to_string()),
+            },
+        ];
+        assert_eq!(
+            format_points(&points),
+            "[first] 2 crash points:\n  1 \"open\"\n  2 \"write\" src/wal.rs:12\n"
+        );
+    }
+
+    #[test]
+    fn test_distinct_labels() {
+        let point = |point_id, label: &str| DiscoveredPoint {
+            point_id,
+            label: label.to_string(),
+            site: None,
+        };
+        let points = vec![
+            point(1, "open"),
+            point(2, "write"),
+            point(3, "open"),
+            point(4, "sync"),
+        ];
+        assert_eq!(distinct_labels(&points), vec!["open", "write", "sync"]);
+    }
+
+    #[test]
+    fn test_parse_timeline_events() {
+        let stderr = concat!(
+            r#"{"event":"op","kind":"rename","target":"a.tmp -> a"}"#,
+            "\n",
+            "unrelated output\n",
+            r#"{"event":"point","point_id":1,"label":"after_rename"}"#,
+            "\n",
+        );
+        let timeline = parse_timeline_events(stderr.as_bytes());
+        assert_eq!(
+            timeline[0],
+            TimelineEntry::Op {
+                kind: "rename".to_string(),
+                target: "a.tmp -> a".to_string(),
+            }
+        );
+        assert_eq!(points_of(&timeline)[0].label, "after_rename");
+        assert_eq!(timeline.len(), 2);
+    }
+
+    #[test]
+    fn test_parse_point_json() {
+        let point = parse_point_json(r#"{"event":"point","point_id":3,"label":"a, b"}"#).unwrap();
+        assert_eq!(point.point_id, 3);
+        assert_eq!(point.label, "a, b");
+        assert_eq!(point.site, None);
+
+        let json = r#"{"event":"point","point_id":4,"site_file":"tests/wal.rs","site_line":12,"label":"x"}"#;
+        let point = parse_point_json(json).unwrap();
+        assert_eq!(point.site.as_deref(), Some("tests/wal.rs:12"));
+        assert_eq!(point.label, "x");
+    }
+}
@@ -2,7 +2,18 @@
 //!
 //! Provides context to test closures.
 
+use std::fs::{File, OpenOptions};
+use std::io::{self, Write};
+use std::ops::Range;
 use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+use crate::checkpoint::Checkpoint;
+use crate::chunking::ChunkedReader;
+use crate::fault::{self, FaultyFile, InjectedFault};
+use crate::journal;
+use crate::mmap::MappedFile;
+use crate::rt::{self, Hit};
 
 /// Environment provided to test closures.
 ///
@@ -10,12 +21,59 @@ use std::path::{Path, PathBuf};
 /// This type is opaque; users interact with it only through [`Env::path()`].
 pub struct Env {
     work_dir: PathBuf,
+    metadata_dir: PathBuf,
+    /// The crash verified in the orchestrator by `verify_in_process()`;
+    /// VERIFY children read it from their environment instead.
+    crash: Option<CrashContext>,
+}
+
+/// The crash an in-process verify runs after.
+struct CrashContext {
+    target: usize,
+    point_id: usize,
+    label: String,
 }
 
 impl Env {
-    /// Create a new environment with the given work directory.
-    pub(crate) fn new(work_dir: PathBuf) -> Self {
-        Self { work_dir }
+    /// Create a new environment with the given work and metadata directories.
+    pub(crate) fn new(work_dir: PathBuf, metadata_dir: PathBuf) -> Self {
+        Self {
+            work_dir,
+            metadata_dir,
+            crash: None,
+        }
+    }
+
+    /// Attach the crash at sweep target `target`, for verify run outside a
+    /// VERIFY child.
+    pub(crate) fn with_crash(mut self, target: usize, crash_info: &CrashInfo) -> Self {
+        self.crash = Some(CrashContext {
+            target,
+            point_id: crash_info.point_id,
+            label: crash_info.label.clone(),
+        });
+        self
+    }
+
+    /// The sweep target of the crash being verified, naming its metadata
+    /// sidecars (`FIRST_CRASH_TARGET`).
+    pub(crate) fn crash_target(&self) -> Option<String> {
+        match &self.crash {
+            Some(crash) => Some(crash.target.to_string()),
+            None => std::env::var("FIRST_CRASH_TARGET").ok(),
+        }
+    }
+
+    /// The ID and label of the crash point being verified
+    /// (`FIRST_CRASH_POINT_ID`, `FIRST_CRASH_LABEL`).
+    pub(crate) fn crash_point(&self) -> Option<(String, String)> {
+        match &self.crash {
+            Some(crash) => Some((crash.point_id.to_string(), crash.label.clone())),
+            None => Some((
+                std::env::var("FIRST_CRASH_POINT_ID").ok()?,
+                std::env::var("FIRST_CRASH_LABEL").ok()?,
+            )),
+        }
     }
 
     /// Returns an absolute path inside this test's isolated workspace.
@@ -26,7 +84,8 @@ impl Env {
     /// - Be reset for each crash-restart iteration
     ///
     /// This function performs no I/O beyond path construction.
-    /// The caller is responsible for creating any subdirectories or files.
+    /// The caller is responsible for creating any subdirectories or files,
+    /// e.g. with [`Env::create_dir()`] and [`Env::write()`].
     ///
     /// # Panics
     ///
@@ -48,6 +107,1109 @@ impl Env {
         );
         self.work_dir.join(name)
     }
+
+    /// Create the workspace directory `name`, with any missing parents,
+    /// and return its absolute path.
+    ///
+    /// Like [`std::fs::create_dir_all()`], an existing directory is not an
+    /// error.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `name` is an absolute path, like [`Env::path()`].
+    ///
+    /// # Example
+    ///
+    /// ```ignore
+    /// let db = env.create_dir("db/segments")?;
+    /// ```
+    pub fn create_dir(&self, name: impl AsRef<Path>) -> io::Result<PathBuf> {
+        let path = self.path(name);
+        std::fs::create_dir_all(&path)?;
+        Ok(path)
+    }
+
+    /// Write `bytes` to the workspace file `name`, replacing its contents.
+    ///
+    /// The file is created if it does not exist, like [`std::fs::write()`];
+    /// its directory must already exist. Nothing is synced.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `name` is an absolute path, like [`Env::path()`].
+    pub fn write(&self, name: impl AsRef<Path>, bytes: impl AsRef<[u8]>) -> io::Result<()> {
+        let name = name.as_ref();
+        fault::inject("write")?;
+        std::fs::write(self.path(name), bytes)?;
+        rt::record_op("write", &name.display().to_string());
+        Ok(())
+    }
+
+    /// Read the whole workspace file `name`, like [`std::fs::read()`].
+    ///
+    /// # Panics
+    ///
+    /// Panics if `name` is an absolute path, like [`Env::path()`].
+    ///
+    /// # Example
+    ///
+    /// ```ignore
+    /// .verify(|env, _| {
+    ///     assert_eq!(env.read("manifest").unwrap(), b"v2");
+    /// })
+    /// ```
+    pub fn read(&self, name: impl AsRef<Path>) -> io::Result<Vec<u8>> {
+        std::fs::read(self.path(name))
+    }
+
+    /// Record `value` under `key`, for verify to read back with
+    /// [`Env::restore()`].
+    ///
+    /// Lets the workload tell verify what it intended, e.g. the IDs of the
+    /// transactions whose commit returned, without an ad-hoc expectations
+    /// file. Only values checkpointed *before* the crash point reach
+    /// verify: a later call never ran. Each call for a key replaces its
+    /// value. Values are kept as JSON in the metadata directory, not the
+    /// workspace, so crash effects such as `TestBuilder::drop_unsynced()`
+    /// never touch them, and they are not among the [`Env::survivors()`].
+    ///
+    /// A no-op outside the EXECUTION phase. Requires the `serde` feature.
+    ///
+    /// # Example
+    ///
+    /// ```ignore
+    /// .run(|env| {
+    ///     let mut committed = Vec::new();
+    ///     for txid in 1..=3 {
+    ///         db.commit(txid)?;
+    ///         committed.push(txid);
+    ///         env.checkpoint("committed", &committed)?;
+    ///         first::crash_point("after_commit");
+    ///     }
+    /// })
+    /// .verify(|env, _| {
+    ///     let committed: Vec<u64> = env.restore("committed").unwrap_or_default();
+    ///     for txid in committed {
+    ///         assert!(db.contains(txid));
+    ///     }
+    /// })
+    /// ```
+    #[cfg(feature = "serde")]
+    pub fn checkpoint<T: serde::Serialize + ?Sized>(&self, key: &str, value: &T) -> io::Result<()> {
+        let value = serde_json::to_value(value).map_err(io::Error::other)?;
+        crate::checkpoint::store_value(self, key, value)
+    }
+
+    /// The value last recorded under `key` by [`Env::checkpoint()`] before
+    /// the crash.
+    ///
+    /// Returns `None` if the workload crashed before checkpointing `key`.
+    /// Requires the `serde` feature.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the value does not deserialize as `T`.
+    #[cfg(feature = "serde")]
+    pub fn restore<T: serde::de::DeserializeOwned>(&self, key: &str) -> Option<T> {
+        let value = crate::checkpoint::load_value(self, key)?;
+        match serde_json::from_value(value) {
+            Ok(value) => Some(value),
+            Err(e) => panic!("cannot restore checkpoint {:?}: {}", key, e),
+        }
+    }
+
+    /// Record `committed` as keys the workload has durably committed, for
+    /// verify to read back with [`Env::expected()`].
+    ///
+    /// Verify then asserts that recovery kept every key recorded before the
+    /// crash, instead of inferring from the recovered state which commits
+    /// must have survived. Calls accumulate: each adds its keys to those of
+    /// earlier calls.
+    ///
+    /// # Ordering contract
+    ///
+    /// Call it only once every key in `committed` is durable: after the
+    /// write *and* the sync that makes it survive a crash have returned,
+    /// i.e. after the commit returns to its caller. The recorded set is an
+    /// oracle only if it never runs ahead of the disk: a key recorded
+    /// before its fsync may be lost by a crash in between, and verify
+    /// would report a correct engine. Recording late is always safe; a
+    /// crash between the commit and the call only leaves the key out of
+    /// the set, so verify checks less.
+    ///
+    /// Keys are appended to a file in the metadata directory with a single
+    /// write per call, so crash effects never touch them and a crash never
+    /// records part of a call. A no-op outside the EXECUTION phase.
+    ///
+    /// # Errors
+    ///
+    /// Fails if a key contains a newline, or if the record cannot be
+    /// written.
+    ///
+    /// # Example
+    ///
+    /// ```ignore
+    /// .run(|env| {
+    ///     let tx = wal.begin();
+    ///     wal.put(tx, "key1", "value1");
+    ///     wal.commit(tx); // returns once the commit record is fsynced
+    ///     env.expect(&["key1"]).unwrap();
+    ///     first::crash_point("after_commit");
+    /// })
+    /// .verify(|env, _| {
+    ///     let wal = Wal::open(&env.path("wal")).unwrap();
+    ///     env.expected().assert_present(|key| wal.get(key).is_some());
+    /// })
+    /// ```
+    pub fn expect(&self, committed: &[&str]) -> io::Result<()> {
+        crate::checkpoint::record_expected(self, committed)
+    }
+
+    /// The keys recorded by [`Env::expect()`] before the crash.
+    ///
+    /// Empty if the workload crashed before its first call.
+    pub fn expected(&self) -> Checkpoint {
+        crate::checkpoint::load_expected(self)
+    }
+
+    /// Returns an absolute path in a per-sweep scratch area that is NOT reset
+    /// between crash-restart iterations.
+    ///
+    /// Unlike [`Env::path()`], files written here survive until the end of
+    /// the sweep, so a verify closure can accumulate observations across
+    /// crash points (e.g. appending a line per point to a report). The
+    /// directory is emptied when the next sweep starts.
+    ///
+    /// The metadata directory exists before `run()` and `verify()` are
+    /// executed. This function performs no I/O beyond path construction.
+    ///
+    /// Parallel jobs sharing a metadata directory must coordinate their
+    /// writes (e.g. append whole lines with a single `write` call).
+    ///
+    /// # Panics
+    ///
+    /// Panics if `name` is an absolute path.
+    ///
+    /// # Example
+    ///
+    /// ```ignore
+    /// .verify(|env, crash| {
+    ///     let mut notes = OpenOptions::new()
+    ///         .create(true)
+    ///         .append(true)
+    ///         .open(env.metadata_path("notes.txt"))
+    ///         .unwrap();
+    ///     writeln!(notes, "{}: recovered", crash.label).unwrap();
+    /// })
+    /// ```
+    pub fn metadata_path(&self, name: impl AsRef<Path>) -> PathBuf {
+        let name = name.as_ref();
+        assert!(
+            !name.is_absolute(),
+            "Env::metadata_path() requires a relative path, got absolute: {:?}",
+            name
+        );
+        self.metadata_dir.join(name)
+    }
+
+    /// Whether the workspace was restored from a
+    /// `TestBuilder::snapshot_after()` snapshot.
+    ///
+    /// When true, the crash points up to the snapshot's are already behind
+    /// this run: the workload skips the setup that led to them and carries
+    /// on from the state in the workspace. Always false outside EXECUTION.
+    pub fn is_resumed(&self) -> bool {
+        crate::snapshot::is_resumed()
+    }
+
+    /// Every file in the workspace, as sorted paths relative to it.
+    ///
+    /// Lists regular files and symlinks (not followed) in all
+    /// subdirectories; directories themselves are left out. Lets verify
+    /// assert the exact set of files that survived a crash, e.g. that no
+    /// temporary file outlives the rename that replaced it.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the workspace cannot be read; [`Env::survivors()`]
+    /// returns the error instead.
+    ///
+    /// # Example
+    ///
+    /// ```ignore
+    /// .verify(|env, _| {
+    ///     assert_eq!(env.list_files(), [PathBuf::from("db/manifest")]);
+    /// })
+    /// ```
+    pub fn list_files(&self) -> Vec<PathBuf> {
+        self.survivors()
+            .unwrap_or_else(|e| panic!("cannot list workspace {}: {}", self.work_dir.display(), e))
+    }
+
+    /// The files that survived the crash, as sorted paths relative to the
+    /// workspace.
+    ///
+    /// Like [`Env::list_files()`], but returns an error instead of
+    /// panicking, for verifiers that read whatever survived and treat an
+    /// unreadable workspace as a finding of their own. Symlinks are listed
+    /// but never followed, so a link pointing outside the workspace does
+    /// not pull in files from there. FIRST keeps none of its own files in
+    /// the workspace while verify runs (its records live in the metadata
+    /// directory, and a failing verify's `verify.log` is only written once
+    /// it returns), so every path listed was written by the workload.
+    ///
+    /// # Example
+    ///
+    /// ```ignore
+    /// .verify(|env, _| {
+    ///     for file in env.survivors().unwrap() {
+    ///         replay_segment(&env.path(file));
+    ///     }
+    /// })
+    /// ```
+    pub fn survivors(&self) -> io::Result<Vec<PathBuf>> {
+        let mut files = Vec::new();
+        let mut stack = vec![self.work_dir.clone()];
+        while let Some(dir) = stack.pop() {
+            for entry in std::fs::read_dir(&dir)? {
+                let entry = entry?;
+                let path = entry.path();
+                if entry.file_type()?.is_dir() {
+                    stack.push(path);
+                } else if let Ok(relative) = path.strip_prefix(&self.work_dir) {
+                    files.push(relative.to_path_buf());
+                }
+            }
+        }
+        files.sort();
+        Ok(files)
+    }
+
+    /// Copy the whole workspace into a new directory under `dest`, and
+    /// return its path.
+    ///
+    /// Work dirs live under `/tmp` and are removed once their crash point
+    /// passes, or on the next run. Calling this from verify, typically
+    /// just before failing, keeps a snapshot of the crashed state at a
+    /// stable, e.g. project-relative, path. The directory is named after
+    /// the crash point, as in `point_3_after_commit_write` (characters
+    /// other than letters, digits, `-` and `_` in the label become `_`),
+    /// or after the work dir outside a VERIFY child. An earlier snapshot
+    /// of the same point is replaced. Symlinks are copied, not followed.
+    ///
+    /// # Example
+    ///
+    /// ```ignore
+    /// .verify(|env, _| {
+    ///     if let Err(e) = check_db(&env.path("db")) {
+    ///         let kept = env.persist("target/first-artifacts").unwrap();
+    ///         panic!("{} (workspace kept in {})", e, kept.display());
+    ///     }
+    /// })
+    /// ```
+    pub fn persist(&self, dest: impl AsRef<Path>) -> io::Result<PathBuf> {
+        let name = match self.crash_point() {
+            Some((id, label)) => format!("point_{}_{}", id, snapshot_label(&label)),
+            None => self
+                .work_dir
+                .file_name()
+                .map_or("workspace".to_string(), |name| {
+                    name.to_string_lossy().into_owned()
+                }),
+        };
+        let dir = dest.as_ref().join(name);
+        match std::fs::remove_dir_all(&dir) {
+            Err(e) if e.kind() != io::ErrorKind::NotFound => return Err(e),
+            _ => {}
+        }
+        crate::bundle::copy_tree(&self.work_dir, &dir)?;
+        Ok(dir)
+    }
+
+    /// Append `data` to the workspace file `name`, with a crash point in the
+    /// middle of the write.
+    ///
+    /// This call counts as one crash point (labelled `write_then_crash`).
+    /// When it is the target, only the first `at_byte` bytes of `data` are
+    /// written, nothing is synced, and the process is killed immediately,
+    /// leaving a genuinely partial write on disk. Otherwise all of `data` is
+    /// appended and execution continues.
+    ///
+    /// The interrupted write is reported to verify as
+    /// [`CrashInfo::partial_write`], so the verifier knows exactly how many
+    /// bytes reached the file and at which offset.
+    ///
+    /// The file is created if it does not exist. `at_byte` is clamped to
+    /// `data.len()`.
+    ///
+    /// # Example
+    ///
+    /// ```ignore
+    /// env.write_then_crash("wal.log", b"PUT 1 key value\n", 7)?;
+    /// ```
+    pub fn write_then_crash(
+        &self,
+        name: impl AsRef<Path>,
+        data: &[u8],
+        at_byte: usize,
+    ) -> io::Result<()> {
+        const LABEL: &str = "write_then_crash";
+
+        let name = name.as_ref();
+        let mut file = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(self.path(name))?;
+
+        match rt::hit(LABEL) {
+            Hit::Target(id) => {
+                let written = at_byte.min(data.len());
+                let offset = file.metadata()?.len();
+                file.write_all(&data[..written])?;
+                journal::with(|j| {
+                    j.partial_write = Some(PartialWrite {
+                        file: name.to_path_buf(),
+                        offset,
+                        written,
+                        len: data.len(),
+                    })
+                });
+                rt::crash_at(id, LABEL, None)
+            }
+            Hit::Passed(_) | Hit::Inactive => {
+                file.write_all(data)?;
+                rt::record_op("write", &name.display().to_string());
+                Ok(())
+            }
+        }
+    }
+
+    /// Open the workspace file `name` for direct I/O (`O_DIRECT`).
+    ///
+    /// Adds `O_DIRECT` to `options` and opens `self.path(name)`. FIRST never
+    /// falls back to buffered I/O: if the platform or the workspace
+    /// filesystem does not support direct I/O (e.g. tmpfs), the error is
+    /// returned so the test cannot silently exercise different crash
+    /// semantics than production.
+    ///
+    /// # Alignment
+    ///
+    /// Direct I/O requires the buffer address, the file offset and the
+    /// length of every transfer to be multiples of the device's logical
+    /// block size. [`Env::direct_io_alignment()`] returns a safe value.
+    /// [`Env::write_at_then_crash()`] preserves this by rounding the
+    /// simulated cut down to the alignment.
+    ///
+    /// Linux only, like the rest of FIRST's instrumentation.
+    ///
+    /// # Example
+    ///
+    /// ```ignore
+    /// let file = env.open_direct("data.db", OpenOptions::new().read(true).write(true).create(true))?;
+    /// ```
+    pub fn open_direct(
+        &self,
+        name: impl AsRef<Path>,
+        options: &mut OpenOptions,
+    ) -> io::Result<File> {
+        use std::os::unix::fs::OpenOptionsExt;
+
+        let path = self.path(name);
+        options
+            .custom_flags(libc::O_DIRECT)
+            .open(&path)
+            .map_err(|e| {
+                if e.raw_os_error() == Some(libc::EINVAL) {
+                    io::Error::new(
+                        e.kind(),
+                        format!(
+                            "the filesystem of {} does not support O_DIRECT",
+                            path.display()
+                        ),
+                    )
+                } else {
+                    e
+                }
+            })
+    }
+
+    /// Alignment, in bytes, that direct I/O transfers in the workspace
+    /// must respect.
+    ///
+    /// This is the filesystem's preferred block size (`st_blksize`), a
+    /// multiple of the logical block size that `O_DIRECT` strictly
+    /// requires.
+    pub fn direct_io_alignment(&self) -> io::Result<usize> {
+        use std::os::unix::fs::MetadataExt;
+
+        Ok(std::fs::metadata(&self.work_dir)?.blksize() as usize)
+    }
+
+    /// Write `data` at `offset` in an already open `file`, with a crash
+    /// point in the middle of the write.
+    ///
+    /// The positional counterpart of [`Env::write_then_crash()`], for
+    /// engines that manage their own file handles, including those opened
+    /// with [`Env::open_direct()`]. Counts as one crash point (labelled
+    /// `write_at_then_crash`). When it is the target, only the first
+    /// `at_byte` bytes are written and the process is killed; the write is
+    /// reported as [`CrashInfo::partial_write`] with the file's
+    /// workspace-relative path.
+    ///
+    /// If `file` was opened with `O_DIRECT`, the cut is rounded down to
+    /// [`Env::direct_io_alignment()`] so the partial write stays a valid
+    /// direct transfer, which also matches how direct I/O tears at block
+    /// granularity. `data` and `offset` must then already be aligned.
+    pub fn write_at_then_crash(
+        &self,
+        file: &File,
+        offset: u64,
+        data: &[u8],
+        at_byte: usize,
+    ) -> io::Result<()> {
+        use std::os::unix::fs::FileExt;
+
+        const LABEL: &str = "write_at_then_crash";
+
+        let relative = self.workspace_relative(file);
+        match rt::hit(LABEL) {
+            Hit::Target(id) => {
+                let mut written = at_byte.min(data.len());
+                if is_direct(file) {
+                    written -= written % self.direct_io_alignment()?.max(1);
+                }
+                file.write_all_at(&data[..written], offset)?;
+                journal::with(|j| {
+                    j.partial_write = Some(PartialWrite {
+                        file: relative.unwrap_or_default(),
+                        offset,
+                        written,
+                        len: data.len(),
+                    })
+                });
+                rt::crash_at(id, LABEL, None)
+            }
+            Hit::Passed(_) | Hit::Inactive => {
+                file.write_all_at(data, offset)?;
+                rt::record_op(
+                    "write",
+                    &relative.map_or_else(|| "?".to_string(), |f| f.display().to_string()),
+                );
+                Ok(())
+            }
+        }
+    }
+
+    /// Write all of `data` to `file` at its current position, logging the
+    /// write.
+    ///
+    /// Behaves exactly like [`Write::write_all()`], but FIRST remembers the
+    /// offset and length of the last write to each file until the file is
+    /// synced through [`Env::fsync()`] or [`Env::fdatasync()`]. With
+    /// `TestBuilder::torn_last_write()`, the most recent of these writes is
+    /// torn at the crash. Files opened in append mode are logged at the
+    /// offset the data actually landed at.
+    ///
+    /// # Example
+    ///
+    /// ```ignore
+    /// let mut wal = OpenOptions::new().create(true).append(true).open(env.path("wal"))?;
+    /// env.write_all(&mut wal, b"PUT 1 key value\n")?;
+    /// ```
+    pub fn write_all(&self, file: &mut File, data: &[u8]) -> io::Result<()> {
+        use std::io::Seek;
+
+        fault::inject("write")?;
+        file.write_all(data)?;
+        let end = file.stream_position()?;
+        journal::record_write(file, end - data.len() as u64, data.len());
+        Ok(())
+    }
+
+    /// Write all of `data` at `offset` in `file`, logging the write.
+    ///
+    /// The positional counterpart of [`Env::write_all()`], behaving like
+    /// [`FileExt::write_all_at()`](std::os::unix::fs::FileExt::write_all_at).
+    pub fn write_all_at(&self, file: &File, data: &[u8], offset: u64) -> io::Result<()> {
+        use std::os::unix::fs::FileExt;
+
+        fault::inject("write")?;
+        file.write_all_at(data, offset)?;
+        journal::record_write(file, offset, data.len());
+        Ok(())
+    }
+
+    /// Wrap `file` so its writes and syncs fail at a targeted
+    /// [`fault_point()`](crate::fault_point).
+    ///
+    /// The `Env` write and sync helpers already fail there; this is for
+    /// code that writes through [`Write`], such as a `BufWriter`.
+    ///
+    /// # Example
+    ///
+    /// ```ignore
+    /// let file = File::create(env.path("wal"))?;
+    /// let mut wal = BufWriter::new(env.faulty_file(file));
+    /// first::fault_point("before_append", first::FaultKind::NoSpace);
+    /// // Fails with ENOSPC when the fault point is the target
+    /// wal.write_all(b"PUT 1 key value\n")?;
+    /// wal.flush()?;
+    /// ```
+    pub fn faulty_file(&self, file: File) -> FaultyFile {
+        FaultyFile::new(file)
+    }
+
+    /// Flush `file`'s data and metadata to disk (`fsync`), recording the
+    /// barrier.
+    ///
+    /// Behaves exactly like [`File::sync_all()`], but FIRST counts every
+    /// completed call and reports the total issued before the crash as
+    /// [`CrashInfo::fsync_count`]. Use it in place of `sync_all()` in the
+    /// workload to make durability barriers visible to verify.
+    pub fn fsync(&self, file: &File) -> io::Result<()> {
+        fault::inject("fsync")?;
+        inject_eintr()?;
+        self.crash_mid_fsync(file);
+        file.sync_all()?;
+        journal::record_synced(file);
+        barrier_latency();
+        self.record_barrier(BarrierKind::Fsync, file);
+        Ok(())
+    }
+
+    /// Flush `file`'s data to disk (`fdatasync`), recording the barrier.
+    ///
+    /// The instrumented equivalent of [`File::sync_data()`]; counted
+    /// together with [`Env::fsync()`].
+    pub fn fdatasync(&self, file: &File) -> io::Result<()> {
+        fault::inject("fdatasync")?;
+        inject_eintr()?;
+        self.crash_mid_fsync(file);
+        file.sync_data()?;
+        journal::record_synced(file);
+        barrier_latency();
+        self.record_barrier(BarrierKind::Fdatasync, file);
+        Ok(())
+    }
+
+    /// Flush the workspace directory `name` to disk (`fsync` on the
+    /// directory), recording the barrier.
+    ///
+    /// Creating, renaming or removing a file only becomes durable once its
+    /// parent directory is synced; `name` is relative to the workspace,
+    /// and `""` syncs the workspace itself. Recorded in
+    /// [`CrashInfo::barriers()`] as a [`BarrierKind::Fsync`] on the
+    /// directory and counted in [`CrashInfo::fsync_count`].
+    ///
+    /// With `TestBuilder::fail_dir_fsync()`, one call can be made to fail
+    /// with a chosen error, or to report success without syncing, to check
+    /// how the engine handles a failed directory sync. The injected fault
+    /// is reported as [`CrashInfo::dir_fsync_fault`].
+    ///
+    /// # Example
+    ///
+    /// ```ignore
+    /// std::fs::rename(env.path("manifest.tmp"), env.path("manifest"))?;
+    /// env.fsync_dir("")?;
+    /// ```
+    pub fn fsync_dir(&self, name: impl AsRef<Path>) -> io::Result<()> {
+        let dir = File::open(self.path(name))?;
+        fault::inject("fsync_dir")?;
+        if inject_dir_fsync_fault(self.workspace_relative(&dir))? {
+            return Ok(());
+        }
+        inject_eintr()?;
+        dir.sync_all()?;
+        barrier_latency();
+        self.record_barrier(BarrierKind::Fsync, &dir);
+        Ok(())
+    }
+
+    /// Journal a completed barrier on `file`.
+    fn record_barrier(&self, kind: BarrierKind, file: &File) {
+        let record = BarrierRecord {
+            kind,
+            file: self.workspace_relative(file),
+            after_point: rt::points_passed(),
+            range: None,
+        };
+        journal_barrier(record);
+    }
+
+    /// Map the first `len` bytes of the workspace file `name` into memory
+    /// (`MAP_SHARED`), tracking which pages are `msync`ed.
+    ///
+    /// The file is created if needed and extended to `len` bytes. Writes
+    /// through the returned [`MappedFile`] reach the page cache at once but
+    /// are only durable after [`MappedFile::msync()`], which is recorded as
+    /// a [`BarrierKind::Msync`] barrier with its page range.
+    ///
+    /// A real crash may or may not flush dirty mapped pages. By default
+    /// FIRST leaves them to the kernel; with
+    /// `TestBuilder::lose_unsynced_mmap()`, every page modified since its
+    /// last `msync` is reverted just before the `SIGKILL`, modelling the
+    /// worst case.
+    pub fn mmap(&self, name: impl AsRef<Path>, len: usize) -> io::Result<MappedFile> {
+        let file = OpenOptions::new()
+            .read(true)
+            .write(true)
+            .create(true)
+            .truncate(false)
+            .open(self.path(name.as_ref()))?;
+        let relative = self.workspace_relative(&file);
+        MappedFile::new(file, relative, len)
+    }
+
+    /// Open a workspace file for reading through a [`ChunkedReader`].
+    ///
+    /// In the VERIFY runs of `TestBuilder::vary_read_chunking()`, every
+    /// `read` returns at most the chunk size being tested; elsewhere the
+    /// reader behaves like the plain [`File`]. Recovery code that reads
+    /// through it is checked at every configured read granularity.
+    ///
+    /// # Example
+    ///
+    /// ```ignore
+    /// let mut log = std::io::BufReader::new(env.open_chunked("wal")?);
+    /// ```
+    pub fn open_chunked(&self, name: impl AsRef<Path>) -> io::Result<ChunkedReader> {
+        File::open(self.path(name)).map(ChunkedReader::new)
+    }
+
+    /// Crash inside a barrier on a file listed by
+    /// `TestBuilder::crash_mid_fsync()`.
+    ///
+    /// Each such barrier is a crash point. At the target, the pages of
+    /// the listed files that reach the disk are chosen and journaled
+    /// before the crash; they are applied with the other crash effects.
+    fn crash_mid_fsync(&self, file: &File) {
+        let options = rt::options();
+        let Some((label, files)) = &options.mid_fsync else {
+            return;
+        };
+        if !self
+            .workspace_relative(file)
+            .is_some_and(|relative| files.contains(&relative))
+        {
+            return;
+        }
+        if let Hit::Target(id) = rt::hit(label) {
+            let seed = options.shuffle.unwrap_or(0) ^ id as u64;
+            let flushes = crate::crash::plan_partial_flush(&self.work_dir, files, seed);
+            journal::with(|j| j.partial_flush = flushes);
+            rt::crash_at(id, label, None)
+        }
+    }
+
+    /// Path of an open file, relative to the workspace when inside it.
+    ///
+    /// Resolved through `/proc/self/fd`, so `None` on other platforms.
+    fn workspace_relative(&self, file: &File) -> Option<PathBuf> {
+        use std::os::unix::io::AsRawFd;
+
+        let path = std::fs::read_link(format!("/proc/self/fd/{}", file.as_raw_fd())).ok()?;
+        let root = self.work_dir.canonicalize().ok()?;
+        match path.strip_prefix(&root) {
+            Ok(relative) => Some(relative.to_path_buf()),
+            Err(_) => Some(path),
+        }
+    }
+}
+
+/// Fail a barrier with `EINTR`, as configured by
+/// `TestBuilder::fsync_eintr()`.
+///
+/// Each barrier succeeds only after `count` interrupted attempts in a
+/// row, so an engine that retries sees the same sequence on every run.
+/// Nothing is synced by an interrupted attempt.
+fn inject_eintr() -> io::Result<()> {
+    let count = rt::options().fsync_eintr;
+    if count == 0 {
+        return Ok(());
+    }
+    journal::with(|j| {
+        if j.eintr_streak < count {
+            j.eintr_streak += 1;
+            j.eintr_count += 1;
+            Err(io::Error::from_raw_os_error(libc::EINTR))
+        } else {
+            j.eintr_streak = 0;
+            Ok(())
+        }
+    })
+}
+
+/// Apply `TestBuilder::fail_dir_fsync()` to the current directory sync.
+///
+/// Returns the injected error, or `Ok(true)` if the sync must be
+/// silently skipped. The injection is journaled with the directory, so
+/// verify learns what failed.
+fn inject_dir_fsync_fault(dir: Option<PathBuf>) -> io::Result<bool> {
+    let Some((nth, fault)) = rt::options().dir_fsync_fault else {
+        return Ok(false);
+    };
+    let hit = journal::with(|j| {
+        j.dir_fsync_calls += 1;
+        if j.dir_fsync_calls != nth {
+            return false;
+        }
+        j.dir_fsync_fault = Some(InjectedDirFsync {
+            dir: dir.unwrap_or_default(),
+            fault,
+            after_point: rt::points_passed(),
+        });
+        true
+    });
+    match fault {
+        _ if !hit => Ok(false),
+        DirFsyncFault::Error(code) => Err(io::Error::from_raw_os_error(code)),
+        DirFsyncFault::Dropped => Ok(true),
+    }
+}
+
+/// Journal a completed barrier of any kind.
+pub(crate) fn journal_barrier(record: BarrierRecord) {
+    rt::record_op(
+        record.kind.as_str(),
+        &record
+            .file
+            .as_ref()
+            .map(|f| f.display().to_string())
+            .unwrap_or_else(|| "?".to_string()),
+    );
+    journal::with(|j| {
+        if record.kind != BarrierKind::Msync {
+            j.fsync_count += 1;
+        }
+        j.barriers.push(record);
+    });
+}
+
+/// A crash label made safe for a directory name by `Env::persist()`.
+fn snapshot_label(label: &str) -> String {
+    label
+        .chars()
+        .map(|c| {
+            if c.is_ascii_alphanumeric() || c == '-' || c == '_' {
+                c
+            } else {
+                '_'
+            }
+        })
+        .collect()
+}
+
+/// Returns true if `file` was opened with `O_DIRECT`.
+fn is_direct(file: &File) -> bool {
+    use std::os::unix::io::AsRawFd;
+
+    let flags = unsafe { libc::fcntl(file.as_raw_fd(), libc::F_GETFL) };
+    flags != -1 && flags & libc::O_DIRECT != 0
+}
+
+/// Delay a completed barrier by `TestBuilder::fsync_latency()`.
+///
+/// With `crash_during_fsync()`, a crash point sits halfway through the
+/// delay: the data is already durable, but the caller never sees the call
+/// return.
+fn barrier_latency() {
+    let options = rt::options();
+    let Some(latency) = options.fsync_latency else {
+        return;
+    };
+    if options.crash_during_fsync {
+        let half = latency / 2;
+        std::thread::sleep(half);
+        rt::crash_point("fsync_latency_window");
+        std::thread::sleep(latency - half);
+    } else {
+        std::thread::sleep(latency);
+    }
+}
+
+/// Kind of durability barrier recorded in a [`BarrierRecord`].
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum BarrierKind {
+    /// [`Env::fsync()`]: data and metadata.
+    Fsync,
+    /// [`Env::fdatasync()`]: data only.
+    Fdatasync,
+    /// [`MappedFile::msync()`]: a page range of a memory-mapped file.
+    Msync,
+}
+
+impl BarrierKind {
+    /// Name used in crash metadata.
+    pub(crate) fn as_str(self) -> &'static str {
+        match self {
+            BarrierKind::Fsync => "fsync",
+            BarrierKind::Fdatasync => "fdatasync",
+            BarrierKind::Msync => "msync",
+        }
+    }
+
+    /// Parse a name produced by [`BarrierKind::as_str()`].
+    pub(crate) fn parse(s: &str) -> Option<Self> {
+        match s {
+            "fsync" => Some(BarrierKind::Fsync),
+            "fdatasync" => Some(BarrierKind::Fdatasync),
+            "msync" => Some(BarrierKind::Msync),
+            _ => None,
+        }
+    }
+}
+
+/// A durability barrier that completed before the crash.
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct BarrierRecord {
+    /// Which barrier was issued.
+    pub kind: BarrierKind,
+    /// The synced file, relative to the workspace when inside it.
+    ///
+    /// `None` if the path could not be resolved (non-Linux, or the file
+    /// was already unlinked).
+    pub file: Option<PathBuf>,
+    /// Number of crash points passed before the barrier was issued.
+    pub after_point: usize,
+    /// Byte range made durable, for [`BarrierKind::Msync`]; `None` when
+    /// the whole file was synced.
+    pub range: Option<Range<u64>>,
+}
+
+impl BarrierRecord {
+    /// Encode for the `FIRST_CRASH_BARRIERS` variable as
+    /// `kind:after_point:start-end:file` (empty range for whole files).
+    pub(crate) fn to_env(&self) -> String {
+        format!(
+            "{}:{}:{}:{}",
+            self.kind.as_str(),
+            self.after_point,
+            self.range
+                .as_ref()
+                .map(|r| format!("{}-{}", r.start, r.end))
+                .unwrap_or_default(),
+            self.file
+                .as_ref()
+                .map(|f| f.display().to_string())
+                .unwrap_or_default()
+        )
+    }
+
+    /// Decode from [`BarrierRecord::to_env()`] format.
+    pub(crate) fn from_env(s: &str) -> Option<Self> {
+        let mut parts = s.splitn(4, ':');
+        let kind = BarrierKind::parse(parts.next()?)?;
+        let after_point = parts.next()?.parse().ok()?;
+        let range = match parts.next()? {
+            "" => None,
+            range => {
+                let (start, end) = range.split_once('-')?;
+                Some(start.parse().ok()?..end.parse().ok()?)
+            }
+        };
+        let file = Some(parts.next()?)
+            .filter(|f| !f.is_empty())
+            .map(PathBuf::from);
+        Some(Self {
+            kind,
+            file,
+            after_point,
+            range,
+        })
+    }
+}
+
+/// Fault injected into one [`Env::fsync_dir()`] call by
+/// `TestBuilder::fail_dir_fsync()`.
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DirFsyncFault {
+    /// Fail with this raw OS error code (e.g. `libc::EIO`), without
+    /// syncing.
+    Error(i32),
+    /// Report success without syncing: the directory's new entries are
+    /// not durable, and the engine cannot tell.
+    Dropped,
+}
+
+impl DirFsyncFault {
+    /// Encode as the error code, or `dropped`.
+    pub(crate) fn to_env(self) -> String {
+        match self {
+            DirFsyncFault::Error(code) => code.to_string(),
+            DirFsyncFault::Dropped => "dropped".to_string(),
+        }
+    }
+
+    /// Decode from [`DirFsyncFault::to_env()`] format.
+    pub(crate) fn from_env(s: &str) -> Option<Self> {
+        match s {
+            "dropped" => Some(DirFsyncFault::Dropped),
+            code => code.parse().ok().map(DirFsyncFault::Error),
+        }
+    }
+}
+
+/// A directory sync that FIRST made fail before the crash.
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct InjectedDirFsync {
+    /// The directory passed to [`Env::fsync_dir()`], relative to the
+    /// workspace (empty for the workspace itself).
+    pub dir: PathBuf,
+    /// What the call did instead of syncing.
+    pub fault: DirFsyncFault,
+    /// Number of crash points passed before the call.
+    pub after_point: usize,
+}
+
+impl InjectedDirFsync {
+    /// Encode for the `FIRST_CRASH_DIR_FSYNC` variable as
+    /// `after_point:fault:dir`.
+    pub(crate) fn to_env(&self) -> String {
+        format!(
+            "{}:{}:{}",
+            self.after_point,
+            self.fault.to_env(),
+            self.dir.display()
+        )
+    }
+
+    /// Decode from [`InjectedDirFsync::to_env()`] format.
+    pub(crate) fn from_env(s: &str) -> Option<Self> {
+        let mut parts = s.splitn(3, ':');
+        let after_point = parts.next()?.parse().ok()?;
+        let fault = DirFsyncFault::from_env(parts.next()?)?;
+        let dir = PathBuf::from(parts.next()?);
+        Some(Self {
+            dir,
+            fault,
+            after_point,
+        })
+    }
+}
+
+/// A write interrupted by [`Env::write_then_crash()`], or torn by
+/// `TestBuilder::torn_last_write()`.
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct PartialWrite {
+    /// Path of the written file, relative to the workspace.
+    pub file: PathBuf,
+    /// File offset at which the interrupted write started.
+    pub offset: u64,
+    /// Number of bytes that reached the file before the crash.
+    pub written: usize,
+    /// Number of bytes the write was meant to append.
+    pub len: usize,
+}
+
+impl PartialWrite {
+    /// Create an empty record for `file`.
+    pub(crate) fn new(file: PathBuf) -> Self {
+        Self {
+            file,
+            offset: 0,
+            written: 0,
+            len: 0,
+        }
+    }
+
+    /// Encode for the `FIRST_CRASH_PARTIAL_WRITE` variable as
+    /// `offset:written:len:file`.
+    pub(crate) fn to_env(&self) -> String {
+        format!(
+            "{}:{}:{}:{}",
+            self.offset,
+            self.written,
+            self.len,
+            self.file.display()
+        )
+    }
+
+    /// Decode from [`PartialWrite::to_env()`] format.
+    pub(crate) fn from_env(s: &str) -> Option<Self> {
+        let mut parts = s.splitn(4, ':');
+        let offset = parts.next()?.parse().ok()?;
+        let written = parts.next()?.parse().ok()?;
+        let len = parts.next()?.parse().ok()?;
+        let file = PathBuf::from(parts.next()?);
+        Some(Self {
+            file,
+            offset,
+            written,
+            len,
+        })
+    }
+}
+
+/// A file flushed only in part by a crash inside a barrier
+/// (`TestBuilder::crash_mid_fsync()`).
+///
+/// The bytes past `synced_len` were pending when the barrier started.
+/// Of those, only the `persisted` ranges reached the disk; the pages
+/// between them read back as zeroes, and the file ends after the last
+/// persisted page (or at `synced_len` if none was).
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct PartialFlush {
+    /// Path of the file, relative to the workspace.
+    pub file: PathBuf,
+    /// Length of the file as of its previous sync.
+    pub synced_len: u64,
+    /// Length of the file when the barrier started.
+    pub len: u64,
+    /// Byte ranges of the pending data that were persisted, in order.
+    pub persisted: Vec<Range<u64>>,
+}
+
+impl PartialFlush {
+    /// Encode for the `FIRST_CRASH_PARTIAL_FLUSH` variable as
+    /// `synced_len:len:start-end,...:file`.
+    pub(crate) fn to_env(&self) -> String {
+        let persisted: Vec<String> = self
+            .persisted
+            .iter()
+            .map(|r| format!("{}-{}", r.start, r.end))
+            .collect();
+        format!(
+            "{}:{}:{}:{}",
+            self.synced_len,
+            self.len,
+            persisted.join(","),
+            self.file.display()
+        )
+    }
+
+    /// Decode from [`PartialFlush::to_env()`] format.
+    pub(crate) fn from_env(s: &str) -> Option<Self> {
+        let mut parts = s.splitn(4, ':');
+        let synced_len = parts.next()?.parse().ok()?;
+        let len = parts.next()?.parse().ok()?;
+        let persisted = parts
+            .next()?
+            .split(',')
+            .filter(|r| !r.is_empty())
+            .map(|r| {
+                let (start, end) = r.split_once('-')?;
+                Some(start.parse().ok()?..end.parse().ok()?)
+            })
+            .collect::<Option<Vec<_>>>()?;
+        let file = PathBuf::from(parts.next()?);
+        Some(Self {
+            file,
+            synced_len,
+            len,
+            persisted,
+        })
+    }
 }
 
 /// Information about a crash that occurred.
@@ -66,6 +1228,12 @@ impl Env {
 /// - `point_id` values may change if crash points are added or removed
 /// - Labels are not required to be unique
 ///
+/// # Serialization
+///
+/// With the `serde` feature, `CrashInfo` and the records it holds
+/// implement `serde::Serialize` and `serde::Deserialize`, so verify can
+/// store it with its own artifacts and read it back later.
+///
 /// # Example
 ///
 /// ```ignore
@@ -73,6 +1241,7 @@ impl Env {
 ///     println!("Verifying after crash point {} ({})", crash.point_id, crash.label);
 /// })
 /// ```
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 #[derive(Debug, Clone)]
 #[non_exhaustive]
 pub struct CrashInfo {
@@ -100,11 +1269,168 @@ pub struct CrashInfo {
     /// - `"before_manifest_update"`
     /// - `"committed"`
     pub label: String,
+
+    /// The workspace the crash happened in, as given to the EXECUTION
+    /// child in `FIRST_WORK_DIR` (the same directory as [`Env::path()`]
+    /// resolves against).
+    ///
+    /// Lets a verifier log the exact directory, and tools reading the
+    /// crash metadata correlate a crash with its artifacts. Empty if
+    /// unknown.
+    pub work_dir: PathBuf,
+
+    /// Maximum number of open file descriptors observed in the EXECUTION
+    /// child up to and including this crash point.
+    ///
+    /// Only populated when the test was built with
+    /// `TestBuilder::track_fds()`.
+    pub max_fds: Option<usize>,
+
+    /// The write interrupted by the crash, if the crash point was an
+    /// [`Env::write_then_crash()`] call, or the write torn by
+    /// `TestBuilder::torn_last_write()`.
+    pub partial_write: Option<PartialWrite>,
+
+    /// Total number of crash points in the workload.
+    ///
+    /// Only known when the orchestrator ran a DISCOVER pass
+    /// (`TestBuilder::discover()`).
+    pub total_points: Option<usize>,
+
+    /// Number of [`Env::fsync()`] / [`Env::fdatasync()`] calls that
+    /// completed before the crash.
+    ///
+    /// Barriers issued directly through `File::sync_all()` are not counted.
+    /// A verify closure can assert that at least one barrier preceded a
+    /// durability-dependent point, or spot over-syncing.
+    pub fsync_count: usize,
+
+    /// Number of `EINTR` errors injected into barriers before the crash
+    /// (`TestBuilder::fsync_eintr()`).
+    pub eintr_count: usize,
+
+    /// The directory sync that failed by injection before the crash
+    /// (`TestBuilder::fail_dir_fsync()`), if it was reached.
+    pub dir_fsync_fault: Option<InjectedDirFsync>,
+
+    /// The I/O error injected by the targeted
+    /// [`fault_point()`](crate::fault_point), if the crash point was one.
+    ///
+    /// The workload ran to completion after the fault point, handling the
+    /// error, and was killed at its end.
+    pub fault: Option<InjectedFault>,
+
+    /// How long the EXECUTION child's workload had run when it crashed,
+    /// measured with a monotonic clock from the start of the workload.
+    ///
+    /// For a crash by the timer of `TestBuilder::crash_after_duration()`,
+    /// the run time at which the timer fired. A crash point reached only
+    /// after a suspiciously long time hints at a hang or a slow path.
+    /// `None` if the crash metadata was lost.
+    pub elapsed: Option<Duration>,
+
+    /// Source location `(file, line)` of the crash point.
+    ///
+    /// Only populated for crash points marked with the
+    /// [`crash_point!`](crate::crash_point!) macro; `None` for
+    /// [`crash_point()`](crate::crash_point()) and the `Env` helpers.
+    #[cfg_attr(feature = "serde", serde(with = "crate::crash_event::site"))]
+    pub site: Option<(&'static str, u32)>,
+
+    /// The crash points hit so far in this workspace, oldest first; the
+    /// last entry is this crash.
+    ///
+    /// With `TestBuilder::crash_schedule()` or `TestBuilder::replay_script()`
+    /// the workspace has crashed and recovered before, and the earlier
+    /// entries are the points of those crashes, each numbered within its
+    /// own run. In a sweep this is just `[point_id]`.
+    pub history: Vec<usize>,
+
+    /// Barriers completed before the crash, in issue order.
+    pub(crate) barriers: Vec<BarrierRecord>,
+
+    /// Files flushed in part by the crash.
+    pub(crate) partial_flush: Vec<PartialFlush>,
 }
 
 impl CrashInfo {
     /// Create crash info from parsed metadata.
     pub(crate) fn new(point_id: usize, label: String) -> Self {
-        Self { point_id, label }
+        Self {
+            point_id,
+            label,
+            work_dir: PathBuf::new(),
+            max_fds: None,
+            partial_write: None,
+            total_points: None,
+            fsync_count: 0,
+            eintr_count: 0,
+            dir_fsync_fault: None,
+            fault: None,
+            elapsed: None,
+            site: None,
+            history: Vec::new(),
+            barriers: Vec::new(),
+            partial_flush: Vec::new(),
+        }
+    }
+
+    /// The durability barriers that completed before the crash, in the
+    /// order they were issued.
+    ///
+    /// Only [`Env::fsync()`] and [`Env::fdatasync()`] calls are recorded.
+    /// Use [`invariants::assert_barrier_order()`](crate::invariants::assert_barrier_order)
+    /// to check that one file was synced before another.
+    pub fn barriers(&self) -> &[BarrierRecord] {
+        &self.barriers
+    }
+
+    /// The files the crash flushed in part, when it landed inside a
+    /// barrier listed by `TestBuilder::crash_mid_fsync()`; empty
+    /// otherwise.
+    ///
+    /// Only files with pending bytes are listed.
+    pub fn partial_flush(&self) -> &[PartialFlush] {
+        &self.partial_flush
+    }
+
+    /// Set the crash site from a `file:line` string.
+    ///
+    /// The file name is leaked to obtain a `'static` string; this happens
+    /// at most once per crash point.
+    pub(crate) fn set_site(&mut self, site: &str) {
+        self.site = site.rsplit_once(':').and_then(|(file, line)| {
+            let line = line.parse().ok()?;
+            Some((&*Box::leak(file.to_string().into_boxed_str()), line))
+        });
+    }
+
+    /// Encode the crash site as `file:line`, for `FIRST_CRASH_SITE`.
+    pub(crate) fn site_to_env(&self) -> Option<String> {
+        self.site.map(|(file, line)| format!("{}:{}", file, line))
+    }
+
+    /// Returns true if this is the first crash point of the workload.
+    ///
+    /// At the first point nothing after it has run, so typically nothing
+    /// beyond setup has been written yet.
+    pub fn is_first_point(&self) -> bool {
+        self.point_id == 1
+    }
+
+    /// Returns true if this is the last crash point of the workload.
+    ///
+    /// Useful for end-state checks such as "on the last point, everything
+    /// must be durable".
+    ///
+    /// # Panics
+    ///
+    /// Panics if the total number of crash points is unknown. The total is
+    /// only available when the test is built with `TestBuilder::discover()`.
+    pub fn is_last_point(&self) -> bool {
+        let total = self
+            .total_points
+            .expect("CrashInfo::is_last_point() requires TestBuilder::discover()");
+        self.point_id == total
     }
 }
@@ -0,0 +1,207 @@
+//! I/O error injection at fault points.
+//!
+//! [`fault_point()`](crate::fault_point) is a crash point that, when it is
+//! the target, arms an error instead of killing the process: the next
+//! instrumented write or sync of the workload fails with `ENOSPC` or `EIO`.
+//! The workload then runs on, so its error handling is exercised, and the
+//! EXECUTION child is killed once the workload returns, as if it had
+//! crashed at the fault point. Verify sees the state the error handling
+//! left behind, with the injection in [`CrashInfo::fault`].
+//!
+//! Only I/O through FIRST can fail: the `Env` write and sync helpers, and
+//! files wrapped with [`Env::faulty_file()`](crate::Env::faulty_file).
+//!
+//! [`CrashInfo::fault`]: crate::CrashInfo::fault
+
+use std::fs::File;
+use std::io::{self, Write};
+use std::sync::OnceLock;
+
+use crate::journal;
+
+/// Error injected by a [`fault_point()`](crate::fault_point).
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum FaultKind {
+    /// The device is full (`ENOSPC`).
+    NoSpace,
+    /// The device failed (`EIO`).
+    IoError,
+}
+
+impl FaultKind {
+    /// The raw OS error code the failing call returns.
+    pub fn errno(self) -> i32 {
+        match self {
+            FaultKind::NoSpace => libc::ENOSPC,
+            FaultKind::IoError => libc::EIO,
+        }
+    }
+
+    /// Encode for crash metadata.
+    pub(crate) fn as_str(self) -> &'static str {
+        match self {
+            FaultKind::NoSpace => "enospc",
+            FaultKind::IoError => "eio",
+        }
+    }
+
+    /// Decode from [`FaultKind::as_str()`] format.
+    pub(crate) fn parse(s: &str) -> Option<Self> {
+        match s {
+            "enospc" => Some(FaultKind::NoSpace),
+            "eio" => Some(FaultKind::IoError),
+            _ => None,
+        }
+    }
+}
+
+/// The error a fault point injected before the crash.
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct InjectedFault {
+    /// The error injected.
+    pub kind: FaultKind,
+    /// The call that failed with it (`"write"`, `"fsync"`, `"fdatasync"`
+    /// or `"fsync_dir"`), or `None` if the workload made no instrumented
+    /// write or sync after the fault point.
+    pub operation: Option<String>,
+}
+
+impl InjectedFault {
+    /// Encode for the `FIRST_CRASH_FAULT` variable as `kind[:operation]`.
+    pub(crate) fn to_env(&self) -> String {
+        match &self.operation {
+            Some(operation) => format!("{}:{}", self.kind.as_str(), operation),
+            None => self.kind.as_str().to_string(),
+        }
+    }
+
+    /// Decode from [`InjectedFault::to_env()`] format.
+    pub(crate) fn from_env(s: &str) -> Option<Self> {
+        let (kind, operation) = match s.split_once(':') {
+            Some((kind, operation)) => (kind, Some(operation.to_string())),
+            None => (s, None),
+        };
+        Some(Self {
+            kind: FaultKind::parse(kind)?,
+            operation,
+        })
+    }
+}
+
+/// The targeted fault point, once armed: its ID and label.
+static TARGET: OnceLock<(usize, String)> = OnceLock::new();
+
+/// Arm `kind` at the targeted fault point `point_id`.
+pub(crate) fn arm(point_id: usize, label: &str, kind: FaultKind) {
+    if TARGET.set((point_id, label.to_string())).is_err() {
+        return;
+    }
+    journal::with(|j| {
+        j.armed_fault = Some(kind);
+        j.fault = Some(InjectedFault {
+            kind,
+            operation: None,
+        });
+    });
+}
+
+/// Fail the instrumented call `operation` if a fault is armed.
+pub(crate) fn inject(operation: &str) -> io::Result<()> {
+    journal::with(|j| match j.armed_fault.take() {
+        Some(kind) => {
+            j.fault = Some(InjectedFault {
+                kind,
+                operation: Some(operation.to_string()),
+            });
+            Err(io::Error::from_raw_os_error(kind.errno()))
+        }
+        None => Ok(()),
+    })
+}
+
+/// Kill the EXECUTION child at the end of its workload if a fault point
+/// was its target.
+pub(crate) fn finish_workload() {
+    if let Some((point_id, label)) = TARGET.get() {
+        crate::rt::crash_at(*point_id, label, None);
+    }
+}
+
+/// A workspace file whose writes and syncs can fail at a fault point.
+///
+/// Returned by [`Env::faulty_file()`](crate::Env::faulty_file), for code
+/// that writes through [`Write`] rather than the `Env` helpers. Each
+/// [`write()`](Write::write), [`sync_all()`](Self::sync_all) and
+/// [`sync_data()`](Self::sync_data) fails with the armed error, once,
+/// and otherwise goes straight to the file. Writes and syncs are not
+/// journaled: use [`Env::write_all()`](crate::Env::write_all) and
+/// [`Env::fsync()`](crate::Env::fsync) for that.
+#[derive(Debug)]
+pub struct FaultyFile {
+    file: File,
+}
+
+impl FaultyFile {
+    pub(crate) fn new(file: File) -> Self {
+        Self { file }
+    }
+
+    /// Sync data and metadata, like [`File::sync_all()`].
+    pub fn sync_all(&self) -> io::Result<()> {
+        inject("fsync")?;
+        self.file.sync_all()
+    }
+
+    /// Sync data, like [`File::sync_data()`].
+    pub fn sync_data(&self) -> io::Result<()> {
+        inject("fdatasync")?;
+        self.file.sync_data()
+    }
+
+    /// The wrapped file.
+    pub fn get_ref(&self) -> &File {
+        &self.file
+    }
+
+    /// Unwrap the file; its I/O no longer fails at fault points.
+    pub fn into_inner(self) -> File {
+        self.file
+    }
+}
+
+impl Write for FaultyFile {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        inject("write")?;
+        self.file.write(buf)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.file.flush()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_injected_fault_env_round_trip() {
+        for fault in [
+            InjectedFault {
+                kind: FaultKind::NoSpace,
+                operation: Some("write".to_string()),
+            },
+            InjectedFault {
+                kind: FaultKind::IoError,
+                operation: None,
+            },
+        ] {
+            assert_eq!(InjectedFault::from_env(&fault.to_env()), Some(fault));
+        }
+        assert_eq!(InjectedFault::from_env("ebusy:write"), None);
+    }
+}
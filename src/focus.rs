@@ -0,0 +1,133 @@
+//! Sweeps focused on recently changed code.
+//!
+//! With `TestBuilder::prioritize_changed()`, the orchestrator reads the
+//! files changed by the current diff from `FIRST_CHANGED_FILES` and splits
+//! the discovered crash points by the file of their `crash_point!` call
+//! site. Points in a changed file are swept first, every one of them; of
+//! the others, only a seeded sample is swept. Points without a call site
+//! (plain `crash_point()`) count as unchanged.
+
+use std::path::Path;
+
+use crate::orchestrator::shuffled;
+
+/// Files changed by the current diff, comma- or newline-separated.
+pub(crate) const ENV_CHANGED_FILES: &str = "FIRST_CHANGED_FILES";
+
+/// The crash points (or sites) to sweep, in order.
+#[derive(Debug, PartialEq, Eq)]
+pub(crate) struct Plan {
+    /// Points in changed files, swept exhaustively.
+    pub(crate) exhaustive: Vec<usize>,
+    /// Sampled points in unchanged files.
+    pub(crate) sampled: Vec<usize>,
+    /// Number of points in unchanged files, sampled or not.
+    pub(crate) unchanged: usize,
+}
+
+impl Plan {
+    /// Split the points `1..=sites.len()` by whether `sites[i - 1]`, a
+    /// `file:line` call site, lies in one of `changed`, and sample
+    /// `percent` of the unchanged ones with `seed`.
+    ///
+    /// `base` is the order to sweep in within each group.
+    pub(crate) fn new(
+        sites: &[Option<&str>],
+        changed: &[String],
+        percent: u32,
+        seed: u64,
+        base: &[usize],
+    ) -> Self {
+        let is_changed = |point: usize| {
+            sites[point - 1]
+                .map(|site| site.rsplit_once(':').map_or(site, |(file, _)| file))
+                .is_some_and(|file| changed.iter().any(|c| same_file(file, c)))
+        };
+        let unchanged: Vec<usize> = (1..=sites.len()).filter(|&p| !is_changed(p)).collect();
+        let count = (unchanged.len() * percent.min(100) as usize).div_ceil(100);
+        let picked: Vec<usize> = shuffled(unchanged.len(), seed)
+            .into_iter()
+            .take(count)
+            .map(|i| unchanged[i - 1])
+            .collect();
+
+        Self {
+            exhaustive: base.iter().copied().filter(|&p| is_changed(p)).collect(),
+            sampled: base
+                .iter()
+                .copied()
+                .filter(|p| picked.contains(p))
+                .collect(),
+            unchanged: unchanged.len(),
+        }
+    }
+
+    /// The sweep order: exhaustive points first, then the sample.
+    pub(crate) fn order(&self) -> Vec<usize> {
+        self.exhaustive
+            .iter()
+            .chain(&self.sampled)
+            .copied()
+            .collect()
+    }
+}
+
+/// The changed files named by `FIRST_CHANGED_FILES`, if it is set.
+pub(crate) fn changed_files() -> Option<Vec<String>> {
+    let value = std::env::var(ENV_CHANGED_FILES).ok()?;
+    let files: Vec<String> = value
+        .split([',', '\n'])
+        .map(str::trim)
+        .filter(|f| !f.is_empty())
+        .map(str::to_string)
+        .collect();
+    (!files.is_empty()).then_some(files)
+}
+
+/// Whether two paths name the same source file.
+///
+/// `file!()` is relative to the directory cargo was invoked from, while
+/// diff tools print paths relative to the repository root, so either may
+/// be a suffix of the other.
+fn same_file(site_file: &str, changed: &str) -> bool {
+    let (a, b) = (Path::new(site_file), Path::new(changed));
+    a.ends_with(b) || b.ends_with(a)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_changed_points_first_then_sample() {
+        let sites = [
+            Some("src/wal.rs:10"),
+            Some("src/index.rs:4"),
+            None,
+            Some("src/wal.rs:30"),
+            Some("src/index.rs:9"),
+        ];
+        let changed = vec!["crates/db/src/wal.rs".to_string()];
+        let base: Vec<usize> = (1..=5).collect();
+
+        let plan = Plan::new(&sites, &changed, 50, 7, &base);
+        assert_eq!(plan.exhaustive, vec![1, 4]);
+        assert_eq!(plan.unchanged, 3);
+        assert_eq!(plan.sampled.len(), 2);
+        assert!(plan.sampled.iter().all(|p| [2, 3, 5].contains(p)));
+        assert_eq!(plan.order()[..2], [1, 4]);
+        assert_eq!(plan, Plan::new(&sites, &changed, 50, 7, &base));
+
+        let none = Plan::new(&sites, &changed, 0, 7, &[4, 3, 2, 1, 5]);
+        assert_eq!(none.order(), vec![4, 1]);
+        let all = Plan::new(&sites, &changed, 100, 7, &[4, 3, 2, 1, 5]);
+        assert_eq!(all.order(), vec![4, 1, 3, 2, 5]);
+    }
+
+    #[test]
+    fn test_same_file() {
+        assert!(same_file("src/wal.rs", "db/src/wal.rs"));
+        assert!(same_file("db/src/wal.rs", "src/wal.rs"));
+        assert!(!same_file("src/wal.rs", "src/old_wal.rs"));
+    }
+}
@@ -0,0 +1,112 @@
+//! GitHub Actions workflow commands.
+//!
+//! When the sweep runs in GitHub Actions (`GITHUB_ACTIONS=true`), the
+//! orchestrator wraps the output of each crash point in a collapsible
+//! `::group::`, and reports each failing crash point as an `::error::`
+//! annotation with its label, reason and reproduction command, placed on
+//! the crash site when it is known. The usual `[first]` output is printed
+//! as well.
+//!
+//! Commands are written straight to stdout, past libtest's output capture,
+//! so annotations appear even when the test's own output is captured.
+
+use std::io::Write;
+use std::sync::atomic::{AtomicBool, Ordering};
+
+/// Set to `true` by GitHub Actions on its runners.
+const ENV_GITHUB_ACTIONS: &str = "GITHUB_ACTIONS";
+
+/// Whether the orchestrator runs in GitHub Actions.
+pub(crate) fn enabled() -> bool {
+    std::env::var(ENV_GITHUB_ACTIONS).as_deref() == Ok("true")
+}
+
+/// Set once the first command is written.
+static STARTED: AtomicBool = AtomicBool::new(false);
+
+/// Write one workflow command line.
+fn command(line: &str) {
+    let mut stdout = std::io::stdout().lock();
+    // Commands must start a line, and the harness leaves "test name ... "
+    // open while the test runs
+    if !STARTED.swap(true, Ordering::Relaxed) {
+        let _ = writeln!(stdout);
+    }
+    let _ = writeln!(stdout, "{}", line);
+    let _ = stdout.flush();
+}
+
+/// A log group, ended when dropped.
+pub(crate) struct Group {
+    enabled: bool,
+}
+
+impl Group {
+    /// Start a log group titled `title`, if in GitHub Actions.
+    pub(crate) fn start(title: &str) -> Self {
+        let enabled = enabled();
+        if enabled {
+            command(&format!("::group::{}", escape_data(title)));
+        }
+        Self { enabled }
+    }
+}
+
+impl Drop for Group {
+    fn drop(&mut self) {
+        if self.enabled {
+            command("::endgroup::");
+        }
+    }
+}
+
+/// Annotate a failure, at `site` if known, if in GitHub Actions.
+pub(crate) fn error(title: &str, message: &str, site: Option<(&str, u32)>) {
+    if enabled() {
+        command(&error_command(title, message, site));
+    }
+}
+
+/// The `::error` command annotating a failure.
+fn error_command(title: &str, message: &str, site: Option<(&str, u32)>) -> String {
+    let mut properties = Vec::new();
+    if let Some((file, line)) = site {
+        properties.push(format!("file={}", escape_property(file)));
+        properties.push(format!("line={}", line));
+    }
+    properties.push(format!("title={}", escape_property(title)));
+    format!("::error {}::{}", properties.join(","), escape_data(message))
+}
+
+/// Escape a command's message, which ends at the end of the line.
+fn escape_data(s: &str) -> String {
+    s.replace('%', "%25")
+        .replace('\r', "%0D")
+        .replace('\n', "%0A")
+}
+
+/// Escape a command property, which also ends at `,` and `::`.
+fn escape_property(s: &str) -> String {
+    escape_data(s).replace(':', "%3A").replace(',', "%2C")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_error_command() {
+        assert_eq!(
+            error_command(
+                "crash point 3 (\"after_commit\")",
+                "verification failed\nto reproduce: cargo test",
+                Some(("src/db.rs", 42)),
+            ),
+            "::error file=src/db.rs,line=42,title=crash point 3 (\"after_commit\")::verification failed%0Ato reproduce: cargo test"
+        );
+        assert_eq!(
+            error_command("a, b: c", "100%", None),
+            "::error title=a%2C b%3A c::100%25"
+        );
+    }
+}
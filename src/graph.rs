@@ -0,0 +1,90 @@
+//! Crash point graph export.
+//!
+//! Renders the DISCOVER timeline (instrumented filesystem operations
+//! interleaved with crash points) as a Graphviz DOT file, so reviewers can
+//! see which on-disk state each crash point actually tests.
+
+use std::fs;
+use std::io;
+use std::path::Path;
+
+use crate::discover::TimelineEntry;
+
+/// Write the timeline of `test_name` to `path` as a DOT graph.
+pub(crate) fn write(
+    path: &Path,
+    test_name: Option<&str>,
+    timeline: &[TimelineEntry],
+) -> io::Result<()> {
+    if let Some(parent) = path.parent().filter(|p| !p.as_os_str().is_empty()) {
+        fs::create_dir_all(parent)?;
+    }
+    fs::write(path, render_dot(test_name, timeline))
+}
+
+/// Render the timeline as a top-to-bottom chain of nodes.
+///
+/// Operations are boxes, crash points are highlighted octagons labelled
+/// with their ID and label.
+pub(crate) fn render_dot(test_name: Option<&str>, timeline: &[TimelineEntry]) -> String {
+    let mut out = String::from("digraph first {\n");
+    out.push_str(&format!(
+        "    label=\"crash points: {}\";\n",
+        escape(test_name.unwrap_or("<all tests>"))
+    ));
+    out.push_str("    labelloc=t;\n");
+    out.push_str("    node [fontname=\"monospace\"];\n");
+    out.push_str("    start [shape=circle, label=\"start\"];\n");
+
+    for (i, entry) in timeline.iter().enumerate() {
+        let node = match entry {
+            TimelineEntry::Point(point) => format!(
+                "shape=octagon, style=filled, fillcolor=\"#f4cccc\", label=\"#{} {}\"",
+                point.point_id,
+                escape(&point.label)
+            ),
+            TimelineEntry::Op { kind, target } => {
+                format!("shape=box, label=\"{} {}\"", kind, escape(target))
+            }
+        };
+        out.push_str(&format!("    n{} [{}];\n", i + 1, node));
+    }
+    out.push_str("    end [shape=doublecircle, label=\"end\"];\n");
+
+    let mut chain = vec!["start".to_string()];
+    chain.extend((1..=timeline.len()).map(|i| format!("n{}", i)));
+    chain.push("end".to_string());
+    out.push_str(&format!("    {};\n", chain.join(" -> ")));
+    out.push_str("}\n");
+    out
+}
+
+/// Escape a string for use inside a quoted DOT label.
+fn escape(s: &str) -> String {
+    s.replace('\\', "\\\\").replace('"', "\\\"")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::discover::DiscoveredPoint;
+
+    #[test]
+    fn test_render_dot() {
+        let timeline = vec![
+            TimelineEntry::Op {
+                kind: "write".to_string(),
+                target: "wal.log".to_string(),
+            },
+            TimelineEntry::Point(DiscoveredPoint {
+                point_id: 1,
+                label: "after \"write\"".to_string(),
+                site: None,
+            }),
+        ];
+        let dot = render_dot(Some("wal_test"), &timeline);
+        assert!(dot.contains("n1 [shape=box, label=\"write wal.log\"];"));
+        assert!(dot.contains("label=\"#1 after \\\"write\\\"\""));
+        assert!(dot.contains("start -> n1 -> n2 -> end;"));
+    }
+}
@@ -0,0 +1,226 @@
+//! Idempotence of recovery under its own crashes.
+//!
+//! With `TestBuilder::recover_idempotent()`, every workspace left by a
+//! crash at point K is also recovered in RECOVER children, on copies:
+//!
+//! 1. once without crashing, giving the reference state;
+//! 2. for each recovery crash point J, once crashing at J and once more to
+//!    completion.
+//!
+//! Each double-crash recovery must leave exactly the reference workspace.
+//! The original workspace is left untouched for the VERIFY child.
+
+use std::collections::BTreeMap;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+use crate::bundle::copy_tree;
+use crate::cgroup::ResourceLimits;
+use crate::orchestrator::{ChildResult, FIRST_BASE_DIR, cleanup_work_dir, io_failure, spawn_child};
+
+/// Recovery crash target meaning "never crash".
+const NO_TARGET: usize = usize::MAX;
+
+/// Check that recovering the workspace of crash point `target` is
+/// idempotent under crashes at up to `max_points` recovery crash points.
+///
+/// Returns why the crash point fails, if it does. The recovery dirs are
+/// kept for inspection on failure.
+#[allow(clippy::too_many_arguments)]
+pub(crate) fn check(
+    exe: &Path,
+    test_name: &Option<String>,
+    target: usize,
+    work_dir: &Path,
+    metadata_dir: &Path,
+    max_points: usize,
+    coverage_dir: Option<&Path>,
+    limits: Option<&ResourceLimits>,
+) -> Option<String> {
+    let base = PathBuf::from(FIRST_BASE_DIR)
+        .join("recover")
+        .join(format!("point_{}", target));
+    cleanup_work_dir(&base);
+
+    let recover = |dir: &Path, recovery_target: usize| {
+        spawn_child(
+            exe,
+            test_name,
+            "RECOVER",
+            recovery_target,
+            None,
+            dir,
+            metadata_dir,
+            coverage_dir,
+            limits,
+        )
+    };
+    let copy =
+        |dir: &Path| copy_tree(work_dir, dir).map_err(|e| io_failure("copy workspace to", dir, &e));
+
+    let clean = base.join("clean");
+    if let Err(e) = copy(&clean) {
+        return Some(e);
+    }
+    match recover(&clean, NO_TARGET) {
+        ChildResult::Success => {}
+        result => {
+            return Some(format!(
+                "clean recovery {} (see {})",
+                describe(&result),
+                clean.display()
+            ));
+        }
+    }
+
+    for point in 1..=max_points {
+        let dir = base.join(format!("crash_{}", point));
+        if let Err(e) = copy(&dir) {
+            return Some(e);
+        }
+        let crash_info = match recover(&dir, point) {
+            ChildResult::Crashed(crash_info) => crash_info,
+            // Recovery has fewer crash points than `point`
+            ChildResult::Success => break,
+            ChildResult::Skipped(_) => {
+                cleanup_work_dir(&dir);
+                continue;
+            }
+            result => {
+                return Some(format!(
+                    "recovery aiming at recovery crash point {} {} (see {})",
+                    point,
+                    describe(&result),
+                    dir.display()
+                ));
+            }
+        };
+        let pair = format!(
+            "crash point {}, then recovery crash point {} (\"{}\")",
+            target, point, crash_info.label
+        );
+        match recover(&dir, NO_TARGET) {
+            ChildResult::Success => {}
+            result => {
+                return Some(format!(
+                    "recovery after {} {} (see {})",
+                    pair,
+                    describe(&result),
+                    dir.display()
+                ));
+            }
+        }
+        match first_difference(&clean, &dir) {
+            Ok(None) => cleanup_work_dir(&dir),
+            Ok(Some(difference)) => {
+                return Some(format!(
+                    "recovery is not idempotent: after {}, {} compared to a single recovery (see {} and {})",
+                    pair,
+                    difference,
+                    clean.display(),
+                    dir.display()
+                ));
+            }
+            Err(e) => return Some(format!("cannot compare recovered workspaces: {}", e)),
+        }
+    }
+
+    cleanup_work_dir(&base);
+    None
+}
+
+/// Describe a recovery child that did not succeed.
+fn describe(result: &ChildResult) -> String {
+    match result {
+        ChildResult::Success => "succeeded".to_string(),
+        ChildResult::Crashed(_) => "crashed unexpectedly".to_string(),
+        ChildResult::Skipped(_) => "skipped a disabled crash point".to_string(),
+        ChildResult::Failed(code) => format!("failed with exit code {}", code),
+        ChildResult::TimedOut(limit) => format!("timed out after {:?}", limit),
+    }
+}
+
+/// Every entry of a workspace tree, keyed by its path relative to the
+/// root, for comparison.
+pub(crate) type Tree = BTreeMap<PathBuf, Entry>;
+
+/// An entry of a workspace tree, for comparison.
+#[derive(Debug, PartialEq, Eq)]
+pub(crate) enum Entry {
+    Dir,
+    File(Vec<u8>),
+    Symlink(PathBuf),
+}
+
+/// Every entry under `root`, keyed by its path relative to `root`.
+pub(crate) fn snapshot(root: &Path) -> io::Result<Tree> {
+    let mut entries = BTreeMap::new();
+    let mut stack = vec![root.to_path_buf()];
+    while let Some(dir) = stack.pop() {
+        for entry in fs::read_dir(&dir)? {
+            let entry = entry?;
+            let path = entry.path();
+            let relative = path.strip_prefix(root).unwrap_or(&path).to_path_buf();
+            let file_type = entry.file_type()?;
+            if file_type.is_dir() {
+                entries.insert(relative, Entry::Dir);
+                stack.push(path);
+            } else if file_type.is_symlink() {
+                entries.insert(relative, Entry::Symlink(fs::read_link(&path)?));
+            } else {
+                entries.insert(relative, Entry::File(fs::read(&path)?));
+            }
+        }
+    }
+    Ok(entries)
+}
+
+/// Describe the first path, in sorted order, where the trees at `expected`
+/// and `actual` differ.
+pub(crate) fn first_difference(expected: &Path, actual: &Path) -> io::Result<Option<String>> {
+    Ok(tree_difference(&snapshot(expected)?, &snapshot(actual)?))
+}
+
+/// Describe the first path, in sorted order, where two snapshots differ.
+pub(crate) fn tree_difference(expected: &Tree, actual: &Tree) -> Option<String> {
+    let mut paths: Vec<&PathBuf> = expected.keys().chain(actual.keys()).collect();
+    paths.sort();
+    paths.dedup();
+    paths
+        .into_iter()
+        .find_map(|path| match (expected.get(path), actual.get(path)) {
+            (Some(e), Some(a)) if e == a => None,
+            (Some(_), Some(_)) => Some(format!("{} differs", path.display())),
+            (Some(_), None) => Some(format!("{} is missing", path.display())),
+            (None, _) => Some(format!("{} is unexpected", path.display())),
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_first_difference() {
+        let expected = tempfile::tempdir().unwrap();
+        let actual = tempfile::tempdir().unwrap();
+        for dir in [expected.path(), actual.path()] {
+            fs::create_dir(dir.join("db")).unwrap();
+            fs::write(dir.join("db/data"), b"v1").unwrap();
+        }
+        let diff = || first_difference(expected.path(), actual.path()).unwrap();
+        assert_eq!(diff(), None);
+
+        fs::write(actual.path().join("db/data"), b"v2").unwrap();
+        assert_eq!(diff().as_deref(), Some("db/data differs"));
+
+        fs::write(actual.path().join("db/data"), b"v1").unwrap();
+        fs::write(actual.path().join("db/tmp"), b"").unwrap();
+        assert_eq!(diff().as_deref(), Some("db/tmp is unexpected"));
+
+        fs::remove_file(actual.path().join("db/tmp")).unwrap();
+        fs::remove_file(actual.path().join("db/data")).unwrap();
+        assert_eq!(diff().as_deref(), Some("db/data is missing"));
+    }
+}
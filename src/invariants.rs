@@ -0,0 +1,923 @@
+//! Invariant helpers for verify closures.
+//!
+//! Packages common crash-consistency checks so verify code can declare
+//! what is allowed instead of hand-writing exhaustive `match` arms.
+
+use std::fmt::Debug;
+use std::fs::{self, OpenOptions};
+use std::io::{self, Write};
+use std::os::unix::fs::MetadataExt;
+use std::path::{Path, PathBuf};
+
+use crate::env::{CrashInfo, Env};
+
+/// Assert that `actual` equals one of the `allowed` states.
+///
+/// On mismatch, panics with a message naming the unexpected state and
+/// listing every allowed alternative.
+///
+/// # Panics
+///
+/// Panics if `actual` is not equal to any element of `allowed`.
+///
+/// # Example
+///
+/// ```
+/// use first::invariants::assert_one_of;
+///
+/// let records = vec!["RECORD1"];
+/// assert_one_of(&records, &[vec![], vec!["RECORD1"], vec!["RECORD1", "RECORD2"]]);
+/// ```
+#[track_caller]
+pub fn assert_one_of<T: PartialEq + Debug>(actual: &T, allowed: &[T]) {
+    if allowed.contains(actual) {
+        return;
+    }
+    panic!(
+        "unexpected recovered state: {:?}\n{}",
+        actual,
+        format_allowed(allowed)
+    );
+}
+
+/// Declarative table of allowed recovered states per crash label.
+///
+/// Replaces `match (label, state)` blocks with a list of
+/// "after this label, the state is X or Y" declarations.
+///
+/// # Example
+///
+/// ```ignore
+/// let allowed = AllowedStates::new()
+///     .at("after_write_1", [vec![], vec!["RECORD1"]])
+///     .at("after_fsync", [vec!["RECORD1", "RECORD2"]]);
+///
+/// allowed.check(crash_info, &records);
+/// ```
+#[derive(Debug, Clone)]
+pub struct AllowedStates<T> {
+    entries: Vec<(String, Vec<T>)>,
+}
+
+impl<T: PartialEq + Debug> AllowedStates<T> {
+    /// Create an empty table.
+    pub fn new() -> Self {
+        Self {
+            entries: Vec::new(),
+        }
+    }
+
+    /// Declare the allowed states after crashing at `label`.
+    ///
+    /// Calling `at` again for the same label adds to its allowed states.
+    pub fn at(mut self, label: &str, states: impl IntoIterator<Item = T>) -> Self {
+        match self.entries.iter_mut().find(|(l, _)| l == label) {
+            Some((_, existing)) => existing.extend(states),
+            None => self
+                .entries
+                .push((label.to_string(), states.into_iter().collect())),
+        }
+        self
+    }
+
+    /// Check `actual` against the states declared for the crash's label.
+    ///
+    /// # Panics
+    ///
+    /// Panics if no states were declared for `crash_info.label`, or if
+    /// `actual` matches none of them.
+    #[track_caller]
+    pub fn check(&self, crash_info: &CrashInfo, actual: &T) {
+        let Some((_, allowed)) = self.entries.iter().find(|(l, _)| *l == crash_info.label) else {
+            panic!(
+                "no allowed states declared for crash point {} ('{}')",
+                crash_info.point_id, crash_info.label
+            );
+        };
+        if allowed.contains(actual) {
+            return;
+        }
+        panic!(
+            "Invariant violation at '{}' (point {}): unexpected recovered state: {:?}\n{}",
+            crash_info.label,
+            crash_info.point_id,
+            actual,
+            format_allowed(allowed)
+        );
+    }
+}
+
+impl<T: PartialEq + Debug> Default for AllowedStates<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Assert that `before` was synced before any barrier on `after`.
+///
+/// Checks the happens-before relation between durability barriers
+/// recorded by [`Env::fsync()`](crate::Env::fsync) and
+/// [`Env::fdatasync()`](crate::Env::fdatasync): every barrier on `after`
+/// that completed before the crash must be preceded by at least one
+/// barrier on `before`. Paths are workspace-relative, as in
+/// [`BarrierRecord::file`](crate::BarrierRecord::file).
+///
+/// Holds trivially if `after` was never synced.
+///
+/// # Panics
+///
+/// Panics, listing the recorded barriers, if `after` was synced while no
+/// barrier on `before` had completed.
+///
+/// # Example
+///
+/// ```ignore
+/// .verify(|env, crash_info| {
+///     // The segment must be durable before the manifest points at it.
+///     assert_barrier_order(crash_info, "data/segment_1", "MANIFEST");
+/// })
+/// ```
+#[track_caller]
+pub fn assert_barrier_order(
+    crash_info: &CrashInfo,
+    before: impl AsRef<Path>,
+    after: impl AsRef<Path>,
+) {
+    let (before, after) = (before.as_ref(), after.as_ref());
+    let barriers = crash_info.barriers();
+    let synced = |path: &Path| {
+        barriers
+            .iter()
+            .position(|b| b.file.as_deref() == Some(path))
+    };
+    let Some(first_after) = synced(after) else {
+        return;
+    };
+    if synced(before).is_some_and(|first_before| first_before < first_after) {
+        return;
+    }
+
+    let mut listing = String::from("recorded barriers:");
+    for barrier in barriers {
+        listing.push_str(&format!(
+            "\n  - {} {} (after point {})",
+            barrier.kind.as_str(),
+            barrier
+                .file
+                .as_ref()
+                .map(|f| f.display().to_string())
+                .unwrap_or_else(|| "<unknown>".to_string()),
+            barrier.after_point
+        ));
+    }
+    panic!(
+        "Invariant violation at '{}' (point {}): {} was synced before {}\n{}",
+        crash_info.label,
+        crash_info.point_id,
+        after.display(),
+        before.display(),
+        listing
+    );
+}
+
+/// File that [`checked()`] appends to, set by the orchestrator.
+pub(crate) const ENV_CHECKS_FILE: &str = "FIRST_CHECKS_FILE";
+
+/// Record that the named invariant was checked at the current crash point.
+///
+/// Call it inside verify next to the check itself. At the end of the
+/// sweep the orchestrator reports, per name, at how many crash points the
+/// invariant actually ran, e.g. `durability: checked at 12/40 points`.
+/// This shows whether conditional verify logic really exercised its
+/// checks or silently skipped them.
+///
+/// A no-op outside the VERIFY phase.
+///
+/// # Example
+///
+/// ```ignore
+/// .verify(|env, crash_info| {
+///     if crash_info.fsync_count > 0 {
+///         first::checked("durability");
+///         assert_eq!(recover(env), committed);
+///     }
+/// })
+/// ```
+pub fn checked(invariant: &str) {
+    let Some(path) = std::env::var_os(ENV_CHECKS_FILE) else {
+        return;
+    };
+    let target = std::env::var("FIRST_CRASH_TARGET").unwrap_or_default();
+    let name = invariant.replace(['\t', '\n'], " ");
+    // One write per line, so concurrent appends never interleave
+    if let Ok(mut file) = OpenOptions::new().create(true).append(true).open(path) {
+        let _ = file.write_all(format!("{}\t{}\n", target, name).as_bytes());
+    }
+}
+
+/// File that [`record_committed()`] appends to, set by the orchestrator.
+pub(crate) const ENV_COMMITTED_FILE: &str = "FIRST_COMMITTED_FILE";
+
+/// Record how much committed data recovery found at the current crash
+/// point.
+///
+/// For append-only or commit-ordered workloads, a later crash can only
+/// leave more committed data, never less. After the sweep the orchestrator
+/// checks that the recorded counts never decrease as the crash point ID
+/// grows, and fails the test naming the first pair of points that breaks
+/// this, e.g. a recovery that drops an already durable commit only when a
+/// later one is half written. Points without a record are skipped; if one
+/// point records several counts, the last one counts.
+///
+/// A no-op outside the VERIFY phase.
+///
+/// # Example
+///
+/// ```ignore
+/// .verify(|env, _| {
+///     let db = Db::open(env.path("db"));
+///     first::invariants::record_committed(db.committed_txns());
+/// })
+/// ```
+pub fn record_committed(count: u64) {
+    let Some(path) = std::env::var_os(ENV_COMMITTED_FILE) else {
+        return;
+    };
+    let point = std::env::var("FIRST_CRASH_POINT_ID").unwrap_or_default();
+    let label = std::env::var("FIRST_CRASH_LABEL")
+        .unwrap_or_default()
+        .replace(['\t', '\n'], " ");
+    if let Ok(mut file) = OpenOptions::new().create(true).append(true).open(path) {
+        let _ = file.write_all(format!("{}\t{}\t{}\n", point, count, label).as_bytes());
+    }
+}
+
+/// A committed count recorded by [`record_committed()`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct CommittedCount {
+    pub(crate) point_id: usize,
+    pub(crate) count: u64,
+    pub(crate) label: String,
+}
+
+/// Parse the lines written by [`record_committed()`], keeping the last
+/// count of each point, ordered by crash point ID.
+pub(crate) fn committed_counts(log: &str) -> Vec<CommittedCount> {
+    let mut counts: Vec<CommittedCount> = Vec::new();
+    for line in log.lines() {
+        let mut fields = line.splitn(3, '\t');
+        let (Some(Ok(point_id)), Some(Ok(count))) =
+            (fields.next().map(str::parse), fields.next().map(str::parse))
+        else {
+            continue;
+        };
+        let label = fields.next().unwrap_or_default().to_string();
+        counts.retain(|c| c.point_id != point_id);
+        counts.push(CommittedCount {
+            point_id,
+            count,
+            label,
+        });
+    }
+    counts.sort_by_key(|c| c.point_id);
+    counts
+}
+
+/// Index of the first count lower than the one before it, if any.
+pub(crate) fn first_decrease(counts: &[CommittedCount]) -> Option<usize> {
+    (1..counts.len()).find(|&i| counts[i].count < counts[i - 1].count)
+}
+
+/// Count, per invariant, the distinct crash points at which it was
+/// checked, from the lines written by [`checked()`].
+///
+/// Invariants are returned in order of first appearance.
+pub(crate) fn check_coverage(log: &str) -> Vec<(String, usize)> {
+    let mut seen: Vec<(&str, &str)> = Vec::new();
+    let mut coverage: Vec<(String, usize)> = Vec::new();
+    for (target, name) in log.lines().filter_map(|l| l.split_once('\t')) {
+        if seen.contains(&(target, name)) {
+            continue;
+        }
+        seen.push((target, name));
+        match coverage.iter_mut().find(|(n, _)| n == name) {
+            Some((_, count)) => *count += 1,
+            None => coverage.push((name.to_string(), 1)),
+        }
+    }
+    coverage
+}
+
+/// Sidecar with the synced length of each workspace file at crash point
+/// `target`.
+fn synced_lengths_name(target: &str) -> String {
+    format!("synced_lengths_{}", target)
+}
+
+/// Record how many bytes of each workspace file were covered by a
+/// barrier when the crash happened, for
+/// [`assert_not_durable_before_barrier()`].
+///
+/// Called by the EXECUTION child right before the crash effects. Errors
+/// are ignored, as for every crash-time action.
+pub(crate) fn record_synced_lengths(work_dir: &Path) {
+    let (Ok(metadata_dir), Ok(target)) = (
+        std::env::var("FIRST_METADATA_DIR"),
+        std::env::var("FIRST_CRASH_TARGET"),
+    ) else {
+        return;
+    };
+    let synced = crate::journal::with(|j| j.synced.clone());
+    let mut lines = String::new();
+    for path in crate::crash::regular_files(work_dir).unwrap_or_default() {
+        let Ok(meta) = fs::metadata(&path) else {
+            continue;
+        };
+        let len = synced
+            .iter()
+            .find(|s| s.dev == meta.dev() && s.ino == meta.ino())
+            .map_or(0, |s| s.len);
+        let relative = path.strip_prefix(work_dir).unwrap_or(&path);
+        lines.push_str(&format!("{}\t{}\n", len, relative.display()));
+    }
+    let _ = fs::write(
+        Path::new(&metadata_dir).join(synced_lengths_name(&target)),
+        lines,
+    );
+}
+
+/// Assert that no byte written after a file's last barrier survived the
+/// crash.
+///
+/// For each workspace file in `files`, the bytes covered by its last
+/// completed [`Env::fsync()`](crate::Env::fsync) or
+/// [`Env::fdatasync()`](crate::Env::fdatasync) before the crash are the
+/// only ones allowed on disk. Anything beyond them was written past the
+/// barrier the engine declared, e.g. a commit that returns while the next
+/// record is already half written. Call it at crash points where the
+/// engine claims nothing is pending, such as right after a commit
+/// returns, and before recovery modifies the files. Files that do not
+/// exist pass.
+///
+/// A process crash keeps unsynced bytes in the page cache, which is what
+/// makes them visible here. Do not combine it with
+/// `TestBuilder::lose_unsynced_writes()`, which removes exactly these
+/// bytes before verify runs.
+///
+/// # Panics
+///
+/// Panics, naming the file and both lengths, if a file is longer than its
+/// synced length.
+///
+/// # Example
+///
+/// ```ignore
+/// .verify(|env, crash_info| {
+///     if crash_info.label == "after_commit" {
+///         first::assert_not_durable_before_barrier(env, &["wal"]);
+///     }
+///     // recovery ...
+/// })
+/// ```
+#[track_caller]
+pub fn assert_not_durable_before_barrier(env: &Env, files: &[impl AsRef<Path>]) {
+    let target = env.crash_target().unwrap_or_default();
+    let sidecar = fs::read_to_string(env.metadata_path(synced_lengths_name(&target)))
+        .expect("no synced lengths recorded for this crash point (call this in verify)");
+    let synced: Vec<(u64, &str)> = sidecar
+        .lines()
+        .filter_map(|l| l.split_once('\t'))
+        .filter_map(|(len, file)| Some((len.parse().ok()?, file)))
+        .collect();
+    let (point, label) = env.crash_point().unwrap_or_default();
+
+    for file in files {
+        let file = file.as_ref();
+        let Ok(meta) = fs::metadata(env.path(file)) else {
+            continue;
+        };
+        let durable = synced
+            .iter()
+            .find(|(_, f)| Path::new(f) == file)
+            .map_or(0, |(len, _)| *len);
+        if meta.len() > durable {
+            panic!(
+                "Invariant violation at '{}' (point {}): {} has {} bytes after the crash, but only {} were synced by a barrier; {} bytes were written past the last barrier",
+                label,
+                point,
+                file.display(),
+                meta.len(),
+                durable,
+                meta.len() - durable
+            );
+        }
+    }
+}
+
+/// Open the crashed workspace, then reopen it `n` more times, asserting
+/// that recovery is a fixed point.
+///
+/// `open` opens (and so recovers) the engine, reads back its state and
+/// closes it again, e.g. by dropping the handle before returning. The
+/// first open may repair the workspace; every reopen after it must
+/// return a state equal to the first and leave every workspace file
+/// exactly as the previous open left it. Some recovery bugs only show on
+/// the second open after a crash, when recovery wrote something the next
+/// open mishandles.
+///
+/// Returns the state of the first open, for further checks.
+///
+/// # Panics
+///
+/// Panics naming the reopen that diverged if its state differs from the
+/// first, if it changed the workspace, or if `open` panicked in it.
+///
+/// # Example
+///
+/// ```ignore
+/// .verify(|env, crash_info| {
+///     let records = first::invariants::assert_stable_across_reopens(env, |env| {
+///         let db = Db::open(env.path("db")).unwrap();
+///         db.scan()
+///     }, 3);
+///     // records ...
+/// })
+/// ```
+#[track_caller]
+pub fn assert_stable_across_reopens<T, F>(env: &Env, mut open: F, n: usize) -> T
+where
+    T: PartialEq + Debug,
+    F: FnMut(&Env) -> T,
+{
+    let (point, label) = env.crash_point().unwrap_or_default();
+    let snapshot = || {
+        crate::idempotence::snapshot(&env.path(""))
+            .unwrap_or_else(|e| panic!("cannot snapshot the workspace: {}", e))
+    };
+
+    let first = open(env);
+    let mut tree = snapshot();
+    for reopen in 1..=n {
+        let state = match std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| open(env))) {
+            Ok(state) => state,
+            Err(payload) => panic!(
+                "Invariant violation at '{}' (point {}): reopen {} of {} panicked: {}",
+                label,
+                point,
+                reopen,
+                n,
+                crate::diagnose::payload_message(payload.as_ref())
+            ),
+        };
+        if state != first {
+            panic!(
+                "Invariant violation at '{}' (point {}): reopen {} of {} recovered a different state\n  first open: {:?}\n  reopen {}: {:?}",
+                label, point, reopen, n, first, reopen, state
+            );
+        }
+        let reopened = snapshot();
+        if let Some(difference) = crate::idempotence::tree_difference(&tree, &reopened) {
+            panic!(
+                "Invariant violation at '{}' (point {}): reopen {} of {} changed the workspace: {}",
+                label, point, reopen, n, difference
+            );
+        }
+        tree = reopened;
+    }
+    first
+}
+
+/// Assert that two byte strings are equal, with a hex dump on mismatch.
+///
+/// `assert_eq!` on large buffers prints two walls of decimal numbers.
+/// This instead reports both lengths, the first differing offset, and a
+/// hex dump of both sides around it with the differing bytes marked,
+/// which is what on-disk format debugging needs.
+///
+/// # Panics
+///
+/// Panics if `actual` and `expected` differ in content or length.
+///
+/// # Example
+///
+/// ```
+/// let recovered = b"HDR\x01record".to_vec();
+/// first::assert_bytes_eq(&recovered, b"HDR\x01record");
+/// ```
+#[track_caller]
+pub fn assert_bytes_eq(actual: impl AsRef<[u8]>, expected: impl AsRef<[u8]>) {
+    if let Some(diff) = byte_diff(actual.as_ref(), expected.as_ref()) {
+        panic!("{}", diff);
+    }
+}
+
+/// Bytes per hex dump row.
+const DUMP_ROW: usize = 16;
+
+/// Hex dump rows shown before and after the first difference.
+const DUMP_CONTEXT_ROWS: usize = 2;
+
+/// Describe how `actual` differs from `expected`, or `None` if it does not.
+fn byte_diff(actual: &[u8], expected: &[u8]) -> Option<String> {
+    let offset = actual
+        .iter()
+        .zip(expected)
+        .position(|(a, e)| a != e)
+        .or_else(|| (actual.len() != expected.len()).then(|| actual.len().min(expected.len())))?;
+
+    let mut out = format!(
+        "bytes differ at offset {} ({:#x}): actual is {} bytes, expected {} bytes",
+        offset,
+        offset,
+        actual.len(),
+        expected.len()
+    );
+    let row = offset / DUMP_ROW;
+    let first_row = row.saturating_sub(DUMP_CONTEXT_ROWS);
+    let end = actual.len().max(expected.len());
+    let last_row = (row + DUMP_CONTEXT_ROWS).min(end.saturating_sub(1) / DUMP_ROW);
+    for row in first_row..=last_row {
+        let start = row * DUMP_ROW;
+        out.push_str(&format!("\nexpected {}", dump_row(expected, start)));
+        out.push_str(&format!("\nactual   {}", dump_row(actual, start)));
+        let marks: String = (start..start + DUMP_ROW)
+            .map(|i| match actual.get(i) == expected.get(i) {
+                true => "   ",
+                false => "^^ ",
+            })
+            .collect();
+        if marks.contains('^') {
+            out.push_str(&format!("\n{:19}{}", "", marks.trim_end()));
+        }
+    }
+    Some(out)
+}
+
+/// One hex dump row of `bytes` starting at `start`; bytes past the end
+/// are left blank.
+fn dump_row(bytes: &[u8], start: usize) -> String {
+    let cells = start..start + DUMP_ROW;
+    let hex: Vec<String> = cells
+        .clone()
+        .map(|i| {
+            bytes
+                .get(i)
+                .map_or("  ".to_string(), |b| format!("{:02x}", b))
+        })
+        .collect();
+    let ascii: String = cells
+        .filter_map(|i| bytes.get(i))
+        .map(|&b| match b {
+            0x20..=0x7e => b as char,
+            _ => '.',
+        })
+        .collect();
+    format!("{:08x}  {}  |{}|", start, hex.join(" "), ascii)
+}
+
+/// Checksums of the committed versions of workspace files.
+///
+/// The workload records the checksum of each file version it commits;
+/// verify then checks that every file present after the crash is one of
+/// those versions, never a stale or partially written one. Useful for
+/// engines that checksum their own files.
+///
+/// Records are kept in a sidecar file in the metadata directory (see
+/// [`Env::metadata_path()`]), one per crash point, so they survive the
+/// crash and reach the verify child of the same point.
+///
+/// Record a version before writing it: a crash between the write and the
+/// record would otherwise expose a file the manifest does not know.
+///
+/// # Example
+///
+/// ```ignore
+/// .run(|env| {
+///     let manifest = DurabilityManifest::open(env);
+///     manifest.commit("segment", crc(b"v1")).unwrap();
+///     first::atomic_write(env, "segment", b"v1").unwrap();
+/// })
+/// .verify(|env, _| {
+///     DurabilityManifest::open(env).assert_files(env, crc);
+/// })
+/// ```
+#[derive(Debug, Clone)]
+pub struct DurabilityManifest {
+    path: PathBuf,
+}
+
+impl DurabilityManifest {
+    /// Open the manifest of the current crash point.
+    pub fn open(env: &Env) -> Self {
+        let target = env.crash_target().unwrap_or_default();
+        Self {
+            path: env.metadata_path(format!("durability_manifest_{}", target)),
+        }
+    }
+
+    /// Record `checksum` as a committed version of the workspace file
+    /// `file`.
+    pub fn commit(&self, file: impl AsRef<Path>, checksum: u64) -> io::Result<()> {
+        let mut manifest = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&self.path)?;
+        manifest.write_all(format!("{:016x}\t{}\n", checksum, file.as_ref().display()).as_bytes())
+    }
+
+    /// Checksums committed for `file`, oldest first.
+    pub fn committed(&self, file: impl AsRef<Path>) -> Vec<u64> {
+        let file = file.as_ref().display().to_string();
+        self.records()
+            .into_iter()
+            .filter(|(_, f)| *f == file)
+            .map(|(checksum, _)| checksum)
+            .collect()
+    }
+
+    /// Assert that `checksum` is a committed version of `file`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `checksum` was never committed for `file`.
+    #[track_caller]
+    pub fn assert_committed(&self, file: impl AsRef<Path>, checksum: u64) {
+        let file = file.as_ref();
+        let committed = self.committed(file);
+        assert!(
+            committed.contains(&checksum),
+            "{} has checksum {:016x}, which matches no committed version\n{}",
+            file.display(),
+            checksum,
+            format_allowed(
+                &committed
+                    .iter()
+                    .map(|c| format!("{:016x}", c))
+                    .collect::<Vec<_>>()
+            )
+        );
+    }
+
+    /// Assert that every file in the manifest that exists in the workspace
+    /// is a committed version, computing checksums with `checksum`.
+    ///
+    /// Files that do not exist pass: whether they must exist is for the
+    /// caller to check.
+    ///
+    /// # Panics
+    ///
+    /// Panics on the first file whose contents match no committed version,
+    /// or that exists but cannot be read.
+    #[track_caller]
+    pub fn assert_files(&self, env: &Env, checksum: impl Fn(&[u8]) -> u64) {
+        let mut files: Vec<String> = Vec::new();
+        for (_, file) in self.records() {
+            if !files.contains(&file) {
+                files.push(file);
+            }
+        }
+        for file in files {
+            let data = match fs::read(env.path(&file)) {
+                Ok(data) => data,
+                Err(e) if e.kind() == io::ErrorKind::NotFound => continue,
+                Err(e) => panic!("cannot read {}: {}", file, e),
+            };
+            self.assert_committed(&file, checksum(&data));
+        }
+    }
+
+    /// Every `(checksum, file)` record, in commit order.
+    fn records(&self) -> Vec<(u64, String)> {
+        let contents = fs::read_to_string(&self.path).unwrap_or_default();
+        contents
+            .lines()
+            .filter_map(|line| {
+                let (checksum, file) = line.split_once('\t')?;
+                Some((u64::from_str_radix(checksum, 16).ok()?, file.to_string()))
+            })
+            .collect()
+    }
+}
+
+/// Render the allowed states as an indented list for failure messages.
+fn format_allowed<T: Debug>(allowed: &[T]) -> String {
+    let mut out = String::from("allowed states:");
+    for state in allowed {
+        out.push_str(&format!("\n  - {:?}", state));
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::env::{BarrierKind, BarrierRecord};
+
+    fn barrier(file: &str) -> BarrierRecord {
+        BarrierRecord {
+            kind: BarrierKind::Fsync,
+            file: Some(file.into()),
+            after_point: 0,
+            range: None,
+        }
+    }
+
+    #[test]
+    fn test_assert_barrier_order_accepts_correct_order() {
+        let mut info = CrashInfo::new(2, "after_manifest".to_string());
+        info.barriers = vec![barrier("data"), barrier("MANIFEST")];
+        assert_barrier_order(&info, "data", "MANIFEST");
+        // Never synced: holds trivially
+        assert_barrier_order(&info, "data", "other");
+    }
+
+    #[test]
+    #[should_panic(expected = "MANIFEST was synced before data")]
+    fn test_assert_barrier_order_rejects_reversed_order() {
+        let mut info = CrashInfo::new(2, "after_data".to_string());
+        info.barriers = vec![barrier("MANIFEST"), barrier("data")];
+        assert_barrier_order(&info, "data", "MANIFEST");
+    }
+
+    #[test]
+    fn test_byte_diff() {
+        assert_eq!(byte_diff(b"same", b"same"), None);
+
+        let expected: Vec<u8> = (0..64).collect();
+        let mut actual = expected.clone();
+        actual[37] = 0xff;
+        let diff = byte_diff(&actual, &expected).unwrap();
+        let lines: Vec<&str> = diff.lines().collect();
+        assert_eq!(
+            lines[0],
+            "bytes differ at offset 37 (0x25): actual is 64 bytes, expected 64 bytes"
+        );
+        assert!(lines[1].starts_with("expected 00000000  00 01 02"));
+        let row = lines
+            .iter()
+            .position(|l| l.starts_with("actual   00000020"))
+            .unwrap();
+        assert!(lines[row].contains(" 24 ff 26 "));
+        assert_eq!(lines[row + 1].find('^'), lines[row].find("ff"));
+
+        let truncated = byte_diff(&expected[..20], &expected).unwrap();
+        assert!(truncated.starts_with("bytes differ at offset 20 (0x14): actual is 20 bytes"));
+    }
+
+    #[test]
+    #[should_panic(expected = "bytes differ at offset 1")]
+    fn test_assert_bytes_eq_panics() {
+        assert_bytes_eq(b"ab", b"ac");
+    }
+
+    #[test]
+    fn test_assert_stable_across_reopens_accepts_fixed_point() {
+        let dir = tempfile::tempdir().unwrap();
+        let env = manifest_env(dir.path());
+        let mut opens = 0;
+        let state = assert_stable_across_reopens(
+            &env,
+            |env| {
+                opens += 1;
+                // Recovery repairs once, then leaves the file alone
+                if !env.path("log").exists() {
+                    fs::write(env.path("log"), b"repaired").unwrap();
+                }
+                fs::read(env.path("log")).unwrap()
+            },
+            3,
+        );
+        assert_eq!(state, b"repaired");
+        assert_eq!(opens, 4);
+    }
+
+    #[test]
+    #[should_panic(expected = "reopen 2 of 3 recovered a different state")]
+    fn test_assert_stable_across_reopens_rejects_diverging_state() {
+        let dir = tempfile::tempdir().unwrap();
+        let env = manifest_env(dir.path());
+        let mut opens = 0;
+        assert_stable_across_reopens(
+            &env,
+            |_| {
+                opens += 1;
+                opens == 3
+            },
+            3,
+        );
+    }
+
+    #[test]
+    #[should_panic(expected = "reopen 1 of 2 changed the workspace: log differs")]
+    fn test_assert_stable_across_reopens_rejects_changed_workspace() {
+        let dir = tempfile::tempdir().unwrap();
+        let env = manifest_env(dir.path());
+        assert_stable_across_reopens(
+            &env,
+            |env| {
+                let mut log = OpenOptions::new()
+                    .create(true)
+                    .append(true)
+                    .open(env.path("log"))
+                    .unwrap();
+                log.write_all(b"opened\n").unwrap();
+            },
+            2,
+        );
+    }
+
+    #[test]
+    fn test_check_coverage() {
+        let log = "1\tatomicity\n1\tatomicity\n2\tatomicity\n2\tdurability\n";
+        assert_eq!(
+            check_coverage(log),
+            vec![("atomicity".to_string(), 2), ("durability".to_string(), 1)]
+        );
+    }
+
+    #[test]
+    fn test_committed_counts_find_decrease() {
+        let log = "2\t1\tafter_b\n1\t0\tafter_a\n3\t2\tafter_c\n3\t0\tafter_c\n";
+        let counts = committed_counts(log);
+        assert_eq!(
+            counts
+                .iter()
+                .map(|c| (c.point_id, c.count))
+                .collect::<Vec<_>>(),
+            vec![(1, 0), (2, 1), (3, 0)]
+        );
+        assert_eq!(counts[2].label, "after_c");
+        assert_eq!(first_decrease(&counts), Some(2));
+        assert_eq!(first_decrease(&counts[..2]), None);
+    }
+
+    #[test]
+    fn test_assert_one_of_accepts_member() {
+        assert_one_of(&2, &[1, 2, 3]);
+    }
+
+    #[test]
+    #[should_panic(expected = "unexpected recovered state: 4")]
+    fn test_assert_one_of_rejects_non_member() {
+        assert_one_of(&4, &[1, 2, 3]);
+    }
+
+    #[test]
+    fn test_allowed_states_check() {
+        let allowed = AllowedStates::new()
+            .at("after_write", [0, 1])
+            .at("after_fsync", [1]);
+        allowed.check(&CrashInfo::new(1, "after_write".to_string()), &0);
+        allowed.check(&CrashInfo::new(2, "after_fsync".to_string()), &1);
+    }
+
+    #[test]
+    #[should_panic(expected = "Invariant violation at 'after_fsync'")]
+    fn test_allowed_states_rejects_unexpected() {
+        let allowed = AllowedStates::new().at("after_fsync", [1]);
+        allowed.check(&CrashInfo::new(2, "after_fsync".to_string()), &0);
+    }
+
+    #[test]
+    #[should_panic(expected = "no allowed states declared")]
+    fn test_allowed_states_unknown_label() {
+        let allowed: AllowedStates<i32> = AllowedStates::new();
+        allowed.check(&CrashInfo::new(1, "mystery".to_string()), &0);
+    }
+
+    fn manifest_env(dir: &Path) -> Env {
+        fs::create_dir_all(dir.join("work")).unwrap();
+        fs::create_dir_all(dir.join("meta")).unwrap();
+        Env::new(dir.join("work"), dir.join("meta"))
+    }
+
+    fn len_checksum(data: &[u8]) -> u64 {
+        data.len() as u64
+    }
+
+    #[test]
+    fn test_durability_manifest_accepts_committed_versions() {
+        let dir = tempfile::tempdir().unwrap();
+        let env = manifest_env(dir.path());
+        let manifest = DurabilityManifest::open(&env);
+        manifest.commit("segment", 2).unwrap();
+        manifest.commit("segment", 4).unwrap();
+        manifest.commit("missing", 1).unwrap();
+        assert_eq!(manifest.committed("segment"), vec![2, 4]);
+
+        fs::write(env.path("segment"), b"v2").unwrap();
+        manifest.assert_files(&env, len_checksum);
+    }
+
+    #[test]
+    #[should_panic(expected = "matches no committed version")]
+    fn test_durability_manifest_rejects_partial_version() {
+        let dir = tempfile::tempdir().unwrap();
+        let env = manifest_env(dir.path());
+        let manifest = DurabilityManifest::open(&env);
+        manifest.commit("segment", 4).unwrap();
+
+        fs::write(env.path("segment"), b"v").unwrap();
+        manifest.assert_files(&env, len_checksum);
+    }
+}
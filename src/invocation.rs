@@ -0,0 +1,113 @@
+//! Several `first::test()` invocations in one `#[test]` function.
+//!
+//! Every child runs the whole test function again, so each `execute()`
+//! call in a process is numbered from 1 in the order it is reached. The
+//! orchestrator sweeps one invocation at a time and passes its number, or
+//! its `TestBuilder::label()`, to every child in `FIRST_INVOCATION`. A
+//! child runs only the phase of that invocation: the others return at
+//! once, so the crash point counter of the child counts the targeted
+//! invocation's crash points alone.
+//!
+//! The first invocation without a label keeps the work dirs and
+//! reproduction commands of a single-invocation test; later or labelled
+//! invocations get their own work dir root, so one sweep never cleans up
+//! the failing workspaces of another.
+
+use std::path::PathBuf;
+use std::sync::Mutex;
+
+use crate::loopback::LoopbackFs;
+
+/// Invocation (number or label) children run; unset means the first.
+pub(crate) const ENV_INVOCATION: &str = "FIRST_INVOCATION";
+
+/// The invocation being executed in this process.
+struct Current {
+    /// Number of `execute()` calls so far, i.e. that of the current one.
+    index: usize,
+    label: Option<String>,
+}
+
+static CURRENT: Mutex<Current> = Mutex::new(Current {
+    index: 0,
+    label: None,
+});
+
+fn current() -> std::sync::MutexGuard<'static, Current> {
+    CURRENT.lock().unwrap_or_else(|e| e.into_inner())
+}
+
+/// Start the next invocation of this process, returning whether this
+/// process runs it: always in the orchestrator, and in a child only if
+/// `FIRST_INVOCATION` names it.
+pub(crate) fn begin(label: Option<&str>, orchestrator: bool) -> bool {
+    let mut current = current();
+    current.index += 1;
+    current.label = label.map(str::to_string);
+    orchestrator || is_target(current.index, label, std::env::var(ENV_INVOCATION).ok())
+}
+
+/// Whether invocation `index` with `label` is the one `target` names.
+fn is_target(index: usize, label: Option<&str>, target: Option<String>) -> bool {
+    match target {
+        None => index == 1,
+        Some(target) => target == index.to_string() || label.is_some_and(|l| l == target),
+    }
+}
+
+/// Value of `FIRST_INVOCATION` for the children of the current invocation.
+pub(crate) fn env_value() -> String {
+    let current = current();
+    match &current.label {
+        Some(label) => label.clone(),
+        None => current.index.max(1).to_string(),
+    }
+}
+
+/// The label or number of the current invocation, unless it is the
+/// default one.
+pub(crate) fn name() -> Option<String> {
+    (!is_default()).then(env_value)
+}
+
+/// ` FIRST_INVOCATION=<label>` for reproduction commands, unless the
+/// current invocation is the default one.
+pub(crate) fn repro_env() -> String {
+    name()
+        .map(|name| format!(" {}=\"{}\"", ENV_INVOCATION, name))
+        .unwrap_or_default()
+}
+
+/// The work dir of crash point `target` in the current invocation, for a
+/// sweep with workspaces on `fs`.
+pub(crate) fn run_dir(target: usize, fs: Option<&LoopbackFs>) -> PathBuf {
+    let root = crate::loopback::work_root(fs);
+    let run = format!("run_{}", target);
+    if is_default() {
+        root.join(run)
+    } else {
+        let name = env_value().replace('/', "_");
+        root.join(format!("invocation_{}", name)).join(run)
+    }
+}
+
+/// A first invocation without a label, as in a single-invocation test.
+fn is_default() -> bool {
+    let current = current();
+    current.index <= 1 && current.label.is_none()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_is_target() {
+        assert!(is_target(1, None, None));
+        assert!(!is_target(2, None, None));
+        assert!(is_target(2, None, Some("2".to_string())));
+        assert!(is_target(2, Some("wal"), Some("wal".to_string())));
+        assert!(is_target(2, Some("wal"), Some("2".to_string())));
+        assert!(!is_target(1, Some("wal"), Some("2".to_string())));
+    }
+}
@@ -0,0 +1,272 @@
+//! Execution journal.
+//!
+//! Records what the instrumented `Env` helpers did during the EXECUTION
+//! phase, so the crash metadata can describe the state at the crash point.
+
+use std::fs::File;
+use std::os::unix::fs::MetadataExt;
+use std::os::unix::io::AsRawFd;
+use std::sync::Mutex;
+
+use crate::env::{BarrierRecord, CrashInfo, InjectedDirFsync, PartialFlush, PartialWrite};
+use crate::fault::{FaultKind, InjectedFault};
+
+/// Facts recorded by instrumented I/O during execution.
+#[derive(Debug, Default)]
+pub(crate) struct Journal {
+    /// The write that was cut short by `Env::write_then_crash()`.
+    pub(crate) partial_write: Option<PartialWrite>,
+    /// Number of `Env::fsync()` / `Env::fdatasync()` calls completed so far.
+    pub(crate) fsync_count: usize,
+    /// `EINTR` errors injected into barriers so far.
+    pub(crate) eintr_count: usize,
+    /// `EINTR` errors injected since the last barrier that went through.
+    pub(crate) eintr_streak: usize,
+    /// `Env::fsync_dir()` calls so far.
+    pub(crate) dir_fsync_calls: usize,
+    /// The directory sync made to fail by `TestBuilder::fail_dir_fsync()`.
+    pub(crate) dir_fsync_fault: Option<InjectedDirFsync>,
+    /// Error armed by the targeted fault point, until a call fails with it.
+    pub(crate) armed_fault: Option<FaultKind>,
+    /// The error injected by the targeted fault point.
+    pub(crate) fault: Option<InjectedFault>,
+    /// Completed `Env::fsync()` / `Env::fdatasync()` calls, in order.
+    pub(crate) barriers: Vec<BarrierRecord>,
+    /// Length of each file as of its last sync, keyed by inode.
+    pub(crate) synced: Vec<SyncedFile>,
+    /// Pages persisted by the crash inside a barrier
+    /// (`TestBuilder::crash_mid_fsync()`).
+    pub(crate) partial_flush: Vec<PartialFlush>,
+    /// Contents of each file as of its last sync, keyed by inode
+    /// (`TestBuilder::drop_unsynced()`).
+    pub(crate) synced_data: Vec<SyncedData>,
+    /// The last unsynced write through `Env::write_all()` /
+    /// `Env::write_all_at()` to each file, most recent last.
+    pub(crate) writes: Vec<LoggedWrite>,
+}
+
+/// A file's durable length, recorded when it was last synced.
+///
+/// Keyed by device and inode so the record follows the file across
+/// renames and separate handles.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) struct SyncedFile {
+    pub(crate) dev: u64,
+    pub(crate) ino: u64,
+    pub(crate) len: u64,
+}
+
+/// A file's durable contents, recorded when it was last synced.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct SyncedData {
+    pub(crate) dev: u64,
+    pub(crate) ino: u64,
+    pub(crate) data: Vec<u8>,
+}
+
+/// A write logged by the `Env` write shims and not synced since.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) struct LoggedWrite {
+    pub(crate) dev: u64,
+    pub(crate) ino: u64,
+    pub(crate) offset: u64,
+    pub(crate) len: u64,
+}
+
+/// Process-wide journal. Only the EXECUTION child ever writes to it.
+static JOURNAL: Mutex<Journal> = Mutex::new(Journal {
+    partial_write: None,
+    fsync_count: 0,
+    eintr_count: 0,
+    eintr_streak: 0,
+    dir_fsync_calls: 0,
+    dir_fsync_fault: None,
+    armed_fault: None,
+    fault: None,
+    barriers: Vec::new(),
+    synced: Vec::new(),
+    partial_flush: Vec::new(),
+    synced_data: Vec::new(),
+    writes: Vec::new(),
+});
+
+/// Run `f` with exclusive access to the journal.
+pub(crate) fn with<T>(f: impl FnOnce(&mut Journal) -> T) -> T {
+    let mut journal = JOURNAL.lock().unwrap_or_else(|e| e.into_inner());
+    f(&mut journal)
+}
+
+/// Record the current length of `file` as durable, after a sync.
+pub(crate) fn record_synced(file: &File) {
+    let Ok(meta) = file.metadata() else {
+        return;
+    };
+    let synced = SyncedFile {
+        dev: meta.dev(),
+        ino: meta.ino(),
+        len: meta.len(),
+    };
+    // Through /proc, since the handle itself may be write-only
+    let data = crate::rt::options()
+        .drop_unsynced
+        .then(|| std::fs::read(format!("/proc/self/fd/{}", file.as_raw_fd())).ok())
+        .flatten();
+    with(|journal| {
+        journal
+            .writes
+            .retain(|w| w.dev != synced.dev || w.ino != synced.ino);
+        match journal
+            .synced
+            .iter_mut()
+            .find(|s| s.dev == synced.dev && s.ino == synced.ino)
+        {
+            Some(existing) => *existing = synced,
+            None => journal.synced.push(synced),
+        }
+        if let Some(data) = data {
+            journal
+                .synced_data
+                .retain(|s| s.dev != synced.dev || s.ino != synced.ino);
+            journal.synced_data.push(SyncedData {
+                dev: synced.dev,
+                ino: synced.ino,
+                data,
+            });
+        }
+    });
+}
+
+/// Log a write of `len` bytes at `offset` in `file`, replacing the
+/// file's previous one.
+pub(crate) fn record_write(file: &File, offset: u64, len: usize) {
+    let Ok(meta) = file.metadata() else {
+        return;
+    };
+    let write = LoggedWrite {
+        dev: meta.dev(),
+        ino: meta.ino(),
+        offset,
+        len: len as u64,
+    };
+    with(|journal| {
+        journal
+            .writes
+            .retain(|w| w.dev != write.dev || w.ino != write.ino);
+        journal.writes.push(write);
+    });
+}
+
+/// Copy the journal into the crash info reported at the crash point.
+pub(crate) fn fill_crash_info(info: &mut CrashInfo) {
+    with(|journal| {
+        info.partial_write = journal.partial_write.clone();
+        info.fsync_count = journal.fsync_count;
+        info.eintr_count = journal.eintr_count;
+        info.dir_fsync_fault = journal.dir_fsync_fault.clone();
+        info.fault = journal.fault.clone();
+        info.barriers = journal.barriers.clone();
+        info.partial_flush = journal.partial_flush.clone();
+    });
+}
+
+/// Render the journal as extra fields for the crash metadata JSON.
+///
+/// Returns a string starting with `,`, ready to be appended
+/// after the last fixed field.
+pub(crate) fn metadata_fields() -> String {
+    with(|journal| {
+        let mut fields = format!(
+            r#","fsync_count":{},"eintr_count":{}"#,
+            journal.fsync_count, journal.eintr_count
+        );
+        if let Some(injected) = &journal.dir_fsync_fault {
+            fields.push_str(&format!(
+                r#","dir_fsync_after_point":{},"dir_fsync_fault":"{}","dir_fsync_dir":"{}""#,
+                injected.after_point,
+                injected.fault.to_env(),
+                crate::report::escape_json(&injected.dir.to_string_lossy()),
+            ));
+        }
+        if let Some(fault) = &journal.fault {
+            fields.push_str(&format!(
+                r#","fault":"{}""#,
+                crate::report::escape_json(&fault.to_env())
+            ));
+        }
+        if let Some(partial) = &journal.partial_write {
+            fields.push_str(&format!(
+                r#","partial_file":"{}","partial_offset":{},"partial_written":{},"partial_len":{}"#,
+                crate::report::escape_json(&partial.file.to_string_lossy()),
+                partial.offset,
+                partial.written,
+                partial.len
+            ));
+        }
+        fields
+    })
+}
+
+/// Render each partially flushed file as a `{"event":"partial_flush",...}`
+/// line.
+///
+/// The persisted ranges use the `FIRST_CRASH_PARTIAL_FLUSH` encoding; the
+/// file is the last field so it can be read up to the final quote.
+pub(crate) fn partial_flush_events() -> Vec<String> {
+    with(|journal| {
+        journal
+            .partial_flush
+            .iter()
+            .map(|flush| {
+                let persisted: Vec<String> = flush
+                    .persisted
+                    .iter()
+                    .map(|r| format!("{}-{}", r.start, r.end))
+                    .collect();
+                format!(
+                    r#"{{"event":"partial_flush","synced_len":{},"len":{},"persisted":"{}","file":"{}"}}"#,
+                    flush.synced_len,
+                    flush.len,
+                    persisted.join(","),
+                    crate::report::escape_json(&flush.file.to_string_lossy())
+                )
+            })
+            .collect()
+    })
+}
+
+/// Render each recorded barrier as a `{"event":"barrier",...}` line.
+///
+/// The file, when known, is the last field so it can be read up to the
+/// final quote.
+pub(crate) fn barrier_events() -> Vec<String> {
+    with(|journal| {
+        journal
+            .barriers
+            .iter()
+            .map(|barrier| {
+                let file = barrier
+                    .file
+                    .as_ref()
+                    .map(|f| {
+                        format!(
+                            r#","file":"{}""#,
+                            crate::report::escape_json(&f.to_string_lossy())
+                        )
+                    })
+                    .unwrap_or_default();
+                let range = barrier
+                    .range
+                    .as_ref()
+                    .map(|r| format!(r#","range_start":{},"range_end":{}"#, r.start, r.end))
+                    .unwrap_or_default();
+                format!(
+                    r#"{{"event":"barrier","kind":"{}","after_point":{}{}{}}}"#,
+                    barrier.kind.as_str(),
+                    barrier.after_point,
+                    range,
+                    file
+                )
+            })
+            .collect()
+    })
+}
@@ -0,0 +1,192 @@
+//! Crash point label coverage.
+//!
+//! With `FIRST_COVERAGE=<path>`, every EXECUTION child appends the label
+//! of each crash point it passes, crashed at or not, to its own file in
+//! the metadata directory. When the sweep ends, the orchestrator merges
+//! these with the verdicts of the sweep into a JSON report at `<path>`:
+//! how often each label was reached in one run, and how many of its crash
+//! points were crashed at. A label reached but never crashed at points at
+//! a schedule that ends before it or a label that was disabled; a label
+//! missing from the report was never reached at all.
+
+use std::collections::BTreeMap;
+use std::fs::{self, File, OpenOptions};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::sync::{Mutex, OnceLock};
+
+use crate::report::escape_json;
+
+/// Path of the label coverage report; unset disables label recording.
+pub(crate) const ENV_COVERAGE: &str = "FIRST_COVERAGE";
+
+/// Version of the coverage report format, bumped on incompatible changes.
+const COVERAGE_VERSION: u32 = 1;
+
+/// Directory of the per-child label files in `metadata_dir`.
+fn dir(metadata_dir: &Path) -> PathBuf {
+    metadata_dir.join("labels")
+}
+
+/// Record that an EXECUTION child passed a crash point labelled `label`.
+///
+/// A no-op without `FIRST_COVERAGE`. Each label is written as it is
+/// passed, so the file is complete however the child ends.
+pub(crate) fn record(label: &str) {
+    static FILE: OnceLock<Option<Mutex<File>>> = OnceLock::new();
+    let file = FILE.get_or_init(|| {
+        std::env::var_os(ENV_COVERAGE)?;
+        let dir = dir(Path::new(&std::env::var_os("FIRST_METADATA_DIR")?));
+        fs::create_dir_all(&dir).ok()?;
+        OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(dir.join(std::process::id().to_string()))
+            .ok()
+            .map(Mutex::new)
+    });
+    if let Some(file) = file {
+        // Labels are kept one per line
+        let line = format!("{}\n", label.replace('\n', " "));
+        let mut file = file.lock().unwrap_or_else(|e| e.into_inner());
+        let _ = file.write_all(line.as_bytes());
+    }
+}
+
+/// Coverage of one label.
+#[derive(Debug, Default, PartialEq, Eq)]
+struct Coverage {
+    /// Most crash points with this label passed by one EXECUTION child.
+    reached: usize,
+    /// Crash points with this label that were crashed at.
+    crashed: usize,
+}
+
+/// Write the label coverage report of a sweep, if `FIRST_COVERAGE` is set.
+///
+/// `verdicts` are the ID, label and result of every crash point swept.
+pub(crate) fn write_report(metadata_dir: &Path, verdicts: &[(usize, String, &'static str)]) {
+    let Some(path) = std::env::var_os(ENV_COVERAGE).filter(|p| !p.is_empty()) else {
+        return;
+    };
+    let path = PathBuf::from(path);
+    let runs: Vec<String> = fs::read_dir(dir(metadata_dir))
+        .into_iter()
+        .flatten()
+        .flatten()
+        .filter_map(|entry| fs::read_to_string(entry.path()).ok())
+        .collect();
+    let coverage = merge(&runs, verdicts);
+
+    let entries: Vec<String> = coverage
+        .iter()
+        .map(|(label, c)| {
+            format!(
+                "{{\"label\":\"{}\",\"reached\":{},\"crashed\":{}}}",
+                escape_json(label),
+                c.reached,
+                c.crashed
+            )
+        })
+        .collect();
+    let json = format!(
+        "{{\"version\":{},\"labels\":[{}]}}\n",
+        COVERAGE_VERSION,
+        entries.join(",")
+    );
+    if let Err(e) = fs::write(&path, json) {
+        eprintln!(
+            "[first] warning: {}",
+            crate::orchestrator::io_failure("write label coverage", &path, &e)
+        );
+        return;
+    }
+
+    let never_crashed: Vec<&str> = coverage
+        .iter()
+        .filter(|(_, c)| c.crashed == 0)
+        .map(|(label, _)| label.as_str())
+        .collect();
+    eprintln!(
+        "[first] label coverage written to {}: {} labels reached",
+        path.display(),
+        coverage.len()
+    );
+    if !never_crashed.is_empty() {
+        eprintln!(
+            "[first] labels reached but never crashed at: {:?}",
+            never_crashed
+        );
+    }
+}
+
+/// Merge the label files of EXECUTION children with the sweep verdicts.
+fn merge(
+    runs: &[String],
+    verdicts: &[(usize, String, &'static str)],
+) -> BTreeMap<String, Coverage> {
+    let mut coverage: BTreeMap<String, Coverage> = BTreeMap::new();
+    for run in runs {
+        let mut counts: BTreeMap<&str, usize> = BTreeMap::new();
+        for label in run.lines() {
+            *counts.entry(label).or_default() += 1;
+        }
+        for (label, count) in counts {
+            let entry = coverage.entry(label.to_string()).or_default();
+            entry.reached = entry.reached.max(count);
+        }
+    }
+    for (_, label, result) in verdicts {
+        if *result != "skipped"
+            && let Some(entry) = coverage.get_mut(label)
+        {
+            entry.crashed += 1;
+        }
+    }
+    coverage
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_merge() {
+        let runs = [
+            "open\nwrite\n".to_string(),
+            "open\nwrite\nwrite\nsync\n".to_string(),
+        ];
+        let verdicts = [
+            (1, "open".to_string(), "pass"),
+            (2, "write".to_string(), "fail"),
+            (3, "write".to_string(), "skipped"),
+        ];
+        let coverage = merge(&runs, &verdicts);
+        assert_eq!(
+            coverage.into_iter().collect::<Vec<_>>(),
+            [
+                (
+                    "open".to_string(),
+                    Coverage {
+                        reached: 1,
+                        crashed: 1
+                    }
+                ),
+                (
+                    "sync".to_string(),
+                    Coverage {
+                        reached: 1,
+                        crashed: 0
+                    }
+                ),
+                (
+                    "write".to_string(),
+                    Coverage {
+                        reached: 2,
+                        crashed: 1
+                    }
+                ),
+            ]
+        );
+    }
+}
@@ -27,18 +27,74 @@
 //!
 //! # Limitations (v0.1)
 //!
-//! - One `first::test()` per `#[test]` function
-//! - Async tests (`#[tokio::test]`) not supported
-//! - Not thread-safe (`crash_point()` from spawned threads is undefined)
+//! - Several `first::test()` invocations per `#[test]` function must be
+//!   reached in a deterministic order
+//! - Async workloads (`run_async()`) need a current-thread runtime for
+//!   deterministic crash point IDs
+//! - `crash_point()` from spawned threads needs `allow_threads()`, and
+//!   its IDs follow scheduling order
 //! - No nested workspaces
 //!
 //! See `docs/limitations.md` for full details.
 
+mod atomic;
+mod bisect;
+mod bundle;
+mod cgroup;
+mod checkpoint;
+mod chunking;
+mod crash;
+#[cfg(feature = "serde")]
+mod crash_event;
+mod determinism;
+mod diagnose;
+mod discover;
 mod env;
+mod fault;
+mod focus;
+mod github;
+mod graph;
+mod idempotence;
+pub mod invariants;
+mod invocation;
+mod journal;
+mod labels;
+mod loopback;
+mod metadata;
+mod mmap;
 mod orchestrator;
+mod otel;
+mod reader;
+mod recovery;
+mod repeat;
+mod replay;
+mod report;
 mod rt;
+mod snapshot;
+mod suite;
+mod terminate;
 mod test;
+mod timed;
+mod timeout;
+mod verify_log;
 
-pub use env::{CrashInfo, Env};
-pub use rt::crash_point;
-pub use test::test;
+pub use atomic::atomic_write;
+pub use cgroup::ResourceLimits;
+pub use checkpoint::{Checkpoint, MemoryCheckpoint, checkpoint_memory, memory_checkpoint};
+pub use chunking::ChunkedReader;
+pub use env::{
+    BarrierKind, BarrierRecord, CrashInfo, DirFsyncFault, Env, InjectedDirFsync, PartialFlush,
+    PartialWrite,
+};
+pub use fault::{FaultKind, FaultyFile, InjectedFault};
+pub use first_macros::{crash_test, run, verify};
+pub use invariants::{
+    DurabilityManifest, assert_bytes_eq, assert_not_durable_before_barrier,
+    assert_stable_across_reopens, checked,
+};
+pub use loopback::LoopbackFs;
+pub use mmap::MappedFile;
+pub use recovery::{RecoveryTimer, recovery_timer};
+pub use rt::{crash_point, crash_point_at, crash_point_if, fault_point, is_orchestrator, seed};
+pub use suite::suite_invariant;
+pub use test::{PointSelector, test};
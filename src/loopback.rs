@@ -0,0 +1,257 @@
+//! Workspaces on a loopback filesystem image (Linux).
+//!
+//! With `TestBuilder::loopback_fs()`, the orchestrator creates an image
+//! file, formats it with `mkfs.<type>` and loop-mounts it. Every crash
+//! point's workspace is then created on that filesystem instead of the
+//! host's `/tmp`, so the sweep runs against the chosen filesystem whatever
+//! the host uses. Sweeps asking for the same type and size share an image;
+//! the images are unmounted and removed when the orchestrator exits, unless
+//! failing workspaces were kept on them.
+//!
+//! Formatting and mounting need root (or `CAP_SYS_ADMIN`) and the mkfs
+//! tool of the type. Where that fails, a warning is printed for the sweep
+//! and its workspaces stay on the host filesystem.
+
+use std::fs::{self, File};
+use std::io;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+use std::sync::{Mutex, Once};
+
+use crate::orchestrator::FIRST_BASE_DIR;
+
+/// Image size unless set with [`LoopbackFs::size()`].
+const DEFAULT_SIZE: u64 = 256 * 1024 * 1024;
+
+/// Filesystem image holding the workspaces.
+///
+/// # Example
+///
+/// ```ignore
+/// first::test()
+///     .loopback_fs(first::LoopbackFs::new("xfs").size(512 * 1024 * 1024))
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LoopbackFs {
+    fs_type: String,
+    size: u64,
+}
+
+impl LoopbackFs {
+    /// An image formatted as `fs_type`, e.g. `"ext4"` or `"xfs"`, with
+    /// `mkfs.<fs_type>`.
+    pub fn new(fs_type: &str) -> Self {
+        Self {
+            fs_type: fs_type.to_string(),
+            size: DEFAULT_SIZE,
+        }
+    }
+
+    /// Size of the image in bytes (256 MiB by default). It must hold the
+    /// workspaces of one crash point and the filesystem's own metadata;
+    /// xfs needs at least 300 MiB.
+    pub fn size(mut self, bytes: u64) -> Self {
+        self.size = bytes;
+        self
+    }
+}
+
+/// The images set up so far, one per distinct [`LoopbackFs`].
+static MOUNTS: Mutex<Vec<Mount>> = Mutex::new(Vec::new());
+
+/// Registers the unmount of all images, with the first mount.
+static AT_EXIT: Once = Once::new();
+
+#[derive(Debug)]
+struct Mount {
+    fs: LoopbackFs,
+    image: PathBuf,
+    dir: PathBuf,
+    /// Whether the image could be mounted at `dir`.
+    mounted: bool,
+}
+
+/// Mount the image described by a sweep's `fs`, unless an earlier sweep
+/// with the same `fs` did.
+///
+/// Called by the orchestrator at the start of every sweep; prints a
+/// warning and leaves the sweep's workspaces on the host filesystem if
+/// the image cannot be set up.
+pub(crate) fn configure(fs: Option<&LoopbackFs>) {
+    let Some(fs) = fs else {
+        return;
+    };
+    let mut mounts = MOUNTS.lock().unwrap_or_else(|e| e.into_inner());
+    if let Some(mount) = mounts.iter().find(|m| m.fs == *fs) {
+        if !mount.mounted {
+            warn_unavailable(fs, "set up failed earlier");
+        }
+        return;
+    }
+    let base = Path::new(FIRST_BASE_DIR)
+        .join("loop")
+        .join(format!("{}_{}", fs.fs_type, fs.size));
+    let image = base.join(format!("{}.img", fs.fs_type));
+    let dir = base.join("mnt");
+    let mounted = match mount(fs, &image, &dir) {
+        Ok(()) => {
+            AT_EXIT.call_once(|| {
+                // SAFETY: registers a function without arguments; atexit
+                // has no other preconditions.
+                if unsafe { libc::atexit(unmount_at_exit) } != 0 {
+                    eprintln!("[first] warning: cannot register unmount of loopback images");
+                }
+            });
+            eprintln!(
+                "[first] workspaces on {} loopback image ({} MiB) at {}",
+                fs.fs_type,
+                fs.size / (1024 * 1024),
+                dir.display()
+            );
+            true
+        }
+        Err(e) => {
+            warn_unavailable(fs, &e.to_string());
+            unmount(&image, &dir);
+            false
+        }
+    };
+    mounts.push(Mount {
+        fs: fs.clone(),
+        image,
+        dir,
+        mounted,
+    });
+}
+
+fn warn_unavailable(fs: &LoopbackFs, reason: &str) {
+    eprintln!(
+        "[first] warning: {} loopback filesystem unavailable ({}); using the host filesystem",
+        fs.fs_type, reason
+    );
+}
+
+/// Directory to create the crash point workspaces of a sweep with `fs`
+/// in.
+pub(crate) fn work_root(fs: Option<&LoopbackFs>) -> PathBuf {
+    fs.and_then(mount_dir)
+        .unwrap_or_else(|| PathBuf::from(FIRST_BASE_DIR))
+}
+
+/// Where the image for `fs` is mounted, if it is.
+fn mount_dir(fs: &LoopbackFs) -> Option<PathBuf> {
+    let mounts = MOUNTS.lock().unwrap_or_else(|e| e.into_inner());
+    mounts
+        .iter()
+        .find(|m| m.mounted && m.fs == *fs)
+        .map(|m| m.dir.clone())
+}
+
+/// The filesystem a sweep with `fs` had its workspaces on, for the run
+/// summary.
+pub(crate) fn describe(fs: Option<&LoopbackFs>) -> Option<String> {
+    let fs = fs?;
+    mount_dir(fs)?;
+    Some(format!(
+        "{} (loopback image, {} MiB)",
+        fs.fs_type,
+        fs.size / (1024 * 1024)
+    ))
+}
+
+/// Create, format and mount the image.
+fn mount(fs: &LoopbackFs, image: &Path, dir: &Path) -> io::Result<()> {
+    // A mount left by an orchestrator that was killed
+    unmount(image, dir);
+    fs::create_dir_all(dir)?;
+    File::create(image)?.set_len(fs.size)?;
+
+    let mut mkfs = Command::new(format!("mkfs.{}", fs.fs_type));
+    mkfs.args(mkfs_flags(&fs.fs_type)).arg(image);
+    run(&mut mkfs)?;
+
+    let mut mount = Command::new("mount");
+    mount
+        .args(["-t", &fs.fs_type, "-o", "loop"])
+        .arg(image)
+        .arg(dir);
+    run(&mut mount)
+}
+
+/// Flags that make `mkfs.<fs_type>` quiet and overwrite without asking.
+fn mkfs_flags(fs_type: &str) -> &'static [&'static str] {
+    match fs_type {
+        "ext2" | "ext3" | "ext4" => &["-q", "-F"],
+        "xfs" | "btrfs" => &["-q", "-f"],
+        _ => &[],
+    }
+}
+
+/// Run `cmd`, turning a failure into an error carrying its stderr.
+fn run(cmd: &mut Command) -> io::Result<()> {
+    let output = cmd.output().map_err(|e| {
+        io::Error::new(
+            e.kind(),
+            format!("cannot run {}: {}", cmd.get_program().to_string_lossy(), e),
+        )
+    })?;
+    if output.status.success() {
+        return Ok(());
+    }
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    Err(io::Error::other(format!(
+        "{} failed: {}",
+        cmd.get_program().to_string_lossy(),
+        stderr.trim()
+    )))
+}
+
+/// Unmount `dir` and remove `image`, ignoring what is already gone.
+fn unmount(image: &Path, dir: &Path) {
+    let _ = Command::new("umount").arg(dir).output();
+    let _ = fs::remove_file(image);
+}
+
+/// Unmount the images, except those with workspaces kept for inspection.
+extern "C" fn unmount_at_exit() {
+    let mounts = MOUNTS.lock().unwrap_or_else(|e| e.into_inner());
+    for mount in mounts.iter().filter(|m| m.mounted) {
+        if holds_workspaces(&mount.dir) {
+            // The next orchestrator replaces it
+            eprintln!(
+                "[first] loopback image left mounted at {} for inspection",
+                mount.dir.display()
+            );
+        } else {
+            unmount(&mount.image, &mount.dir);
+        }
+    }
+}
+
+/// Whether `dir` holds anything besides what mkfs created.
+fn holds_workspaces(dir: &Path) -> bool {
+    fs::read_dir(dir)
+        .map(|entries| entries.flatten().any(|e| e.file_name() != "lost+found"))
+        .unwrap_or(false)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_mkfs_flags() {
+        assert_eq!(mkfs_flags("ext4"), ["-q", "-F"]);
+        assert_eq!(mkfs_flags("xfs"), ["-q", "-f"]);
+        assert!(mkfs_flags("vfat").is_empty());
+    }
+
+    #[test]
+    fn test_holds_workspaces() {
+        let dir = tempfile::tempdir().unwrap();
+        fs::create_dir(dir.path().join("lost+found")).unwrap();
+        assert!(!holds_workspaces(dir.path()));
+        fs::create_dir(dir.path().join("run_3")).unwrap();
+        assert!(holds_workspaces(dir.path()));
+    }
+}
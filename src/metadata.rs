@@ -0,0 +1,310 @@
+//! Binary crash metadata.
+//!
+//! With `TestBuilder::binary_metadata()`, the EXECUTION child reports its
+//! crash as one binary record written to a file in the metadata directory,
+//! instead of JSON lines on stderr. The orchestrator names the file in
+//! `FIRST_METADATA_FILE` and prefers it over stderr when it exists.
+//!
+//! # Format
+//!
+//! All integers are little-endian. A record is the magic `FRSTMETA`, a
+//! `u32` format version, then the fields below in order. Strings are a
+//! `u32` byte length followed by UTF-8 bytes; a length of `u32::MAX`
+//! encodes `None`. Optional integers use `u64::MAX` (or `0` for the line)
+//! for `None`.
+//!
+//! | Field | Type |
+//! |-------|------|
+//! | `point_id` | `u64` |
+//! | `max_fds` | `u64` |
+//! | `fsync_count` | `u64` |
+//! | `eintr_count` | `u64` |
+//! | `label` | string |
+//! | site file | string |
+//! | site line | `u32` |
+//! | partial write file | string; the next three fields follow only if present |
+//! | partial write offset, written, len | `u64` each |
+//! | injected directory sync fault | string (`FIRST_CRASH_DIR_FSYNC` format) |
+//! | fault point error | string (`FIRST_CRASH_FAULT` format) |
+//! | elapsed workload time at the crash | `u64` microseconds |
+//! | partial flush count | `u32`, then per file a string (`FIRST_CRASH_PARTIAL_FLUSH` format) |
+//! | barrier count | `u32`, then per barrier: kind (string), `after_point` (`u64`), range start and end (`u64` each, both `u64::MAX` for `None`), file (string) |
+//!
+//! Readers reject records with an unknown magic or version.
+
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+use crate::env::{
+    BarrierKind, BarrierRecord, CrashInfo, InjectedDirFsync, PartialFlush, PartialWrite,
+};
+use crate::fault::InjectedFault;
+
+/// File the EXECUTION child writes its binary crash record to.
+pub(crate) const ENV_METADATA_FILE: &str = "FIRST_METADATA_FILE";
+
+/// Start of every record.
+const MAGIC: &[u8; 8] = b"FRSTMETA";
+
+/// Format version; bump on any layout change.
+const VERSION: u32 = 5;
+
+/// Encoding of `None` for an optional string.
+const NO_STRING: u32 = u32::MAX;
+
+/// Encoding of `None` for an optional integer.
+const NO_NUMBER: u64 = u64::MAX;
+
+/// Binary record file of the `phase` child for crash point `target`.
+pub(crate) fn record_path(metadata_dir: &Path, phase: &str, target: usize) -> PathBuf {
+    metadata_dir.join(format!(".crash_{}_{}", phase.to_lowercase(), target))
+}
+
+/// Write `info` to the file named by `FIRST_METADATA_FILE`.
+///
+/// Returns false if the variable is unset or the write failed, in which
+/// case the caller falls back to JSON.
+pub(crate) fn write(info: &CrashInfo) -> bool {
+    let Some(path) = std::env::var_os(ENV_METADATA_FILE) else {
+        return false;
+    };
+    // The page cache survives the SIGKILL; no sync is needed
+    fs::write(path, encode(info)).is_ok()
+}
+
+/// Read and remove the record at `path`, if the child wrote one.
+pub(crate) fn take(path: &Path) -> Option<CrashInfo> {
+    let bytes = fs::read(path).ok()?;
+    let _ = fs::remove_file(path);
+    decode(&bytes)
+}
+
+/// Encode `info` as a binary record.
+pub(crate) fn encode(info: &CrashInfo) -> Vec<u8> {
+    let mut out = Vec::with_capacity(128);
+    out.extend_from_slice(MAGIC);
+    out.extend_from_slice(&VERSION.to_le_bytes());
+    put_u64(&mut out, info.point_id as u64);
+    put_u64(&mut out, info.max_fds.map_or(NO_NUMBER, |n| n as u64));
+    put_u64(&mut out, info.fsync_count as u64);
+    put_u64(&mut out, info.eintr_count as u64);
+    put_str(&mut out, Some(&info.label));
+    put_str(&mut out, info.site.map(|(file, _)| file));
+    out.extend_from_slice(&info.site.map_or(0, |(_, line)| line).to_le_bytes());
+    match &info.partial_write {
+        Some(partial) => {
+            put_str(&mut out, Some(&partial.file.to_string_lossy()));
+            put_u64(&mut out, partial.offset);
+            put_u64(&mut out, partial.written as u64);
+            put_u64(&mut out, partial.len as u64);
+        }
+        None => put_str(&mut out, None),
+    }
+    let injected = info.dir_fsync_fault.as_ref().map(|i| i.to_env());
+    put_str(&mut out, injected.as_deref());
+    let fault = info.fault.as_ref().map(|f| f.to_env());
+    put_str(&mut out, fault.as_deref());
+    put_u64(
+        &mut out,
+        info.elapsed.map_or(NO_NUMBER, |d| d.as_micros() as u64),
+    );
+    out.extend_from_slice(&(info.partial_flush.len() as u32).to_le_bytes());
+    for flush in &info.partial_flush {
+        put_str(&mut out, Some(&flush.to_env()));
+    }
+    out.extend_from_slice(&(info.barriers.len() as u32).to_le_bytes());
+    for barrier in &info.barriers {
+        put_str(&mut out, Some(barrier.kind.as_str()));
+        put_u64(&mut out, barrier.after_point as u64);
+        let (start, end) = barrier
+            .range
+            .as_ref()
+            .map_or((NO_NUMBER, NO_NUMBER), |r| (r.start, r.end));
+        put_u64(&mut out, start);
+        put_u64(&mut out, end);
+        let file = barrier.file.as_ref().map(|f| f.to_string_lossy());
+        put_str(&mut out, file.as_deref());
+    }
+    out
+}
+
+/// Decode a record produced by [`encode()`].
+pub(crate) fn decode(bytes: &[u8]) -> Option<CrashInfo> {
+    let mut r = Reader { bytes };
+    if r.take(MAGIC.len())? != MAGIC || r.u32()? != VERSION {
+        return None;
+    }
+    let point_id = r.u64()? as usize;
+    let max_fds = r.u64()?;
+    let fsync_count = r.u64()? as usize;
+    let eintr_count = r.u64()? as usize;
+    let label = r.string()??;
+
+    let mut info = CrashInfo::new(point_id, label);
+    info.max_fds = (max_fds != NO_NUMBER).then_some(max_fds as usize);
+    info.fsync_count = fsync_count;
+    info.eintr_count = eintr_count;
+    let site_file = r.string()?;
+    let site_line = r.u32()?;
+    if let Some(file) = site_file {
+        info.set_site(&format!("{}:{}", file, site_line));
+    }
+    if let Some(file) = r.string()? {
+        let mut partial = PartialWrite::new(PathBuf::from(file));
+        partial.offset = r.u64()?;
+        partial.written = r.u64()? as usize;
+        partial.len = r.u64()? as usize;
+        info.partial_write = Some(partial);
+    }
+    if let Some(injected) = r.string()? {
+        info.dir_fsync_fault = Some(InjectedDirFsync::from_env(&injected)?);
+    }
+    if let Some(fault) = r.string()? {
+        info.fault = Some(InjectedFault::from_env(&fault)?);
+    }
+    let elapsed = r.u64()?;
+    info.elapsed = (elapsed != NO_NUMBER).then(|| Duration::from_micros(elapsed));
+    for _ in 0..r.u32()? {
+        info.partial_flush
+            .push(PartialFlush::from_env(&r.string()??)?);
+    }
+    for _ in 0..r.u32()? {
+        let kind = BarrierKind::parse(&r.string()??)?;
+        let after_point = r.u64()? as usize;
+        let (start, end) = (r.u64()?, r.u64()?);
+        let file = r.string()?.map(PathBuf::from);
+        info.barriers.push(BarrierRecord {
+            kind,
+            file,
+            after_point,
+            range: (start != NO_NUMBER).then_some(start..end),
+        });
+    }
+    Some(info)
+}
+
+fn put_u64(out: &mut Vec<u8>, n: u64) {
+    out.extend_from_slice(&n.to_le_bytes());
+}
+
+fn put_str(out: &mut Vec<u8>, s: Option<&str>) {
+    match s {
+        Some(s) => {
+            out.extend_from_slice(&(s.len() as u32).to_le_bytes());
+            out.extend_from_slice(s.as_bytes());
+        }
+        None => out.extend_from_slice(&NO_STRING.to_le_bytes()),
+    }
+}
+
+/// Cursor over a record; every read fails on truncated input.
+struct Reader<'a> {
+    bytes: &'a [u8],
+}
+
+impl<'a> Reader<'a> {
+    fn take(&mut self, n: usize) -> Option<&'a [u8]> {
+        if self.bytes.len() < n {
+            return None;
+        }
+        let (head, tail) = self.bytes.split_at(n);
+        self.bytes = tail;
+        Some(head)
+    }
+
+    fn u32(&mut self) -> Option<u32> {
+        Some(u32::from_le_bytes(self.take(4)?.try_into().ok()?))
+    }
+
+    fn u64(&mut self) -> Option<u64> {
+        Some(u64::from_le_bytes(self.take(8)?.try_into().ok()?))
+    }
+
+    /// An optional string: `Some(None)` is a valid `None`.
+    fn string(&mut self) -> Option<Option<String>> {
+        match self.u32()? {
+            NO_STRING => Some(None),
+            len => {
+                let bytes = self.take(len as usize)?;
+                Some(Some(String::from_utf8(bytes.to_vec()).ok()?))
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_round_trip() {
+        let mut info = CrashInfo::new(7, "after_commit".to_string());
+        info.max_fds = Some(12);
+        info.fsync_count = 3;
+        info.eintr_count = 1;
+        info.set_site("src/db.rs:42");
+        let mut partial = PartialWrite::new(PathBuf::from("wal"));
+        partial.offset = 100;
+        partial.written = 5;
+        partial.len = 9;
+        info.partial_write = Some(partial.clone());
+        info.dir_fsync_fault = InjectedDirFsync::from_env("2:dropped:db");
+        info.elapsed = Some(Duration::from_micros(100_250));
+        info.fault = InjectedFault::from_env("eio:fsync");
+        info.partial_flush = vec![
+            PartialFlush::from_env("100:9000:4096-8192,8192-9000:wal").unwrap(),
+            PartialFlush::from_env("0:10::data/a:b").unwrap(),
+        ];
+        info.barriers = vec![
+            BarrierRecord {
+                kind: BarrierKind::Fsync,
+                file: Some(PathBuf::from("wal")),
+                after_point: 2,
+                range: None,
+            },
+            BarrierRecord {
+                kind: BarrierKind::Msync,
+                file: None,
+                after_point: 6,
+                range: Some(0..4096),
+            },
+        ];
+
+        let bytes = encode(&info);
+        let decoded = decode(&bytes).unwrap();
+        assert_eq!(decoded.point_id, 7);
+        assert_eq!(decoded.label, "after_commit");
+        assert_eq!(decoded.max_fds, Some(12));
+        assert_eq!((decoded.fsync_count, decoded.eintr_count), (3, 1));
+        assert_eq!(decoded.site, Some(("src/db.rs", 42)));
+        assert_eq!(decoded.partial_write, Some(partial));
+        assert_eq!(decoded.dir_fsync_fault, info.dir_fsync_fault);
+        assert_eq!(decoded.elapsed, info.elapsed);
+        assert_eq!(decoded.fault, info.fault);
+        assert_eq!(decoded.barriers, info.barriers);
+        assert_eq!(decoded.partial_flush, info.partial_flush);
+        assert_eq!(
+            decoded.partial_flush[0].persisted,
+            vec![4096..8192, 8192..9000]
+        );
+        assert_eq!(decoded.partial_flush[1].file, PathBuf::from("data/a:b"));
+
+        let plain = decode(&encode(&CrashInfo::new(1, "a".to_string()))).unwrap();
+        assert_eq!((plain.max_fds, plain.site), (None, None));
+        assert!(plain.partial_write.is_none() && plain.barriers.is_empty());
+        assert!(plain.dir_fsync_fault.is_none() && plain.elapsed.is_none());
+    }
+
+    #[test]
+    fn test_decode_rejects_bad_records() {
+        let bytes = encode(&CrashInfo::new(1, "a".to_string()));
+        assert!(decode(&bytes[..bytes.len() - 1]).is_none());
+
+        let mut future = bytes.clone();
+        future[8..12].copy_from_slice(&(VERSION + 1).to_le_bytes());
+        assert!(decode(&future).is_none());
+
+        assert!(decode(b"{\"event\":\"crash\"}").is_none());
+    }
+}
@@ -0,0 +1,259 @@
+//! Memory-mapped files with `msync` tracking.
+//!
+//! Stores to a `MAP_SHARED` mapping land in the page cache immediately and
+//! reach the disk whenever the kernel writes them back, or at `msync`. A
+//! crash can therefore lose any page modified since its last `msync`. Every
+//! live [`MappedFile`] is registered here with a copy of its last synced
+//! contents, so the crash effects can revert unsynced pages before
+//! `SIGKILL`.
+
+use std::fs::File;
+use std::io;
+use std::ops::{Deref, DerefMut, Range};
+use std::os::unix::fs::FileExt;
+use std::os::unix::io::AsRawFd;
+use std::path::PathBuf;
+use std::sync::Mutex;
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+use crate::env::{self, BarrierKind, BarrierRecord};
+use crate::rt;
+
+/// A live mapping, as seen by the crash effects.
+struct Mapping {
+    id: usize,
+    addr: usize,
+    len: usize,
+    file: File,
+    /// Contents as of the last `msync`, or `None` when not tracked.
+    synced: Option<Vec<u8>>,
+}
+
+/// Every live mapping of the process.
+static MAPPINGS: Mutex<Vec<Mapping>> = Mutex::new(Vec::new());
+
+/// Source of mapping IDs.
+static NEXT_ID: AtomicUsize = AtomicUsize::new(0);
+
+/// Run `f` with exclusive access to the registry.
+fn with<T>(f: impl FnOnce(&mut Vec<Mapping>) -> T) -> T {
+    let mut mappings = MAPPINGS.lock().unwrap_or_else(|e| e.into_inner());
+    f(&mut mappings)
+}
+
+/// A file mapped into memory with `MAP_SHARED`, created by [`Env::mmap()`].
+///
+/// Dereferences to the mapped bytes. Changes become durable only through
+/// [`msync()`](Self::msync) or [`flush()`](Self::flush), each recorded as
+/// a [`BarrierKind::Msync`] barrier. The mapping is removed on drop; drop
+/// does not sync.
+///
+/// [`Env::mmap()`]: crate::Env::mmap
+pub struct MappedFile {
+    id: usize,
+    ptr: *mut u8,
+    len: usize,
+    path: Option<PathBuf>,
+}
+
+impl MappedFile {
+    /// Map the first `len` bytes of `file`, extending it as needed.
+    pub(crate) fn new(file: File, path: Option<PathBuf>, len: usize) -> io::Result<Self> {
+        if len == 0 {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "cannot map zero bytes",
+            ));
+        }
+        if file.metadata()?.len() < len as u64 {
+            file.set_len(len as u64)?;
+        }
+
+        // SAFETY: a fresh shared mapping of an open file; the result is
+        // checked before use and unmapped exactly once, in Drop.
+        let addr = unsafe {
+            libc::mmap(
+                std::ptr::null_mut(),
+                len,
+                libc::PROT_READ | libc::PROT_WRITE,
+                libc::MAP_SHARED,
+                file.as_raw_fd(),
+                0,
+            )
+        };
+        if addr == libc::MAP_FAILED {
+            return Err(io::Error::last_os_error());
+        }
+
+        let ptr = addr as *mut u8;
+        // Snapshots are only needed when unsynced pages will be discarded.
+        let synced = rt::options().lose_unsynced_mmap.then(|| {
+            // SAFETY: the mapping is `len` bytes long and not yet shared.
+            unsafe { std::slice::from_raw_parts(ptr, len) }.to_vec()
+        });
+        let id = NEXT_ID.fetch_add(1, Ordering::SeqCst);
+        with(|mappings| {
+            mappings.push(Mapping {
+                id,
+                addr: ptr as usize,
+                len,
+                file,
+                synced,
+            })
+        });
+        Ok(Self { id, ptr, len, path })
+    }
+
+    /// Synchronously write back the pages covering `range`.
+    ///
+    /// The range is widened to page boundaries, as the kernel syncs whole
+    /// pages, and the widened range is recorded in
+    /// [`BarrierRecord::range`]. Panics if `range` is out of bounds.
+    pub fn msync(&mut self, range: Range<usize>) -> io::Result<()> {
+        assert!(
+            range.start <= range.end && range.end <= self.len,
+            "msync range {:?} out of bounds for mapping of {} bytes",
+            range,
+            self.len
+        );
+        let page = page_size();
+        let start = range.start / page * page;
+        let end = range.end.div_ceil(page).saturating_mul(page).min(self.len);
+
+        // SAFETY: start is page aligned and start..end lies in the mapping.
+        let ret = unsafe {
+            libc::msync(
+                self.ptr.add(start) as *mut libc::c_void,
+                end - start,
+                libc::MS_SYNC,
+            )
+        };
+        if ret != 0 {
+            return Err(io::Error::last_os_error());
+        }
+
+        with(|mappings| {
+            if let Some(synced) = mappings
+                .iter_mut()
+                .find(|m| m.id == self.id)
+                .and_then(|m| m.synced.as_mut())
+            {
+                synced[start..end].copy_from_slice(&self[start..end]);
+            }
+        });
+        env::journal_barrier(BarrierRecord {
+            kind: BarrierKind::Msync,
+            file: self.path.clone(),
+            after_point: rt::points_passed(),
+            range: Some(start as u64..end as u64),
+        });
+        Ok(())
+    }
+
+    /// Synchronously write back the whole mapping.
+    pub fn flush(&mut self) -> io::Result<()> {
+        self.msync(0..self.len)
+    }
+}
+
+impl Deref for MappedFile {
+    type Target = [u8];
+
+    fn deref(&self) -> &[u8] {
+        // SAFETY: the mapping stays valid for the lifetime of self.
+        unsafe { std::slice::from_raw_parts(self.ptr, self.len) }
+    }
+}
+
+impl DerefMut for MappedFile {
+    fn deref_mut(&mut self) -> &mut [u8] {
+        // SAFETY: as above, and &mut self guarantees exclusive access.
+        unsafe { std::slice::from_raw_parts_mut(self.ptr, self.len) }
+    }
+}
+
+impl Drop for MappedFile {
+    fn drop(&mut self) {
+        with(|mappings| mappings.retain(|m| m.id != self.id));
+        // SAFETY: ptr/len came from a successful mmap and are unmapped once.
+        unsafe {
+            libc::munmap(self.ptr as *mut libc::c_void, self.len);
+        }
+    }
+}
+
+/// Revert every page modified since its last `msync` in all live mappings.
+///
+/// The last synced contents are written back through the file, which also
+/// updates the shared mapping and the page cache, so a process started
+/// after the crash sees only synced data. Errors are ignored, as for every
+/// crash effect.
+pub(crate) fn discard_unsynced() {
+    let page = page_size();
+    with(|mappings| {
+        for mapping in mappings.iter() {
+            let Some(synced) = &mapping.synced else {
+                continue;
+            };
+            // SAFETY: registered mappings are live until unregistered.
+            let current =
+                unsafe { std::slice::from_raw_parts(mapping.addr as *const u8, mapping.len) };
+            for (start, end) in dirty_pages(current, synced, page) {
+                let _ = mapping.file.write_all_at(&synced[start..end], start as u64);
+            }
+        }
+    });
+}
+
+/// Byte ranges of the `page`-sized pages where `current` differs from
+/// `synced`.
+fn dirty_pages(current: &[u8], synced: &[u8], page: usize) -> Vec<(usize, usize)> {
+    (0..current.len())
+        .step_by(page)
+        .map(|start| (start, (start + page).min(current.len())))
+        .filter(|&(start, end)| current[start..end] != synced[start..end])
+        .collect()
+}
+
+/// The system page size.
+fn page_size() -> usize {
+    // SAFETY: sysconf has no preconditions.
+    let size = unsafe { libc::sysconf(libc::_SC_PAGESIZE) };
+    if size > 0 { size as usize } else { 4096 }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_dirty_pages() {
+        let synced = vec![0u8; 10];
+        let mut current = synced.clone();
+        current[1] = 1;
+        current[9] = 1;
+        assert_eq!(dirty_pages(&current, &synced, 4), vec![(0, 4), (8, 10)]);
+        assert!(dirty_pages(&synced, &synced, 4).is_empty());
+    }
+
+    #[test]
+    fn test_mapped_file_writes_through() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("data");
+        let file = std::fs::OpenOptions::new()
+            .read(true)
+            .write(true)
+            .create(true)
+            .truncate(false)
+            .open(&path)
+            .unwrap();
+        let mut map = MappedFile::new(file, None, 100).unwrap();
+        map[..5].copy_from_slice(b"hello");
+        map.msync(0..5).unwrap();
+        drop(map);
+
+        let contents = std::fs::read(&path).unwrap();
+        assert_eq!(contents.len(), 100);
+        assert_eq!(&contents[..5], b"hello");
+    }
+}
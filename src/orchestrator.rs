@@ -3,31 +3,42 @@
 //! Manages crash → restart → verify cycles.
 
 use std::fs;
-use std::io::{BufRead, BufReader};
+use std::io::{BufRead, BufReader, Write};
 use std::path::{Path, PathBuf};
-use std::process::{Command, ExitStatus, Stdio};
+use std::process::{Child, Command, ExitStatus, Stdio};
+use std::sync::OnceLock;
+use std::time::Duration;
 
-use crate::env::{CrashInfo, Env};
+use crate::bundle::{self, CapturedOutput};
+use crate::cgroup::{Cgroup, ResourceLimits};
+use crate::discover::DiscoveredPoint;
+use crate::env::{
+    BarrierKind, BarrierRecord, CrashInfo, DirFsyncFault, Env, InjectedDirFsync, PartialFlush,
+    PartialWrite,
+};
+use crate::otel::Spans;
+use crate::replay::ScriptStep;
+use crate::report::{self, LibtestJson, Outcome, RunReport};
+use crate::terminate::CRASH_EXIT_CODE;
+use crate::test::Options;
+use crate::timeout::Watchdog;
+use crate::verify_log;
+use crate::{chunking, idempotence, metadata, reader};
 
 /// Base directory for FIRST test runs.
-const FIRST_BASE_DIR: &str = "/tmp/first";
+pub(crate) const FIRST_BASE_DIR: &str = "/tmp/first";
 
-/// Exit code for SIGKILL (128 + 9).
-const SIGKILL_EXIT_CODE: i32 = 137;
+/// The verify closures called by `TestBuilder::verify_in_process()`,
+/// returning `false` for a crash point that needs a VERIFY child.
+pub(crate) type VerifyRef<'a> = &'a dyn Fn(&Env, &CrashInfo) -> bool;
 
 /// Run the orchestrator loop.
 ///
 /// Iterates through crash points, spawning execution and verification
-/// processes for each one.
-pub(crate) fn run<R, V>(_run_fn: Option<R>, _verify_fn: Option<V>)
-where
-    R: FnOnce(&Env),
-    V: FnOnce(&Env, &CrashInfo),
-{
-    // Note: We don't actually use the closures here.
-    // The orchestrator spawns child processes that re-run the test binary.
-    // The closures are stored so the type signature matches, but children
-    // will re-parse and call their own closures.
+/// processes for each one, unless the options select another mode.
+pub(crate) fn run(in_process: Option<VerifyRef<'_>>, options: &Options) {
+    // Children re-run the test binary and call their own closures; only
+    // verify_in_process() hands the verify closures to the orchestrator.
 
     let exe = match std::env::current_exe() {
         Ok(e) => e,
@@ -37,65 +48,823 @@ where
         }
     };
 
-    // Try to get test name from args (e.g., `cargo test test_name`)
-    let test_name = extract_test_name();
+    let test_name = extract_test_name(options.test_name.as_deref());
+    if let Some(name) = crate::invocation::name() {
+        eprintln!("[first] sweeping invocation \"{}\"", name);
+    }
+    crate::rt::resolve_run_seed(options.seed);
+
+    // Scratch area that persists across iterations (see Env::metadata_path).
+    let metadata_dir = PathBuf::from(FIRST_BASE_DIR).join("meta");
+    let _ = fs::remove_dir_all(&metadata_dir);
+    if let Err(e) = fs::create_dir_all(&metadata_dir) {
+        eprintln!("[first] error: {}", io_failure("create", &metadata_dir, &e));
+        std::process::exit(1);
+    }
+
+    crate::cgroup::configure(options.resource_limits.as_ref());
+    crate::loopback::configure(options.loopback_fs.as_ref());
+    crate::timeout::configure(options.timeout);
+
+    if let Some(dir) = &options.coverage_dir
+        && let Err(e) = fs::create_dir_all(dir)
+    {
+        eprintln!("[first] error: {}", io_failure("create", dir, &e));
+        std::process::exit(1);
+    }
+
+    if run_mode(&exe, &test_name, &metadata_dir, in_process, options) {
+        return;
+    }
+    let plan = plan_sweep(&exe, &test_name, &metadata_dir, options);
+    sweep(&exe, &test_name, &metadata_dir, plan, in_process, options);
+}
+
+/// Run the mode the options select instead of a sweep: a replay, a
+/// repeated or timed crash, listing the crash points or bisecting.
+///
+/// Returns whether one ran.
+fn run_mode(
+    exe: &Path,
+    test_name: &Option<String>,
+    metadata_dir: &Path,
+    in_process: Option<VerifyRef<'_>>,
+    options: &Options,
+) -> bool {
+    if let Some(script) = std::env::var("FIRST_REPLAY_SCRIPT")
+        .ok()
+        .map(PathBuf::from)
+        .or_else(|| options.replay_script.clone())
+    {
+        match crate::replay::load_script(&script) {
+            Ok(steps) => {
+                let source = script.display().to_string();
+                crate::replay::run(exe, test_name, metadata_dir, &source, &steps, options);
+                return true;
+            }
+            Err(e) => {
+                eprintln!(
+                    "[first] error: cannot read crash script {}: {}",
+                    script.display(),
+                    e
+                );
+                std::process::exit(1);
+            }
+        }
+    }
+
+    if let Some(schedule) = &options.crash_schedule {
+        let steps: Vec<ScriptStep> = schedule.iter().copied().map(ScriptStep::Point).collect();
+        let source = format!("crash schedule {:?}", schedule);
+        crate::replay::run(exe, test_name, metadata_dir, &source, &steps, options);
+        return true;
+    }
+
+    if let Some(bundle) = std::env::var("FIRST_REPLAY_BUNDLE")
+        .ok()
+        .map(PathBuf::from)
+        .or_else(|| options.replay_bundle.clone())
+    {
+        crate::bundle::replay(exe, test_name, &bundle, options);
+        return true;
+    }
+
+    if let Some((point, times)) = &options.repeat_point {
+        crate::repeat::run(exe, test_name, point, *times, options);
+        return true;
+    }
+
+    if let Some(after) = options.crash_after {
+        crate::timed::run(exe, test_name, metadata_dir, after, options);
+        return true;
+    }
+
+    if options.list_points
+        || std::env::var(crate::discover::ENV_LIST_POINTS).is_ok_and(|v| v == "1")
+    {
+        match crate::discover::discover(exe, test_name, Path::new(FIRST_BASE_DIR), options) {
+            Some(points) => crate::discover::print_points(&points),
+            None => {
+                eprintln!("[first] error: crash point discovery failed");
+                std::process::exit(1);
+            }
+        }
+        return true;
+    }
+
+    if options.bisect {
+        if std::env::var(crate::bisect::ENV_NO_BISECT).is_err() {
+            crate::bisect::run(exe, test_name, metadata_dir, options, in_process);
+            return true;
+        }
+        eprintln!(
+            "[first] {} is set; sweeping every crash point",
+            crate::bisect::ENV_NO_BISECT
+        );
+    }
+    false
+}
+
+/// What a sweep targets, and in which order.
+struct SweepPlan {
+    /// Crash points found by discovery, when the options need it.
+    discovered: Option<Vec<DiscoveredPoint>>,
+    /// Call sites or labels targeted in site or label mode.
+    names: Option<Vec<String>>,
+    /// What `names` holds: "sites" or "labels".
+    names_unit: &'static str,
+    /// Steps to targets, unless swept in ascending order.
+    order: Option<Vec<usize>>,
+    /// Changed and sampled points with `prioritize_changed()`.
+    focus: Option<crate::focus::Plan>,
+    /// Points picked by `sample()`, out of how many, with which seed.
+    sample: Option<(Vec<usize>, usize, u64)>,
+}
+
+/// Discover what the sweep needs and decide its targets and their order.
+fn plan_sweep(
+    exe: &Path,
+    test_name: &Option<String>,
+    metadata_dir: &Path,
+    options: &Options,
+) -> SweepPlan {
+    // A graph export needs the uncached timeline, which also yields the points
+    let timeline = options.export_graph.as_ref().and_then(|path| {
+        let timeline =
+            crate::discover::timeline(exe, test_name, Path::new(FIRST_BASE_DIR), options);
+        match &timeline {
+            Some(timeline) => match crate::graph::write(path, test_name.as_deref(), timeline) {
+                Ok(()) => eprintln!("[first] crash point graph written to {}", path.display()),
+                Err(e) => eprintln!(
+                    "[first] warning: cannot write crash point graph {}: {}",
+                    path.display(),
+                    e
+                ),
+            },
+            None => eprintln!("[first] warning: crash point graph export failed"),
+        }
+        timeline
+    });
+
+    let discovered = if options.discover
+        || options.assert_deterministic
+        || options.target_sites
+        || options.target_labels
+        || options.shuffle.is_some()
+        || options.prioritize_changed.is_some()
+        || options.sample.is_some()
+    {
+        let points = match &timeline {
+            Some(timeline) => Some(crate::discover::points_of(timeline)),
+            None => crate::discover::discover(exe, test_name, Path::new(FIRST_BASE_DIR), options),
+        };
+        match &points {
+            Some(points) => eprintln!("[first] discovered {} crash points", points.len()),
+            None => eprintln!("[first] warning: crash point discovery failed"),
+        }
+        points
+    } else {
+        None
+    };
+
+    // EXECUTION children check their crash points against discovery
+    if options.assert_deterministic {
+        let written = match &discovered {
+            Some(points) => crate::determinism::write_reference(metadata_dir, points),
+            None => {
+                eprintln!("[first] error: assert_deterministic() requires crash point discovery");
+                std::process::exit(1);
+            }
+        };
+        if let Err(e) = written {
+            eprintln!(
+                "[first] error: {}",
+                io_failure("write reference schedule to", metadata_dir, &e)
+            );
+            std::process::exit(1);
+        }
+    }
+
+    // With discovery, unreachable points are caught before sweeping
+    if let Some(point) = discovered
+        .iter()
+        .flatten()
+        .find(|p| options.unreachable_labels.contains(&p.label))
+    {
+        eprintln!(
+            "[first] error: expected-unreachable crash point was reached: \"{}\" (point {})",
+            point.label, point.point_id
+        );
+        std::process::exit(1);
+    }
+
+    // In site or label mode, each sweep step targets one call site or label
+    if options.target_sites && options.target_labels {
+        eprintln!("[first] error: target_sites() and target_labels() cannot be combined");
+        std::process::exit(1);
+    }
+    let (names_unit, mode) = if options.target_labels {
+        ("labels", "target_labels()")
+    } else {
+        ("sites", "target_sites()")
+    };
+    let names = if options.target_sites || options.target_labels {
+        match &discovered {
+            Some(points) => {
+                let names = if options.target_labels {
+                    crate::discover::distinct_labels(points)
+                } else {
+                    crate::discover::distinct_sites(points)
+                };
+                eprintln!("[first] sweeping {} crash {}", names.len(), names_unit);
+                Some(names)
+            }
+            None => {
+                eprintln!("[first] error: {} requires crash point discovery", mode);
+                std::process::exit(1);
+            }
+        }
+    } else {
+        None
+    };
+    // In shuffle mode, every point (or site) is swept in a seeded order
+    let order = options.shuffle.map(|seed| {
+        let count = match (&names, &discovered) {
+            (Some(names), _) => names.len(),
+            (None, Some(points)) => points.len(),
+            (None, None) => {
+                eprintln!("[first] error: shuffle() requires crash point discovery");
+                std::process::exit(1);
+            }
+        };
+        let order = shuffled(count, seed);
+        eprintln!("[first] shuffled sweep order (seed {}): {:?}", seed, order);
+        order
+    });
+
+    // Focused on a diff, changed points come first and the rest is sampled
+    let focus = options.prioritize_changed.and_then(|percent| {
+        let point_sites: Vec<Option<&str>> = match (&names, &discovered) {
+            (Some(labels), Some(points)) if options.target_labels => labels
+                .iter()
+                .map(|label| {
+                    points
+                        .iter()
+                        .find(|p| &p.label == label)
+                        .and_then(|p| p.site.as_deref())
+                })
+                .collect(),
+            (Some(sites), _) => sites.iter().map(|s| Some(s.as_str())).collect(),
+            (None, Some(points)) => points.iter().map(|p| p.site.as_deref()).collect(),
+            (None, None) => {
+                eprintln!("[first] error: prioritize_changed() requires crash point discovery");
+                std::process::exit(1);
+            }
+        };
+        let Some(changed) = crate::focus::changed_files() else {
+            eprintln!(
+                "[first] {} is not set; sweeping every crash point",
+                crate::focus::ENV_CHANGED_FILES
+            );
+            return None;
+        };
+        let base = order
+            .clone()
+            .unwrap_or_else(|| (1..=point_sites.len()).collect());
+        let seed = options.shuffle.unwrap_or(0);
+        let plan = crate::focus::Plan::new(&point_sites, &changed, percent, seed, &base);
+        let unit = if names.is_some() {
+            names_unit
+        } else {
+            "points"
+        };
+        eprintln!(
+            "[first] {} crash {} in changed files, swept exhaustively: {:?}",
+            plan.exhaustive.len(),
+            unit,
+            plan.exhaustive
+        );
+        eprintln!(
+            "[first] {} of {} crash {} in other files, sampled ({}%, seed {}): {:?}",
+            plan.sampled.len(),
+            plan.unchanged,
+            unit,
+            percent.min(100),
+            seed,
+            plan.sampled
+        );
+        Some(plan)
+    });
+    let order = focus.as_ref().map(|focus| focus.order()).or(order);
+
+    // In sample mode, a seeded subset of the points (or sites) is swept
+    if options.sample.is_some() && options.prioritize_changed.is_some() {
+        eprintln!("[first] error: sample() and prioritize_changed() cannot be combined");
+        std::process::exit(1);
+    }
+    let sample = options.sample.map(|(count, seed)| {
+        let (total, unit) = match (&names, &discovered) {
+            (Some(names), _) => (names.len(), names_unit),
+            (None, Some(points)) => (points.len(), "points"),
+            (None, None) => {
+                eprintln!("[first] error: sample() requires crash point discovery");
+                std::process::exit(1);
+            }
+        };
+        let picked = sample_points(total, count, seed);
+        let base = order.clone().unwrap_or_else(|| (1..=total).collect());
+        let sample: Vec<usize> = base.into_iter().filter(|p| picked.contains(p)).collect();
+        eprintln!(
+            "[first] sampled {} of {} crash {} (seed {}): {:?}",
+            sample.len(),
+            total,
+            unit,
+            seed,
+            sample
+        );
+        (sample, total, seed)
+    });
+    let order = sample
+        .as_ref()
+        .map(|(sample, _, _)| sample.clone())
+        .or(order);
+
+    SweepPlan {
+        discovered,
+        names,
+        names_unit,
+        order,
+        focus,
+        sample,
+    }
+}
+
+/// The outcomes of a sweep's crash points, reported as they come in.
+struct Results {
+    libtest: LibtestJson,
+    spans: Spans,
+    report: RunReport,
+    /// Without it, the first failure ends the process.
+    continue_after_failure: bool,
+    failures: Vec<SweepFailure>,
+}
+
+impl Results {
+    /// Report the failure of crash point `target` and record it for the
+    /// summary, or exit unless the sweep goes on after failures.
+    fn record_failure(&mut self, target: usize, label: &str, work_dir: &Path, reason: String) {
+        self.libtest.failed(target, &reason);
+        self.spans.end(target, label, Outcome::Failed(&reason));
+        self.report
+            .end(target, label, work_dir, Outcome::Failed(&reason));
+        if !self.continue_after_failure {
+            self.report.finish();
+            std::process::exit(1);
+        }
+        self.failures.push(SweepFailure {
+            target,
+            label: label.to_string(),
+            reason,
+            work_dir: work_dir.to_path_buf(),
+        });
+    }
+}
+
+/// Sweep the crash points of `plan`, then report the results.
+fn sweep(
+    exe: &Path,
+    test_name: &Option<String>,
+    metadata_dir: &Path,
+    plan: SweepPlan,
+    in_process: Option<VerifyRef<'_>>,
+    options: &Options,
+) {
+    let SweepPlan {
+        discovered,
+        names,
+        names_unit,
+        order,
+        focus,
+        sample,
+    } = plan;
+    let stable_target = |name| {
+        if options.target_labels {
+            StableTarget::Label(name)
+        } else {
+            StableTarget::Site(name)
+        }
+    };
+    let run_tag = report::run_tag(options);
+    let tag = report::tag_suffix(run_tag.as_deref());
+    // Invariant names recorded by first::checked() in verify children
+    let _ = fs::remove_file(checks_log_path());
+    let _ = fs::remove_file(committed_log_path());
 
-    let mut target: usize = 1;
+    let mut results = Results {
+        libtest: LibtestJson::new(
+            options.libtest_json && report::harness_format_is_json(),
+            test_name.as_deref(),
+            run_tag.as_deref(),
+        ),
+        spans: Spans::new(test_name.as_deref(), run_tag.as_deref()),
+        report: RunReport::new(
+            options,
+            test_name.as_deref(),
+            run_tag.as_deref(),
+            metadata_dir,
+        ),
+        // With expect_violation(), a failure ends the sweep but not the test
+        continue_after_failure: options.continue_on_failure || options.expect_violation,
+        failures: Vec::new(),
+    };
+    let mut step: usize = 1;
+    let mut verified: usize = 0;
+    // Crash points not crashed at because their label is disabled
+    let mut skipped: Vec<(usize, String)> = Vec::new();
+    // Passing run dirs kept by FIRST_KEEP_ARTIFACTS, freed if disk runs out
+    let mut kept_dirs: Vec<PathBuf> = Vec::new();
 
     loop {
-        let work_dir = PathBuf::from(FIRST_BASE_DIR).join(format!("run_{}", target));
+        if let Some(max) = options.max_crash_points
+            && step > max
+        {
+            if results.failures.is_empty() {
+                let unit = if names.is_some() {
+                    names_unit
+                } else {
+                    "points"
+                };
+                eprintln!(
+                    "[first] explored first {} crash {} (capped){}",
+                    max, unit, tag
+                );
+            }
+            break;
+        }
+        let target = match &order {
+            Some(order) => match order.get(step - 1) {
+                Some(&target) => target,
+                None => {
+                    if results.failures.is_empty() {
+                        let unit = if names.is_some() {
+                            names_unit
+                        } else {
+                            "points"
+                        };
+                        let how = match (&focus, &sample) {
+                            (Some(focus), _) => format!(
+                                " ({} exhaustively, {} sampled)",
+                                focus.exhaustive.len(),
+                                focus.sampled.len()
+                            ),
+                            (None, Some((_, total, seed))) => {
+                                format!(" (sampled of {}, seed {})", total, seed)
+                            }
+                            (None, None) => " in shuffled order".to_string(),
+                        };
+                        eprintln!(
+                            "[first] all {} crash {} passed{}{}",
+                            order.len() - skipped.len(),
+                            unit,
+                            how,
+                            tag
+                        );
+                    }
+                    break;
+                }
+            },
+            None => step,
+        };
+
+        let stable = match &names {
+            Some(names) => match names.get(target - 1) {
+                Some(name) => Some(stable_target(name.as_str())),
+                None => {
+                    if results.failures.is_empty() {
+                        eprintln!(
+                            "[first] all {} crash {} passed{}",
+                            names.len() - skipped.len(),
+                            names_unit,
+                            tag
+                        );
+                    }
+                    break;
+                }
+            },
+            None => None,
+        };
+
+        let work_dir = crate::invocation::run_dir(target, options.loopback_fs.as_ref());
+        // Collapsible in GitHub Actions; ends with the iteration
+        let _group = crate::github::Group::start(&match stable {
+            Some(stable) => stable.describe(),
+            None => format!("crash point {}", target),
+        });
+        results.spans.begin(target);
+        results.report.begin(target);
 
-        // Create fresh work directory
-        if let Err(e) = fs::create_dir_all(&work_dir) {
-            eprintln!("[first] error: cannot create {}: {}", work_dir.display(), e);
+        // Create fresh work directory, dropping any left by a failed run
+        cleanup_work_dir(&work_dir);
+        let mut created = create_work_dir(&work_dir, options);
+        if let Err(e) = &created
+            && is_out_of_space(e)
+            && !kept_dirs.is_empty()
+        {
+            eprintln!(
+                "[first] warning: out of disk space, removing {} kept run dirs of passing crash points",
+                kept_dirs.len()
+            );
+            for dir in kept_dirs.drain(..) {
+                cleanup_work_dir(&dir);
+            }
+            cleanup_work_dir(&work_dir);
+            created = create_work_dir(&work_dir, options);
+        }
+        if let Err(e) = created {
+            eprintln!("[first] error: {}", io_failure("create", &work_dir, &e));
             std::process::exit(1);
         }
 
+        // Children see the stable alias (if any) instead of the rotating run dir
+        let child_dir = match &options.stable_path {
+            Some(alias) => {
+                let link = stable_link_path(alias);
+                if let Err(e) = point_stable_link(&link, &work_dir) {
+                    eprintln!("[first] error: cannot link {}: {}", link.display(), e);
+                    std::process::exit(1);
+                }
+                link
+            }
+            None => work_dir.clone(),
+        };
+
+        // A concurrent reader starts first, so it sees the whole workload
+        let reader = options
+            .reader
+            .then(|| reader::spawn(exe, test_name, target, &child_dir, metadata_dir));
+
         // Spawn EXECUTION phase
-        let exec_result = spawn_child(&exe, &test_name, "EXECUTION", target, &work_dir);
+        let exec_result = spawn_child(
+            exe,
+            test_name,
+            "EXECUTION",
+            target,
+            stable,
+            &child_dir,
+            metadata_dir,
+            options.coverage_dir.as_deref(),
+            options.resource_limits.as_ref(),
+        );
+        let mut reader_failure =
+            reader.and_then(|reader| reader::finish(reader, metadata_dir, target));
 
         match exec_result {
-            ChildResult::Crashed(crash_info) => {
+            // Crash IDs start at 1: point 0 is the stand-in for missing metadata
+            ChildResult::Crashed(crash_info)
+                if options.strict_metadata && crash_info.point_id == 0 =>
+            {
+                eprintln!(
+                    "[first] crash point {}: FAILED (see {})",
+                    target,
+                    work_dir.display()
+                );
+                let reason = "FIRST internal error: the EXECUTION child was killed but its crash metadata could not be parsed; the engine under test is not at fault".to_string();
+                eprintln!("[first] reason: {}", reason);
+                results.record_failure(target, &crash_info.label, &work_dir, reason);
+            }
+            ChildResult::Crashed(mut crash_info) => {
+                self_crash_point("after_execution");
+                crash_info.total_points = discovered.as_ref().map(Vec::len);
+
+                if options.drop_caches {
+                    evict_work_dir(&work_dir);
+                }
+
+                // Recovery runs on copies, leaving the crashed workspace to verify
+                let mut recovery_failure = options.recovery_crashes.and_then(|max_points| {
+                    idempotence::check(
+                        exe,
+                        test_name,
+                        target,
+                        &child_dir,
+                        metadata_dir,
+                        max_points,
+                        options.coverage_dir.as_deref(),
+                        options.resource_limits.as_ref(),
+                    )
+                });
+                let mut chunking_failure = if options.read_chunk_sizes.is_empty() {
+                    None
+                } else {
+                    chunking::check(
+                        exe,
+                        test_name,
+                        target,
+                        &child_dir,
+                        metadata_dir,
+                        &crash_info,
+                        &options.read_chunk_sizes,
+                        options.coverage_dir.as_deref(),
+                        options.resource_limits.as_ref(),
+                    )
+                };
+
                 // Child crashed as expected, now verify
-                let verify_result =
-                    spawn_child_with_crash_info(&exe, &test_name, target, &work_dir, &crash_info);
+                results.libtest.started(target);
+                verified += 1;
+                let mut output = CapturedOutput::default();
+                let in_process_result = in_process.and_then(|verify| {
+                    verify_in_process(verify, target, &child_dir, metadata_dir, &crash_info)
+                });
+                let verify_result = match in_process_result {
+                    Some(result) => result,
+                    None => spawn_child_with_crash_info(
+                        exe,
+                        test_name,
+                        target,
+                        &child_dir,
+                        metadata_dir,
+                        &crash_info,
+                        None,
+                        options.coverage_dir.as_deref(),
+                        options.resource_limits.as_ref(),
+                        options.bundle_on_failure.as_ref().map(|_| &mut output),
+                    ),
+                };
+                self_crash_point("after_verify");
 
-                match verify_result {
+                let reason = match verify_result {
+                    ChildResult::Success if reader_failure.is_some() => reader_failure.take(),
+                    ChildResult::Success if recovery_failure.is_some() => recovery_failure.take(),
+                    ChildResult::Success if chunking_failure.is_some() => chunking_failure.take(),
                     ChildResult::Success => {
-                        eprintln!("[first] crash point {}: OK", target);
+                        let point = match stable {
+                            Some(stable) => stable.describe(),
+                            None => format!("crash point {}", target),
+                        };
+                        match crash_info.max_fds {
+                            Some(fds) if options.track_fds => {
+                                eprintln!("[first] {}: OK (max fds: {})", point, fds)
+                            }
+                            _ => eprintln!("[first] {}: OK", point),
+                        }
+                        results.libtest.ok(target);
+                        results.spans.end(target, &crash_info.label, Outcome::Ok);
+                        results
+                            .report
+                            .end(target, &crash_info.label, &work_dir, Outcome::Ok);
                         // Clean up work dir on success (unless FIRST_KEEP_ARTIFACTS)
                         if std::env::var("FIRST_KEEP_ARTIFACTS").is_err() {
-                            let _ = fs::remove_dir_all(&work_dir);
+                            cleanup_work_dir(&work_dir);
+                        } else {
+                            kept_dirs.push(work_dir.clone());
                         }
+                        self_crash_point("after_cleanup");
+                        None
                     }
                     ChildResult::Failed(code) => {
-                        print_failure_info(
-                            target,
-                            &work_dir,
-                            &crash_info,
-                            &test_name,
-                            &format!("verification failed with exit code {}", code),
-                        );
-                        std::process::exit(1);
+                        Some(format!("verification failed with exit code {}", code))
+                    }
+                    ChildResult::Crashed(_) | ChildResult::Skipped(_) => {
+                        Some("verify phase crashed unexpectedly".to_string())
                     }
-                    ChildResult::Crashed(_) => {
-                        print_failure_info(
+                    ChildResult::TimedOut(limit) => {
+                        Some(format!("verify phase timed out after {:?}", limit))
+                    }
+                };
+
+                // In a shuffled sweep, a point failing only after others ran
+                // points at shared state. Failures in orders that are only
+                // sampled or prioritized are reported as they are.
+                let reason = match (reason, &order) {
+                    (Some(reason), Some(order))
+                        if options.shuffle.is_some()
+                            && passes_in_isolation(exe, test_name, target, stable, options) =>
+                    {
+                        Some(format!(
+                            "isolation violation: {} after crash points {:?}, but passes on its own",
+                            reason,
+                            &order[..step - 1]
+                        ))
+                    }
+                    (reason, _) => reason,
+                };
+
+                if let Some(reason) = reason {
+                    print_failure_info(target, &work_dir, &crash_info, test_name, &reason);
+                    if let Some(path) = &options.bundle_on_failure {
+                        let failure = bundle::Failure {
                             target,
-                            &work_dir,
-                            &crash_info,
-                            &test_name,
-                            "verify phase crashed unexpectedly",
-                        );
-                        std::process::exit(1);
+                            reason: &reason,
+                            repro: &repro_command(target, &work_dir, &crash_info, test_name),
+                            test_name,
+                            work_dir: &work_dir,
+                            metadata_dir,
+                            crash_info: &crash_info,
+                            output: &output,
+                        };
+                        // Keep going: one bundle per failing point
+                        let path = if options.continue_on_failure {
+                            bundle::path_for_point(path, target)
+                        } else {
+                            path.clone()
+                        };
+                        match bundle::write(&path, &failure) {
+                            Ok(()) => {
+                                eprintln!("[first] failure bundle written to {}", path.display())
+                            }
+                            Err(e) => eprintln!(
+                                "[first] warning: {}",
+                                io_failure("write failure bundle", &path, &e)
+                            ),
+                        }
                     }
+                    results.record_failure(target, &crash_info.label, &work_dir, reason);
                 }
             }
+            ChildResult::Skipped(crash_info) => {
+                eprintln!(
+                    "[first] crash point {} (\"{}\"): SKIPPED (label disabled)",
+                    target, crash_info.label
+                );
+                results.libtest.started(target);
+                results.libtest.ignored(target);
+                results
+                    .spans
+                    .end(target, &crash_info.label, Outcome::Skipped);
+                results
+                    .report
+                    .end(target, &crash_info.label, &work_dir, Outcome::Skipped);
+                cleanup_work_dir(&work_dir);
+                skipped.push((target, crash_info.label));
+            }
+            ChildResult::Success if target == 1 && !metadata_dir.join(STARTED_MARKER).exists() => {
+                let reason = match &test_name {
+                    Some(name) => format!(
+                        "the EXECUTION child ran no FIRST test: the filter \"{}\" matched no test",
+                        name
+                    ),
+                    None => "the EXECUTION child ran no FIRST test".to_string(),
+                };
+                eprintln!(
+                    "[first] crash point {}: FAILED (see {})",
+                    target,
+                    work_dir.display()
+                );
+                eprintln!("[first] reason: {}", reason);
+                eprintln!(
+                    "[first] set the test's full name with TestBuilder::test_name() if it cannot be detected"
+                );
+                // Every later child runs nothing as well: stop here
+                results.record_failure(target, "unknown", &work_dir, reason);
+                break;
+            }
+            ChildResult::Success if stable.is_some() || order.is_some() => {
+                let (point, unit) = match stable {
+                    Some(stable) => (stable.describe(), stable.unit()),
+                    None => (format!("crash point {}", target), "point"),
+                };
+                let name = stable.map_or("unknown", |stable| stable.name());
+                eprintln!("[first] {}: FAILED (see {})", point, work_dir.display());
+                eprintln!(
+                    "[first] the workload completed without reaching this {} (is the workload deterministic?)",
+                    unit
+                );
+                let reason = format!("crash {} was never reached", unit);
+                results.record_failure(target, name, &work_dir, reason);
+            }
+            ChildResult::Success if reader_failure.is_some() => {
+                let reason = reader_failure.take().unwrap_or_default();
+                eprintln!(
+                    "[first] workload run to completion: FAILED (see {})",
+                    work_dir.display()
+                );
+                eprintln!("[first] reason: {}", reason);
+                results.record_failure(target, "completion", &work_dir, reason);
+                break;
+            }
             ChildResult::Success => {
-                // Child completed normally - no more crash points
-                eprintln!("[first] all {} crash points passed", target - 1);
+                // Child completed normally - no more crash points.
+                // The last target never crashed, so it is not a crash point.
+                if results.failures.is_empty() {
+                    eprintln!(
+                        "[first] all {} crash points passed{}",
+                        target - 1 - skipped.len(),
+                        tag
+                    );
+                }
+                if let Some(points) = &discovered
+                    && points.len() != target - 1
+                {
+                    eprintln!(
+                        "[first] warning: discovery found {} crash points but the sweep explored {} (is the workload deterministic?)",
+                        points.len(),
+                        target - 1
+                    );
+                }
                 // Clean up the unused work dir
-                let _ = fs::remove_dir_all(&work_dir);
-                return;
+                cleanup_work_dir(&work_dir);
+                break;
             }
             ChildResult::Failed(code) => {
                 eprintln!(
@@ -104,26 +873,483 @@ where
                     work_dir.display()
                 );
                 eprintln!("[first] execution failed with exit code {}", code);
-                eprintln!("[first] to reproduce:");
+                print_execution_repro(target, stable, &work_dir, test_name);
+                let reason = format!("execution failed with exit code {}", code);
+                // Later targets cannot get past a failing workload: stop here
+                results.record_failure(target, "unknown", &work_dir, reason);
+                break;
+            }
+            ChildResult::TimedOut(limit) => {
+                // No crash metadata from a killed child: name the point from discovery
+                let label = match stable {
+                    Some(stable) => stable.name().to_string(),
+                    None => discovered
+                        .as_ref()
+                        .and_then(|points| points.get(target - 1))
+                        .map_or_else(|| "unknown".to_string(), |point| point.label.clone()),
+                };
+                let reason = format!("workload timed out after {:?}", limit);
                 eprintln!(
-                    "  FIRST_PHASE=EXECUTION FIRST_CRASH_TARGET={} FIRST_WORK_DIR={} cargo test{} -- --exact",
+                    "[first] crash point {}: FAILED (see {})",
                     target,
-                    work_dir.display(),
-                    test_name
-                        .as_ref()
-                        .map(|n| format!(" {}", n))
-                        .unwrap_or_default()
+                    work_dir.display()
                 );
-                std::process::exit(1);
+                eprintln!("[first] crash label: \"{}\"", label);
+                eprintln!("[first] reason: {}", reason);
+                print_execution_repro(target, stable, &work_dir, test_name);
+                // Later targets hang at the same place: stop here
+                results.record_failure(target, &label, &work_dir, reason);
+                break;
+            }
+        }
+
+        if !options.continue_on_failure && !results.failures.is_empty() {
+            break;
+        }
+        step += 1;
+    }
+    results.spans.flush();
+    results.report.finish();
+
+    if let Some(alias) = &options.stable_path {
+        let link = stable_link_path(alias);
+        match results.failures.first() {
+            // Leave the alias on the first failing workspace
+            Some(first) => {
+                let _ = point_stable_link(&link, &first.work_dir);
+            }
+            None => {
+                let _ = fs::remove_file(link);
+            }
+        }
+    }
+
+    print_check_coverage(verified);
+    print_skipped(&skipped);
+    if let Some(fs) = crate::loopback::describe(options.loopback_fs.as_ref()) {
+        eprintln!("[first] workspace filesystem: {}", fs);
+    }
+    if let Some(dir) = &options.coverage_dir {
+        eprintln!("[first] coverage profiles written to {}", dir.display());
+    }
+    let monotonic = check_committed_monotonic();
+
+    if options.continue_on_failure && !results.failures.is_empty() {
+        results.report.print_table();
+    }
+    if options.expect_violation {
+        report_expected_violation(&results.failures, monotonic, &tag);
+        return;
+    }
+    if !results.failures.is_empty() {
+        print_failure_summary(&results.failures, &tag);
+        std::process::exit(1);
+    }
+    if !monotonic {
+        std::process::exit(1);
+    }
+}
+
+/// The points `1..=count` in a deterministic order derived from `seed`.
+///
+/// A Fisher-Yates shuffle driven by SplitMix64, so an order can be
+/// reproduced from its seed on any platform.
+pub(crate) fn shuffled(count: usize, seed: u64) -> Vec<usize> {
+    let mut state = seed;
+    let mut next = || {
+        state = state.wrapping_add(0x9e37_79b9_7f4a_7c15);
+        let mut z = state;
+        z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+        z ^ (z >> 31)
+    };
+    let mut order: Vec<usize> = (1..=count).collect();
+    for i in (1..count).rev() {
+        let j = (next() % (i as u64 + 1)) as usize;
+        order.swap(i, j);
+    }
+    order
+}
+
+/// `count` of the points `1..=total` picked with `seed`, in ascending
+/// order; every point if `count` is at least `total`.
+fn sample_points(total: usize, count: usize, seed: u64) -> Vec<usize> {
+    let mut picked: Vec<usize> = shuffled(total, seed).into_iter().take(count).collect();
+    picked.sort_unstable();
+    picked
+}
+
+/// Re-run crash point `target` alone, in a fresh workspace and metadata
+/// dir, and report whether it passes there.
+fn passes_in_isolation(
+    exe: &Path,
+    test_name: &Option<String>,
+    target: usize,
+    stable: Option<StableTarget>,
+    options: &Options,
+) -> bool {
+    let base = crate::loopback::work_root(options.loopback_fs.as_ref()).join("isolated");
+    let work_dir = base.join("run");
+    let metadata_dir = base.join("meta");
+    cleanup_work_dir(&base);
+    if create_work_dir(&work_dir, options).is_err() || fs::create_dir_all(&metadata_dir).is_err() {
+        return false;
+    }
+    let passed = match spawn_child(
+        exe,
+        test_name,
+        "EXECUTION",
+        target,
+        stable,
+        &work_dir,
+        &metadata_dir,
+        None,
+        options.resource_limits.as_ref(),
+    ) {
+        ChildResult::Crashed(crash_info) => matches!(
+            spawn_child_with_crash_info(
+                exe,
+                test_name,
+                target,
+                &work_dir,
+                &metadata_dir,
+                &crash_info,
+                None,
+                None,
+                options.resource_limits.as_ref(),
+                None,
+            ),
+            ChildResult::Success
+        ),
+        _ => false,
+    };
+    cleanup_work_dir(&base);
+    passed
+}
+
+/// Coverage profile of a child, e.g. `verify_point_3_<pid>.profraw`.
+///
+/// `%p` is expanded to the child's PID by the LLVM profiling runtime, so
+/// repeated runs of one point (as in replay) do not overwrite each other.
+fn profile_path(dir: &Path, phase: &str, target: usize) -> PathBuf {
+    dir.join(format!(
+        "{}_point_{}_%p.profraw",
+        phase.to_lowercase(),
+        target
+    ))
+}
+
+/// File that verify children append `first::checked()` records to.
+fn checks_log_path() -> PathBuf {
+    PathBuf::from(FIRST_BASE_DIR).join("checks.log")
+}
+
+/// File that verify children append `first::invariants::record_committed()`
+/// records to.
+fn committed_log_path() -> PathBuf {
+    PathBuf::from(FIRST_BASE_DIR).join("committed.log")
+}
+
+/// Check that the committed counts recorded in verify never decrease as
+/// the crash point moves later, printing the neighbourhood of the first
+/// decrease. Returns false on a violation.
+fn check_committed_monotonic() -> bool {
+    let log = fs::read_to_string(committed_log_path()).unwrap_or_default();
+    let counts = crate::invariants::committed_counts(&log);
+    let Some(bad) = crate::invariants::first_decrease(&counts) else {
+        return true;
+    };
+    let (before, after) = (&counts[bad - 1], &counts[bad]);
+    eprintln!(
+        "[first] committed count decreased from {} at crash point {} (\"{}\") to {} at crash point {} (\"{}\")",
+        before.count, before.point_id, before.label, after.count, after.point_id, after.label
+    );
+    for (i, c) in counts
+        .iter()
+        .enumerate()
+        .take(bad + 3)
+        .skip(bad.saturating_sub(3))
+    {
+        let marker = if i == bad - 1 || i == bad { '>' } else { ' ' };
+        eprintln!(
+            "[first] {} crash point {} (\"{}\"): {}",
+            marker, c.point_id, c.label, c.count
+        );
+    }
+    false
+}
+
+/// Report at how many of the `verified` crash points each named invariant
+/// was checked.
+fn print_check_coverage(verified: usize) {
+    let log = fs::read_to_string(checks_log_path()).unwrap_or_default();
+    let coverage = crate::invariants::check_coverage(&log);
+    if coverage.is_empty() {
+        return;
+    }
+    eprintln!("[first] invariant coverage:");
+    for (name, count) in coverage {
+        eprintln!(
+            "[first]   {}: checked at {}/{} points",
+            name, count, verified
+        );
+    }
+}
+
+/// A crash point that failed while sweeping with `continue_on_failure()`.
+struct SweepFailure {
+    target: usize,
+    label: String,
+    reason: String,
+    work_dir: PathBuf,
+}
+
+/// Print every failure recorded during a sweep.
+fn print_failure_summary(failures: &[SweepFailure], tag: &str) {
+    eprintln!("[first] {} crash points FAILED{}:", failures.len(), tag);
+    for failure in failures {
+        eprintln!(
+            "[first]   crash point {} (\"{}\"): {} (see {})",
+            failure.target,
+            failure.label,
+            failure.reason,
+            failure.work_dir.display()
+        );
+    }
+}
+
+/// With `expect_violation()`, report the violation the sweep was expected
+/// to find, or exit with status 1 if it found none.
+fn report_expected_violation(failures: &[SweepFailure], monotonic: bool, tag: &str) {
+    if failures.len() > 1 {
+        print_failure_summary(failures, tag);
+    }
+    match failures.first() {
+        Some(first) => eprintln!(
+            "[first] expected violation found at crash point {} (\"{}\"): {}{}",
+            first.target, first.label, first.reason, tag
+        ),
+        None if !monotonic => eprintln!(
+            "[first] expected violation found: the committed count decreased{}",
+            tag
+        ),
+        None => {
+            eprintln!(
+                "[first] FAILED: expected a crash-consistency violation but none was found{}",
+                tag
+            );
+            std::process::exit(1);
+        }
+    }
+}
+
+/// List the crash points skipped because their label is disabled.
+fn print_skipped(skipped: &[(usize, String)]) {
+    if skipped.is_empty() {
+        return;
+    }
+    let points: Vec<String> = skipped
+        .iter()
+        .map(|(target, label)| format!("{} (\"{}\")", target, label))
+        .collect();
+    eprintln!(
+        "[first] {} crash points skipped (label disabled): {}",
+        skipped.len(),
+        points.join(", ")
+    );
+}
+
+/// Maximum attempts to remove a work dir before giving up.
+const CLEANUP_ATTEMPTS: u32 = 5;
+
+/// Overrides how often a transiently failing child spawn is retried.
+const ENV_SPAWN_RETRIES: &str = "FIRST_SPAWN_RETRIES";
+
+/// Retries of a transiently failing child spawn, unless overridden.
+const DEFAULT_SPAWN_RETRIES: u32 = 3;
+
+/// Names the orchestrator phase boundary to crash at, in FIRST's own tests.
+#[cfg(test)]
+const ENV_SELF_CRASH_AT: &str = "FIRST_SELF_CRASH_AT";
+
+/// SIGKILL the orchestrator at `boundary` if `FIRST_SELF_CRASH_AT` names it.
+///
+/// Boundaries are `after_execution` (crash metadata parsed, before verify),
+/// `after_verify` and `after_cleanup`, each at the first crash point that
+/// gets there. Only compiled into FIRST's own unit tests, which check that
+/// a dying orchestrator leaves nothing behind that breaks the next run.
+#[cfg(test)]
+fn self_crash_point(boundary: &str) {
+    if std::env::var(ENV_SELF_CRASH_AT).is_ok_and(|at| at == boundary) {
+        let _ = writeln!(
+            std::io::stderr().lock(),
+            "[first] self-crash at {}",
+            boundary
+        );
+        crate::rt::trigger_crash();
+    }
+}
+
+#[cfg(not(test))]
+fn self_crash_point(_boundary: &str) {}
+
+/// Returns true if `FIRST_VERBOSE` is set, enabling diagnostic output.
+pub(crate) fn verbose() -> bool {
+    std::env::var_os("FIRST_VERBOSE").is_some()
+}
+
+/// Returns true if `e` means the filesystem (or the user's quota) is full.
+pub(crate) fn is_out_of_space(e: &std::io::Error) -> bool {
+    e.kind() == std::io::ErrorKind::StorageFull
+        || e.kind() == std::io::ErrorKind::QuotaExceeded
+        || matches!(e.raw_os_error(), Some(libc::ENOSPC | libc::EDQUOT))
+}
+
+/// Describe a failure to `action` FIRST's own file at `path`.
+///
+/// A full disk is called out explicitly: on constrained CI it would
+/// otherwise read like a bug in the engine under test.
+pub(crate) fn io_failure(action: &str, path: &Path, e: &std::io::Error) -> String {
+    if is_out_of_space(e) {
+        format!(
+            "FIRST ran out of disk space (cannot {} {}); the engine under test is not at fault",
+            action,
+            path.display()
+        )
+    } else {
+        format!("cannot {} {}: {}", action, path.display(), e)
+    }
+}
+
+/// Remove a work dir, retrying transient failures with backoff.
+///
+/// Right after a child exits, the OS may still be flushing files in the
+/// directory, so `remove_dir_all` can fail spuriously (e.g. `ENOTEMPTY`).
+/// Persistent failures are reported rather than silently leaking the dir.
+pub(crate) fn cleanup_work_dir(path: &Path) {
+    let mut delay = Duration::from_millis(10);
+    for attempt in 1..=CLEANUP_ATTEMPTS {
+        let err = match fs::remove_dir_all(path) {
+            Ok(()) => return,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return,
+            Err(e) => e,
+        };
+
+        let transient = matches!(
+            err.kind(),
+            std::io::ErrorKind::DirectoryNotEmpty
+                | std::io::ErrorKind::ResourceBusy
+                | std::io::ErrorKind::Interrupted
+        );
+        if !transient || attempt == CLEANUP_ATTEMPTS {
+            eprintln!("[first] warning: {}", io_failure("remove", path, &err));
+            return;
+        }
+
+        if verbose() {
+            eprintln!(
+                "[first] cleanup of {} failed ({}), retrying (attempt {}/{})",
+                path.display(),
+                err,
+                attempt + 1,
+                CLEANUP_ATTEMPTS
+            );
+        }
+        std::thread::sleep(delay);
+        delay *= 2;
+    }
+}
+
+/// Returns true if a failed spawn may succeed when retried: the system
+/// was temporarily out of processes or memory.
+fn is_transient_spawn_error(e: &std::io::Error) -> bool {
+    e.kind() == std::io::ErrorKind::OutOfMemory
+        || matches!(e.raw_os_error(), Some(libc::EAGAIN | libc::ENOMEM))
+}
+
+/// Spawn `cmd`, retrying transient failures with backoff.
+///
+/// Every crash point costs FIRST several processes, so on a busy CI
+/// machine `fork` can fail with `EAGAIN` or `ENOMEM` for a moment.
+/// Such failures are retried up to `FIRST_SPAWN_RETRIES` times (default
+/// 3); any other error is returned immediately.
+pub(crate) fn spawn_with_retry(cmd: &mut Command) -> std::io::Result<Child> {
+    let retries = std::env::var(ENV_SPAWN_RETRIES)
+        .ok()
+        .and_then(|s| s.parse().ok())
+        .unwrap_or(DEFAULT_SPAWN_RETRIES);
+    let mut delay = Duration::from_millis(10);
+    let mut retry = 0;
+    loop {
+        match cmd.spawn() {
+            Err(e) if is_transient_spawn_error(&e) && retry < retries => {
+                retry += 1;
+                if verbose() {
+                    eprintln!(
+                        "[first] cannot spawn child ({}), retrying (retry {}/{})",
+                        e, retry, retries
+                    );
+                }
+                std::thread::sleep(delay);
+                delay *= 2;
             }
+            result => return result,
         }
+    }
+}
+
+/// Create a work dir with the layout and permissions from `options`.
+///
+/// Subdirectories are created before any mode is applied, so a restrictive
+/// mode on the root cannot block their creation.
+pub(crate) fn create_work_dir(path: &Path, options: &Options) -> std::io::Result<()> {
+    use std::os::unix::fs::PermissionsExt;
 
-        target += 1;
+    fs::create_dir_all(path)?;
+    for dir in &options.workspace_dirs {
+        fs::create_dir_all(path.join(dir))?;
     }
+
+    if let Some(mode) = options.workspace_mode {
+        // Deepest directories first, the root last.
+        for dir in options.workspace_dirs.iter().rev() {
+            for sub in dir.ancestors().filter(|a| !a.as_os_str().is_empty()) {
+                fs::set_permissions(path.join(sub), fs::Permissions::from_mode(mode))?;
+            }
+        }
+        fs::set_permissions(path, fs::Permissions::from_mode(mode))?;
+    }
+    Ok(())
+}
+
+/// Drop a crashed workspace from the page cache, warning on failure.
+pub(crate) fn evict_work_dir(path: &Path) {
+    if let Err(e) = crate::crash::drop_page_cache(path) {
+        eprintln!(
+            "[first] warning: cannot drop page cache for {}: {}",
+            path.display(),
+            e
+        );
+    }
+}
+
+/// Path of the stable symlink for `TestBuilder::stable_path(alias)`.
+fn stable_link_path(alias: &str) -> PathBuf {
+    std::env::temp_dir().join(format!("first-stable-{}", alias))
+}
+
+/// Atomically re-point the stable symlink `link` at `target`.
+fn point_stable_link(link: &Path, target: &Path) -> std::io::Result<()> {
+    // Create the new link beside the old one and rename over it, so the
+    // alias never dangles.
+    let tmp = link.with_extension("tmp");
+    let _ = fs::remove_file(&tmp);
+    std::os::unix::fs::symlink(target, &tmp)?;
+    fs::rename(&tmp, link)
 }
 
 /// Print detailed failure information for debugging.
-fn print_failure_info(
+pub(crate) fn print_failure_info(
     target: usize,
     work_dir: &Path,
     crash_info: &CrashInfo,
@@ -136,58 +1362,238 @@ fn print_failure_info(
         work_dir.display()
     );
     eprintln!("[first] crash label: \"{}\"", crash_info.label);
+    if let Some((file, line)) = crash_info.site {
+        eprintln!("[first] crash site: {}:{}", file, line);
+    }
+    if let Some(fault) = &crash_info.fault {
+        let errno = std::io::Error::from_raw_os_error(fault.kind.errno());
+        match &fault.operation {
+            Some(operation) => eprintln!(
+                "[first] injected fault: {} failed with {}",
+                operation, errno
+            ),
+            None => eprintln!(
+                "[first] injected fault: {} (no write or sync failed)",
+                errno
+            ),
+        }
+    }
+    if let Some(elapsed) = crash_info.elapsed {
+        eprintln!("[first] crashed after {:?} of workload", elapsed);
+    }
     eprintln!("[first] reason: {}", reason);
+    verify_log::print_tail(work_dir);
+    if let Some(seed) = crate::rt::run_seed() {
+        eprintln!("[first] seed: {}", seed);
+    }
+    let repro = repro_command(target, work_dir, crash_info, test_name);
+    eprintln!("[first] to reproduce:");
+    eprintln!("  {}", repro);
+    crate::github::error(
+        &format!("crash point {} (\"{}\")", target, crash_info.label),
+        &format!("{}\nto reproduce: {}", reason, repro),
+        crash_info.site,
+    );
+}
+
+/// Print the command line that re-runs the EXECUTION phase of a point.
+fn print_execution_repro(
+    target: usize,
+    stable: Option<StableTarget>,
+    work_dir: &Path,
+    test_name: &Option<String>,
+) {
     eprintln!("[first] to reproduce:");
     eprintln!(
-        "  FIRST_PHASE=VERIFY FIRST_CRASH_TARGET={} FIRST_WORK_DIR={} FIRST_CRASH_POINT_ID={} FIRST_CRASH_LABEL=\"{}\" cargo test{} -- --exact",
+        "  FIRST_PHASE=EXECUTION {} FIRST_WORK_DIR={}{} cargo test{} -- --exact",
+        match stable {
+            Some(stable) => {
+                let (name, value) = stable.env();
+                format!("{}=\"{}\"", name, value)
+            }
+            None => format!("FIRST_CRASH_TARGET={}", target),
+        },
+        work_dir.display(),
+        repro_env(),
+        test_name
+            .as_ref()
+            .map(|n| format!(" {}", n))
+            .unwrap_or_default()
+    );
+}
+
+/// Command line that re-runs the VERIFY phase of a failed crash point.
+fn repro_command(
+    target: usize,
+    work_dir: &Path,
+    crash_info: &CrashInfo,
+    test_name: &Option<String>,
+) -> String {
+    format!(
+        "FIRST_PHASE=VERIFY FIRST_CRASH_TARGET={} FIRST_WORK_DIR={} FIRST_CRASH_POINT_ID={} FIRST_CRASH_LABEL=\"{}\"{}{} cargo test{} -- --exact",
         target,
         work_dir.display(),
         crash_info.point_id,
         crash_info.label,
+        crash_info
+            .site_to_env()
+            .map(|site| format!(" FIRST_CRASH_SITE={}", site))
+            .unwrap_or_default(),
+        repro_env(),
         test_name
             .as_ref()
             .map(|n| format!(" {}", n))
             .unwrap_or_default()
-    );
+    )
+}
+
+/// ` FIRST_SEED=<seed>` for reproduction commands, if the run has a seed,
+/// and ` FIRST_INVOCATION=<label>` if the test has several invocations.
+fn repro_env() -> String {
+    let seed = crate::rt::run_seed()
+        .map(|seed| format!(" {}={}", crate::rt::ENV_SEED, seed))
+        .unwrap_or_default();
+    seed + &crate::invocation::repro_env()
+}
+
+/// Sidecar the EXECUTION child of crash point 1 writes once its test
+/// starts, proving the test name filter matched.
+const STARTED_MARKER: &str = "execution_started";
+
+/// Record in `metadata_dir` that the EXECUTION child of crash point 1
+/// started the test.
+pub(crate) fn mark_started(metadata_dir: &Path) {
+    if std::env::var("FIRST_CRASH_TARGET").as_deref() == Ok("1") {
+        let _ = fs::write(metadata_dir.join(STARTED_MARKER), "");
+    }
 }
 
 /// Result of a child process execution.
-enum ChildResult {
+#[allow(clippy::large_enum_variant)]
+pub(crate) enum ChildResult {
     /// Child exited successfully (exit code 0).
     Success,
     /// Child was killed by SIGKILL (crash occurred).
     Crashed(CrashInfo),
+    /// Child reached its target crash point, but the point's label is
+    /// disabled, so it exited without crashing.
+    Skipped(CrashInfo),
     /// Child failed with a non-zero exit code.
     Failed(i32),
+    /// Child was killed for running longer than the phase timeout.
+    TimedOut(Duration),
+}
+
+/// The result of a child killed by its `timeout::Watchdog`.
+fn timed_out() -> ChildResult {
+    ChildResult::TimedOut(crate::timeout::limit().unwrap_or_default())
+}
+
+/// A crash point targeted by something more stable than its counter ID.
+#[derive(Debug, Clone, Copy)]
+pub(crate) enum StableTarget<'a> {
+    /// The first hit of a `file:line` call site (`target_sites()`).
+    Site(&'a str),
+    /// The first crash point with a label (`target_labels()`).
+    Label(&'a str),
+}
+
+impl<'a> StableTarget<'a> {
+    /// The site or label itself.
+    fn name(&self) -> &'a str {
+        match self {
+            StableTarget::Site(name) | StableTarget::Label(name) => name,
+        }
+    }
+
+    /// What is swept: `"site"` or `"label"`.
+    fn unit(&self) -> &'static str {
+        match self {
+            StableTarget::Site(_) => "site",
+            StableTarget::Label(_) => "label",
+        }
+    }
+
+    /// The target as shown in the sweep, e.g. `crash label "commit"`.
+    fn describe(&self) -> String {
+        match self {
+            StableTarget::Site(site) => format!("crash site {}", site),
+            StableTarget::Label(label) => format!("crash label \"{}\"", label),
+        }
+    }
+
+    /// The environment variable and value that target it in a child.
+    fn env(&self) -> (&'static str, String) {
+        match self {
+            StableTarget::Site(site) => (
+                "FIRST_CRASH_TARGET_SITE",
+                format!("{:016x}", hash_site(site)),
+            ),
+            StableTarget::Label(label) => (crate::rt::ENV_CRASH_LABEL_TARGET, label.to_string()),
+        }
+    }
+}
+
+/// Hash a `file:line` crash site, as the EXECUTION child does.
+fn hash_site(site: &str) -> u64 {
+    let (file, line) = site.rsplit_once(':').unwrap_or((site, ""));
+    crate::rt::site_id(file, line.parse().unwrap_or(0))
 }
 
 /// Spawn a child process in the given phase.
-fn spawn_child(
+///
+/// With `stable`, the child crashes at the first hit of that call site or
+/// label instead of at crash point `target`.
+#[allow(clippy::too_many_arguments)]
+pub(crate) fn spawn_child(
     exe: &Path,
     test_name: &Option<String>,
     phase: &str,
     target: usize,
+    stable: Option<StableTarget>,
     work_dir: &Path,
+    metadata_dir: &Path,
+    coverage_dir: Option<&Path>,
+    limits: Option<&ResourceLimits>,
 ) -> ChildResult {
     let mut cmd = Command::new(exe);
 
     // Set FIRST environment variables
     cmd.env("FIRST_PHASE", phase);
+    if let Some(seed) = crate::rt::run_seed() {
+        cmd.env(crate::rt::ENV_SEED, seed.to_string());
+    }
+    cmd.env(
+        crate::invocation::ENV_INVOCATION,
+        crate::invocation::env_value(),
+    );
+    if let Some(dir) = coverage_dir {
+        cmd.env("LLVM_PROFILE_FILE", profile_path(dir, phase, target));
+    }
     cmd.env("FIRST_CRASH_TARGET", target.to_string());
+    if let Some(stable) = stable {
+        let (name, value) = stable.env();
+        cmd.env(name, value);
+    }
     cmd.env("FIRST_WORK_DIR", work_dir.to_string_lossy().to_string());
+    cmd.env(
+        "FIRST_METADATA_DIR",
+        metadata_dir.to_string_lossy().to_string(),
+    );
+    // Only written with binary_metadata(), replacing the JSON on stderr
+    let record = metadata::record_path(metadata_dir, phase, target);
+    let _ = fs::remove_file(&record);
+    cmd.env(metadata::ENV_METADATA_FILE, &record);
 
     // If we know the test name, filter to just that test
-    if let Some(name) = test_name {
-        cmd.arg(name);
-        cmd.arg("--");
-        cmd.arg("--exact");
-    }
+    harness_args(&mut cmd, test_name);
 
     // Capture stderr to parse crash metadata
     cmd.stderr(Stdio::piped());
-    cmd.stdout(Stdio::null());
+    cmd.stdout(child_stdout());
+    let cgroup = Cgroup::attach(&mut cmd, limits);
 
-    let mut child = match cmd.spawn() {
+    let mut child = match spawn_with_retry(&mut cmd) {
         Ok(c) => c,
         Err(e) => {
             eprintln!("[first] error: cannot spawn child: {}", e);
@@ -195,6 +1601,8 @@ fn spawn_child(
         }
     };
 
+    let watchdog = Watchdog::start(child.id(), crate::timeout::limit());
+
     // Read stderr for crash metadata
     let stderr = child.stderr.take();
     let crash_info = stderr.and_then(parse_crash_metadata);
@@ -207,68 +1615,223 @@ fn spawn_child(
             return ChildResult::Failed(1);
         }
     };
+    if watchdog.is_some_and(Watchdog::finish) {
+        return timed_out();
+    }
+    // Binary metadata leaves out the work dir, which the orchestrator knows
+    let crash_info = metadata::take(&record).or(crash_info).map(|mut info| {
+        if info.work_dir.as_os_str().is_empty() {
+            info.work_dir = work_dir.to_path_buf();
+        }
+        info
+    });
+    if cgroup.is_some_and(|cgroup| oom_killed(&cgroup, phase)) {
+        return ChildResult::Failed(CRASH_EXIT_CODE);
+    }
 
     interpret_exit_status(status, crash_info)
 }
 
+/// Call verify in the orchestrator (`TestBuilder::verify_in_process()`).
+///
+/// A panic fails the crash point with the exit code a VERIFY child's
+/// test harness would have returned for it. Returns `None` if the crash
+/// point must be verified by a VERIFY child instead.
+pub(crate) fn verify_in_process(
+    verify: VerifyRef<'_>,
+    target: usize,
+    work_dir: &Path,
+    metadata_dir: &Path,
+    crash_info: &CrashInfo,
+) -> Option<ChildResult> {
+    const PANIC_EXIT_CODE: i32 = 101;
+
+    let env =
+        Env::new(work_dir.to_path_buf(), metadata_dir.to_path_buf()).with_crash(target, crash_info);
+    match std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| verify(&env, crash_info))) {
+        Ok(true) => Some(ChildResult::Success),
+        Ok(false) => None,
+        Err(_) => Some(ChildResult::Failed(PANIC_EXIT_CODE)),
+    }
+}
+
 /// Spawn a child process in VERIFY phase with crash info.
-fn spawn_child_with_crash_info(
+#[allow(clippy::too_many_arguments)]
+pub(crate) fn spawn_child_with_crash_info(
     exe: &Path,
     test_name: &Option<String>,
     target: usize,
     work_dir: &Path,
+    metadata_dir: &Path,
     crash_info: &CrashInfo,
+    read_chunk: Option<usize>,
+    coverage_dir: Option<&Path>,
+    limits: Option<&ResourceLimits>,
+    output: Option<&mut CapturedOutput>,
 ) -> ChildResult {
     let mut cmd = Command::new(exe);
 
     // Set FIRST environment variables
     cmd.env("FIRST_PHASE", "VERIFY");
+    if let Some(seed) = crate::rt::run_seed() {
+        cmd.env(crate::rt::ENV_SEED, seed.to_string());
+    }
+    cmd.env(
+        crate::invocation::ENV_INVOCATION,
+        crate::invocation::env_value(),
+    );
+    if let Some(dir) = coverage_dir {
+        cmd.env("LLVM_PROFILE_FILE", profile_path(dir, "VERIFY", target));
+    }
     cmd.env("FIRST_CRASH_TARGET", target.to_string());
     cmd.env("FIRST_WORK_DIR", work_dir.to_string_lossy().to_string());
+    cmd.env(
+        "FIRST_METADATA_DIR",
+        metadata_dir.to_string_lossy().to_string(),
+    );
     cmd.env("FIRST_CRASH_POINT_ID", crash_info.point_id.to_string());
+    cmd.env(crate::invariants::ENV_CHECKS_FILE, checks_log_path());
+    cmd.env(crate::invariants::ENV_COMMITTED_FILE, committed_log_path());
     cmd.env("FIRST_CRASH_LABEL", &crash_info.label);
+    if let Some(fds) = crash_info.max_fds {
+        cmd.env("FIRST_CRASH_MAX_FDS", fds.to_string());
+    }
+    if let Some(partial) = &crash_info.partial_write {
+        cmd.env("FIRST_CRASH_PARTIAL_WRITE", partial.to_env());
+    }
+    cmd.env(
+        "FIRST_CRASH_FSYNC_COUNT",
+        crash_info.fsync_count.to_string(),
+    );
+    cmd.env(
+        "FIRST_CRASH_EINTR_COUNT",
+        crash_info.eintr_count.to_string(),
+    );
+    if let Some(fault) = &crash_info.fault {
+        cmd.env("FIRST_CRASH_FAULT", fault.to_env());
+    }
+    if let Some(injected) = &crash_info.dir_fsync_fault {
+        cmd.env("FIRST_CRASH_DIR_FSYNC", injected.to_env());
+    }
+    if let Some(elapsed) = crash_info.elapsed {
+        cmd.env("FIRST_CRASH_ELAPSED_US", elapsed.as_micros().to_string());
+    }
+    if let Some(total) = crash_info.total_points {
+        cmd.env("FIRST_TOTAL_POINTS", total.to_string());
+    }
+    if let Some(site) = crash_info.site_to_env() {
+        cmd.env("FIRST_CRASH_SITE", site);
+    }
+    if !crash_info.history.is_empty() {
+        let history: Vec<String> = crash_info.history.iter().map(|id| id.to_string()).collect();
+        cmd.env("FIRST_CRASH_HISTORY", history.join(","));
+    }
+    if !crash_info.barriers.is_empty() {
+        let barriers: Vec<String> = crash_info.barriers.iter().map(|b| b.to_env()).collect();
+        cmd.env("FIRST_CRASH_BARRIERS", barriers.join("\n"));
+    }
+    if !crash_info.partial_flush.is_empty() {
+        let flushes: Vec<String> = crash_info
+            .partial_flush
+            .iter()
+            .map(|f| f.to_env())
+            .collect();
+        cmd.env("FIRST_CRASH_PARTIAL_FLUSH", flushes.join("\n"));
+    }
+    if let Some(size) = read_chunk {
+        cmd.env(crate::chunking::ENV_READ_CHUNK, size.to_string());
+    }
 
     // If we know the test name, filter to just that test
-    if let Some(name) = test_name {
-        cmd.arg(name);
-        cmd.arg("--");
-        cmd.arg("--exact");
-    }
+    harness_args(&mut cmd, test_name);
 
-    // Don't capture stderr for verify - let it pass through
-    cmd.stderr(Stdio::inherit());
-    cmd.stdout(Stdio::null());
+    // Capture both streams for the verify log and a failure bundle
+    cmd.stderr(Stdio::piped());
+    cmd.stdout(Stdio::piped());
+    let cgroup = Cgroup::attach(&mut cmd, limits);
 
-    let mut child = match cmd.spawn() {
-        Ok(c) => c,
+    let mut watchdog = None;
+    let captured = match spawn_with_retry(&mut cmd).and_then(|c| {
+        watchdog = Watchdog::start(c.id(), crate::timeout::limit());
+        c.wait_with_output()
+    }) {
+        Ok(o) => o,
         Err(e) => {
-            eprintln!("[first] error: cannot spawn verify child: {}", e);
+            eprintln!("[first] error: cannot run verify child: {}", e);
             return ChildResult::Failed(1);
         }
     };
+    let timed_out_child = watchdog.is_some_and(Watchdog::finish);
 
-    // Wait for child to exit
-    let status = match child.wait() {
-        Ok(s) => s,
-        Err(e) => {
-            eprintln!("[first] error: cannot wait for verify child: {}", e);
-            return ChildResult::Failed(1);
-        }
+    // Still pass stderr through, past libtest's output capture
+    if nocapture() {
+        let _ = std::io::stdout().write_all(&captured.stdout);
+    }
+    let mut stderr = std::io::stderr().lock();
+    let _ = stderr.write_all(&captured.stderr);
+    let _ = stderr.flush();
+    drop(stderr);
+
+    let result = if timed_out_child {
+        timed_out()
+    } else if cgroup.is_some_and(|cgroup| oom_killed(&cgroup, "VERIFY")) {
+        ChildResult::Failed(CRASH_EXIT_CODE)
+    } else {
+        interpret_exit_status(captured.status, None)
     };
+    match result {
+        ChildResult::Success => verify_log::remove(work_dir),
+        _ => verify_log::write(work_dir, &captured),
+    }
+    if let Some(output) = output {
+        output.stdout = captured.stdout;
+        output.stderr = captured.stderr;
+    }
+    result
+}
 
-    interpret_exit_status(status, None)
+/// Report whether the child in `cgroup` was OOM-killed rather than
+/// crash-injected, explaining it if so.
+fn oom_killed(cgroup: &Cgroup, phase: &str) -> bool {
+    if !cgroup.oom_killed() {
+        return false;
+    }
+    let limit = cgroup
+        .memory_max()
+        .map(|bytes| format!(" (memory_max {} bytes)", bytes))
+        .unwrap_or_default();
+    eprintln!(
+        "[first] {} child was OOM-killed by its resource limits{}, not crash-injected",
+        phase, limit
+    );
+    true
 }
 
 /// Parse crash metadata from child's stderr.
 fn parse_crash_metadata(stderr: impl std::io::Read) -> Option<CrashInfo> {
     let reader = BufReader::new(stderr);
+    let mut barriers = Vec::new();
+    let mut partial_flush = Vec::new();
     for line in reader.lines().map_while(Result::ok) {
         // Look for JSON crash metadata
-        if line.starts_with(r#"{"event":"crash""#) {
-            // Simple JSON parsing (avoid adding serde dependency for now)
-            if let Some(info) = parse_crash_json(&line) {
+        if line.starts_with(r#"{"event":"crash""#) || line.starts_with(r#"{"event":"skipped""#) {
+            #[cfg(feature = "serde")]
+            if let Some(info) = crate::crash_event::decode(&line) {
+                return Some(info);
+            }
+            // Without serde: simple JSON parsing of the flat format
+            if let Some(mut info) = parse_crash_json(&line) {
+                info.barriers = barriers;
+                info.partial_flush = partial_flush;
                 return Some(info);
             }
+        } else if line.starts_with(r#"{"event":"barrier""#) {
+            barriers.extend(parse_barrier_json(&line));
+        } else if line.starts_with(r#"{"event":"partial_flush""#) {
+            partial_flush.extend(parse_partial_flush_json(&line));
+        } else if line.starts_with("[first]") || nocapture() {
+            // Diagnostics the child wrote past libtest's output capture
+            eprintln!("{}", line);
         }
     }
     None
@@ -276,23 +1839,111 @@ fn parse_crash_metadata(stderr: impl std::io::Read) -> Option<CrashInfo> {
 
 /// Simple JSON parser for crash metadata.
 fn parse_crash_json(json: &str) -> Option<CrashInfo> {
-    // Format: {"event":"crash","point_id":N,"label":"...","seed":...,"work_dir":"..."}
-    let point_id = json.find(r#""point_id":"#).and_then(|i| {
-        let start = i + 11;
-        let end = json[start..].find(',')?;
-        json[start..start + end].parse().ok()
-    })?;
-
-    let label = json
-        .find(r#""label":""#)
-        .and_then(|i| {
-            let start = i + 9;
-            let end = json[start..].find('"')?;
-            Some(json[start..start + end].to_string())
+    // Format: {"event":"crash","point_id":N,"label":"...","seed":...,"work_dir":"...","max_fds":N}
+    let point_id = parse_json_number(json, "point_id")?;
+
+    let label = parse_json_string(json, "label").unwrap_or_else(|| "unknown".to_string());
+
+    let mut info = CrashInfo::new(point_id, label);
+    info.work_dir = parse_json_string(json, "work_dir")
+        .map(PathBuf::from)
+        .unwrap_or_default();
+    info.max_fds = parse_json_number(json, "max_fds");
+    info.fsync_count = parse_json_number(json, "fsync_count").unwrap_or(0);
+    info.eintr_count = parse_json_number(json, "eintr_count").unwrap_or(0);
+    if let Some(file) = parse_json_string(json, "site_file")
+        && let Some(line) = parse_json_number(json, "site_line")
+    {
+        info.set_site(&format!("{}:{}", file, line));
+    }
+    info.dir_fsync_fault = parse_json_string(json, "dir_fsync_dir").and_then(|dir| {
+        Some(InjectedDirFsync {
+            dir: PathBuf::from(dir),
+            fault: DirFsyncFault::from_env(&parse_json_string(json, "dir_fsync_fault")?)?,
+            after_point: parse_json_number(json, "dir_fsync_after_point")?,
         })
-        .unwrap_or_else(|| "unknown".to_string());
+    });
+    info.fault = parse_json_string(json, "fault")
+        .and_then(|fault| crate::fault::InjectedFault::from_env(&fault));
+    info.elapsed =
+        parse_json_number(json, "elapsed_us").map(|us| std::time::Duration::from_micros(us as u64));
+    info.partial_write = parse_json_string(json, "partial_file").and_then(|file| {
+        let mut partial = PartialWrite::new(PathBuf::from(file));
+        partial.offset = parse_json_number(json, "partial_offset")? as u64;
+        partial.written = parse_json_number(json, "partial_written")?;
+        partial.len = parse_json_number(json, "partial_len")?;
+        Some(partial)
+    });
+    Some(info)
+}
+
+/// Parse a barrier event emitted just before the crash event.
+fn parse_barrier_json(json: &str) -> Option<BarrierRecord> {
+    // Format: {"event":"barrier","kind":"...","after_point":N[,"range_start":N,"range_end":N][,"file":"..."]}
+    let kind = BarrierKind::parse(&parse_json_string(json, "kind")?)?;
+    let after_point = parse_json_number(json, "after_point")?;
+    let range = parse_json_number(json, "range_start")
+        .zip(parse_json_number(json, "range_end"))
+        .map(|(start, end)| start as u64..end as u64);
+    let file = parse_json_string(json, "file").map(PathBuf::from);
+    Some(BarrierRecord {
+        kind,
+        file,
+        after_point,
+        range,
+    })
+}
 
-    Some(CrashInfo::new(point_id, label))
+/// Parse a partial flush event emitted just before the crash event.
+fn parse_partial_flush_json(json: &str) -> Option<PartialFlush> {
+    // Format: {"event":"partial_flush","synced_len":N,"len":N,"persisted":"a-b,...","file":"..."}
+    let file = parse_json_string(json, "file")?;
+    PartialFlush::from_env(&format!(
+        "{}:{}:{}:{}",
+        parse_json_number(json, "synced_len")?,
+        parse_json_number(json, "len")?,
+        parse_json_string(json, "persisted")?,
+        file
+    ))
+}
+
+/// Extract a string field from flat crash metadata JSON.
+///
+/// Undoes the escapes of `report::escape_json()`, which the child uses for
+/// every string it writes, so a value may contain quotes, backslashes,
+/// commas or braces.
+fn parse_json_string(json: &str, key: &str) -> Option<String> {
+    let pattern = format!(r#""{}":""#, key);
+    let start = json.find(&pattern)? + pattern.len();
+    let mut value = String::new();
+    let mut chars = json[start..].chars();
+    while let Some(c) = chars.next() {
+        match c {
+            '"' => return Some(value),
+            '\\' => match chars.next()? {
+                'n' => value.push('\n'),
+                'r' => value.push('\r'),
+                't' => value.push('\t'),
+                'u' => {
+                    let hex: String = chars.by_ref().take(4).collect();
+                    value.push(char::from_u32(u32::from_str_radix(&hex, 16).ok()?)?);
+                }
+                escaped => value.push(escaped),
+            },
+            c => value.push(c),
+        }
+    }
+    None
+}
+
+/// Extract an unsigned numeric field from flat crash metadata JSON.
+///
+/// Returns `None` if the key is missing or its value is `null`.
+fn parse_json_number(json: &str, key: &str) -> Option<usize> {
+    let pattern = format!(r#""{}":"#, key);
+    let start = json.find(&pattern)? + pattern.len();
+    let end = json[start..].find([',', '}'])?;
+    json[start..start + end].parse().ok()
 }
 
 /// Interpret child exit status.
@@ -303,36 +1954,61 @@ fn interpret_exit_status(status: ExitStatus, crash_info: Option<CrashInfo>) -> C
 
     let code = status.code().unwrap_or(-1);
 
-    if code == SIGKILL_EXIT_CODE {
-        // SIGKILL - this is an expected crash
-        let info = crash_info.unwrap_or_else(|| CrashInfo::new(0, "unknown".to_string()));
-        return ChildResult::Crashed(info);
+    if code == crate::rt::SKIPPED_EXIT_CODE
+        && let Some(info) = crash_info
+    {
+        return ChildResult::Skipped(info);
     }
 
-    // Check for signal on Unix
-    #[cfg(unix)]
-    {
-        use std::os::unix::process::ExitStatusExt;
-        if let Some(signal) = status.signal()
-            && signal == libc::SIGKILL
-        {
-            let info = crash_info.unwrap_or_else(|| CrashInfo::new(0, "unknown".to_string()));
-            return ChildResult::Crashed(info);
-        }
+    if crate::terminate::is_crash(status) {
+        // Killed at the crash point - this is an expected crash
+        let info = crash_info.unwrap_or_else(|| CrashInfo::new(0, "unknown".to_string()));
+        return ChildResult::Crashed(info);
     }
 
     ChildResult::Failed(code)
 }
 
-/// Extract test name from command line arguments.
-fn extract_test_name() -> Option<String> {
+/// libtest flags whose value is passed as a separate argument.
+const FLAGS_WITH_VALUE: &[&str] = &[
+    "--format",
+    "--test-threads",
+    "--skip",
+    "--color",
+    "--logfile",
+    "-Z",
+];
+
+/// Name of the test children are filtered on: `explicit`
+/// (`TestBuilder::test_name()`), else the name of the test's thread, else
+/// a guess from the command line arguments.
+fn extract_test_name(explicit: Option<&str>) -> Option<String> {
+    if let Some(name) = explicit {
+        return Some(name.to_string());
+    }
+    // The test harness runs each test on a thread named after it
+    if let Some(name) = std::thread::current().name()
+        && name != "main"
+    {
+        return Some(name.to_string());
+    }
     // Look for test name in args
     // Typical: target/debug/deps/first-xxx test_name
     let args: Vec<String> = std::env::args().collect();
+    test_name_from_args(&args)
+}
 
+/// Find the test name filter in a full argument list (including argv[0]).
+fn test_name_from_args(args: &[String]) -> Option<String> {
     // Skip the executable path, look for something that looks like a test name
-    for arg in args.iter().skip(1) {
-        // Skip flags
+    let mut iter = args.iter().skip(1);
+    while let Some(arg) = iter.next() {
+        // Skip flags, along with the value of flags that take one
+        // (e.g. `--format json`, `-Z unstable-options`)
+        if FLAGS_WITH_VALUE.contains(&arg.as_str()) {
+            iter.next();
+            continue;
+        }
         if arg.starts_with('-') {
             continue;
         }
@@ -346,3 +2022,425 @@ fn extract_test_name() -> Option<String> {
 
     None
 }
+
+/// libtest flags of the orchestrator that its children are run with too.
+const FORWARDED_FLAGS: &[&str] = &[
+    "--nocapture",
+    "--no-capture",
+    "--test-threads",
+    "--ignored",
+    "--include-ignored",
+];
+
+/// The [`FORWARDED_FLAGS`] the orchestrator was run with.
+fn harness_flags() -> &'static [String] {
+    static FLAGS: OnceLock<Vec<String>> = OnceLock::new();
+    FLAGS.get_or_init(|| {
+        let args: Vec<String> = std::env::args().collect();
+        harness_flags_from_args(&args)
+    })
+}
+
+/// Find the [`FORWARDED_FLAGS`] in a full argument list (including
+/// argv[0]), with their values.
+fn harness_flags_from_args(args: &[String]) -> Vec<String> {
+    let mut flags = Vec::new();
+    let mut iter = args.iter().skip(1);
+    while let Some(arg) = iter.next() {
+        if arg == "--" {
+            break;
+        }
+        let name = arg.split_once('=').map_or(arg.as_str(), |(name, _)| name);
+        if FORWARDED_FLAGS.contains(&name) {
+            flags.push(arg.clone());
+        }
+        if FLAGS_WITH_VALUE.contains(&arg.as_str()) {
+            let value = iter.next();
+            if FORWARDED_FLAGS.contains(&arg.as_str()) {
+                flags.extend(value.cloned());
+            }
+        }
+    }
+    flags
+}
+
+/// Whether the output of children is shown rather than captured, like
+/// the test harness decides it: `--nocapture` or `RUST_TEST_NOCAPTURE`.
+pub(crate) fn nocapture() -> bool {
+    harness_flags()
+        .iter()
+        .any(|flag| flag == "--nocapture" || flag == "--no-capture")
+        || std::env::var_os("RUST_TEST_NOCAPTURE").is_some_and(|v| v != "0")
+}
+
+/// Stdout of a child running the workload: the orchestrator's own under
+/// `--nocapture`, discarded otherwise.
+pub(crate) fn child_stdout() -> Stdio {
+    if nocapture() {
+        Stdio::inherit()
+    } else {
+        Stdio::null()
+    }
+}
+
+/// Pass a child the forwarded test harness flags, and filter it to exactly
+/// the test `test_name`.
+pub(crate) fn harness_args(cmd: &mut Command, test_name: &Option<String>) {
+    cmd.args(harness_flags());
+    if let Some(name) = test_name {
+        cmd.args(["--exact", "--", name]);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_crash_json_basic() {
+        let json = r#"{"event":"crash","point_id":5,"label":"after_commit","seed":null,"work_dir":"/tmp/first/run_5","max_fds":null}"#;
+        let info = parse_crash_json(json).unwrap();
+        assert_eq!(info.point_id, 5);
+        assert_eq!(info.label, "after_commit");
+        assert_eq!(info.work_dir, PathBuf::from("/tmp/first/run_5"));
+        assert_eq!(info.max_fds, None);
+    }
+
+    #[test]
+    fn test_harness_flags_from_args() {
+        let args: Vec<String> = [
+            "first-xxx",
+            "--format",
+            "pretty",
+            "--nocapture",
+            "wal",
+            "--test-threads",
+            "2",
+            "--include-ignored",
+            "--",
+            "--ignored",
+        ]
+        .iter()
+        .map(|s| s.to_string())
+        .collect();
+        assert_eq!(
+            harness_flags_from_args(&args),
+            ["--nocapture", "--test-threads", "2", "--include-ignored"]
+        );
+
+        let args = vec!["first-xxx".to_string(), "--test-threads=1".to_string()];
+        assert_eq!(harness_flags_from_args(&args), ["--test-threads=1"]);
+    }
+
+    #[test]
+    fn test_extract_test_name() {
+        assert_eq!(
+            extract_test_name(Some("wal::commit")).as_deref(),
+            Some("wal::commit")
+        );
+        // The harness names this test's thread after it
+        assert_eq!(
+            extract_test_name(None).as_deref(),
+            Some("orchestrator::tests::test_extract_test_name")
+        );
+    }
+
+    #[test]
+    fn test_parse_crash_json_fault() {
+        let json = r#"{"event":"crash","point_id":2,"label":"a","seed":null,"work_dir":"/tmp","max_fds":null,"fsync_count":0,"eintr_count":0,"dir_fsync_after_point":1,"dir_fsync_fault":"5","dir_fsync_dir":"db","fault":"enospc:write"}"#;
+        let info = parse_crash_json(json).unwrap();
+        let fault = info.fault.unwrap();
+        assert_eq!(fault.kind, crate::FaultKind::NoSpace);
+        assert_eq!(fault.operation.as_deref(), Some("write"));
+        assert_eq!(info.dir_fsync_fault.unwrap().after_point, 1);
+    }
+
+    #[test]
+    fn test_parse_crash_json_elapsed() {
+        let json = r#"{"event":"crash","point_id":2,"label":"a","seed":null,"work_dir":"/tmp","max_fds":null,"elapsed_us":1500}"#;
+        let info = parse_crash_json(json).unwrap();
+        assert_eq!(info.elapsed, Some(std::time::Duration::from_micros(1500)));
+    }
+
+    #[test]
+    fn test_parse_crash_json_max_fds() {
+        let json = r#"{"event":"crash","point_id":2,"label":"a","seed":null,"work_dir":"/tmp","max_fds":17}"#;
+        let info = parse_crash_json(json).unwrap();
+        assert_eq!(info.max_fds, Some(17));
+    }
+
+    #[test]
+    fn test_parse_crash_json_tricky_labels() {
+        let labels = [
+            r#"he said "hi""#,
+            "after commit, before fsync",
+            "key: {value}",
+            r"C:\wal\",
+            r#"\"quoted\""#,
+            "two\nlines\tand\u{1}control",
+            "",
+        ];
+        for label in labels {
+            let json = format!(
+                r#"{{"event":"crash","point_id":1,"label":"{}","seed":null,"work_dir":"{}","max_fds":null,"fsync_count":3}}"#,
+                report::escape_json(label),
+                report::escape_json("/tmp/first/run_1")
+            );
+            let info = parse_crash_json(&json).unwrap();
+            assert_eq!(info.label, label);
+            assert_eq!(info.fsync_count, 3);
+        }
+    }
+
+    #[test]
+    fn test_parse_crash_json_partial_write() {
+        let json = r#"{"event":"crash","point_id":2,"label":"write_then_crash","seed":null,"work_dir":"/tmp","max_fds":null,"partial_file":"wal.log","partial_offset":8,"partial_written":4,"partial_len":8}"#;
+        let partial = parse_crash_json(json).unwrap().partial_write.unwrap();
+        assert_eq!(partial.file, PathBuf::from("wal.log"));
+        assert_eq!(partial.offset, 8);
+        assert_eq!(partial.written, 4);
+        assert_eq!(partial.len, 8);
+        assert_eq!(PartialWrite::from_env(&partial.to_env()), Some(partial));
+    }
+
+    #[test]
+    fn test_parse_crash_json_dir_fsync_fault() {
+        let json = r#"{"event":"crash","point_id":3,"label":"a","seed":null,"work_dir":"/tmp","max_fds":null,"fsync_count":1,"eintr_count":0,"dir_fsync_after_point":2,"dir_fsync_fault":"5","dir_fsync_dir":"db"}"#;
+        let injected = parse_crash_json(json).unwrap().dir_fsync_fault.unwrap();
+        assert_eq!(injected.dir, PathBuf::from("db"));
+        assert_eq!(injected.fault, DirFsyncFault::Error(5));
+        assert_eq!(injected.after_point, 2);
+        assert_eq!(
+            InjectedDirFsync::from_env(&injected.to_env()),
+            Some(injected)
+        );
+
+        let json = json.replace(r#""5""#, r#""dropped""#);
+        let injected = parse_crash_json(&json).unwrap().dir_fsync_fault.unwrap();
+        assert_eq!(injected.fault, DirFsyncFault::Dropped);
+    }
+
+    #[test]
+    fn test_parse_crash_json_site() {
+        let json = r#"{"event":"crash","point_id":4,"label":"a","seed":null,"work_dir":"/tmp","max_fds":null,"site_file":"tests/wal.rs","site_line":12,"fsync_count":0}"#;
+        let info = parse_crash_json(json).unwrap();
+        assert_eq!(info.site, Some(("tests/wal.rs", 12)));
+        assert_eq!(info.site_to_env().as_deref(), Some("tests/wal.rs:12"));
+        assert_eq!(
+            hash_site("tests/wal.rs:12"),
+            crate::rt::site_id("tests/wal.rs", 12)
+        );
+    }
+
+    #[test]
+    fn test_parse_crash_metadata_partial_flush() {
+        let stderr = concat!(
+            r#"{"event":"partial_flush","synced_len":10,"len":9000,"persisted":"4096-9000","file":"wal.log"}"#,
+            "\n",
+            r#"{"event":"partial_flush","synced_len":0,"len":20,"persisted":"","file":"idx"}"#,
+            "\n",
+            r#"{"event":"crash","point_id":2,"label":"wal_fsync","seed":null,"work_dir":"/tmp","max_fds":null}"#,
+            "\n",
+        );
+        let info = parse_crash_metadata(stderr.as_bytes()).unwrap();
+        let flushes = info.partial_flush();
+        assert_eq!(flushes.len(), 2);
+        assert_eq!(flushes[0].file, PathBuf::from("wal.log"));
+        assert_eq!((flushes[0].synced_len, flushes[0].len), (10, 9000));
+        assert_eq!(flushes[0].persisted, vec![4096..9000]);
+        assert!(flushes[1].persisted.is_empty());
+        assert_eq!(
+            PartialFlush::from_env(&flushes[1].to_env()).as_ref(),
+            Some(&flushes[1])
+        );
+    }
+
+    #[test]
+    fn test_parse_crash_metadata_barriers() {
+        let stderr = concat!(
+            r#"{"event":"barrier","kind":"fdatasync","after_point":0,"file":"data/seg 1"}"#,
+            "\n",
+            r#"{"event":"barrier","kind":"msync","after_point":2,"range_start":0,"range_end":4096}"#,
+            "\n",
+            r#"{"event":"crash","point_id":3,"label":"a","seed":null,"work_dir":"/tmp","max_fds":null,"fsync_count":2,"eintr_count":1}"#,
+            "\n",
+        );
+        let info = parse_crash_metadata(stderr.as_bytes()).unwrap();
+        let barriers = info.barriers();
+        assert_eq!(barriers.len(), 2);
+        assert_eq!(barriers[0].kind, BarrierKind::Fdatasync);
+        assert_eq!(barriers[0].file, Some(PathBuf::from("data/seg 1")));
+        assert_eq!(barriers[1].after_point, 2);
+        assert_eq!(barriers[1].range, Some(0..4096));
+        assert_eq!(barriers[1].file, None);
+        assert_eq!(
+            BarrierRecord::from_env(&barriers[1].to_env()).as_ref(),
+            Some(&barriers[1])
+        );
+        assert_eq!(
+            BarrierRecord::from_env(&barriers[0].to_env()).as_ref(),
+            Some(&barriers[0])
+        );
+    }
+
+    #[test]
+    fn test_cleanup_work_dir() {
+        let dir = tempfile::tempdir().unwrap();
+        let work_dir = dir.path().join("run_1");
+        fs::create_dir_all(work_dir.join("nested")).unwrap();
+        fs::write(work_dir.join("nested/file"), b"x").unwrap();
+
+        cleanup_work_dir(&work_dir);
+        assert!(!work_dir.exists());
+
+        // Already gone: not an error
+        cleanup_work_dir(&work_dir);
+    }
+
+    /// Gates `self_crash_workload`, which only runs under
+    /// `test_orchestrator_survives_self_crash`.
+    const ENV_SELF_CRASH_WORKLOAD: &str = "FIRST_SELF_CRASH_WORKLOAD";
+
+    #[test]
+    fn self_crash_workload() {
+        if std::env::var_os(ENV_SELF_CRASH_WORKLOAD).is_none() {
+            return;
+        }
+        crate::test()
+            .run(|env| {
+                // Fails on a workspace left behind by a crashed orchestrator
+                fs::OpenOptions::new()
+                    .write(true)
+                    .create_new(true)
+                    .open(env.path("log"))
+                    .unwrap();
+                crate::crash_point("after_create");
+                fs::write(env.path("log"), b"entry").unwrap();
+                crate::crash_point("after_write");
+            })
+            .verify(|env, _crash_info| {
+                let log = fs::read(env.path("log")).unwrap();
+                assert!(log.is_empty() || log == b"entry");
+            })
+            .execute();
+    }
+
+    #[test]
+    fn test_orchestrator_survives_self_crash() {
+        use std::os::unix::process::ExitStatusExt;
+
+        let run = |crash_at: Option<&str>| {
+            let mut cmd = Command::new(std::env::current_exe().unwrap());
+            cmd.args(["orchestrator::tests::self_crash_workload", "--exact"])
+                .env(ENV_SELF_CRASH_WORKLOAD, "1")
+                .env_remove("FIRST_PHASE")
+                .stdout(Stdio::null())
+                .stderr(Stdio::null());
+            match crash_at {
+                Some(boundary) => cmd.env(ENV_SELF_CRASH_AT, boundary),
+                None => cmd.env_remove(ENV_SELF_CRASH_AT),
+            };
+            cmd.status().unwrap()
+        };
+
+        for boundary in ["after_execution", "after_verify", "after_cleanup"] {
+            let status = run(Some(boundary));
+            assert_eq!(status.signal(), Some(libc::SIGKILL), "{}", boundary);
+            assert!(run(None).success(), "rerun after {} failed", boundary);
+        }
+    }
+
+    #[test]
+    fn test_io_failure_blames_full_disk() {
+        let path = Path::new("/tmp/first/run_3");
+        let full = std::io::Error::from_raw_os_error(libc::ENOSPC);
+        assert!(is_out_of_space(&full));
+        assert_eq!(
+            io_failure("create", path, &full),
+            "FIRST ran out of disk space (cannot create /tmp/first/run_3); \
+             the engine under test is not at fault"
+        );
+
+        let denied = std::io::Error::from_raw_os_error(libc::EACCES);
+        assert!(!is_out_of_space(&denied));
+        assert!(
+            io_failure("create", path, &denied).starts_with("cannot create /tmp/first/run_3: ")
+        );
+    }
+
+    #[test]
+    fn test_spawn_retries_only_transient_errors() {
+        assert!(is_transient_spawn_error(
+            &std::io::Error::from_raw_os_error(libc::EAGAIN)
+        ));
+        assert!(is_transient_spawn_error(
+            &std::io::Error::from_raw_os_error(libc::ENOMEM)
+        ));
+        assert!(!is_transient_spawn_error(
+            &std::io::Error::from_raw_os_error(libc::ENOENT)
+        ));
+
+        let err = spawn_with_retry(&mut Command::new("/nonexistent/first-child")).unwrap_err();
+        assert_eq!(err.kind(), std::io::ErrorKind::NotFound);
+    }
+
+    #[test]
+    fn test_shuffled_is_seeded_permutation() {
+        let order = shuffled(10, 42);
+        assert_eq!(order, shuffled(10, 42));
+        assert_ne!(order, shuffled(10, 43));
+        let mut sorted = order.clone();
+        sorted.sort();
+        assert_eq!(sorted, (1..=10).collect::<Vec<_>>());
+        assert!(shuffled(0, 42).is_empty());
+    }
+
+    #[test]
+    fn test_sample_points_is_seeded_subset() {
+        let sample = sample_points(100, 5, 7);
+        assert_eq!(sample, sample_points(100, 5, 7));
+        assert_ne!(sample, sample_points(100, 5, 8));
+        assert_eq!(sample.len(), 5);
+        assert!(sample.windows(2).all(|w| w[0] < w[1]));
+        assert!(sample.iter().all(|&p| (1..=100).contains(&p)));
+        assert_eq!(sample_points(3, 10, 7), [1, 2, 3]);
+    }
+
+    #[test]
+    fn test_create_work_dir_layout() {
+        use std::os::unix::fs::PermissionsExt;
+
+        let dir = tempfile::tempdir().unwrap();
+        let work_dir = dir.path().join("run_1");
+        let options = Options {
+            workspace_mode: Some(0o750),
+            workspace_dirs: vec![PathBuf::from("data/tables"), PathBuf::from("log")],
+            ..Options::default()
+        };
+        create_work_dir(&work_dir, &options).unwrap();
+
+        for sub in ["", "data", "data/tables", "log"] {
+            let mode = fs::metadata(work_dir.join(sub))
+                .unwrap()
+                .permissions()
+                .mode();
+            assert_eq!(mode & 0o777, 0o750, "{:?}", sub);
+        }
+    }
+
+    #[test]
+    fn test_name_from_args_skips_flag_values() {
+        let args: Vec<String> = [
+            "bin",
+            "-Z",
+            "unstable-options",
+            "--format",
+            "json",
+            "my_test",
+        ]
+        .iter()
+        .map(|s| s.to_string())
+        .collect();
+        assert_eq!(test_name_from_args(&args), Some("my_test".to_string()));
+    }
+}
@@ -0,0 +1,458 @@
+//! OpenTelemetry spans for crash points (`otel` feature).
+//!
+//! With the feature enabled and an OTLP endpoint configured, the
+//! orchestrator records one span per crash point, from the start of its
+//! EXECUTION child to its verdict, and exports them with OTLP/HTTP in the
+//! JSON encoding. All spans of a sweep share one trace.
+//!
+//! The endpoint is read from the standard variables:
+//!
+//! | Variable | Use |
+//! |----------|-----|
+//! | `OTEL_EXPORTER_OTLP_TRACES_ENDPOINT` | Full URL spans are posted to |
+//! | `OTEL_EXPORTER_OTLP_ENDPOINT` | Base URL; spans go to `<base>/v1/traces` |
+//! | `OTEL_EXPORTER_OTLP_TRACES_HEADERS`, `OTEL_EXPORTER_OTLP_HEADERS` | Extra `key=value,...` request headers |
+//! | `OTEL_EXPORTER_OTLP_TIMEOUT` | Request timeout in milliseconds (default 10000) |
+//! | `OTEL_EXPORTER_OTLP_TRACES_PROTOCOL`, `OTEL_EXPORTER_OTLP_PROTOCOL` | Must be `http/json` when set |
+//! | `OTEL_SERVICE_NAME` | `service.name` resource attribute (default `first`) |
+//! | `OTEL_SDK_DISABLED` | `true` disables export |
+//!
+//! Nothing is exported unless one of the endpoint variables is set. Only
+//! plain `http://` endpoints are supported, e.g. a local collector.
+//!
+//! Export is best effort: spans are posted when the sweep ends, or when
+//! the orchestrator exits early on a failure, and an unreachable or
+//! failing endpoint only prints a warning. It never fails the test.
+//!
+//! Without the feature, [`Spans`] does nothing and costs nothing.
+
+use crate::report::Outcome;
+
+/// Spans of the crash points of one sweep.
+#[derive(Debug, Default)]
+pub(crate) struct Spans {
+    #[cfg(feature = "otel")]
+    inner: Option<export::Trace>,
+}
+
+#[cfg(not(feature = "otel"))]
+impl Spans {
+    pub(crate) fn new(_test_name: Option<&str>, _run_tag: Option<&str>) -> Self {
+        Self {}
+    }
+
+    pub(crate) fn begin(&mut self, _target: usize) {}
+
+    pub(crate) fn end(&mut self, _target: usize, _label: &str, _outcome: Outcome) {}
+
+    pub(crate) fn flush(&mut self) {}
+}
+
+#[cfg(feature = "otel")]
+impl Spans {
+    /// Start a trace for a sweep, if an endpoint is configured.
+    pub(crate) fn new(test_name: Option<&str>, run_tag: Option<&str>) -> Self {
+        Self {
+            inner: export::Trace::new(test_name, run_tag),
+        }
+    }
+
+    /// Note that crash point `target` started.
+    pub(crate) fn begin(&mut self, target: usize) {
+        if let Some(trace) = &mut self.inner {
+            trace.begin(target);
+        }
+    }
+
+    /// Record the span of crash point `target`, begun by [`Spans::begin()`].
+    pub(crate) fn end(&mut self, target: usize, label: &str, outcome: Outcome) {
+        if let Some(trace) = &mut self.inner {
+            trace.end(target, label, outcome);
+        }
+    }
+
+    /// Export the spans recorded so far.
+    pub(crate) fn flush(&mut self) {
+        if self.inner.is_some() {
+            export::flush();
+        }
+    }
+}
+
+#[cfg(feature = "otel")]
+mod export {
+    use std::io::{self, Read, Write};
+    use std::net::{TcpStream, ToSocketAddrs};
+    use std::sync::{Mutex, Once, OnceLock};
+    use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+    use crate::report::{Outcome, escape_json};
+
+    /// Timeout unless set by `OTEL_EXPORTER_OTLP_TIMEOUT`.
+    const DEFAULT_TIMEOUT: Duration = Duration::from_secs(10);
+
+    /// Where and how spans are posted.
+    #[derive(Debug, Clone, PartialEq, Eq)]
+    pub(super) struct Endpoint {
+        pub(super) host: String,
+        pub(super) port: u16,
+        pub(super) path: String,
+        pub(super) headers: Vec<(String, String)>,
+        pub(super) timeout: Duration,
+    }
+
+    /// The configured endpoint, resolved once.
+    static ENDPOINT: OnceLock<Option<Endpoint>> = OnceLock::new();
+
+    /// Rendered spans not exported yet.
+    static PENDING: Mutex<Vec<String>> = Mutex::new(Vec::new());
+
+    /// Registers the export at exit.
+    static AT_EXIT: Once = Once::new();
+
+    /// One sweep's trace.
+    #[derive(Debug)]
+    pub(super) struct Trace {
+        trace_id: String,
+        ids: u64,
+        test_name: String,
+        run_tag: Option<String>,
+        started: Option<(usize, u128)>,
+    }
+
+    impl Trace {
+        /// A trace for one sweep, or `None` if there is nowhere to send it.
+        pub(super) fn new(test_name: Option<&str>, run_tag: Option<&str>) -> Option<Self> {
+            ENDPOINT.get_or_init(endpoint).as_ref()?;
+            AT_EXIT.call_once(|| {
+                // SAFETY: registers a function without arguments; atexit
+                // has no other preconditions.
+                if unsafe { libc::atexit(flush_at_exit) } != 0 {
+                    eprintln!("[first] warning: cannot register the span export at exit");
+                }
+            });
+            let mut trace = Self {
+                trace_id: String::new(),
+                ids: now_nanos() as u64 ^ (u64::from(std::process::id()) << 32),
+                test_name: test_name
+                    .or(std::thread::current().name().filter(|n| *n != "main"))
+                    .unwrap_or("first")
+                    .to_string(),
+                run_tag: run_tag.map(str::to_string),
+                started: None,
+            };
+            trace.trace_id = format!("{:016x}{:016x}", trace.next_id(), trace.next_id());
+            Some(trace)
+        }
+
+        pub(super) fn begin(&mut self, target: usize) {
+            self.started = Some((target, now_nanos()));
+        }
+
+        pub(super) fn end(&mut self, target: usize, label: &str, outcome: Outcome) {
+            let end = now_nanos();
+            let start = match self.started.take() {
+                Some((started, start)) if started == target => start,
+                _ => end,
+            };
+            let span_id = format!("{:016x}", self.next_id());
+            let span = self.render(&span_id, target, label, outcome, start, end);
+            PENDING.lock().unwrap_or_else(|e| e.into_inner()).push(span);
+        }
+
+        /// Render one span in the OTLP JSON encoding.
+        pub(super) fn render(
+            &self,
+            span_id: &str,
+            target: usize,
+            label: &str,
+            outcome: Outcome,
+            start: u128,
+            end: u128,
+        ) -> String {
+            let (result, status) = match outcome {
+                Outcome::Ok => ("ok", r#"{"code":1}"#.to_string()),
+                Outcome::Failed(reason) => (
+                    "failed",
+                    format!(r#"{{"code":2,"message":"{}"}}"#, escape_json(reason)),
+                ),
+                Outcome::Skipped => ("skipped", r#"{"code":0}"#.to_string()),
+            };
+            let mut attributes = vec![
+                string_attribute("first.test", &self.test_name),
+                format!(
+                    r#"{{"key":"first.crash_point.id","value":{{"intValue":"{}"}}}}"#,
+                    target
+                ),
+                string_attribute("first.crash_point.label", label),
+                string_attribute("first.result", result),
+                format!(
+                    r#"{{"key":"first.duration_ms","value":{{"doubleValue":{}}}}}"#,
+                    (end - start) as f64 / 1e6
+                ),
+            ];
+            if let Outcome::Failed(reason) = outcome {
+                attributes.push(string_attribute("first.reason", reason));
+            }
+            if let Some(tag) = &self.run_tag {
+                attributes.push(string_attribute("first.run_tag", tag));
+            }
+            format!(
+                r#"{{"traceId":"{}","spanId":"{}","name":"crash_point {}","kind":1,"startTimeUnixNano":"{}","endTimeUnixNano":"{}","attributes":[{}],"status":{}}}"#,
+                self.trace_id,
+                span_id,
+                escape_json(label),
+                start,
+                end,
+                attributes.join(","),
+                status
+            )
+        }
+
+        /// Next span or trace id, from a SplitMix64 stream.
+        fn next_id(&mut self) -> u64 {
+            self.ids = self.ids.wrapping_add(0x9e37_79b9_7f4a_7c15);
+            let mut z = self.ids;
+            z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+            z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+            (z ^ (z >> 31)).max(1)
+        }
+    }
+
+    #[cfg(test)]
+    impl Trace {
+        /// A trace with fixed ids, without an endpoint.
+        pub(super) fn for_test(test_name: &str, run_tag: Option<&str>) -> Self {
+            Self {
+                trace_id: format!("{:016x}{:016x}", 1, 2),
+                ids: 0,
+                test_name: test_name.to_string(),
+                run_tag: run_tag.map(str::to_string),
+                started: None,
+            }
+        }
+    }
+
+    fn string_attribute(key: &str, value: &str) -> String {
+        format!(
+            r#"{{"key":"{}","value":{{"stringValue":"{}"}}}}"#,
+            key,
+            escape_json(value)
+        )
+    }
+
+    fn now_nanos() -> u128 {
+        SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map_or(0, |d| d.as_nanos())
+    }
+
+    /// Post the pending spans, warning on failure.
+    pub(super) fn flush() {
+        let spans = std::mem::take(&mut *PENDING.lock().unwrap_or_else(|e| e.into_inner()));
+        let Some(Some(endpoint)) = ENDPOINT.get() else {
+            return;
+        };
+        if spans.is_empty() {
+            return;
+        }
+        if let Err(e) = post(endpoint, &request_body(&spans)) {
+            eprintln!(
+                "[first] warning: cannot export {} crash point spans to {}:{}{}: {}",
+                spans.len(),
+                endpoint.host,
+                endpoint.port,
+                endpoint.path,
+                e
+            );
+        }
+    }
+
+    /// Export whatever an early exit left pending.
+    extern "C" fn flush_at_exit() {
+        flush();
+    }
+
+    /// Wrap rendered spans in an `ExportTraceServiceRequest`.
+    pub(super) fn request_body(spans: &[String]) -> String {
+        let service = std::env::var("OTEL_SERVICE_NAME")
+            .ok()
+            .filter(|s| !s.is_empty())
+            .unwrap_or_else(|| "first".to_string());
+        format!(
+            r#"{{"resourceSpans":[{{"resource":{{"attributes":[{}]}},"scopeSpans":[{{"scope":{{"name":"first","version":"{}"}},"spans":[{}]}}]}}]}}"#,
+            string_attribute("service.name", &service),
+            env!("CARGO_PKG_VERSION"),
+            spans.join(",")
+        )
+    }
+
+    /// The endpoint configured by the `OTEL_*` variables, warning about
+    /// settings that cannot be honoured.
+    fn endpoint() -> Option<Endpoint> {
+        let var = |name: &str| std::env::var(name).ok().filter(|v| !v.is_empty());
+        if var("OTEL_SDK_DISABLED").is_some_and(|v| v.eq_ignore_ascii_case("true")) {
+            return None;
+        }
+        let url = var("OTEL_EXPORTER_OTLP_TRACES_ENDPOINT").or_else(|| {
+            var("OTEL_EXPORTER_OTLP_ENDPOINT")
+                .map(|base| format!("{}/v1/traces", base.trim_end_matches('/')))
+        })?;
+        let protocol = var("OTEL_EXPORTER_OTLP_TRACES_PROTOCOL")
+            .or_else(|| var("OTEL_EXPORTER_OTLP_PROTOCOL"));
+        if let Some(protocol) = protocol.filter(|p| p != "http/json") {
+            eprintln!(
+                "[first] warning: OTLP protocol {} is not supported, only http/json; crash point spans are not exported",
+                protocol
+            );
+            return None;
+        }
+        let timeout = var("OTEL_EXPORTER_OTLP_TIMEOUT")
+            .and_then(|ms| ms.parse().ok())
+            .map_or(DEFAULT_TIMEOUT, Duration::from_millis);
+        let headers = var("OTEL_EXPORTER_OTLP_TRACES_HEADERS")
+            .or_else(|| var("OTEL_EXPORTER_OTLP_HEADERS"))
+            .map(|h| parse_headers(&h))
+            .unwrap_or_default();
+        match parse_url(&url) {
+            Some((host, port, path)) => Some(Endpoint {
+                host,
+                port,
+                path,
+                headers,
+                timeout,
+            }),
+            None => {
+                eprintln!(
+                    "[first] warning: OTLP endpoint {} is not a plain http:// URL; crash point spans are not exported",
+                    url
+                );
+                None
+            }
+        }
+    }
+
+    /// Split an `http://host[:port][/path]` URL.
+    pub(super) fn parse_url(url: &str) -> Option<(String, u16, String)> {
+        let rest = url.strip_prefix("http://")?;
+        let (authority, path) = match rest.find('/') {
+            Some(i) => (&rest[..i], &rest[i..]),
+            None => (rest, "/"),
+        };
+        let (host, port) = match authority.rsplit_once(':') {
+            Some((host, port)) if !port.contains(']') => (host, port.parse().ok()?),
+            _ => (authority, 80),
+        };
+        let host = host.trim_start_matches('[').trim_end_matches(']');
+        (!host.is_empty()).then(|| (host.to_string(), port, path.to_string()))
+    }
+
+    /// Parse `key=value,...` headers, ignoring malformed entries.
+    pub(super) fn parse_headers(headers: &str) -> Vec<(String, String)> {
+        headers
+            .split(',')
+            .filter_map(|h| h.split_once('='))
+            .map(|(k, v)| (k.trim().to_string(), v.trim().to_string()))
+            .filter(|(k, _)| !k.is_empty())
+            .collect()
+    }
+
+    /// POST `body` to `endpoint` and check for a 2xx status.
+    fn post(endpoint: &Endpoint, body: &str) -> io::Result<()> {
+        let addr = (endpoint.host.as_str(), endpoint.port)
+            .to_socket_addrs()?
+            .next()
+            .ok_or_else(|| io::Error::other("no address"))?;
+        let mut stream = TcpStream::connect_timeout(&addr, endpoint.timeout)?;
+        stream.set_read_timeout(Some(endpoint.timeout))?;
+        stream.set_write_timeout(Some(endpoint.timeout))?;
+
+        let mut request = format!(
+            "POST {} HTTP/1.1\r\nHost: {}:{}\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n",
+            endpoint.path,
+            endpoint.host,
+            endpoint.port,
+            body.len()
+        );
+        for (key, value) in &endpoint.headers {
+            request.push_str(&format!("{}: {}\r\n", key, value));
+        }
+        request.push_str("\r\n");
+        stream.write_all(request.as_bytes())?;
+        stream.write_all(body.as_bytes())?;
+
+        let mut response = Vec::new();
+        stream.read_to_end(&mut response)?;
+        let status = String::from_utf8_lossy(&response)
+            .split_whitespace()
+            .nth(1)
+            .and_then(|code| code.parse::<u16>().ok());
+        match status {
+            Some(200..=299) => Ok(()),
+            Some(code) => Err(io::Error::other(format!("HTTP status {}", code))),
+            None => Err(io::Error::other("malformed HTTP response")),
+        }
+    }
+}
+
+#[cfg(all(test, feature = "otel"))]
+mod tests {
+    use super::export::*;
+    use super::*;
+
+    #[test]
+    fn test_parse_url() {
+        assert_eq!(
+            parse_url("http://localhost:4318/v1/traces"),
+            Some(("localhost".to_string(), 4318, "/v1/traces".to_string()))
+        );
+        assert_eq!(
+            parse_url("http://[::1]:4318/v1/traces"),
+            Some(("::1".to_string(), 4318, "/v1/traces".to_string()))
+        );
+        assert_eq!(
+            parse_url("http://collector"),
+            Some(("collector".to_string(), 80, "/".to_string()))
+        );
+        assert_eq!(parse_url("https://collector:4318/v1/traces"), None);
+    }
+
+    #[test]
+    fn test_parse_headers() {
+        assert_eq!(
+            parse_headers("api-key=secret, x-team = storage,bogus"),
+            vec![
+                ("api-key".to_string(), "secret".to_string()),
+                ("x-team".to_string(), "storage".to_string()),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_render_span() {
+        let trace = Trace::for_test("wal", Some("pr-42"));
+        let span = trace.render(
+            "00000000000000aa",
+            3,
+            "after_commit",
+            Outcome::Failed("lost \"a\""),
+            1_000,
+            2_501_000,
+        );
+        assert!(span.starts_with(r#"{"traceId":"00000000000000010000000000000002","#));
+        assert!(span.contains(r#""spanId":"00000000000000aa","name":"crash_point after_commit""#));
+        assert!(span.contains(r#""startTimeUnixNano":"1000","endTimeUnixNano":"2501000""#));
+        assert!(span.contains(r#"{"key":"first.crash_point.id","value":{"intValue":"3"}}"#));
+        assert!(span.contains(r#"{"key":"first.result","value":{"stringValue":"failed"}}"#));
+        assert!(span.contains(r#"{"key":"first.duration_ms","value":{"doubleValue":2.5}}"#));
+        assert!(span.contains(r#"{"key":"first.run_tag","value":{"stringValue":"pr-42"}}"#));
+        assert!(span.ends_with(r#""status":{"code":2,"message":"lost \"a\""}}"#));
+
+        let body = request_body(&[span]);
+        assert!(
+            body.starts_with(
+                r#"{"resourceSpans":[{"resource":{"attributes":[{"key":"service.name""#
+            )
+        );
+    }
+}
@@ -0,0 +1,161 @@
+//! Concurrent readers.
+//!
+//! With `TestBuilder::reader()`, the orchestrator starts a READ child next
+//! to every EXECUTION child, in the same workspace. The reader runs its
+//! closure over and over while the workload runs, and fails the crash
+//! point if any read before the crash saw an inconsistent state.
+//!
+//! # Stopping
+//!
+//! The writer creates a stop marker in the metadata dir immediately before
+//! it crashes (before any crash effects touch the workspace); the
+//! orchestrator creates it when the writer exits on its own. The reader
+//! finishes its current pass and exits. A pass that panics once the marker
+//! exists is ignored, since it may have read state left by the crash
+//! itself rather than by the running workload.
+
+use std::fs;
+use std::io::Write;
+use std::panic::{self, AssertUnwindSafe};
+use std::path::{Path, PathBuf};
+use std::process::{Child, Command};
+use std::sync::Mutex;
+
+use crate::env::Env;
+
+/// Path of the stop marker, passed to READ children.
+const ENV_READER_STOP: &str = "FIRST_READER_STOP";
+
+/// Stop marker for crash point `target`.
+///
+/// Derived from variables every EXECUTION child already has, so the writer
+/// can find it without extra plumbing.
+fn stop_path(metadata_dir: &Path, target: usize) -> PathBuf {
+    metadata_dir.join(format!(".reader_stop_{}", target))
+}
+
+/// Start the READ child for crash point `target`.
+pub(crate) fn spawn(
+    exe: &Path,
+    test_name: &Option<String>,
+    target: usize,
+    work_dir: &Path,
+    metadata_dir: &Path,
+) -> Option<Child> {
+    let stop = stop_path(metadata_dir, target);
+    let _ = fs::remove_file(&stop);
+
+    let mut cmd = Command::new(exe);
+    cmd.env("FIRST_PHASE", "READ");
+    if let Some(seed) = crate::rt::run_seed() {
+        cmd.env(crate::rt::ENV_SEED, seed.to_string());
+    }
+    cmd.env(
+        crate::invocation::ENV_INVOCATION,
+        crate::invocation::env_value(),
+    );
+    cmd.env("FIRST_CRASH_TARGET", target.to_string());
+    cmd.env("FIRST_WORK_DIR", work_dir);
+    cmd.env("FIRST_METADATA_DIR", metadata_dir);
+    cmd.env(ENV_READER_STOP, &stop);
+    crate::orchestrator::harness_args(&mut cmd, test_name);
+    cmd.stdout(crate::orchestrator::child_stdout());
+
+    match crate::orchestrator::spawn_with_retry(&mut cmd) {
+        Ok(child) => Some(child),
+        Err(e) => {
+            eprintln!("[first] error: cannot spawn reader: {}", e);
+            None
+        }
+    }
+}
+
+/// Stop the READ child once the writer has exited, and return why the
+/// crash point fails, if the reader saw an inconsistent state.
+pub(crate) fn finish(reader: Option<Child>, metadata_dir: &Path, target: usize) -> Option<String> {
+    let Some(mut reader) = reader else {
+        return Some("concurrent reader could not be started".to_string());
+    };
+    let stop = stop_path(metadata_dir, target);
+    if let Err(e) = fs::write(&stop, b"") {
+        eprintln!("[first] error: cannot stop reader: {}", e);
+        let _ = reader.kill();
+    }
+    let status = reader.wait();
+    let _ = fs::remove_file(&stop);
+    match status {
+        Ok(status) if status.success() => None,
+        Ok(status) => Some(format!(
+            "concurrent reader observed an inconsistent state before the crash (exit code {})",
+            status.code().unwrap_or(-1)
+        )),
+        Err(e) => Some(format!("cannot wait for reader: {}", e)),
+    }
+}
+
+/// Signal the READ child of this EXECUTION child that the crash begins.
+pub(crate) fn signal_crash() {
+    let (Ok(metadata_dir), Ok(target)) = (
+        std::env::var("FIRST_METADATA_DIR"),
+        std::env::var("FIRST_CRASH_TARGET"),
+    ) else {
+        return;
+    };
+    if let Ok(target) = target.parse() {
+        let _ = fs::write(stop_path(Path::new(&metadata_dir), target), b"");
+    }
+}
+
+/// Most recent panic message of a reader pass.
+static LAST_PANIC: Mutex<Option<String>> = Mutex::new(None);
+
+/// Run reader passes until the stop marker appears.
+///
+/// Panic output is held back until a pass is known to have failed before
+/// the crash, so ignored passes stay quiet.
+pub(crate) fn run(env: &Env, mut pass: impl FnMut(&Env)) {
+    let Some(stop) = std::env::var_os(ENV_READER_STOP).map(PathBuf::from) else {
+        return;
+    };
+
+    let default_hook = panic::take_hook();
+    panic::set_hook(Box::new(|info| {
+        let location = info
+            .location()
+            .map(|l| format!(" at {}:{}", l.file(), l.line()))
+            .unwrap_or_default();
+        let message = info
+            .payload()
+            .downcast_ref::<&str>()
+            .map(|s| s.to_string())
+            .or_else(|| info.payload().downcast_ref::<String>().cloned())
+            .unwrap_or_else(|| "reader panicked".to_string());
+        if let Ok(mut last) = LAST_PANIC.lock() {
+            *last = Some(format!("{}{}", message, location));
+        }
+    }));
+
+    let mut passes: usize = 0;
+    loop {
+        let result = panic::catch_unwind(AssertUnwindSafe(|| pass(env)));
+        passes += 1;
+        if stop.exists() {
+            break;
+        }
+        if let Err(payload) = result {
+            let message = LAST_PANIC.lock().ok().and_then(|mut l| l.take());
+            // Raw stderr: eprintln! is captured by libtest in the child.
+            let _ = writeln!(
+                std::io::stderr().lock(),
+                "[first] reader pass {} at crash point {} failed before the crash: {}",
+                passes,
+                std::env::var("FIRST_CRASH_TARGET").unwrap_or_default(),
+                message.unwrap_or_default()
+            );
+            panic::set_hook(default_hook);
+            panic::resume_unwind(payload);
+        }
+        std::thread::yield_now();
+    }
+    panic::set_hook(default_hook);
+}
@@ -0,0 +1,107 @@
+//! Recovery time measurement.
+//!
+//! Times the user's recovery work in the VERIFY phase, so slow recovery at
+//! a crash point (e.g. quadratic log replay) fails the test like an
+//! incorrect one does.
+
+use std::io::Write;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+use crate::env::CrashInfo;
+
+/// Time measured by [`RecoveryTimer`]s during the current verify closure.
+static TIMED: Mutex<Option<Duration>> = Mutex::new(None);
+
+/// Times a region of a verify closure as the recovery time.
+///
+/// Created by [`recovery_timer()`]; the region ends when the timer is
+/// dropped.
+#[must_use = "the region ends when the timer is dropped"]
+pub struct RecoveryTimer {
+    start: Instant,
+}
+
+impl Drop for RecoveryTimer {
+    fn drop(&mut self) {
+        let elapsed = self.start.elapsed();
+        let mut timed = TIMED.lock().unwrap_or_else(|e| e.into_inner());
+        *timed = Some(timed.unwrap_or_default() + elapsed);
+    }
+}
+
+/// Start timing recovery inside a verify closure.
+///
+/// By default `TestBuilder::max_recovery_time()` measures the whole verify
+/// closure, including the checks after recovery. Hold a timer around the
+/// recovery itself to measure only that; if several timers are used, their
+/// durations are added up.
+///
+/// ```ignore
+/// .verify(|env, _| {
+///     let db = {
+///         let _timer = first::recovery_timer();
+///         Db::open(env.path("db"))
+///     };
+///     assert!(db.is_consistent());
+/// })
+/// ```
+pub fn recovery_timer() -> RecoveryTimer {
+    RecoveryTimer {
+        start: Instant::now(),
+    }
+}
+
+/// Run `f` and return the recovery time it took: the time covered by
+/// recovery timers if any were used, otherwise the whole call.
+pub(crate) fn time(f: impl FnOnce()) -> Duration {
+    TIMED.lock().unwrap_or_else(|e| e.into_inner()).take();
+    let start = Instant::now();
+    f();
+    let total = start.elapsed();
+    TIMED
+        .lock()
+        .unwrap_or_else(|e| e.into_inner())
+        .take()
+        .unwrap_or(total)
+}
+
+/// Report the recovery time of a crash point, failing the VERIFY phase if
+/// it exceeds `limit`.
+pub(crate) fn check(crash_info: &CrashInfo, elapsed: Duration, limit: Option<Duration>) {
+    // Raw stderr: eprintln! is captured by libtest in the child.
+    if crate::orchestrator::verbose() {
+        let _ = writeln!(
+            std::io::stderr().lock(),
+            "[first] crash point {} (\"{}\"): recovery took {:?}",
+            crash_info.point_id,
+            crash_info.label,
+            elapsed
+        );
+    }
+    if let Some(limit) = limit.filter(|&limit| elapsed > limit) {
+        let message = format!(
+            "recovery at crash point {} (\"{}\") took {:?}, exceeding max_recovery_time of {:?}",
+            crash_info.point_id, crash_info.label, elapsed, limit
+        );
+        let _ = writeln!(std::io::stderr().lock(), "[first] {}", message);
+        panic!("{}", message);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_time_prefers_timers() {
+        let elapsed = time(|| {
+            std::thread::sleep(Duration::from_millis(30));
+            let _timer = recovery_timer();
+        });
+        assert!(elapsed < Duration::from_millis(30));
+
+        let elapsed = time(|| std::thread::sleep(Duration::from_millis(5)));
+        assert!(elapsed >= Duration::from_millis(5));
+    }
+}
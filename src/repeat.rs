@@ -0,0 +1,189 @@
+//! Repeated crashes at one point.
+//!
+//! With `TestBuilder::repeat_point()`, the orchestrator does not sweep.
+//! It crashes the workload at one crash point several times, each in a
+//! fresh workspace and metadata directory, and compares every run with
+//! the first: the workspace left behind, file by file, and the crash
+//! metadata. Any difference means the state at that point is not
+//! deterministic, e.g. a background thread racing the kill.
+
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use crate::discover;
+use crate::env::CrashInfo;
+use crate::idempotence::first_difference;
+use crate::orchestrator::{self, ChildResult, FIRST_BASE_DIR, cleanup_work_dir, io_failure};
+use crate::test::{Options, PointSelector};
+
+/// Crash at `point` `times` times and report any variance between runs.
+///
+/// Exits the process with status 1 if the runs differ or one of them
+/// does not crash.
+pub(crate) fn run(
+    exe: &Path,
+    test_name: &Option<String>,
+    point: &PointSelector,
+    times: usize,
+    options: &Options,
+) {
+    let base = PathBuf::from(FIRST_BASE_DIR).join("repeat");
+    cleanup_work_dir(&base);
+
+    let target = match point {
+        PointSelector::Id(id) => *id,
+        PointSelector::Label(label) => {
+            match discover::discover(exe, test_name, Path::new(FIRST_BASE_DIR), options)
+                .and_then(|points| points.into_iter().find(|p| p.label == *label))
+            {
+                Some(point) => point.point_id,
+                None => {
+                    eprintln!(
+                        "[first] error: cannot resolve crash point \"{}\" to repeat",
+                        label
+                    );
+                    std::process::exit(1);
+                }
+            }
+        }
+    };
+    eprintln!("[first] repeating crash point {} {} times", target, times);
+
+    // Run 1, which every other run is compared with
+    let mut reference: Option<(PathBuf, CrashInfo)> = None;
+    let mut variances: Vec<String> = Vec::new();
+    for run in 1..=times {
+        let run_dir = base.join(format!("run_{}", run));
+        let work_dir = run_dir.join("workspace");
+        let metadata_dir = run_dir.join("meta");
+        let created = orchestrator::create_work_dir(&work_dir, options)
+            .map_err(|e| io_failure("create", &work_dir, &e))
+            .and_then(|()| {
+                fs::create_dir_all(&metadata_dir)
+                    .map_err(|e| io_failure("create", &metadata_dir, &e))
+            });
+        if let Err(e) = created {
+            eprintln!("[first] error: {}", e);
+            std::process::exit(1);
+        }
+
+        let crash_info = match orchestrator::spawn_child(
+            exe,
+            test_name,
+            "EXECUTION",
+            target,
+            None,
+            &work_dir,
+            &metadata_dir,
+            options.coverage_dir.as_deref(),
+            options.resource_limits.as_ref(),
+        ) {
+            ChildResult::Crashed(info) => info,
+            result => {
+                let outcome = match result {
+                    ChildResult::Success => "completed without reaching it".to_string(),
+                    ChildResult::Skipped(_) => "skipped it (label disabled)".to_string(),
+                    ChildResult::Failed(code) => format!("failed with exit code {}", code),
+                    ChildResult::TimedOut(limit) => format!("timed out after {:?}", limit),
+                    ChildResult::Crashed(_) => unreachable!(),
+                };
+                eprintln!(
+                    "[first] repeat run {} of crash point {}: FAILED (the workload {}; see {})",
+                    run,
+                    target,
+                    outcome,
+                    work_dir.display()
+                );
+                std::process::exit(1);
+            }
+        };
+
+        let Some((first_dir, first_info)) = &reference else {
+            reference = Some((work_dir, crash_info));
+            continue;
+        };
+        let mut differences = crash_info_differences(first_info, &crash_info);
+        match first_difference(first_dir, &work_dir) {
+            Ok(None) => {}
+            Ok(Some(difference)) => differences.push(format!("workspace: {}", difference)),
+            Err(e) => differences.push(format!("cannot compare workspaces: {}", e)),
+        }
+        if differences.is_empty() {
+            cleanup_work_dir(&run_dir);
+        } else {
+            variances.push(format!(
+                "run {} differs from run 1: {} (see {})",
+                run,
+                differences.join("; "),
+                run_dir.display()
+            ));
+        }
+    }
+
+    let label = reference
+        .as_ref()
+        .map_or("", |(_, info)| info.label.as_str());
+    if variances.is_empty() {
+        eprintln!(
+            "[first] crash point {} (\"{}\"): all {} runs identical{}",
+            target,
+            label,
+            times,
+            crate::report::tag_suffix(crate::report::run_tag(options).as_deref())
+        );
+        cleanup_work_dir(&base);
+        return;
+    }
+    eprintln!(
+        "[first] crash point {} (\"{}\"): FAILED, {} of {} runs differ from run 1 (nondeterministic crash state)",
+        target,
+        label,
+        variances.len(),
+        times
+    );
+    for variance in &variances {
+        eprintln!("[first]   {}", variance);
+    }
+    std::process::exit(1);
+}
+
+/// The crash metadata fields that differ between `expected` and `actual`.
+fn crash_info_differences(expected: &CrashInfo, actual: &CrashInfo) -> Vec<String> {
+    let fields = |info: &CrashInfo| {
+        [
+            ("label", format!("{:?}", info.label)),
+            ("site", format!("{:?}", info.site)),
+            ("fsync_count", info.fsync_count.to_string()),
+            ("eintr_count", info.eintr_count.to_string()),
+            ("max_fds", format!("{:?}", info.max_fds)),
+            ("partial_write", format!("{:?}", info.partial_write)),
+            ("dir_fsync_fault", format!("{:?}", info.dir_fsync_fault)),
+            ("barriers", format!("{:?}", info.barriers)),
+            ("partial_flush", format!("{:?}", info.partial_flush)),
+        ]
+    };
+    fields(expected)
+        .into_iter()
+        .zip(fields(actual))
+        .filter(|((_, e), (_, a))| e != a)
+        .map(|((name, e), (_, a))| format!("{} {} vs {}", name, a, e))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_crash_info_differences() {
+        let first = CrashInfo::new(3, "after_flush".to_string());
+        let mut other = first.clone();
+        assert!(crash_info_differences(&first, &other).is_empty());
+
+        other.fsync_count = 2;
+        assert_eq!(
+            crash_info_differences(&first, &other),
+            vec!["fsync_count 2 vs 0".to_string()]
+        );
+    }
+}
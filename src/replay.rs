@@ -0,0 +1,246 @@
+//! Crash script replay.
+//!
+//! Replays an ordered sequence of crashes against a single workspace:
+//! crash, recover, crash again, recover... This reproduces bugs that only
+//! appear after several successive crashes.
+//!
+//! # Script format
+//!
+//! A plain text file with one step per line. Blank lines and lines starting
+//! with `#` are ignored. Each step is either a numeric crash point ID or a
+//! crash point label (the first occurrence of that label is targeted):
+//!
+//! ```text
+//! # crash during the first commit, then again while re-committing
+//! 3
+//! after_commit_write
+//! ```
+
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+use crate::discover::{self, DiscoveredPoint};
+use crate::orchestrator::{self, ChildResult, FIRST_BASE_DIR};
+use crate::test::Options;
+
+/// One step of a crash script.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) enum ScriptStep {
+    /// Crash at this 1-indexed crash point ID.
+    Point(usize),
+    /// Crash at the first crash point with this label.
+    Label(String),
+}
+
+/// Parse a crash script.
+pub(crate) fn parse_script(contents: &str) -> Vec<ScriptStep> {
+    contents
+        .lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty() && !line.starts_with('#'))
+        .map(|line| match line.parse() {
+            Ok(id) => ScriptStep::Point(id),
+            Err(_) => ScriptStep::Label(line.to_string()),
+        })
+        .collect()
+}
+
+/// Read and parse a crash script file.
+pub(crate) fn load_script(path: &Path) -> io::Result<Vec<ScriptStep>> {
+    Ok(parse_script(&fs::read_to_string(path)?))
+}
+
+/// Replay `steps` in one workspace, verifying after every crash.
+///
+/// `source` names where the steps came from, for the progress message.
+/// Exits the process with status 1 on the first failing step.
+pub(crate) fn run(
+    exe: &Path,
+    test_name: &Option<String>,
+    metadata_dir: &Path,
+    source: &str,
+    steps: &[ScriptStep],
+    options: &Options,
+) {
+    let work_dir = PathBuf::from(FIRST_BASE_DIR).join("replay");
+    let _ = fs::remove_dir_all(&work_dir);
+    if let Err(e) = orchestrator::create_work_dir(&work_dir, options) {
+        eprintln!(
+            "[first] error: {}",
+            orchestrator::io_failure("create", &work_dir, &e)
+        );
+        std::process::exit(1);
+    }
+
+    // Labels are resolved against a discovery run on a fresh workspace.
+    let discovered = if steps.iter().any(|s| matches!(s, ScriptStep::Label(_))) {
+        discover::discover(exe, test_name, Path::new(FIRST_BASE_DIR), options)
+    } else {
+        None
+    };
+
+    eprintln!("[first] replaying {} crashes from {}", steps.len(), source);
+
+    let mut history = Vec::new();
+    for (i, step) in steps.iter().enumerate() {
+        let step_no = i + 1;
+        let Some(target) = resolve(step, discovered.as_deref()) else {
+            eprintln!(
+                "[first] replay step {}: FAILED (cannot resolve {:?})",
+                step_no, step
+            );
+            std::process::exit(1);
+        };
+        history.push(target);
+
+        let mut crash_info = match orchestrator::spawn_child(
+            exe,
+            test_name,
+            "EXECUTION",
+            target,
+            None,
+            &work_dir,
+            metadata_dir,
+            options.coverage_dir.as_deref(),
+            options.resource_limits.as_ref(),
+        ) {
+            ChildResult::Crashed(info) => info,
+            ChildResult::Success => {
+                eprintln!(
+                    "[first] replay step {}: FAILED (crash point {} was never reached)",
+                    step_no, target
+                );
+                std::process::exit(1);
+            }
+            ChildResult::Skipped(info) => {
+                eprintln!(
+                    "[first] replay step {}: FAILED (crash point {} (\"{}\") is disabled)",
+                    step_no, target, info.label
+                );
+                std::process::exit(1);
+            }
+            ChildResult::Failed(code) => {
+                eprintln!(
+                    "[first] replay step {}: FAILED (execution failed with exit code {})",
+                    step_no, code
+                );
+                std::process::exit(1);
+            }
+            ChildResult::TimedOut(limit) => {
+                eprintln!(
+                    "[first] replay step {}: FAILED (workload timed out after {:?}; see {})",
+                    step_no,
+                    limit,
+                    work_dir.display()
+                );
+                std::process::exit(1);
+            }
+        };
+
+        crash_info.history = history.clone();
+
+        if options.drop_caches {
+            orchestrator::evict_work_dir(&work_dir);
+        }
+
+        let reason = match orchestrator::spawn_child_with_crash_info(
+            exe,
+            test_name,
+            target,
+            &work_dir,
+            metadata_dir,
+            &crash_info,
+            None,
+            options.coverage_dir.as_deref(),
+            options.resource_limits.as_ref(),
+            None,
+        ) {
+            ChildResult::Success => None,
+            ChildResult::Failed(code) => {
+                Some(format!("verification failed with exit code {}", code))
+            }
+            ChildResult::Crashed(_) | ChildResult::Skipped(_) => {
+                Some("verify phase crashed unexpectedly".to_string())
+            }
+            ChildResult::TimedOut(limit) => {
+                Some(format!("verify phase timed out after {:?}", limit))
+            }
+        };
+
+        if let Some(reason) = reason {
+            eprintln!(
+                "[first] replay step {}: FAILED (see {})",
+                step_no,
+                work_dir.display()
+            );
+            eprintln!("[first] crash label: \"{}\"", crash_info.label);
+            eprintln!("[first] crash sequence: {:?}", history);
+            eprintln!("[first] reason: {}", reason);
+            crate::verify_log::print_tail(&work_dir);
+            std::process::exit(1);
+        }
+
+        eprintln!(
+            "[first] replay step {}: crash point {} (\"{}\") OK",
+            step_no, target, crash_info.label
+        );
+    }
+
+    eprintln!(
+        "[first] replay of {} crashes passed{}",
+        steps.len(),
+        crate::report::tag_suffix(crate::report::run_tag(options).as_deref())
+    );
+    if std::env::var("FIRST_KEEP_ARTIFACTS").is_err() {
+        orchestrator::cleanup_work_dir(&work_dir);
+    }
+}
+
+/// Resolve a step to a numeric crash point ID.
+fn resolve(step: &ScriptStep, discovered: Option<&[DiscoveredPoint]>) -> Option<usize> {
+    match step {
+        ScriptStep::Point(id) => Some(*id),
+        ScriptStep::Label(label) => discovered?
+            .iter()
+            .find(|p| p.label == *label)
+            .map(|p| p.point_id),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_script() {
+        let script = "# header\n3\n\n  after_commit  \n5\n";
+        assert_eq!(
+            parse_script(script),
+            vec![
+                ScriptStep::Point(3),
+                ScriptStep::Label("after_commit".to_string()),
+                ScriptStep::Point(5),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_resolve_label() {
+        let points = vec![
+            DiscoveredPoint {
+                point_id: 1,
+                label: "a".to_string(),
+                site: None,
+            },
+            DiscoveredPoint {
+                point_id: 2,
+                label: "b".to_string(),
+                site: None,
+            },
+        ];
+        let step = ScriptStep::Label("b".to_string());
+        assert_eq!(resolve(&step, Some(&points)), Some(2));
+        assert_eq!(resolve(&step, None), None);
+    }
+}
@@ -0,0 +1,417 @@
+//! Machine-readable result output.
+//!
+//! Emits per-crash-point results in formats consumed by external tooling.
+
+use std::fs;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::time::Instant;
+
+use crate::test::Options;
+
+/// Verdict of a crash point, for reports and spans.
+#[derive(Debug, Clone, Copy)]
+pub(crate) enum Outcome<'a> {
+    Ok,
+    Failed(&'a str),
+    Skipped,
+}
+
+/// Emitter for supplementary libtest JSON events.
+///
+/// When the harness runs with `--format json`, each crash point is reported
+/// as a synthetic test named `<test_name>::crash_point_<N>`, using the same
+/// event schema libtest uses for real tests.
+pub(crate) struct LibtestJson {
+    enabled: bool,
+    test_name: String,
+    run_tag: Option<String>,
+}
+
+impl LibtestJson {
+    /// Create an emitter. Events are only written when `enabled` is set.
+    ///
+    /// Without an explicit test name, falls back to the harness thread
+    /// name, which libtest sets to the name of the running test.
+    ///
+    /// Every event carries `run_tag`, when set, as a `"run_tag"` field.
+    pub(crate) fn new(enabled: bool, test_name: Option<&str>, run_tag: Option<&str>) -> Self {
+        let thread = std::thread::current();
+        let test_name = test_name
+            .or(thread.name().filter(|n| *n != "main"))
+            .unwrap_or("first")
+            .to_string();
+        Self {
+            enabled,
+            test_name,
+            run_tag: run_tag.map(str::to_string),
+        }
+    }
+
+    /// Report that crash point `target` started.
+    pub(crate) fn started(&self, target: usize) {
+        self.emit(target, "started", None);
+    }
+
+    /// Report that crash point `target` passed verification.
+    pub(crate) fn ok(&self, target: usize) {
+        self.emit(target, "ok", None);
+    }
+
+    /// Report that crash point `target` was skipped.
+    pub(crate) fn ignored(&self, target: usize) {
+        self.emit(target, "ignored", None);
+    }
+
+    /// Report that crash point `target` failed with the given reason.
+    pub(crate) fn failed(&self, target: usize, reason: &str) {
+        self.emit(target, "failed", Some(reason));
+    }
+
+    fn emit(&self, target: usize, event: &str, stdout: Option<&str>) {
+        if !self.enabled {
+            return;
+        }
+        let line = self.event_line(target, event, stdout);
+
+        // Write straight to the stdout handle: libtest's output capture only
+        // intercepts the print! family, and these events belong in the same
+        // stream as libtest's own JSON.
+        let mut out = std::io::stdout().lock();
+        let _ = out.write_all(line.as_bytes());
+        let _ = out.flush();
+    }
+
+    /// Render one event as a JSON line.
+    fn event_line(&self, target: usize, event: &str, stdout: Option<&str>) -> String {
+        let name = format!("{}::crash_point_{}", self.test_name, target);
+        let mut line = format!(
+            r#"{{ "type": "test", "event": "{}", "name": "{}""#,
+            event,
+            escape_json(&name)
+        );
+        if let Some(stdout) = stdout {
+            line.push_str(&format!(r#", "stdout": "{}""#, escape_json(stdout)));
+        }
+        if let Some(tag) = &self.run_tag {
+            line.push_str(&format!(r#", "run_tag": "{}""#, escape_json(tag)));
+        }
+        line.push_str(" }\n");
+        line
+    }
+}
+
+/// Environment variable naming the JSON report file, overriding
+/// `TestBuilder::report_json()`.
+pub(crate) const ENV_REPORT_JSON: &str = "FIRST_REPORT_JSON";
+
+/// Version of the JSON report format, bumped on incompatible changes.
+const REPORT_VERSION: u32 = 1;
+
+/// Verdicts of the crash points of a sweep, and its JSON report,
+/// rewritten after every crash point.
+///
+/// Each write replaces the file atomically, so it always holds a complete
+/// JSON document; `"complete": false` marks a sweep that has not finished,
+/// e.g. because the orchestrator itself was killed.
+pub(crate) struct RunReport {
+    path: Option<PathBuf>,
+    /// Metadata directory of the sweep, holding its label files.
+    metadata_dir: PathBuf,
+    test_name: Option<String>,
+    run_tag: Option<String>,
+    seed: Option<u64>,
+    started: Option<(usize, Instant)>,
+    /// Rendered JSON entries, only kept with a report path.
+    points: Vec<String>,
+    /// ID, label and result of every crash point, for the summary table.
+    verdicts: Vec<(usize, String, &'static str)>,
+}
+
+impl RunReport {
+    /// Start the report of a sweep, if a path is configured, and write it
+    /// with no crash points yet.
+    pub(crate) fn new(
+        options: &Options,
+        test_name: Option<&str>,
+        run_tag: Option<&str>,
+        metadata_dir: &Path,
+    ) -> Self {
+        let path = std::env::var(ENV_REPORT_JSON)
+            .ok()
+            .filter(|p| !p.is_empty())
+            .map(PathBuf::from)
+            .or_else(|| options.report_json.clone());
+        let report = Self {
+            path,
+            metadata_dir: metadata_dir.to_path_buf(),
+            test_name: test_name.map(str::to_string),
+            run_tag: run_tag.map(str::to_string),
+            seed: crate::rt::run_seed(),
+            started: None,
+            points: Vec::new(),
+            verdicts: Vec::new(),
+        };
+        report.write(false);
+        report
+    }
+
+    /// Note that crash point `target` started.
+    pub(crate) fn begin(&mut self, target: usize) {
+        self.started = Some((target, Instant::now()));
+    }
+
+    /// Record the verdict of crash point `target`, begun by
+    /// [`RunReport::begin()`], and rewrite the report.
+    pub(crate) fn end(&mut self, target: usize, label: &str, work_dir: &Path, outcome: Outcome) {
+        self.verdicts
+            .push((target, label.to_string(), result_name(outcome)));
+        if self.path.is_none() {
+            return;
+        }
+        let duration = match self.started.take() {
+            Some((started, at)) if started == target => at.elapsed(),
+            _ => Default::default(),
+        };
+        self.points.push(render_point(
+            target,
+            label,
+            work_dir,
+            outcome,
+            duration.as_millis(),
+        ));
+        self.write(false);
+    }
+
+    /// Print a table of every crash point recorded and its result.
+    pub(crate) fn print_table(&self) {
+        eprintln!("[first] crash point results:");
+        for line in self.table() {
+            eprintln!("[first]   {}", line);
+        }
+    }
+
+    /// Lines of the result table, aligned on the widest ID.
+    fn table(&self) -> Vec<String> {
+        let width = self
+            .verdicts
+            .iter()
+            .map(|(id, _, _)| id.to_string().len())
+            .max()
+            .unwrap_or(0)
+            .max(2);
+        let mut lines = vec![format!("{:>width$}  {:<7}  label", "id", "result")];
+        lines.extend(
+            self.verdicts
+                .iter()
+                .map(|(id, label, result)| format!("{:>width$}  {:<7}  {}", id, result, label)),
+        );
+        lines
+    }
+
+    /// Mark the sweep as finished, and write its label coverage report.
+    pub(crate) fn finish(&self) {
+        self.write(true);
+        crate::labels::write_report(&self.metadata_dir, &self.verdicts);
+    }
+
+    /// Replace the report file with the current state.
+    fn write(&self, complete: bool) {
+        let Some(path) = &self.path else {
+            return;
+        };
+        let json = self.render(complete);
+        let tmp = PathBuf::from(format!("{}.tmp", path.display()));
+        if let Err(e) = fs::write(&tmp, json).and_then(|()| fs::rename(&tmp, path)) {
+            eprintln!(
+                "[first] warning: {}",
+                crate::orchestrator::io_failure("write JSON report", path, &e)
+            );
+        }
+    }
+
+    /// Render the whole report.
+    fn render(&self, complete: bool) -> String {
+        let string = |s: &Option<String>| match s {
+            Some(s) => format!("\"{}\"", escape_json(s)),
+            None => "null".to_string(),
+        };
+        format!(
+            "{{\"version\":{},\"test\":{},\"run_tag\":{},\"seed\":{},\"complete\":{},\"points\":[{}]}}\n",
+            REPORT_VERSION,
+            string(&self.test_name),
+            string(&self.run_tag),
+            self.seed.map_or("null".to_string(), |s| s.to_string()),
+            complete,
+            self.points.join(",")
+        )
+    }
+}
+
+/// Render the report entry of one crash point.
+fn render_point(
+    target: usize,
+    label: &str,
+    work_dir: &Path,
+    outcome: Outcome,
+    duration_ms: u128,
+) -> String {
+    let reason = match outcome {
+        Outcome::Failed(reason) => Some(reason),
+        Outcome::Ok | Outcome::Skipped => None,
+    };
+    format!(
+        "{{\"id\":{},\"label\":\"{}\",\"result\":\"{}\",\"reason\":{},\"work_dir\":\"{}\",\"duration_ms\":{}}}",
+        target,
+        escape_json(label),
+        result_name(outcome),
+        reason.map_or("null".to_string(), |r| format!("\"{}\"", escape_json(r))),
+        escape_json(&work_dir.to_string_lossy()),
+        duration_ms
+    )
+}
+
+/// Name of a verdict in the report and the summary table.
+fn result_name(outcome: Outcome) -> &'static str {
+    match outcome {
+        Outcome::Ok => "pass",
+        Outcome::Failed(_) => "fail",
+        Outcome::Skipped => "skipped",
+    }
+}
+
+/// Environment variable supplying the run tag when the builder sets none.
+pub(crate) const ENV_RUN_TAG: &str = "FIRST_RUN_TAG";
+
+/// The tag correlating this run with external telemetry, if any.
+///
+/// `TestBuilder::run_tag()` takes precedence over `FIRST_RUN_TAG`.
+pub(crate) fn run_tag(options: &Options) -> Option<String> {
+    options.run_tag.clone().or_else(|| {
+        std::env::var(ENV_RUN_TAG)
+            .ok()
+            .filter(|tag| !tag.is_empty())
+    })
+}
+
+/// Suffix for summary lines naming the run tag, e.g. ` [run_tag: ci-123]`.
+pub(crate) fn tag_suffix(run_tag: Option<&str>) -> String {
+    run_tag
+        .map(|tag| format!(" [run_tag: {}]", tag))
+        .unwrap_or_default()
+}
+
+/// Returns true if the test harness was invoked with `--format json`.
+pub(crate) fn harness_format_is_json() -> bool {
+    let args: Vec<String> = std::env::args().collect();
+    args.iter().enumerate().any(|(i, arg)| {
+        arg == "--format=json"
+            || (arg == "--format" && args.get(i + 1).is_some_and(|v| v == "json"))
+    })
+}
+
+/// Escape a string for inclusion in a JSON string literal.
+pub(crate) fn escape_json(s: &str) -> String {
+    let mut out = String::with_capacity(s.len());
+    for c in s.chars() {
+        match c {
+            '"' => out.push_str("\\\""),
+            '\\' => out.push_str("\\\\"),
+            '\n' => out.push_str("\\n"),
+            '\r' => out.push_str("\\r"),
+            '\t' => out.push_str("\\t"),
+            c if (c as u32) < 0x20 => out.push_str(&format!("\\u{:04x}", c as u32)),
+            c => out.push(c),
+        }
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_run_tag_prefers_builder() {
+        let options = Options {
+            run_tag: Some("pr-42".to_string()),
+            ..Options::default()
+        };
+        assert_eq!(run_tag(&options).as_deref(), Some("pr-42"));
+        assert_eq!(tag_suffix(Some("pr-42")), " [run_tag: pr-42]");
+        assert_eq!(tag_suffix(None), "");
+    }
+
+    #[test]
+    fn test_event_line_carries_run_tag() {
+        let libtest = LibtestJson::new(true, Some("wal"), Some("pr-42"));
+        assert_eq!(
+            libtest.event_line(3, "failed", Some("boom")),
+            "{ \"type\": \"test\", \"event\": \"failed\", \"name\": \"wal::crash_point_3\", \
+             \"stdout\": \"boom\", \"run_tag\": \"pr-42\" }\n"
+        );
+    }
+
+    #[test]
+    fn test_render_report() {
+        let report = RunReport {
+            path: None,
+            metadata_dir: PathBuf::new(),
+            test_name: Some("wal".to_string()),
+            run_tag: None,
+            seed: Some(7),
+            started: None,
+            verdicts: Vec::new(),
+            points: vec![
+                render_point(1, "a", Path::new("/tmp/first/run_1"), Outcome::Ok, 3),
+                render_point(
+                    2,
+                    "b\"",
+                    Path::new("/tmp/first/run_2"),
+                    Outcome::Failed("torn"),
+                    4,
+                ),
+            ],
+        };
+        assert_eq!(
+            report.render(true),
+            "{\"version\":1,\"test\":\"wal\",\"run_tag\":null,\"seed\":7,\"complete\":true,\"points\":[\
+             {\"id\":1,\"label\":\"a\",\"result\":\"pass\",\"reason\":null,\"work_dir\":\"/tmp/first/run_1\",\"duration_ms\":3},\
+             {\"id\":2,\"label\":\"b\\\"\",\"result\":\"fail\",\"reason\":\"torn\",\"work_dir\":\"/tmp/first/run_2\",\"duration_ms\":4}]}\n"
+        );
+    }
+
+    #[test]
+    fn test_result_table() {
+        let mut report = RunReport {
+            path: None,
+            metadata_dir: PathBuf::new(),
+            test_name: None,
+            run_tag: None,
+            seed: None,
+            started: None,
+            points: Vec::new(),
+            verdicts: Vec::new(),
+        };
+        let dir = Path::new("/tmp/first/run");
+        report.end(1, "after_a", dir, Outcome::Ok);
+        report.end(2, "after_b", dir, Outcome::Failed("torn"));
+        report.end(10, "after_c", dir, Outcome::Skipped);
+        assert_eq!(
+            report.table(),
+            vec![
+                "id  result   label",
+                " 1  pass     after_a",
+                " 2  fail     after_b",
+                "10  skipped  after_c",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_escape_json() {
+        assert_eq!(escape_json(r#"a "b" \c"#), r#"a \"b\" \\c"#);
+        assert_eq!(escape_json("line\nnext"), "line\\nnext");
+        assert_eq!(escape_json("\u{1}"), "\\u0001");
+    }
+}
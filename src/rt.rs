@@ -2,22 +2,69 @@
 //!
 //! This module contains the core primitives for crash injection.
 
+use std::cell::Cell;
 use std::io::Write;
+use std::path::{Path, PathBuf};
 use std::sync::OnceLock;
-use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::thread::ThreadId;
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+
+use crate::env::CrashInfo;
+use crate::fault::FaultKind;
+use crate::test::Options;
 
 /// Global counter tracking the number of crash points encountered.
 /// Starts at 0, incremented to 1 on first crash_point, etc.
 static CRASH_COUNTER: AtomicUsize = AtomicUsize::new(0);
 
+/// Crash points passed so far whose label is the target label.
+static LABEL_COUNTER: AtomicUsize = AtomicUsize::new(0);
+
 /// Cached runtime configuration, initialized once from environment variables.
 static RUNTIME: OnceLock<RuntimeConfig> = OnceLock::new();
 
+/// Builder options installed by the EXECUTION phase before the workload runs.
+static OPTIONS: OnceLock<Options> = OnceLock::new();
+
+/// Maximum open file descriptor count sampled so far (see `track_fds`).
+static MAX_FDS: AtomicUsize = AtomicUsize::new(0);
+
+/// Workload run time at which the `crash_after_duration` timer fired.
+static TIMER_ELAPSED: OnceLock<Duration> = OnceLock::new();
+
+/// When the workload started, i.e. when its options were installed.
+static WORKLOAD_START: OnceLock<Instant> = OnceLock::new();
+
+/// Thread that runs the workload, i.e. that installed the options.
+static WORKLOAD_THREAD: OnceLock<ThreadId> = OnceLock::new();
+
+/// Set by the first thread to reach `crash_at()`; the process is dying.
+static CRASHING: AtomicBool = AtomicBool::new(false);
+
+thread_local! {
+    /// Seed of the sweep orchestrated on this thread. Tests in one binary
+    /// run on separate threads, each with its own seed.
+    static RUN_SEED: Cell<Option<u64>> = const { Cell::new(None) };
+}
+
 /// Environment variable names used by FIRST.
 const ENV_PHASE: &str = "FIRST_PHASE";
 const ENV_CRASH_TARGET: &str = "FIRST_CRASH_TARGET";
 const ENV_WORK_DIR: &str = "FIRST_WORK_DIR";
-const ENV_SEED: &str = "FIRST_SEED";
+pub(crate) const ENV_SEED: &str = "FIRST_SEED";
+const ENV_CRASH_TARGET_SITE: &str = "FIRST_CRASH_TARGET_SITE";
+pub(crate) const ENV_CRASH_LABEL_TARGET: &str = "FIRST_CRASH_LABEL_TARGET";
+pub(crate) const ENV_CRASH_LABEL_OCCURRENCE: &str = "FIRST_CRASH_LABEL_OCCURRENCE";
+const ENV_DISABLE_LABELS: &str = "FIRST_DISABLE_LABELS";
+
+/// Exit code of a child whose target crash point is disabled.
+///
+/// 77 is the conventional "skipped" status of test drivers.
+pub(crate) const SKIPPED_EXIT_CODE: i32 = 77;
+
+/// Source location of a `crash_point!` call: `(file!(), line!())`.
+pub(crate) type Site = (&'static str, u32);
 
 /// Execution phase of the current process.
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
@@ -28,6 +75,12 @@ pub(crate) enum Phase {
     Execution,
     /// Verify: runs recovery and invariant checks.
     Verify,
+    /// Discover: runs workload to completion, reporting every crash point.
+    Discover,
+    /// Read: runs the concurrent reader next to an EXECUTION child.
+    Read,
+    /// Recover: runs the recovery closure, may crash at target point.
+    Recover,
 }
 
 /// Cached runtime configuration.
@@ -37,6 +90,12 @@ pub(crate) struct RuntimeConfig {
     /// Target crash point (1-indexed per design doc).
     /// `usize::MAX` means "never crash".
     target_crash_point: usize,
+    /// Target call site (see `site_id`). When set, the first crash point
+    /// at this site is the target instead of `target_crash_point`.
+    target_site: Option<u64>,
+    /// Target label and occurrence (1-indexed). When set, the Nth crash
+    /// point with this label is the target instead of `target_crash_point`.
+    target_label: Option<(String, usize)>,
 }
 
 /// Initialize the runtime from environment variables.
@@ -45,10 +104,13 @@ fn init_runtime() -> RuntimeConfig {
     let phase = match std::env::var(ENV_PHASE).as_deref() {
         Ok("EXECUTION") => Phase::Execution,
         Ok("VERIFY") => Phase::Verify,
+        Ok("DISCOVER") => Phase::Discover,
+        Ok("READ") => Phase::Read,
+        Ok("RECOVER") => Phase::Recover,
         _ => Phase::Orchestrator,
     };
 
-    let target_crash_point = if phase == Phase::Execution {
+    let target_crash_point = if matches!(phase, Phase::Execution | Phase::Recover) {
         std::env::var(ENV_CRASH_TARGET)
             .ok()
             .and_then(|s| s.parse::<usize>().ok())
@@ -57,9 +119,31 @@ fn init_runtime() -> RuntimeConfig {
         usize::MAX
     };
 
+    let target_site = if phase == Phase::Execution {
+        std::env::var(ENV_CRASH_TARGET_SITE)
+            .ok()
+            .and_then(|s| u64::from_str_radix(&s, 16).ok())
+    } else {
+        None
+    };
+
+    let target_label = if phase == Phase::Execution {
+        std::env::var(ENV_CRASH_LABEL_TARGET).ok().map(|label| {
+            let occurrence = std::env::var(ENV_CRASH_LABEL_OCCURRENCE)
+                .ok()
+                .and_then(|s| s.parse::<usize>().ok())
+                .unwrap_or(1);
+            (label, occurrence)
+        })
+    } else {
+        None
+    };
+
     RuntimeConfig {
         phase,
         target_crash_point,
+        target_site,
+        target_label,
     }
 }
 
@@ -70,6 +154,54 @@ pub(crate) fn runtime() -> &'static RuntimeConfig {
     RUNTIME.get_or_init(init_runtime)
 }
 
+/// The crash point this EXECUTION child targets by counter, if it
+/// targets one: not with a site or label target, nor a crash timer.
+fn counter_target() -> Option<usize> {
+    let config = runtime();
+    let by_counter = config.phase == Phase::Execution
+        && config.target_site.is_none()
+        && config.target_label.is_none()
+        && config.target_crash_point != usize::MAX;
+    by_counter.then_some(config.target_crash_point)
+}
+
+/// Start this EXECUTION child from the `snapshot_after()` workspace
+/// snapshot, if one was taken before its target, skipping the crash point
+/// IDs up to the snapshot.
+pub(crate) fn resume_from_snapshot(work_dir: &Path, metadata_dir: &Path) {
+    let Some(target) = counter_target().filter(|_| options().snapshot_after.is_some()) else {
+        return;
+    };
+    if let Some(point) = crate::snapshot::resume(work_dir, metadata_dir, target) {
+        CRASH_COUNTER.store(point, Ordering::SeqCst);
+    }
+}
+
+/// Install the builder options for this process.
+///
+/// Called by `TestBuilder::execute()` in the EXECUTION phase. Only the
+/// first call has an effect.
+pub(crate) fn install_options(options: Options) {
+    let _ = OPTIONS.set(options);
+    let _ = WORKLOAD_THREAD.set(std::thread::current().id());
+    let _ = WORKLOAD_START.set(Instant::now());
+}
+
+/// How long the workload had run at the crash: the timer's run time for a
+/// `crash_after_duration` crash, else the time since it started.
+fn elapsed() -> Option<Duration> {
+    TIMER_ELAPSED
+        .get()
+        .copied()
+        .or_else(|| WORKLOAD_START.get().map(Instant::elapsed))
+}
+
+/// Returns the installed builder options, or defaults if none were installed.
+#[inline]
+pub(crate) fn options() -> &'static Options {
+    OPTIONS.get_or_init(Options::default)
+}
+
 /// Marks a potential crash location during test execution.
 ///
 /// Calling this function is always safe in all phases. Outside the
@@ -82,14 +214,30 @@ pub(crate) fn runtime() -> &'static RuntimeConfig {
 /// | Orchestrator | No-op (silently ignored)                         |
 /// | Execution    | Increments counter and may terminate the process |
 /// | Verify       | No-op (silently ignored)                         |
+/// | Discover     | Increments counter and reports the point         |
+///
+/// In Orchestrator and Verify phases, the crash counter is not incremented.
+///
+/// # Threads
 ///
-/// In non-Execution phases, the crash counter is not incremented.
+/// Crash points may be called from threads the workload spawns, such as
+/// background flush threads, with `TestBuilder::allow_threads()`. IDs are
+/// drawn from one atomic counter, so every call gets a distinct ID, but
+/// which thread gets which ID depends on scheduling. Exactly one thread
+/// crashes the process: the first to reach its target. From then on, any
+/// other thread that reaches a crash point blocks there until the
+/// SIGKILL, so it does not write past the crash.
+///
+/// Without `allow_threads()`, crashing at a crash point on a thread other
+/// than the workload's prints a warning.
 ///
 /// # Arguments
 ///
 /// * `label` - A descriptive name for this crash point (required).
-///   The label is used only for logging/debugging; it does not affect crash
-///   scheduling. The label must be a static string or outlive the call.
+///   The label is used for logging/debugging and, with
+///   `TestBuilder::target_labels()`, to target the crash point by label
+///   rather than by counter. The label must be a static string or outlive
+///   the call.
 ///   Dynamic allocations are discouraged for performance.
 ///
 /// # Crash Point Numbering
@@ -101,22 +249,272 @@ pub(crate) fn runtime() -> &'static RuntimeConfig {
 ///
 /// This matches the design spec where `target=1` crashes at the first point.
 ///
+/// # Return Value
+///
+/// The ID this call was assigned, in the phases that count crash points
+/// (Execution, Discover, and the Recover runs of `recover_idempotent()`),
+/// so workload logs can be matched with FIRST's numbering. At the target
+/// the process is killed instead of returning.
+///
+/// In the phases that do not count (Orchestrator, Verify, Read), it always
+/// returns `0`, which is never a valid ID. The value may be ignored.
+///
 /// # Example
 ///
 /// ```
 /// use first::crash_point;
 ///
-/// // These are no-ops when not in EXECUTION phase
-/// crash_point("after_write");   // Would be ID 1 in EXECUTION phase
-/// crash_point("after_sync");    // Would be ID 2 in EXECUTION phase
+/// // These are no-ops when not in EXECUTION phase, and return 0
+/// let id = crash_point("after_write"); // Would be ID 1 in EXECUTION phase
+/// eprintln!("wrote record at crash point {}", id);
+/// crash_point("after_sync"); // Would be ID 2 in EXECUTION phase
+/// # assert_eq!(id, 0);
+/// ```
+pub fn crash_point(label: &str) -> usize {
+    match hit(label) {
+        Hit::Inactive => 0,
+        Hit::Passed(id) => id,
+        Hit::Target(id) => crash_at(id, label, None),
+    }
+}
+
+/// Marks a potential crash location that is live only when `cond` holds.
+///
+/// With `cond` true this is exactly [`crash_point()`]. With `cond` false it
+/// is a no-op in every phase and returns `0`: the call does **not** consume
+/// an ID, so the next live crash point gets the ID this call would have.
+/// Numbering stays stable across runs as long as `cond` is deterministic,
+/// e.g. derived from a loop index rather than the clock.
+///
+/// # Example
+///
 /// ```
-pub fn crash_point(label: &str) {
+/// for i in 0..100 {
+///     // Only every tenth iteration is a crash point: IDs 1 to 10
+///     first::crash_point_if("after_batch", i % 10 == 9);
+/// }
+/// ```
+pub fn crash_point_if(label: &str, cond: bool) -> usize {
+    if !cond {
+        return 0;
+    }
+    crash_point(label)
+}
+
+/// Marks a point where the workload's next I/O can fail instead of the
+/// process crashing.
+///
+/// Counted like a [`crash_point()`] and swept like one, sharing its
+/// numbering. When it is the target, the process is not killed: the next
+/// instrumented write or sync (the `Env` helpers, or a file wrapped with
+/// [`Env::faulty_file()`](crate::Env::faulty_file)) fails once with the
+/// error of `kind`, and the workload runs on to exercise its error
+/// handling. When the workload returns, the EXECUTION child is killed as
+/// if it had crashed at this point, and verify checks the state left
+/// behind, with the injection in [`CrashInfo::fault`].
+///
+/// A workload that panics on the error fails the point like any other
+/// failed execution. In a `recover_idempotent()` recovery, a fault point
+/// crashes like a crash point.
+///
+/// Returns the ID, as [`crash_point()`] does.
+///
+/// # Example
+///
+/// ```
+/// use first::FaultKind;
+///
+/// // A no-op when not in EXECUTION phase, returning 0
+/// first::fault_point("before_wal_append", FaultKind::NoSpace);
+/// ```
+pub fn fault_point(label: &str, kind: FaultKind) -> usize {
+    match hit(label) {
+        Hit::Inactive => 0,
+        Hit::Passed(id) => id,
+        Hit::Target(id) if runtime().phase == Phase::Execution => {
+            crate::fault::arm(id, label, kind);
+            id
+        }
+        Hit::Target(id) => crash_at(id, label, None),
+    }
+}
+
+/// Marks a crash location identified by its call site.
+///
+/// This is the function behind the [`crash_point!`](crate::crash_point!)
+/// macro; call the macro instead.
+#[doc(hidden)]
+pub fn crash_point_at(label: &str, file: &'static str, line: u32) -> usize {
+    let site = (file, line);
+    match hit_at(label, Some(site)) {
+        Hit::Inactive => 0,
+        Hit::Passed(id) => id,
+        Hit::Target(id) => crash_at(id, label, Some(site)),
+    }
+}
+
+/// Marks a potential crash location, recording its call site.
+///
+/// Behaves exactly like [`crash_point()`], including its return value, but also captures `file!()` and
+/// `line!()`. With `TestBuilder::target_sites()`, FIRST targets crash points
+/// by a stable hash of that location instead of by counter, so a
+/// reproduction survives crash points being added or reordered elsewhere as
+/// long as this call site is unchanged. The location is reported to verify
+/// as [`CrashInfo::site`](crate::CrashInfo::site).
+///
+/// # Example
+///
+/// ```
+/// // A no-op when not in EXECUTION phase
+/// first::crash_point!("after_write");
+/// ```
+#[macro_export]
+macro_rules! crash_point {
+    ($label:expr) => {
+        $crate::crash_point_at($label, file!(), line!())
+    };
+}
+
+/// Whether the current process is the orchestrator.
+///
+/// A test body runs in the orchestrator and again in every child it
+/// spawns, so expensive global setup, such as building a large dataset,
+/// is repeated in each of them. Guard it with this to run it once:
+///
+/// ```
+/// let dataset = std::env::temp_dir().join("my_dataset");
+/// if first::is_orchestrator() {
+///     // build the dataset and write it to `dataset`
+/// }
+/// // children read `dataset`
+/// ```
+///
+/// Children do not share the orchestrator's memory: state built there
+/// reaches them only if it is serialized, e.g. to a file at a fixed path
+/// outside the workspace.
+pub fn is_orchestrator() -> bool {
+    runtime().phase == Phase::Orchestrator
+}
+
+/// The seed of the current run, for deterministic randomness in the
+/// workload and verify.
+///
+/// Every child of a sweep sees the same value, so keys or values drawn
+/// from it are the same at every crash point. It is set with
+/// `TestBuilder::seed()`, overridden by `FIRST_SEED`, and otherwise
+/// generated once per sweep. The failure report prints it in the
+/// reproduction command.
+///
+/// Returns 0 outside a FIRST run.
+///
+/// # Example
+///
+/// ```ignore
+/// first::test()
+///     .seed(42)
+///     .run(|env| {
+///         let mut rng = MyRng::new(first::seed());
+///         // write keys drawn from rng
+///     })
+/// ```
+pub fn seed() -> u64 {
+    RUN_SEED
+        .get()
+        .or_else(|| std::env::var(ENV_SEED).ok()?.parse().ok())
+        .unwrap_or(0)
+}
+
+/// Fix the seed of the sweep starting on this thread: `FIRST_SEED` if
+/// set, else `configured`, else a fresh one.
+pub(crate) fn resolve_run_seed(configured: Option<u64>) -> u64 {
+    let from_env = std::env::var(ENV_SEED).ok().and_then(|s| match s.parse() {
+        Ok(seed) => Some(seed),
+        Err(_) => {
+            eprintln!("[first] warning: ignoring {}={:?}: not a u64", ENV_SEED, s);
+            None
+        }
+    });
+    let seed = from_env.or(configured).unwrap_or_else(generate_seed);
+    RUN_SEED.set(Some(seed));
+    seed
+}
+
+/// The seed fixed by [`resolve_run_seed()`] on this thread, to pass to
+/// children as `FIRST_SEED`.
+pub(crate) fn run_seed() -> Option<u64> {
+    RUN_SEED.get()
+}
+
+/// A seed unlikely to repeat between runs, from the time and the pid.
+fn generate_seed() -> u64 {
+    let nanos = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |d| d.as_nanos() as u64);
+    // SplitMix64 finalizer, so close start times give unrelated seeds
+    let mut z = nanos ^ u64::from(std::process::id()).rotate_left(32);
+    z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+    z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+    z ^ (z >> 31)
+}
+
+/// Stable identifier of a call site.
+///
+/// FNV-1a over `file:line`, so the value is the same in every build and
+/// on every toolchain, unlike `std`'s `DefaultHasher`.
+pub(crate) fn site_id(file: &str, line: u32) -> u64 {
+    let mut hash: u64 = 0xcbf2_9ce4_8422_2325;
+    for byte in format!("{}:{}", file, line).bytes() {
+        hash ^= u64::from(byte);
+        hash = hash.wrapping_mul(0x0100_0000_01b3);
+    }
+    hash
+}
+
+/// Outcome of passing a crash point.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum Hit {
+    /// Not in a phase that counts crash points.
+    Inactive,
+    /// Counted, but not the target.
+    Passed(usize),
+    /// This is the target crash point; the caller must crash.
+    Target(usize),
+}
+
+/// Count a crash point and report whether it is the target.
+///
+/// Shared by `crash_point()` and the instrumented `Env` helpers, so every
+/// kind of crash point draws IDs from the same counter.
+pub(crate) fn hit(label: &str) -> Hit {
+    hit_at(label, None)
+}
+
+/// Like [`hit()`], for a crash point whose call site is known.
+fn hit_at(label: &str, site: Option<Site>) -> Hit {
     let config = runtime();
 
-    if config.phase != Phase::Execution {
-        // No-op in Orchestrator or Verify phases.
+    if matches!(config.phase, Phase::Execution | Phase::Discover)
+        && options().unreachable_labels.iter().any(|l| l == label)
+    {
+        report_unreachable(label);
+    }
+
+    if config.phase == Phase::Discover {
+        let id = CRASH_COUNTER.fetch_add(1, Ordering::SeqCst) + 1;
+        emit_point_event(id, label, site);
+        return Hit::Passed(id);
+    }
+
+    if !matches!(config.phase, Phase::Execution | Phase::Recover) {
+        // No-op in Orchestrator, Verify and Read phases.
         // Fast path: no atomic operations, no allocations.
-        return;
+        return Hit::Inactive;
+    }
+
+    // Another thread is crashing the process: stop here, before this one
+    // writes past the crash.
+    if CRASHING.load(Ordering::SeqCst) {
+        wait_for_crash();
     }
 
     // Increment counter FIRST, then check.
@@ -126,36 +524,332 @@ pub fn crash_point(label: &str) {
     let previous = CRASH_COUNTER.fetch_add(1, Ordering::SeqCst);
     let current_id = previous + 1; // 1-indexed: first call = 1, second = 2, etc.
 
+    if config.phase == Phase::Execution {
+        crate::labels::record(label);
+        if options().assert_deterministic {
+            crate::determinism::check(current_id, label);
+        }
+        if let (Some(snapshot_label), Some(target)) = (&options().snapshot_after, counter_target())
+        {
+            crate::snapshot::passed(current_id, label, snapshot_label, target);
+        }
+    }
+
     // SeqCst is used to guarantee deterministic ordering even if users
     // accidentally introduce concurrency in v0.1. This is intentionally
     // conservative; do not "optimize" to weaker orderings.
     let target = config.target_crash_point;
 
-    if current_id == target {
-        emit_crash_metadata(current_id, label);
-        trigger_crash();
+    if options().track_fds
+        && let Some(count) = count_open_fds()
+    {
+        MAX_FDS.fetch_max(count, Ordering::SeqCst);
+    }
+
+    let is_target = match (config.target_site, &config.target_label) {
+        (Some(target_site), _) => {
+            site.is_some_and(|(file, line)| site_id(file, line) == target_site)
+        }
+        (None, Some((target_label, occurrence))) => {
+            label == target_label && LABEL_COUNTER.fetch_add(1, Ordering::SeqCst) + 1 == *occurrence
+        }
+        (None, None) => current_id == target,
+    };
+
+    if is_target && is_disabled(label) {
+        skip_at(current_id, label, site);
+    }
+    if is_target {
+        warn_spawned_thread(current_id, label);
+        Hit::Target(current_id)
+    } else {
+        Hit::Passed(current_id)
+    }
+}
+
+/// Warn when the target crash point is reached off the workload thread,
+/// unless `TestBuilder::allow_threads()` declared such crash points.
+fn warn_spawned_thread(point_id: usize, label: &str) {
+    let spawned = WORKLOAD_THREAD
+        .get()
+        .is_some_and(|id| *id != std::thread::current().id());
+    if !spawned || options().allow_threads {
+        return;
+    }
+    let message = format!(
+        "[first] warning: crash point {} (\"{}\") was reached from a spawned thread; \
+         its ID depends on scheduling (declare such crash points with allow_threads())",
+        point_id, label
+    );
+    // Raw stderr: libtest captures eprintln! output in the child
+    let mut stderr = std::io::stderr().lock();
+    let _ = stderr.write_all(message.as_bytes());
+    let _ = stderr.write_all(b"\n");
+    let _ = stderr.flush();
+}
+
+/// Park the calling thread until the crashing thread kills the process.
+fn wait_for_crash() -> ! {
+    loop {
+        std::thread::park();
+    }
+}
+
+/// Whether crash points labelled `label` must never be armed, through
+/// `TestBuilder::disable_labels()` or `FIRST_DISABLE_LABELS`.
+fn is_disabled(label: &str) -> bool {
+    static FROM_ENV: OnceLock<Vec<String>> = OnceLock::new();
+    let from_env = FROM_ENV.get_or_init(|| {
+        std::env::var(ENV_DISABLE_LABELS)
+            .map(|s| parse_labels(&s))
+            .unwrap_or_default()
+    });
+    from_env
+        .iter()
+        .chain(&options().disabled_labels)
+        .any(|l| l == label)
+}
+
+/// Parse a comma-separated label list, ignoring blanks around labels.
+pub(crate) fn parse_labels(list: &str) -> Vec<String> {
+    list.split(',')
+        .map(str::trim)
+        .filter(|l| !l.is_empty())
+        .map(str::to_string)
+        .collect()
+}
+
+/// Report the disabled target crash point and end the child without
+/// crashing.
+fn skip_at(point_id: usize, label: &str, site: Option<Site>) -> ! {
+    #[cfg(feature = "serde")]
+    {
+        let mut info = CrashInfo::new(point_id, label.to_string());
+        info.site = site;
+        if let Some(event) = crate::crash_event::encode("skipped", &info, Some(seed()), None, None)
+        {
+            write_event(&event);
+            std::process::exit(SKIPPED_EXIT_CODE);
+        }
+    }
+
+    let event = format!(
+        r#"{{"event":"skipped","point_id":{}{},"label":"{}"}}"#,
+        point_id,
+        site_fields(site),
+        crate::report::escape_json(label)
+    );
+    write_event(&event);
+    std::process::exit(SKIPPED_EXIT_CODE);
+}
+
+/// Label of the crash reported by the `crash_after_duration` timer.
+pub(crate) const TIMER_LABEL: &str = "crash_after_duration";
+
+/// Crash the process `after` from now, wherever the workload is.
+///
+/// The crash is reported like one at a crash point, with the number of
+/// points passed so far as its ID and the elapsed time recorded.
+pub(crate) fn arm_crash_timer(after: Duration) {
+    let start = Instant::now();
+    std::thread::spawn(move || {
+        std::thread::sleep(after);
+        let _ = TIMER_ELAPSED.set(start.elapsed());
+        crash_at(points_passed(), TIMER_LABEL, None);
+    });
+}
+
+/// Number of crash points counted so far in this process.
+pub(crate) fn points_passed() -> usize {
+    CRASH_COUNTER.load(Ordering::SeqCst)
+}
+
+/// Crash at the target point: report metadata, apply crash effects, SIGKILL.
+pub(crate) fn crash_at(point_id: usize, label: &str, site: Option<Site>) -> ! {
+    // Threads can reach a target together, at a shared call site or with
+    // the timer of crash_after_duration(): the first one crashes.
+    if CRASHING.swap(true, Ordering::SeqCst) {
+        wait_for_crash();
+    }
+    if options().torn_last_write
+        && runtime().phase == Phase::Execution
+        && let Ok(work_dir) = std::env::var(ENV_WORK_DIR)
+    {
+        crate::crash::tear_last_write(Path::new(&work_dir));
+    }
+    emit_crash_metadata(point_id, label, site);
+    if options().reader {
+        crate::reader::signal_crash();
+    }
+    if let Ok(work_dir) = std::env::var(ENV_WORK_DIR) {
+        if runtime().phase == Phase::Execution {
+            crate::invariants::record_synced_lengths(Path::new(&work_dir));
+        }
+        crate::crash::apply_effects(options(), Path::new(&work_dir));
     }
+    if options().coverage_dir.is_some() {
+        // Exit with the crash status instead: a killed process never
+        // writes its coverage profile. No destructors run either way.
+        std::process::exit(crate::terminate::CRASH_EXIT_CODE);
+    }
+    trigger_crash();
 }
 
 /// Emit crash metadata to stderr before killing the process.
 /// This allows the Orchestrator to parse what happened.
-fn emit_crash_metadata(point_id: usize, label: &str) {
+fn emit_crash_metadata(point_id: usize, label: &str, site: Option<Site>) {
+    if options().binary_metadata && crate::metadata::write(&crash_info(point_id, label, site)) {
+        return;
+    }
+
+    #[cfg(feature = "serde")]
+    if let Some(event) = crate::crash_event::encode(
+        "crash",
+        &crash_info(point_id, label, site),
+        Some(seed()),
+        std::env::var(ENV_WORK_DIR).ok().as_deref(),
+        crate::report::run_tag(options()).as_deref(),
+    ) {
+        write_event(&event);
+        return;
+    }
+
     let seed = std::env::var(ENV_SEED).unwrap_or_else(|_| "null".to_string());
     let work_dir = std::env::var(ENV_WORK_DIR).unwrap_or_else(|_| "unknown".to_string());
+    let max_fds = if options().track_fds {
+        MAX_FDS.load(Ordering::SeqCst).to_string()
+    } else {
+        "null".to_string()
+    };
+    let site = site_fields(site);
+    let elapsed = elapsed()
+        .map(|d| format!(r#","elapsed_us":{}"#, d.as_micros()))
+        .unwrap_or_default();
+    let journal = crate::journal::metadata_fields();
+    let run_tag = crate::report::run_tag(options())
+        .map(|tag| format!(r#","run_tag":"{}""#, crate::report::escape_json(&tag)))
+        .unwrap_or_default();
 
     // Write JSON to stderr (flush immediately to avoid loss on SIGKILL)
     let metadata = format!(
-        r#"{{"event":"crash","point_id":{},"label":"{}","seed":{},"work_dir":"{}"}}"#,
+        r#"{{"event":"crash","point_id":{},"label":"{}","seed":{},"work_dir":"{}","max_fds":{}{}{}{}{}}}"#,
         point_id,
-        label.replace('\\', "\\\\").replace('"', "\\\""),
+        crate::report::escape_json(label),
         seed,
-        work_dir.replace('\\', "\\\\").replace('"', "\\\"")
+        crate::report::escape_json(&work_dir),
+        max_fds,
+        site,
+        elapsed,
+        journal,
+        run_tag
     );
 
+    // Barrier and partial flush events precede the crash event, which
+    // ends the stream
+    let events = crate::journal::barrier_events()
+        .into_iter()
+        .chain(crate::journal::partial_flush_events());
+    for event in events {
+        let _ = std::io::stderr().write_all(event.as_bytes());
+        let _ = std::io::stderr().write_all(b"\n");
+    }
+
+    write_event(&metadata);
+}
+
+/// The crash at `point_id`, with everything journaled up to it.
+fn crash_info(point_id: usize, label: &str, site: Option<Site>) -> CrashInfo {
+    let mut info = CrashInfo::new(point_id, label.to_string());
+    info.work_dir = std::env::var_os(ENV_WORK_DIR)
+        .map(PathBuf::from)
+        .unwrap_or_default();
+    info.max_fds = options().track_fds.then(|| MAX_FDS.load(Ordering::SeqCst));
+    info.site = site;
+    info.elapsed = elapsed();
+    crate::journal::fill_crash_info(&mut info);
+    info
+}
+
+/// Write one metadata line to stderr and flush it at once, so it
+/// survives the `SIGKILL` that follows.
+fn write_event(event: &str) {
     // Use raw write to stderr to minimize buffering
-    let _ = std::io::stderr().write_all(metadata.as_bytes());
-    let _ = std::io::stderr().write_all(b"\n");
-    let _ = std::io::stderr().flush();
+    let mut stderr = std::io::stderr().lock();
+    let _ = stderr.write_all(event.as_bytes());
+    let _ = stderr.write_all(b"\n");
+    let _ = stderr.flush();
+}
+
+/// Fail the workload because an expected-unreachable crash point was hit.
+fn report_unreachable(label: &str) -> ! {
+    let id = CRASH_COUNTER.load(Ordering::SeqCst) + 1;
+    let message = format!(
+        "[first] error: expected-unreachable crash point was reached: \"{}\" (point {})",
+        label, id
+    );
+    // Raw stderr: libtest captures eprintln! output in the child
+    let mut stderr = std::io::stderr().lock();
+    let _ = stderr.write_all(message.as_bytes());
+    let _ = stderr.write_all(b"\n");
+    let _ = stderr.flush();
+    drop(stderr);
+    panic!("{}", message);
+}
+
+/// Report a crash point passed during the DISCOVER phase.
+fn emit_point_event(point_id: usize, label: &str, site: Option<Site>) {
+    // The label stays last: the orchestrator reads it up to the final quote.
+    let event = format!(
+        r#"{{"event":"point","point_id":{}{},"label":"{}"}}"#,
+        point_id,
+        site_fields(site),
+        crate::report::escape_json(label)
+    );
+    let mut stderr = std::io::stderr().lock();
+    let _ = stderr.write_all(event.as_bytes());
+    let _ = stderr.write_all(b"\n");
+    let _ = stderr.flush();
+}
+
+/// Report an instrumented filesystem operation during the DISCOVER phase.
+///
+/// Interleaved with point events, these give the operation timeline used
+/// by `TestBuilder::export_graph()`. A no-op in every other phase.
+pub(crate) fn record_op(kind: &str, target: &str) {
+    if runtime().phase != Phase::Discover {
+        return;
+    }
+    // The target stays last: the orchestrator reads it up to the final quote.
+    let event = format!(
+        r#"{{"event":"op","kind":"{}","target":"{}"}}"#,
+        kind,
+        crate::report::escape_json(target)
+    );
+    let mut stderr = std::io::stderr().lock();
+    let _ = stderr.write_all(event.as_bytes());
+    let _ = stderr.write_all(b"\n");
+    let _ = stderr.flush();
+}
+
+/// Render a call site as `,"site_file":"...","site_line":N` (or nothing).
+fn site_fields(site: Option<Site>) -> String {
+    match site {
+        Some((file, line)) => format!(
+            r#","site_file":"{}","site_line":{}"#,
+            crate::report::escape_json(file),
+            line
+        ),
+        None => String::new(),
+    }
+}
+
+/// Count the file descriptors currently open in this process.
+///
+/// Returns `None` where `/proc/self/fd` is unavailable (non-Linux).
+fn count_open_fds() -> Option<usize> {
+    let entries = std::fs::read_dir("/proc/self/fd").ok()?;
+    // The directory handle used for the listing is itself an open fd.
+    Some(entries.count().saturating_sub(1))
 }
 
 /// Terminate the process immediately using SIGKILL.
@@ -165,22 +859,23 @@ fn emit_crash_metadata(point_id: usize, label: &str) {
 /// - No cleanup handlers execute
 /// - No buffered I/O is flushed
 /// - Filesystem state is left exactly as-is
-fn trigger_crash() -> ! {
-    // SIGKILL cannot be caught, blocked, or ignored.
-    // This is the closest simulation of power loss.
-    unsafe {
-        libc::kill(libc::getpid(), libc::SIGKILL);
-    }
-
-    // Unreachable, but required for `-> !` return type.
-    // If SIGKILL somehow fails, fall back to process exit.
-    std::process::exit(137) // 128 + 9 (SIGKILL)
+pub(crate) fn trigger_crash() -> ! {
+    crate::terminate::kill_self()
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
 
+    #[test]
+    fn test_parse_labels() {
+        assert_eq!(
+            parse_labels("after_gc, after_compaction,,"),
+            ["after_gc", "after_compaction"]
+        );
+        assert!(parse_labels("").is_empty());
+    }
+
     #[test]
     fn test_crash_point_noop_in_orchestrator() {
         // When not in EXECUTION phase, crash_point should be a no-op
@@ -205,6 +900,13 @@ mod tests {
         assert_eq!(before, after);
     }
 
+    #[test]
+    fn test_site_id_is_stable() {
+        // Pinned: changing the hash would invalidate recorded reproductions.
+        assert_eq!(site_id("src/lib.rs", 10), 0x7517_23d9_3aa4_8a69);
+        assert_ne!(site_id("src/lib.rs", 10), site_id("src/lib.rs", 11));
+    }
+
     #[test]
     fn test_runtime_is_cached() {
         // Call runtime() multiple times to verify caching
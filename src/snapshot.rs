@@ -0,0 +1,200 @@
+//! Workspace snapshots that skip a costly workload setup.
+//!
+//! Every EXECUTION child runs the workload from scratch in an empty
+//! workspace, so a sweep costs the setup of the workload once per crash
+//! point. With `TestBuilder::snapshot_after(label)`, the first EXECUTION
+//! child that passes the first crash point labelled `label`, without
+//! crashing there, copies its workspace into the metadata directory. Every
+//! later EXECUTION child targeting a crash point after it starts from that
+//! snapshot instead: its workspace is a copy of the snapshot, its crash
+//! point counter starts at the snapshot's point, and `Env::is_resumed()`
+//! returns true so the workload can skip the setup. Files are cloned with
+//! `FICLONE` where the file system supports it (btrfs, XFS) and copied
+//! otherwise.
+//!
+//! The workload must be resumable: started from the snapshot, it must
+//! reach the same crash points after it as a run from scratch. The label of
+//! the first of them is kept with the snapshot and checked, so a workload
+//! that ignores `Env::is_resumed()` fails instead of shifting every crash
+//! point ID. Nothing outside the workspace is restored: memory, open
+//! files, and FIRST's own records of the writes before the snapshot.
+
+use std::fs::{self, File};
+use std::io;
+use std::os::fd::AsRawFd;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Mutex, MutexGuard};
+
+/// Complete snapshot in `metadata_dir`: the workspace and its `point` file.
+fn dir(metadata_dir: &Path) -> PathBuf {
+    metadata_dir.join("snapshot")
+}
+
+/// Snapshot progress of this child.
+enum State {
+    /// Before the snapshot point.
+    Waiting,
+    /// Workspace copied to `dir` at `point`; the snapshot is complete once
+    /// the label of the next crash point is known.
+    Captured { point: usize, dir: PathBuf },
+    /// Started from the snapshot at `point`, so the next crash point must
+    /// be labelled `next`.
+    Resumed { point: usize, next: String },
+    /// Nothing left to do.
+    Done,
+}
+
+static STATE: Mutex<State> = Mutex::new(State::Waiting);
+
+/// Set once this child started from the snapshot.
+static RESUMED: AtomicBool = AtomicBool::new(false);
+
+fn state() -> MutexGuard<'static, State> {
+    STATE.lock().unwrap_or_else(|e| e.into_inner())
+}
+
+/// Whether this child started from the snapshot.
+pub(crate) fn is_resumed() -> bool {
+    RESUMED.load(Ordering::SeqCst)
+}
+
+/// Fill the workspace of a child targeting crash point `target` from the
+/// snapshot in `metadata_dir`, if there is one from before `target`.
+///
+/// Returns the crash point the snapshot was taken at.
+pub(crate) fn resume(work_dir: &Path, metadata_dir: &Path, target: usize) -> Option<usize> {
+    let dir = dir(metadata_dir);
+    let (point, next) = parse_point(&fs::read_to_string(dir.join("point")).ok()?)?;
+    if point >= target {
+        return None;
+    }
+    if let Err(e) = clone_tree(&dir.join("workspace"), work_dir) {
+        panic!(
+            "[first] cannot restore workspace snapshot {}: {}",
+            dir.display(),
+            e
+        );
+    }
+    RESUMED.store(true, Ordering::SeqCst);
+    *state() = State::Resumed { point, next };
+    Some(point)
+}
+
+/// Track an EXECUTION child passing crash point `point_id` labelled
+/// `label`, taking the snapshot at the first `snapshot_label` before
+/// `target`.
+pub(crate) fn passed(point_id: usize, label: &str, snapshot_label: &str, target: usize) {
+    let mut state = state();
+    match &*state {
+        State::Waiting if label == snapshot_label => {
+            *state = if point_id < target {
+                capture(point_id).unwrap_or(State::Done)
+            } else {
+                State::Done
+            };
+        }
+        State::Captured { point, dir } if point_id == point + 1 => {
+            complete(*point, dir, label);
+            *state = State::Done;
+        }
+        State::Resumed { point, next } if point_id == point + 1 => {
+            if label != next {
+                crate::determinism::report(&format!(
+                    "workload is not resumable: started from the snapshot after \"{}\" (point {}), it reached \"{}\" at point {} instead of \"{}\" (skip the setup when Env::is_resumed())",
+                    snapshot_label, point, label, point_id, next
+                ));
+            }
+            *state = State::Done;
+        }
+        _ => {}
+    }
+}
+
+/// Copy the workspace aside, to become the snapshot of `point`.
+fn capture(point: usize) -> Option<State> {
+    let work_dir = PathBuf::from(std::env::var_os("FIRST_WORK_DIR")?);
+    let metadata_dir = PathBuf::from(std::env::var_os("FIRST_METADATA_DIR")?);
+    let dir = metadata_dir.join(format!("snapshot.{}", std::process::id()));
+    let _ = fs::remove_dir_all(&dir);
+    if let Err(e) = clone_tree(&work_dir, &dir.join("workspace")) {
+        eprintln!(
+            "[first] warning: cannot snapshot workspace {}: {}",
+            work_dir.display(),
+            e
+        );
+        let _ = fs::remove_dir_all(&dir);
+        return None;
+    }
+    Some(State::Captured { point, dir })
+}
+
+/// Publish the snapshot copied to `captured`, whose next crash point is
+/// labelled `next`.
+fn complete(point: usize, captured: &Path, next: &str) {
+    let Some(metadata_dir) = captured.parent() else {
+        return;
+    };
+    let published = fs::write(captured.join("point"), format!("{}\n{}", point, next))
+        .and_then(|()| fs::rename(captured, dir(metadata_dir)));
+    if published.is_err() {
+        let _ = fs::remove_dir_all(captured);
+    }
+}
+
+/// Parse a `point` file: the snapshot's crash point, then the label of the
+/// next one.
+fn parse_point(contents: &str) -> Option<(usize, String)> {
+    let (point, next) = contents.split_once('\n')?;
+    Some((point.parse().ok()?, next.to_string()))
+}
+
+/// Copy `src` to `dst`, cloning files where the file system can.
+fn clone_tree(src: &Path, dst: &Path) -> io::Result<()> {
+    crate::bundle::copy_tree_with(src, dst, clone_file)
+}
+
+/// Clone `from` to `to` with `FICLONE`, or copy it if cloning fails.
+fn clone_file(from: &Path, to: &Path) -> io::Result<()> {
+    let src = File::open(from)?;
+    let dst = File::create(to)?;
+    // SAFETY: both descriptors stay open for the duration of the call.
+    let cloned = unsafe { libc::ioctl(dst.as_raw_fd(), libc::FICLONE as _, src.as_raw_fd()) };
+    if cloned == 0 {
+        return dst.set_permissions(src.metadata()?.permissions());
+    }
+    drop(dst);
+    fs::copy(from, to).map(drop)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_point() {
+        assert_eq!(
+            parse_point("3\nafter_load"),
+            Some((3, "after_load".to_string()))
+        );
+        assert_eq!(
+            parse_point("3\ntwo\nlines"),
+            Some((3, "two\nlines".to_string()))
+        );
+        assert_eq!(parse_point("3"), None);
+        assert_eq!(parse_point("x\nlabel"), None);
+    }
+
+    #[test]
+    fn test_clone_tree() {
+        let tmp = tempfile::tempdir().unwrap();
+        let src = tmp.path().join("src");
+        fs::create_dir_all(src.join("sub")).unwrap();
+        fs::write(src.join("a"), "alpha").unwrap();
+        fs::write(src.join("sub/b"), "beta").unwrap();
+        let dst = tmp.path().join("dst");
+        clone_tree(&src, &dst).unwrap();
+        assert_eq!(fs::read_to_string(dst.join("a")).unwrap(), "alpha");
+        assert_eq!(fs::read_to_string(dst.join("sub/b")).unwrap(), "beta");
+    }
+}
@@ -0,0 +1,213 @@
+//! Suite-level invariants over state shared by several tests.
+//!
+//! [`suite_invariant()`] registers a check on a shared catalog from inside
+//! a test. The checks run once, in the orchestrator process, when the test
+//! binary exits after libtest has run all its tests. A coordination file
+//! per catalog in FIRST's base directory records which tests touched the
+//! catalog and serializes the checks of test binaries running at the same
+//! time.
+
+use std::fs::{self, File, OpenOptions};
+use std::io::{self, Read, Seek, Write};
+use std::os::unix::io::AsRawFd;
+use std::panic::{self, AssertUnwindSafe};
+use std::path::{Path, PathBuf};
+use std::sync::{Mutex, Once};
+
+use crate::diagnose::payload_message;
+use crate::orchestrator::FIRST_BASE_DIR;
+use crate::rt::{self, Phase};
+
+/// A registered check.
+struct Invariant {
+    catalog: PathBuf,
+    check: Box<dyn FnOnce() + Send>,
+}
+
+/// Checks to run at exit, in registration order.
+static INVARIANTS: Mutex<Vec<Invariant>> = Mutex::new(Vec::new());
+
+/// Registers [`run_at_exit()`] with the C runtime once.
+static HOOK: Once = Once::new();
+
+/// Check `check` once after every test of this binary has run.
+///
+/// `path` is the catalog shared by the tests: a file or directory outside
+/// any FIRST workspace that several crash tests update. Call this from
+/// each test that touches it; the first registration for a `path` wins,
+/// later ones only record their test. When the test binary exits, FIRST
+/// runs each registered check in the orchestrator process and fails the
+/// binary (exit code 101) if it panics, listing the tests that touched the
+/// catalog. Which tests ran, e.g. under a name filter, does not matter:
+/// the catalog must be consistent whatever subset of them did.
+///
+/// A no-op in child processes, which only run one phase of one test.
+///
+/// # Ordering and concurrency
+///
+/// - Checks run after libtest has printed its summary, including when
+///   tests failed, but not if the process is killed or aborts.
+/// - Tests of one binary run in parallel threads, and each test sweeps its
+///   crash points in child processes: the catalog must tolerate concurrent
+///   updates, or the tests must run with `--test-threads=1`.
+/// - The check itself runs alone: FIRST holds a lock on the coordination
+///   file `/tmp/first/suite/<catalog>` while it runs, so several test
+///   binaries (or nextest, which runs every test in its own process)
+///   check a catalog one at a time. With nextest, "the binary" is a single
+///   test, so the check runs after each one.
+///
+/// # Example
+///
+/// ```ignore
+/// #[test]
+/// fn create_table() {
+///     first::suite_invariant("/tmp/catalog", || {
+///         assert!(Catalog::open("/tmp/catalog").is_consistent());
+///     });
+///     first::test()
+///         .run(|env| { /* create a table, registering it in the catalog */ })
+///         .verify(|env, _| { /* ... */ })
+///         .execute();
+/// }
+/// ```
+pub fn suite_invariant(path: impl AsRef<Path>, check: impl FnOnce() + Send + 'static) {
+    if rt::runtime().phase != Phase::Orchestrator {
+        return;
+    }
+    let catalog = path.as_ref().to_path_buf();
+    let test = std::thread::current()
+        .name()
+        .unwrap_or("unknown")
+        .to_string();
+    if let Err(e) = record_test(&catalog, &test) {
+        eprintln!(
+            "[first] warning: cannot record test {} for suite invariant on {}: {}",
+            test,
+            catalog.display(),
+            e
+        );
+    }
+
+    let mut invariants = INVARIANTS.lock().unwrap_or_else(|e| e.into_inner());
+    if !invariants.iter().any(|i| i.catalog == catalog) {
+        invariants.push(Invariant {
+            catalog,
+            check: Box::new(check),
+        });
+    }
+    drop(invariants);
+
+    HOOK.call_once(|| {
+        // SAFETY: registers a function without arguments; atexit has no
+        // other preconditions.
+        if unsafe { libc::atexit(run_at_exit) } != 0 {
+            eprintln!("[first] warning: cannot register suite invariants; they will not run");
+        }
+    });
+}
+
+/// Coordination file of `catalog`, one line per test that touched it.
+fn coordination_path(catalog: &Path) -> PathBuf {
+    let name: String = catalog
+        .to_string_lossy()
+        .chars()
+        .map(|c| if c.is_ascii_alphanumeric() { c } else { '_' })
+        .collect();
+    PathBuf::from(FIRST_BASE_DIR).join("suite").join(name)
+}
+
+/// Open the coordination file of `catalog` with an exclusive lock, held
+/// until the file is closed.
+fn lock(catalog: &Path) -> io::Result<File> {
+    let path = coordination_path(catalog);
+    if let Some(dir) = path.parent() {
+        fs::create_dir_all(dir)?;
+    }
+    let file = OpenOptions::new()
+        .read(true)
+        .append(true)
+        .create(true)
+        .open(&path)?;
+    // SAFETY: flock on an fd owned by `file`
+    if unsafe { libc::flock(file.as_raw_fd(), libc::LOCK_EX) } != 0 {
+        return Err(io::Error::last_os_error());
+    }
+    Ok(file)
+}
+
+/// Add `test` to the tests that touched `catalog`.
+fn record_test(catalog: &Path, test: &str) -> io::Result<()> {
+    writeln!(lock(catalog)?, "{}", test)
+}
+
+/// Run the check of `invariant`, returning why it failed, if it did.
+fn run(invariant: Invariant) -> Option<String> {
+    let catalog = invariant.catalog.display().to_string();
+    let mut file = match lock(&invariant.catalog) {
+        Ok(file) => file,
+        Err(e) => return Some(format!("cannot lock {}: {}", catalog, e)),
+    };
+    let mut tests = String::new();
+    let _ = file.read_to_string(&mut tests);
+    let mut tests: Vec<&str> = tests.lines().collect();
+    tests.sort_unstable();
+    tests.dedup();
+
+    let result = panic::catch_unwind(AssertUnwindSafe(invariant.check));
+    // Start over for the next run; tests of other binaries still running
+    // record themselves again
+    let _ = file.set_len(0).and_then(|()| file.rewind());
+    match result {
+        Ok(()) => {
+            eprintln!("[first] suite invariant on {}: OK", catalog);
+            None
+        }
+        Err(payload) => Some(format!(
+            "suite invariant on {} failed after tests {}: {}",
+            catalog,
+            tests.join(", "),
+            payload_message(payload.as_ref())
+        )),
+    }
+}
+
+/// Run and unregister every check, returning the number that failed.
+fn run_all() -> usize {
+    let invariants = std::mem::take(&mut *INVARIANTS.lock().unwrap_or_else(|e| e.into_inner()));
+    let mut failed = 0;
+    for invariant in invariants {
+        if let Some(reason) = run(invariant) {
+            eprintln!("[first] {}", reason);
+            failed += 1;
+        }
+    }
+    failed
+}
+
+extern "C" fn run_at_exit() {
+    if run_all() > 0 {
+        let _ = io::stdout().flush();
+        // exit() is already running: end the process without re-entering it
+        unsafe { libc::_exit(101) };
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_checks_run_once_per_catalog() {
+        let catalog = tempfile::tempdir().unwrap();
+        let path = catalog.path().join("catalog");
+        suite_invariant(&path, || panic!("catalog is corrupt"));
+        suite_invariant(&path, || unreachable!("second registration runs"));
+
+        let tests = fs::read_to_string(coordination_path(&path)).unwrap();
+        assert_eq!(tests.lines().count(), 2);
+        assert_eq!(run_all(), 1);
+        assert_eq!(run_all(), 0);
+        assert_eq!(fs::read(coordination_path(&path)).unwrap(), b"");
+        let _ = fs::remove_file(coordination_path(&path));
+    }
+}
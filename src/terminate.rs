@@ -0,0 +1,49 @@
+//! How a crashing child terminates itself.
+//!
+//! The EXECUTION child ends at its target crash point without running
+//! destructors, cleanup handlers or buffered I/O flushes, which is the
+//! closest a process gets to losing power: it sends itself `SIGKILL`. The
+//! orchestrator recognizes that termination by [`is_crash()`].
+//!
+//! If the kill fails, or when a child must flush its coverage profile
+//! first, the child exits with [`CRASH_EXIT_CODE`] instead, which is also
+//! treated as a crash.
+
+use std::os::unix::process::ExitStatusExt;
+use std::process::ExitStatus;
+
+/// Exit code of a crashed child: 128 + `SIGKILL`, as shells report a
+/// killed process.
+pub(crate) const CRASH_EXIT_CODE: i32 = 137;
+
+/// Terminate the current process as abruptly as possible.
+pub(crate) fn kill_self() -> ! {
+    // SAFETY: kill() on our own pid has no memory-safety preconditions.
+    // SIGKILL cannot be caught, blocked, or ignored.
+    unsafe {
+        libc::kill(libc::getpid(), libc::SIGKILL);
+    }
+
+    // Unreachable, but required for `-> !` return type.
+    // If the kill somehow fails, fall back to process exit.
+    std::process::exit(CRASH_EXIT_CODE)
+}
+
+/// Whether a child exited by [`kill_self()`], or with [`CRASH_EXIT_CODE`].
+pub(crate) fn is_crash(status: ExitStatus) -> bool {
+    status.signal() == Some(libc::SIGKILL) || status.code() == Some(CRASH_EXIT_CODE)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_is_crash() {
+        assert!(is_crash(ExitStatus::from_raw(libc::SIGKILL)));
+        assert!(is_crash(ExitStatus::from_raw(CRASH_EXIT_CODE << 8)));
+        assert!(!is_crash(ExitStatus::from_raw(libc::SIGSEGV)));
+        assert!(!is_crash(ExitStatus::from_raw(101 << 8)));
+        assert!(!is_crash(ExitStatus::from_raw(0)));
+    }
+}
@@ -2,9 +2,16 @@
 //!
 //! Provides the `first::test()` API.
 
-use std::path::PathBuf;
+use std::future::Future;
+use std::path::{Path, PathBuf};
+use std::pin::Pin;
+use std::time::Duration;
 
-use crate::env::{CrashInfo, Env};
+use crate::cgroup::ResourceLimits;
+use crate::env::{
+    BarrierRecord, CrashInfo, DirFsyncFault, Env, InjectedDirFsync, PartialFlush, PartialWrite,
+};
+use crate::loopback::LoopbackFs;
 use crate::rt::{Phase, runtime};
 
 /// Builder for FIRST tests.
@@ -19,6 +26,197 @@ where
 {
     run_fn: Option<R>,
     verify_fn: Option<V>,
+    /// The verify closure of `verify_in_process()`.
+    shared_verify: Option<SharedVerifyFn>,
+    point_verifiers: Vec<PointVerifier>,
+    reader_fn: Option<ReaderFn>,
+    recover_fn: Option<RecoverFn>,
+    async_run_fn: Option<AsyncRunFn>,
+    options: Options,
+}
+
+/// Selects a crash point for `verify_at()` on a [`test()`] builder, by
+/// numeric ID or by label.
+///
+/// Built from a `usize` or a string, so callers write `verify_at(3, ..)`
+/// or `verify_at("after_commit", ..)`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum PointSelector {
+    /// The crash point with this 1-indexed ID.
+    Id(usize),
+    /// Every crash point with this label.
+    Label(String),
+}
+
+impl PointSelector {
+    fn matches(&self, crash_info: &CrashInfo) -> bool {
+        match self {
+            PointSelector::Id(id) => crash_info.point_id == *id,
+            PointSelector::Label(label) => crash_info.label == *label,
+        }
+    }
+}
+
+impl From<usize> for PointSelector {
+    fn from(id: usize) -> Self {
+        PointSelector::Id(id)
+    }
+}
+
+impl From<&str> for PointSelector {
+    fn from(label: &str) -> Self {
+        PointSelector::Label(label.to_string())
+    }
+}
+
+impl From<String> for PointSelector {
+    fn from(label: String) -> Self {
+        PointSelector::Label(label)
+    }
+}
+
+/// A boxed verify closure.
+type VerifyFn = Box<dyn FnOnce(&Env, &CrashInfo)>;
+
+/// A boxed verify closure that can be called at every crash point.
+type SharedVerifyFn = Box<dyn Fn(&Env, &CrashInfo)>;
+
+/// A boxed concurrent reader closure.
+type ReaderFn = Box<dyn FnMut(&Env)>;
+
+/// A boxed recovery closure.
+type RecoverFn = Box<dyn FnOnce(&Env)>;
+
+/// A boxed async workload, returning its future boxed.
+type AsyncRunFn = Box<dyn FnOnce(Env) -> Pin<Box<dyn Future<Output = ()>>>>;
+
+/// Verify logic registered for specific crash points.
+struct PointVerifier {
+    selector: PointSelector,
+    /// Skip the general verify closure at matching points.
+    replaces: bool,
+    f: VerifyFn,
+}
+
+/// Builder options shared by the orchestrator and child processes.
+///
+/// Children re-run the same test function, so they rebuild identical
+/// options without any extra environment variables.
+#[derive(Debug, Clone, Default)]
+pub(crate) struct Options {
+    /// Sample the open file descriptor count at each crash point.
+    pub(crate) track_fds: bool,
+    /// Emit per-crash-point libtest JSON events under `--format json`.
+    pub(crate) libtest_json: bool,
+    /// Pad workspace files to a block boundary with this byte at crash time.
+    pub(crate) block_padding: Option<u8>,
+    /// Enumerate crash points with a DISCOVER run before the sweep.
+    pub(crate) discover: bool,
+    /// Fail EXECUTION children whose labels depart from the DISCOVER run.
+    pub(crate) assert_deterministic: bool,
+    /// Replay the crash sequence in this script instead of sweeping.
+    pub(crate) replay_script: Option<PathBuf>,
+    /// Crash at these points in turn, in one workspace, instead of sweeping.
+    pub(crate) crash_schedule: Option<Vec<usize>>,
+    /// Expose the workspace to children through this stable symlink alias.
+    pub(crate) stable_path: Option<String>,
+    /// Crash point labels that must never be reached.
+    pub(crate) unreachable_labels: Vec<String>,
+    /// Crash point labels that are counted but never crashed at.
+    pub(crate) disabled_labels: Vec<String>,
+    /// Permission bits applied to every created workspace directory.
+    pub(crate) workspace_mode: Option<u32>,
+    /// Subdirectories created inside every workspace before the workload runs.
+    pub(crate) workspace_dirs: Vec<PathBuf>,
+    /// Write a failure report bundle here when verify fails.
+    pub(crate) bundle_on_failure: Option<PathBuf>,
+    /// Write a JSON report of the sweep here.
+    pub(crate) report_json: Option<PathBuf>,
+    /// Verify the failure captured in this bundle instead of sweeping.
+    pub(crate) replay_bundle: Option<PathBuf>,
+    /// Crash at this point this many times and compare the runs.
+    pub(crate) repeat_point: Option<(PointSelector, usize)>,
+    /// Crash once, this long into the workload, instead of sweeping.
+    pub(crate) crash_after: Option<Duration>,
+    /// Sweep the call sites of `crash_point!` instead of the counter.
+    pub(crate) target_sites: bool,
+    /// Sweep the distinct crash point labels instead of the counter.
+    pub(crate) target_labels: bool,
+    /// Start EXECUTION children from a snapshot of the workspace taken at
+    /// the first crash point with this label.
+    pub(crate) snapshot_after: Option<String>,
+    /// List the crash points of the workload instead of sweeping them.
+    pub(crate) list_points: bool,
+    /// Evict the workspace from the page cache between crash and verify.
+    pub(crate) drop_caches: bool,
+    /// Keep sweeping after a failing crash point and report all failures.
+    pub(crate) continue_on_failure: bool,
+    /// Pass only if the sweep finds a failing crash point.
+    pub(crate) expect_violation: bool,
+    /// Name of the enclosing test, filtering the children instead of a
+    /// name found at run time.
+    pub(crate) test_name: Option<String>,
+    /// Delay every `Env::fsync()` / `Env::fdatasync()` by this long.
+    pub(crate) fsync_latency: Option<Duration>,
+    /// Add a crash point inside the `fsync_latency` window.
+    pub(crate) crash_during_fsync: bool,
+    /// Crash point label and files of `crash_mid_fsync()`.
+    pub(crate) mid_fsync: Option<(String, Vec<PathBuf>)>,
+    /// Write the crash point timeline as a DOT graph to this path.
+    pub(crate) export_graph: Option<PathBuf>,
+    /// Revert mapped pages modified since their last `msync` at crash time.
+    pub(crate) lose_unsynced_mmap: bool,
+    /// Fail a crash point whose recovery takes longer than this.
+    pub(crate) max_recovery_time: Option<Duration>,
+    /// Kill and fail any child phase that runs longer than this.
+    pub(crate) timeout: Option<Duration>,
+    /// Name of this invocation among several in one test function.
+    pub(crate) label: Option<String>,
+    /// Call verify in the orchestrator instead of a VERIFY child.
+    pub(crate) verify_in_process: bool,
+    /// Binary-search for the first failing crash point instead of sweeping.
+    pub(crate) bisect: bool,
+    /// Collect an LLVM coverage profile from every child into this directory.
+    pub(crate) coverage_dir: Option<PathBuf>,
+    /// Truncate every workspace file to its last synced length at crash time.
+    pub(crate) lose_unsynced_writes: bool,
+    /// Roll every workspace file back to its last synced contents at crash time.
+    pub(crate) drop_unsynced: bool,
+    /// Tear the last unsynced write logged by `Env::write_all()` at crash time.
+    pub(crate) torn_last_write: bool,
+    /// Sweep crash points in an order shuffled with this seed.
+    pub(crate) shuffle: Option<u64>,
+    /// Stop the sweep after this many crash points.
+    pub(crate) max_crash_points: Option<usize>,
+    /// Sweep only this many crash points, sampled with this seed.
+    pub(crate) sample: Option<(usize, u64)>,
+    /// Seed returned by `first::seed()` in every child.
+    pub(crate) seed: Option<u64>,
+    /// The workload calls crash points from several threads.
+    pub(crate) allow_threads: bool,
+    /// Sweep points in changed files first; sample this percent of the rest.
+    pub(crate) prioritize_changed: Option<u32>,
+    /// Correlation tag added to every JSON event and summary line.
+    pub(crate) run_tag: Option<String>,
+    /// Run a concurrent reader process next to every EXECUTION child.
+    pub(crate) reader: bool,
+    /// Fail each instrumented barrier with `EINTR` this many times first.
+    pub(crate) fsync_eintr: usize,
+    /// Inject this fault into the nth `Env::fsync_dir()` call.
+    pub(crate) dir_fsync_fault: Option<(usize, DirFsyncFault)>,
+    /// Crash recovery at up to this many of its crash points and check
+    /// that it is idempotent.
+    pub(crate) recovery_crashes: Option<usize>,
+    /// Fail a crash point whose crash metadata cannot be parsed.
+    pub(crate) strict_metadata: bool,
+    /// Report crash metadata as a binary record instead of JSON.
+    pub(crate) binary_metadata: bool,
+    /// Run children in cgroups with these limits.
+    pub(crate) resource_limits: Option<ResourceLimits>,
+    /// Create workspaces on this loopback filesystem image.
+    pub(crate) loopback_fs: Option<LoopbackFs>,
+    /// Verify once more per chunk size, with chunked reads of that size.
+    pub(crate) read_chunk_sizes: Vec<usize>,
 }
 
 /// Start building a FIRST test.
@@ -40,6 +238,12 @@ pub fn test() -> TestBuilder<fn(&Env), fn(&Env, &CrashInfo)> {
     TestBuilder {
         run_fn: None,
         verify_fn: None,
+        shared_verify: None,
+        point_verifiers: Vec::new(),
+        reader_fn: None,
+        recover_fn: None,
+        async_run_fn: None,
+        options: Options::default(),
     }
 }
 
@@ -61,7 +265,10 @@ where
     ///
     /// If the closure completes normally without hitting the target crash point,
     /// the orchestrator interprets this as "schedule exhausted" — all crash
-    /// points have been explored and the test passes.
+    /// points have been explored and the test passes. A child that exits
+    /// without starting the test at all, because the test name filter
+    /// matched no test, fails the sweep instead (see
+    /// [`test_name()`](Self::test_name)).
     pub fn run<R2>(self, f: R2) -> TestBuilder<R2, V>
     where
         R2: FnOnce(&Env),
@@ -69,12 +276,60 @@ where
         TestBuilder {
             run_fn: Some(f),
             verify_fn: self.verify_fn,
+            shared_verify: self.shared_verify,
+            point_verifiers: self.point_verifiers,
+            reader_fn: self.reader_fn,
+            recover_fn: self.recover_fn,
+            async_run_fn: self.async_run_fn,
+            options: self.options,
         }
     }
 
+    /// Define an async workload, run with
+    /// [`execute_async()`](Self::execute_async) instead of `execute()`.
+    ///
+    /// For engines whose write path is async. The closure gets the
+    /// [`Env`] by value, so the future it returns can own it:
+    ///
+    /// ```ignore
+    /// #[tokio::test]
+    /// async fn wal_survives_crash() {
+    ///     first::test()
+    ///         .run_async(|env| async move {
+    ///             let wal = tokio::fs::File::create(env.path("wal")).await.unwrap();
+    ///             append(&wal, b"PUT k v").await;
+    ///             first::crash_point("after_append");
+    ///             wal.sync_all().await.unwrap();
+    ///         })
+    ///         .verify(|env, crash_info| { /* recovery */ })
+    ///         .execute_async()
+    ///         .await;
+    /// }
+    /// ```
+    ///
+    /// The future is awaited on the caller's runtime in the EXECUTION
+    /// phase, and [`crash_point()`](crate::crash_point) works in async
+    /// code as anywhere else: the crash kills the whole process, whatever
+    /// the runtime. Crash point IDs stay deterministic across `.await`
+    /// points as long as the runtime is single-threaded, like the
+    /// current-thread runtime of `#[tokio::test]`. On a multi-threaded
+    /// runtime, tasks resumed on other workers reach crash points in
+    /// scheduling order (see [`allow_threads()`](Self::allow_threads)).
+    /// Verify stays synchronous.
+    pub fn run_async<F, Fut>(mut self, f: F) -> Self
+    where
+        F: FnOnce(Env) -> Fut + 'static,
+        Fut: Future<Output = ()> + 'static,
+    {
+        self.async_run_fn = Some(Box::new(move |env| Box::pin(f(env))));
+        self
+    }
+
     /// Define the verification logic.
     ///
-    /// This closure runs during the VERIFY phase after a crash.
+    /// This closure runs during the VERIFY phase after a crash. To call it
+    /// in the orchestrator instead, pass it to
+    /// [`verify_in_process()`](Self::verify_in_process).
     ///
     /// # Panics
     ///
@@ -88,9 +343,1410 @@ where
         TestBuilder {
             run_fn: self.run_fn,
             verify_fn: Some(f),
+            shared_verify: None,
+            point_verifiers: self.point_verifiers,
+            reader_fn: self.reader_fn,
+            recover_fn: self.recover_fn,
+            async_run_fn: self.async_run_fn,
+            options: Options {
+                verify_in_process: false,
+                ..self.options
+            },
         }
     }
 
+    /// Read the workspace concurrently with the workload.
+    ///
+    /// For every crash point, FIRST starts a separate READ process on the
+    /// same workspace just before the workload. It calls `f` over and over
+    /// until the workload crashes (or completes); `f` should open and read
+    /// the engine's state and assert it is consistent, e.g. that a
+    /// snapshot read never sees half of a transaction. If any pass panics
+    /// before the crash, the crash point fails. A pass that overlaps the
+    /// crash itself is ignored, as it may see the crash's own effects on
+    /// the workspace.
+    ///
+    /// `f` must cope with the workspace at any stage of the workload,
+    /// including before any file exists.
+    ///
+    /// # Determinism
+    ///
+    /// Which states the reader observes depends on scheduling, so a bug
+    /// may show up only in some runs, and a failure may not reproduce on
+    /// the first retry. The writer and its crash points stay
+    /// deterministic; only the interleaving of reads varies.
+    pub fn reader(mut self, f: impl FnMut(&Env) + 'static) -> Self {
+        self.reader_fn = Some(Box::new(f));
+        self.options.reader = true;
+        self
+    }
+
+    /// Run every EXECUTION and VERIFY child under CPU and memory limits.
+    ///
+    /// On Linux with cgroup v2, each child gets its own transient cgroup
+    /// below the test process's cgroup, with the limits from `limits`.
+    /// This exposes recovery that mishandles allocation failure or only
+    /// works with ample CPU. A child killed for exceeding its memory limit
+    /// is reported as OOM-killed, not as an injected crash, and fails the
+    /// crash point.
+    ///
+    /// The test process must be allowed to create cgroups, e.g. inside a
+    /// delegated systemd scope (`systemd-run --user --scope -p
+    /// Delegate=yes cargo test`). Where that is not possible, FIRST prints
+    /// a warning and runs the children without limits.
+    pub fn resource_limits(mut self, limits: ResourceLimits) -> Self {
+        self.options.resource_limits = Some(limits);
+        self
+    }
+
+    /// Create every crash point's workspace on a loopback-mounted
+    /// filesystem image of the given type.
+    ///
+    /// Crash behavior depends on the filesystem: ordering of metadata and
+    /// data, what a directory fsync covers, how a torn write looks. With
+    /// this option the orchestrator formats an image with `mkfs.<type>`
+    /// and loop-mounts it under `/tmp/first/loop`, so the sweep exercises
+    /// that filesystem, isolated from and independent of the host's `/tmp`.
+    /// The filesystem used is printed at the start and in the summary.
+    ///
+    /// # Example
+    ///
+    /// ```ignore
+    /// first::test()
+    ///     .loopback_fs(first::LoopbackFs::new("ext4"))
+    ///     .run(|env| { /* workload */ })
+    ///     .verify(|env, _| { /* recovery */ })
+    ///     .execute();
+    /// ```
+    ///
+    /// # Requirements
+    ///
+    /// Linux only. Formatting and mounting need root (or `CAP_SYS_ADMIN`),
+    /// loop devices, and the mkfs tool of the type. Where any of these is
+    /// missing, FIRST prints a warning and creates the workspaces on the
+    /// host filesystem. The image is removed at the end of the sweep
+    /// unless failing workspaces are kept on it.
+    pub fn loopback_fs(mut self, fs: LoopbackFs) -> Self {
+        self.options.loopback_fs = Some(fs);
+        self
+    }
+
+    /// Verify with reads split into chunks of each of `sizes` bytes.
+    ///
+    /// Recovery code often assumes, without noticing, that one `read`
+    /// returns a whole record, and only breaks when a read ends inside
+    /// one. With this option, every crashed workspace is verified once
+    /// more per size, each time on a fresh copy, with every reader opened
+    /// through [`Env::open_chunked()`] returning at most that many bytes
+    /// per `read`. Each of those runs must pass like the regular one, so
+    /// recovery yields the same result whatever the read granularity; a
+    /// failing run fails the crash point, naming the chunk size.
+    ///
+    /// Small and odd sizes (1, 3, 7, ...) are the most likely to split
+    /// headers and length prefixes. Sizes of 0 are ignored.
+    ///
+    /// # Example
+    ///
+    /// ```ignore
+    /// first::test()
+    ///     .run(|env| { /* append records */ })
+    ///     .vary_read_chunking([1, 3, 7, 4096])
+    ///     .verify(|env, _| {
+    ///         let log = Log::recover(env.open_chunked("log").unwrap());
+    ///         assert!(log.is_consistent());
+    ///     })
+    ///     .execute();
+    /// ```
+    pub fn vary_read_chunking(mut self, sizes: impl IntoIterator<Item = usize>) -> Self {
+        self.options.read_chunk_sizes = sizes.into_iter().filter(|&n| n > 0).collect();
+        self
+    }
+
+    /// Report crash metadata in a compact binary format.
+    ///
+    /// By default the EXECUTION child describes its crash as JSON lines on
+    /// stderr, which the orchestrator parses. With this option it instead
+    /// writes one versioned, little-endian record to a file in the
+    /// metadata directory, which is cheaper to produce and parse for
+    /// workloads with thousands of crash points, and cannot be garbled by
+    /// other output on stderr. The information reaching verify is the
+    /// same. JSON stays the default because it can be read by eye.
+    pub fn binary_metadata(mut self) -> Self {
+        self.options.binary_metadata = true;
+        self
+    }
+
+    /// Treat missing crash metadata as an internal error.
+    ///
+    /// The EXECUTION child reports which crash point it stopped at on
+    /// stderr just before it is killed. If that report cannot be parsed,
+    /// FIRST by default still runs verify, with a stand-in [`CrashInfo`]
+    /// of point 0 labelled `"unknown"`, which tends to surface as a
+    /// confusing verify failure. With this option the crash point fails
+    /// instead, reported as an error in FIRST rather than in the engine
+    /// under test.
+    ///
+    /// This will become the default in a future version.
+    pub fn strict_metadata(mut self) -> Self {
+        self.options.strict_metadata = true;
+        self
+    }
+
+    /// Check that recovery survives its own crashes and is idempotent.
+    ///
+    /// `f` is the engine's recovery procedure, e.g. opening the database
+    /// and replaying its log, instrumented with crash points like the
+    /// workload. After the workload crashes at point K, FIRST recovers
+    /// copies of the crashed workspace in separate RECOVER processes:
+    /// once to completion, as the reference, and then, for each recovery
+    /// crash point J up to `max_points`, once crashing at J and once more
+    /// to completion. Every double-crash recovery must leave exactly the
+    /// same workspace files as the reference; otherwise crash point K
+    /// fails, naming the pair (K, J) and the first file that differs.
+    ///
+    /// The [`verify()`](Self::verify) closure still runs on the original
+    /// crashed workspace. Crash effects apply to recovery crashes too;
+    /// files present when recovery starts count as synced.
+    ///
+    /// Recovery must be deterministic: anything it writes must depend on
+    /// the workspace alone, not on time or randomness.
+    ///
+    /// # Example
+    ///
+    /// ```ignore
+    /// first::test()
+    ///     .run(|env| { /* write and commit */ })
+    ///     .recover_idempotent(10, |env| {
+    ///         Db::open(env.path("db")).replay_log();
+    ///     })
+    ///     .verify(|env, _| assert!(Db::open(env.path("db")).is_consistent()))
+    ///     .execute();
+    /// ```
+    pub fn recover_idempotent(mut self, max_points: usize, f: impl FnOnce(&Env) + 'static) -> Self {
+        self.recover_fn = Some(Box::new(f));
+        self.options.recovery_crashes = Some(max_points);
+        self
+    }
+
+    /// Add verification logic for specific crash points.
+    ///
+    /// At every crash point matched by `point` (an ID or a label), `f` runs
+    /// after the general [`verify()`](Self::verify) closure, in the same
+    /// VERIFY child. Several closures may match one point; they run in
+    /// registration order. This keeps point-specific expectations out of
+    /// one large `match` on the label.
+    ///
+    /// Every child process rebuilds the same test, so all closures are
+    /// available in the VERIFY child, which picks them by the crash point
+    /// it was told about.
+    ///
+    /// # Example
+    ///
+    /// ```ignore
+    /// first::test()
+    ///     .run(|env| { /* ... */ })
+    ///     .verify(|env, _| assert!(open_db(env).is_consistent()))
+    ///     .verify_at("after_commit", |env, _| {
+    ///         assert_eq!(open_db(env).get("key"), Some("value"));
+    ///     })
+    ///     .execute();
+    /// ```
+    pub fn verify_at(
+        mut self,
+        point: impl Into<PointSelector>,
+        f: impl FnOnce(&Env, &CrashInfo) + 'static,
+    ) -> Self {
+        self.point_verifiers.push(PointVerifier {
+            selector: point.into(),
+            replaces: false,
+            f: Box::new(f),
+        });
+        self
+    }
+
+    /// Like [`verify_at()`](Self::verify_at), but the general
+    /// [`verify()`](Self::verify) closure is skipped at matching points.
+    ///
+    /// Use it for points whose expected state the general closure cannot
+    /// describe.
+    pub fn verify_only_at(
+        mut self,
+        point: impl Into<PointSelector>,
+        f: impl FnOnce(&Env, &CrashInfo) + 'static,
+    ) -> Self {
+        self.point_verifiers.push(PointVerifier {
+            selector: point.into(),
+            replaces: true,
+            f: Box::new(f),
+        });
+        self
+    }
+
+    /// Track the number of open file descriptors during execution.
+    ///
+    /// At every crash point the EXECUTION child samples `/proc/self/fd` and
+    /// records the maximum seen so far. The value is reported as
+    /// [`CrashInfo::max_fds`] and printed next to each passing crash point.
+    ///
+    /// A maximum that climbs steadily across the sweep usually means the
+    /// engine leaks descriptors in its open or recovery path.
+    ///
+    /// Only supported on Linux; elsewhere `max_fds` is always `None`.
+    pub fn track_fds(mut self) -> Self {
+        self.options.track_fds = true;
+        self
+    }
+
+    /// Report each crash point as a synthetic test in libtest JSON output.
+    ///
+    /// When the harness runs with `-Z unstable-options --format json`, the
+    /// orchestrator writes `started` / `ok` / `failed` events for a test
+    /// named `<test_name>::crash_point_<N>` into the same stdout stream, so
+    /// tooling that consumes libtest JSON sees per-point results.
+    ///
+    /// Has no effect with the default human-readable output format.
+    pub fn libtest_json(mut self) -> Self {
+        self.options.libtest_json = true;
+        self
+    }
+
+    /// Leave garbage past the logical end of files when crashing.
+    ///
+    /// Just before the `SIGKILL`, every regular file in the workspace whose
+    /// size is not a multiple of its block size is extended to the next
+    /// block boundary with `fill`. This models filesystems that expose a
+    /// crashed file rounded up to a whole block, padded with zeroes or
+    /// stale data.
+    ///
+    /// Use it to check that recovery ignores trailing garbage instead of
+    /// misparsing it as a record.
+    pub fn block_padding(mut self, fill: u8) -> Self {
+        self.options.block_padding = Some(fill);
+        self
+    }
+
+    /// Enumerate all crash points before the sweep starts.
+    ///
+    /// The orchestrator first runs the workload once in a DISCOVER phase,
+    /// where `crash_point()` never crashes but reports each point. It then
+    /// prints the total and warns if the sweep explores a different number
+    /// of points, which indicates a nondeterministic workload.
+    ///
+    /// Discovery also makes the total known to verify, enabling
+    /// [`CrashInfo::is_last_point()`].
+    ///
+    /// Discovery results are cached under `/tmp/first/discovery` and reused
+    /// until the test binary is rebuilt. Set `FIRST_REDISCOVER=1` to force
+    /// a fresh discovery run.
+    pub fn discover(mut self) -> Self {
+        self.options.discover = true;
+        self
+    }
+
+    /// Fail the test if the workload does not reach the same crash points
+    /// in the same order in every run.
+    ///
+    /// Crash point IDs are only meaningful if they name the same point in
+    /// every child: a workload whose control flow depends on the clock or
+    /// on randomness may crash at a different point than the one reported,
+    /// so its failures do not reproduce and its passes may not cover what
+    /// they claim. With this option the orchestrator discovers the crash
+    /// points first (see [`discover()`](Self::discover)) and uses their
+    /// labels as the reference schedule. Every EXECUTION child checks each
+    /// crash point it reaches, up to its target, against that schedule and
+    /// fails at the first divergence with
+    /// `nondeterministic crash schedule: expected label "X" at point N, saw "Y"`.
+    ///
+    /// Points only differing by call site, but not by label, are not told
+    /// apart. Workloads with [`allow_threads()`](Self::allow_threads)
+    /// number crash points in scheduling order and are expected to fail
+    /// this check.
+    pub fn assert_deterministic(mut self) -> Self {
+        self.options.assert_deterministic = true;
+        self.discover()
+    }
+
+    /// Replay a recorded sequence of crashes instead of sweeping.
+    ///
+    /// The script lists crash targets, one per line, as numeric crash point
+    /// IDs or labels (`#` starts a comment). All steps share one workspace:
+    /// the workload runs until the first target and is killed, verify runs,
+    /// then the workload runs again on the recovered state until the next
+    /// target, and so on. This reproduces bugs that only appear after two or
+    /// more successive crashes.
+    ///
+    /// Labels are resolved to the ID of their first occurrence using a
+    /// DISCOVER run on a fresh workspace.
+    ///
+    /// The `FIRST_REPLAY_SCRIPT` environment variable overrides this path,
+    /// so a script can be replayed without editing the test.
+    ///
+    /// # Example
+    ///
+    /// ```ignore
+    /// first::test()
+    ///     .replay_script("tests/crashes/double_commit.txt")
+    ///     .run(|env| { /* workload */ })
+    ///     .verify(|env, crash| { /* invariants */ })
+    ///     .execute();
+    /// ```
+    pub fn replay_script(mut self, path: impl Into<PathBuf>) -> Self {
+        self.options.replay_script = Some(path.into());
+        self
+    }
+
+    /// Crash at each point of `schedule` in turn, in one workspace,
+    /// instead of sweeping.
+    ///
+    /// The workload runs until the first target and is killed, verify runs
+    /// on the crashed state, then the workload runs again from there until
+    /// the second target, and so on: `vec![3, 5]` crashes at point 3, then
+    /// at point 5 of the re-run. Each target is numbered within its own
+    /// run. This models recovery after several crashes in a row, which a
+    /// single crash cannot reach. Verify sees the crashes so far in
+    /// [`CrashInfo::history`].
+    ///
+    /// A target the re-run never reaches fails the test. This is
+    /// [`replay_script()`](Self::replay_script) with the schedule written
+    /// in the test; a script set there or in `FIRST_REPLAY_SCRIPT` takes
+    /// precedence.
+    ///
+    /// # Example
+    ///
+    /// ```ignore
+    /// first::test()
+    ///     .crash_schedule(vec![3, 5])
+    ///     .run(|env| { /* workload that recovers on open */ })
+    ///     .verify(|env, crash| {
+    ///         // crash.history is [3] after the first crash, [3, 5] after the second
+    ///     })
+    ///     .execute();
+    /// ```
+    pub fn crash_schedule(mut self, schedule: Vec<usize>) -> Self {
+        self.options.crash_schedule = Some(schedule);
+        self
+    }
+
+    /// Give the workspace the same absolute path in every iteration.
+    ///
+    /// For engines that persist absolute paths into their on-disk files.
+    /// The orchestrator still creates a fresh `run_N` directory per crash
+    /// point, but children see it through a symlink at
+    /// `$TMPDIR/first-stable-<alias>`, so [`Env::path()`] returns the same
+    /// string in every execution and verify child.
+    ///
+    /// # Symlink lifecycle
+    ///
+    /// - Re-pointed atomically at the next `run_N` before each iteration
+    /// - Removed when the sweep completes successfully
+    /// - Left pointing at the failing workspace when the sweep fails
+    ///
+    /// Use a distinct alias per test; tests sharing an alias race on it.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `alias` contains a path separator.
+    pub fn stable_path(mut self, alias: &str) -> Self {
+        assert!(
+            !alias.contains('/') && !alias.is_empty(),
+            "stable_path() alias must be a non-empty file name, got {:?}",
+            alias
+        );
+        self.options.stable_path = Some(alias.to_string());
+        self
+    }
+
+    /// Assert that no crash point labelled `label` is ever reached.
+    ///
+    /// Encodes control-flow invariants into the crash point structure, e.g.
+    /// "no writes happen after final shutdown". If the workload passes a
+    /// crash point with this label during the sweep, the EXECUTION child
+    /// fails with "expected-unreachable crash point was reached". With
+    /// [`discover()`](Self::discover), the violation is reported before any
+    /// crash point is explored.
+    ///
+    /// May be called multiple times to mark several labels.
+    pub fn assert_unreachable(mut self, label: &str) -> Self {
+        self.options.unreachable_labels.push(label.to_string());
+        self
+    }
+
+    /// Never crash at crash points labelled with one of `labels`.
+    ///
+    /// Matching points still count, so every other point keeps its ID,
+    /// but the sweep skips them: the EXECUTION child reaching one as its
+    /// target exits without crashing and verify does not run. Use it to
+    /// set aside a noisy or known-failing point, e.g. during an incident,
+    /// while the rest of the sweep keeps running. Skipped points are
+    /// listed at the end of the sweep so they are not forgotten.
+    ///
+    /// The `FIRST_DISABLE_LABELS` environment variable (comma-separated)
+    /// disables more labels without editing the test:
+    ///
+    /// ```text
+    /// FIRST_DISABLE_LABELS=after_compaction,after_gc cargo test
+    /// ```
+    ///
+    /// Disabled labels also apply to recovery crash points
+    /// ([`recover_idempotent()`](Self::recover_idempotent)).
+    pub fn disable_labels(mut self, labels: &[&str]) -> Self {
+        self.options
+            .disabled_labels
+            .extend(labels.iter().map(|l| l.to_string()));
+        self
+    }
+
+    /// Create workspace directories with these Unix permission bits.
+    ///
+    /// Applies to the workspace root and every directory added with
+    /// [`workspace_dirs()`](Self::workspace_dirs), for the sweep, discovery
+    /// and replay alike. Use it when the engine checks or depends on the
+    /// permissions of its data directory, e.g. `0o700`.
+    ///
+    /// Defaults to the process umask applied to `0o777`. The mode must keep
+    /// owner write and execute permission, or neither the workload nor
+    /// cleanup can create or remove files in the workspace.
+    ///
+    /// Unix only: the bits are set with `chmod` semantics.
+    pub fn workspace_mode(mut self, mode: u32) -> Self {
+        self.options.workspace_mode = Some(mode);
+        self
+    }
+
+    /// Create these subdirectories in every workspace up front.
+    ///
+    /// Mirrors a production layout the engine expects to exist already,
+    /// such as `data/`, `log/` and `tmp/`, so the workload does not fail on
+    /// a missing directory that deployment would have created. Nested
+    /// paths like `"data/tables"` are allowed.
+    ///
+    /// # Example
+    ///
+    /// ```ignore
+    /// first::test()
+    ///     .workspace_mode(0o750)
+    ///     .workspace_dirs(&["data", "log", "tmp"])
+    ///     .run(|env| { /* workload */ })
+    ///     .verify(|env, crash| { /* invariants */ })
+    ///     .execute();
+    /// ```
+    ///
+    /// # Panics
+    ///
+    /// Panics if a path is absolute or contains `..`.
+    pub fn workspace_dirs(mut self, dirs: &[&str]) -> Self {
+        for dir in dirs {
+            let path = PathBuf::from(dir);
+            assert!(
+                path.is_relative()
+                    && !path
+                        .components()
+                        .any(|c| c == std::path::Component::ParentDir),
+                "workspace_dirs() entries must stay inside the workspace, got {:?}",
+                dir
+            );
+            self.options.workspace_dirs.push(path);
+        }
+        self
+    }
+
+    /// Write a JSON report of the sweep to `path`, for CI dashboards and
+    /// scripts.
+    ///
+    /// The report is one JSON object, rewritten atomically after every
+    /// crash point, so a sweep that dies midway still leaves a valid
+    /// partial report. The `FIRST_REPORT_JSON` environment variable
+    /// overrides `path`. Only sweeps write a report, not the replay,
+    /// repeat or timed modes.
+    ///
+    /// ```json
+    /// {"version":1,"test":"wal_commit","run_tag":null,"seed":42,"complete":true,"points":[
+    ///   {"id":1,"label":"after_append","result":"pass","reason":null,"work_dir":"/tmp/first/run_1","duration_ms":12},
+    ///   {"id":2,"label":"after_commit","result":"fail","reason":"verification failed with exit code 101","work_dir":"/tmp/first/run_2","duration_ms":15}
+    /// ]}
+    /// ```
+    ///
+    /// | Field | Meaning |
+    /// |-------|---------|
+    /// | `version` | Format version, `1`; fields are only added within a version |
+    /// | `test` | Test name, or `null` if unknown |
+    /// | `run_tag` | [`run_tag()`](Self::run_tag), or `null` |
+    /// | `seed` | The run's [`seed()`](crate::seed()) |
+    /// | `complete` | `false` while the sweep runs, or if it was cut short |
+    /// | `points` | Crash points explored, in sweep order |
+    /// | `points[].id` | Crash point ID |
+    /// | `points[].label` | Crash point label |
+    /// | `points[].result` | `"pass"`, `"fail"` or `"skipped"` |
+    /// | `points[].reason` | Why the point failed, or `null` |
+    /// | `points[].work_dir` | Workspace of the point, kept when it failed |
+    /// | `points[].duration_ms` | Time from the EXECUTION spawn to the verdict |
+    pub fn report_json(mut self, path: impl Into<PathBuf>) -> Self {
+        self.options.report_json = Some(path.into());
+        self
+    }
+
+    /// Package a failing crash point into a shareable bundle at `path`.
+    ///
+    /// When verify fails, the orchestrator collects the post-crash
+    /// workspace, the [`CrashInfo`], the repro command, the verify child's
+    /// stdout and stderr, and a description of the environment into one
+    /// artifact ready to attach to a bug report. A previous bundle at
+    /// `path` is replaced.
+    ///
+    /// A path ending in `.tar.gz` or `.tgz` produces a gzipped tarball,
+    /// built with the system `tar`; any other path produces a directory.
+    /// Relative paths resolve against the test's working directory (the
+    /// package root under `cargo test`).
+    pub fn bundle_on_failure(mut self, path: impl Into<PathBuf>) -> Self {
+        self.options.bundle_on_failure = Some(path.into());
+        self
+    }
+
+    /// Re-check the failure captured in a bundle instead of sweeping.
+    ///
+    /// `path` is a bundle written by
+    /// [`bundle_on_failure()`](Self::bundle_on_failure), as a directory or
+    /// tarball. The workload does not run: a single VERIFY child gets a
+    /// copy of the bundled post-crash workspace and the captured
+    /// [`CrashInfo`], and runs this test's verify closures. A historical
+    /// failure thus stays reproducible after the workload has changed so
+    /// much that it no longer crashes at the same point, e.g. to check
+    /// that today's recovery handles yesterday's crashed state. The
+    /// `FIRST_REPLAY_BUNDLE` environment variable overrides `path`.
+    ///
+    /// # Limits
+    ///
+    /// Replay can only use what the bundle captured:
+    ///
+    /// - the workspace as it was after the crash, including crash effects
+    ///   such as `lose_unsynced_writes()`;
+    /// - the [`CrashInfo`], without `total_points`;
+    /// - the point's metadata sidecars, so a
+    ///   [`DurabilityManifest`](crate::DurabilityManifest),
+    ///   [`memory_checkpoint()`](crate::memory_checkpoint()) and
+    ///   [`assert_not_durable_before_barrier()`](crate::assert_not_durable_before_barrier)
+    ///   see the records of the original run.
+    ///
+    /// The verify code is the current one, not the one that failed: a
+    /// new invariant can be checked against the captured state, but only
+    /// if it needs nothing that was not recorded. Files written by verify
+    /// to [`Env::metadata_path()`] in earlier crash points, the committed
+    /// counts of [`record_committed()`](crate::invariants::record_committed)
+    /// and every other sweep-wide record are not part of a bundle, and
+    /// options that act on execution have no effect.
+    ///
+    /// # Example
+    ///
+    /// ```ignore
+    /// first::test()
+    ///     .replay_bundle("bugs/issue_42.tar.gz")
+    ///     .run(|_| {})
+    ///     .verify(|env, crash_info| { /* current recovery + invariants */ })
+    ///     .execute();
+    /// ```
+    pub fn replay_bundle(mut self, path: impl Into<PathBuf>) -> Self {
+        self.options.replay_bundle = Some(path.into());
+        self
+    }
+
+    /// Binary-search for the first failing crash point instead of
+    /// sweeping every point.
+    ///
+    /// The orchestrator discovers the crash points and runs the last one
+    /// first. If it passes, the test passes; if it fails, the points
+    /// before it are bisected, and the smallest failing point is reported
+    /// by ID and label with its reproduction command. A workload with 200
+    /// points then runs about 9 of them instead of up to 200.
+    ///
+    /// # Assumption
+    ///
+    /// Bisection assumes failures are monotonic: every point after a
+    /// failing point fails too, as when a bad write stays on disk for the
+    /// rest of the workload. When a bug shows only at some isolated points
+    /// this does not hold, and bisection may report a later failing point
+    /// or none at all. Set `FIRST_NO_BISECT=1` to fall back to the linear
+    /// sweep without changing the test.
+    ///
+    /// Only verify decides whether a point fails; options that add checks
+    /// of their own to the sweep, such as [`reader()`](Self::reader), do
+    /// not apply.
+    ///
+    /// # Example
+    ///
+    /// ```ignore
+    /// first::test()
+    ///     .bisect()
+    ///     .run(|env| { /* long workload */ })
+    ///     .verify(|env, _| { /* recovery + invariants */ })
+    ///     .execute();
+    /// ```
+    pub fn bisect(mut self) -> Self {
+        self.options.bisect = true;
+        self
+    }
+
+    /// Crash at one point `times` times instead of sweeping, and check
+    /// that every run leaves the same state.
+    ///
+    /// A workload can crash at a slightly different moment on each run
+    /// even at a fixed crash point, e.g. when a background thread races
+    /// the kill, which makes sweeps flaky without saying where. Each run
+    /// gets a fresh workspace and metadata directory; the workspace left
+    /// by every run, compared file by file, and its [`CrashInfo`] must
+    /// equal those of the first run. Runs that differ are reported with
+    /// what differs and kept under `/tmp/first/repeat` for inspection.
+    ///
+    /// `point` is a crash point ID or a label (its first occurrence,
+    /// found by a DISCOVER run). Verify does not run.
+    ///
+    /// # Example
+    ///
+    /// ```ignore
+    /// first::test()
+    ///     .repeat_point("after_flush", 20)
+    ///     .run(|env| { /* workload with background threads */ })
+    ///     .verify(|env, _| { /* unused in this mode */ })
+    ///     .execute();
+    /// ```
+    pub fn repeat_point(mut self, point: impl Into<PointSelector>, times: usize) -> Self {
+        self.options.repeat_point = Some((point.into(), times.max(2)));
+        self
+    }
+
+    /// Crash the workload once, `after` into its run, instead of sweeping
+    /// crash points.
+    ///
+    /// Engines that persist from timers (flush every 100ms, periodic
+    /// checkpoints) do their riskiest work where no
+    /// [`crash_point()`](crate::crash_point) marks it. In this mode the
+    /// EXECUTION child arms a timer when the workload starts and SIGKILLs
+    /// itself when it fires, whatever the workload is doing; crash points
+    /// are counted but never crashed at. Verify then runs as usual, with
+    /// [`CrashInfo::label`] set to `"crash_after_duration"`,
+    /// [`CrashInfo::point_id`] to the number of crash points passed
+    /// before the timer fired, and [`CrashInfo::elapsed`] to the run time
+    /// actually reached.
+    ///
+    /// Timers are not deterministic: the same duration lands at slightly
+    /// different places on every run. Use this as a fuzzing complement to
+    /// the deterministic sweep, e.g. over a range of durations, not as a
+    /// replacement. The workload finishing before the timer fires fails
+    /// the test, since nothing was crashed.
+    ///
+    /// # Example
+    ///
+    /// ```ignore
+    /// first::test()
+    ///     .crash_after_duration(Duration::from_millis(250))
+    ///     .run(|env| { /* workload with a 100ms background flush */ })
+    ///     .verify(|env, crash_info| { /* recovery */ })
+    ///     .execute();
+    /// ```
+    pub fn crash_after_duration(mut self, after: Duration) -> Self {
+        self.options.crash_after = Some(after);
+        self
+    }
+
+    /// Target crash points by call site instead of by counter.
+    ///
+    /// Counter-based IDs shift whenever a crash point is added earlier in
+    /// the workload. In this mode the orchestrator discovers every
+    /// [`crash_point!`](crate::crash_point!) call site, then crashes once at
+    /// the first hit of each site, identified by a stable hash of its
+    /// `file!()` and `line!()`. A reproduction stays valid as long as that
+    /// call site is unchanged.
+    ///
+    /// Only macro crash points carry a site: [`crash_point()`](crate::crash_point())
+    /// calls and the `Env` helpers are still counted but never targeted.
+    /// Implies a DISCOVER run, as with [`discover()`](Self::discover).
+    pub fn target_sites(mut self) -> Self {
+        self.options.target_sites = true;
+        self
+    }
+
+    /// Target crash points by label instead of by counter.
+    ///
+    /// Like [`target_sites()`](Self::target_sites), but keyed on the label
+    /// passed to [`crash_point()`](crate::crash_point()), so it works for
+    /// every crash point, macro or not. The orchestrator discovers the
+    /// distinct labels, then crashes once at the first crash point with
+    /// each label. The child is targeted with `FIRST_CRASH_LABEL_TARGET`,
+    /// so a reproduction stays valid when crash points with other labels
+    /// are added or moved. Set `FIRST_CRASH_LABEL_OCCURRENCE=<n>` with it
+    /// to crash at the `n`th crash point with the label instead.
+    ///
+    /// Cannot be combined with `target_sites()`. Implies a DISCOVER run,
+    /// as with [`discover()`](Self::discover).
+    pub fn target_labels(mut self) -> Self {
+        self.options.target_labels = true;
+        self
+    }
+
+    /// Reuse the workspace at the first crash point labelled `label` as
+    /// the starting state of the crash points after it.
+    ///
+    /// Every EXECUTION child normally runs the whole workload, so an
+    /// expensive setup, such as loading a dataset, is paid once per crash
+    /// point. With this option the first child that passes the `label`
+    /// point snapshots its workspace, cloned with reflinks where the file
+    /// system supports them. Children targeting later crash points start
+    /// from a copy of the snapshot, with the crash point counter already
+    /// at the `label` point, and [`Env::is_resumed()`] returns true: the
+    /// workload must then skip everything up to and including that crash
+    /// point.
+    ///
+    /// Only state in the workspace is restored. The workload must rebuild
+    /// anything else, such as open handles or caches, from the workspace,
+    /// and FIRST's tracking of unsynced writes (e.g.
+    /// `lose_unsynced_writes()`) does not see writes made before the
+    /// snapshot. A resumed child whose first crash point has another label
+    /// than in the run that took the snapshot fails as not resumable.
+    ///
+    /// # Example
+    ///
+    /// ```ignore
+    /// first::test()
+    ///     .snapshot_after("loaded")
+    ///     .run(|env| {
+    ///         if !env.is_resumed() {
+    ///             load_dataset(env);
+    ///             first::crash_point("loaded");
+    ///         }
+    ///         let mut db = Db::open(env.path("db")).unwrap();
+    ///         db.compact();
+    ///         first::crash_point("compacted");
+    ///     })
+    /// ```
+    pub fn snapshot_after(mut self, label: &str) -> Self {
+        self.options.snapshot_after = Some(label.to_string());
+        self
+    }
+
+    /// List the crash points of the workload instead of testing them.
+    ///
+    /// A dry run: the workload runs once to completion without crashing,
+    /// as in [`discover()`](Self::discover), and every crash point it
+    /// passes is printed with its ID, label and call site (for
+    /// [`crash_point!`](crate::crash_point!) points). Nothing is crashed
+    /// or verified, and the test passes. The listing is stable, so a CI
+    /// job can diff it to catch crash points added or removed by accident:
+    ///
+    /// ```text
+    /// [first] 3 crash points:
+    ///   1 "after_open"
+    ///   2 "after_write" tests/wal.rs:42
+    ///   3 "after_sync"
+    /// ```
+    ///
+    /// `FIRST_LIST_POINTS=1` does the same without changing the test.
+    pub fn list_points(mut self) -> Self {
+        self.options.list_points = true;
+        self
+    }
+
+    /// Simulate a reboot by evicting the workspace from the page cache
+    /// before verify runs.
+    ///
+    /// A `SIGKILL` ends the process but not the OS page cache, so the
+    /// verify child may be served pages that never reached the disk.
+    /// With this option the orchestrator drops the cached pages of every
+    /// workspace file after the crash (`posix_fadvise(POSIX_FADV_DONTNEED)`,
+    /// falling back to `/proc/sys/vm/drop_caches` when permitted), so
+    /// verify reads what is actually on disk.
+    ///
+    /// Dirty pages cannot be evicted: unsynced writes remain visible until
+    /// the kernel flushes them. Combine with [`Env::fsync()`] accounting or
+    /// [`Env::write_then_crash()`] to model lost writes explicitly.
+    ///
+    /// Linux only; failures are reported as warnings and verify still runs.
+    pub fn drop_caches(mut self) -> Self {
+        self.options.drop_caches = true;
+        self
+    }
+
+    /// Keep sweeping past failing crash points.
+    ///
+    /// By default the orchestrator stops at the first failure. With this
+    /// option each failing point is reported and its `run_N` directory kept,
+    /// the sweep moves on to the next point, and at the end a table of
+    /// every point with its result and a summary of every failure are
+    /// printed before the test fails. One run then yields the full list of
+    /// failing points for triage.
+    ///
+    /// A failing EXECUTION phase still ends the sweep, since later crash
+    /// points lie beyond the failure. With
+    /// [`bundle_on_failure()`](Self::bundle_on_failure), each failing point
+    /// gets its own bundle, named with a `_point_N` suffix.
+    pub fn continue_on_failure(mut self) -> Self {
+        self.options.continue_on_failure = true;
+        self
+    }
+
+    /// Alias of [`continue_on_failure()`](Self::continue_on_failure), after
+    /// `make -k`.
+    pub fn keep_going(self) -> Self {
+        self.continue_on_failure()
+    }
+
+    /// Expect the sweep to find a crash-consistency violation.
+    ///
+    /// Inverts the result: the test passes once a crash point fails, and
+    /// fails with "expected a crash-consistency violation but none was
+    /// found" if every point passes. The sweep stops at the first failing
+    /// point, which is reported as the expected violation; with
+    /// [`continue_on_failure()`](Self::continue_on_failure) it goes on and
+    /// lists them all. A decreasing
+    /// [`record_committed()`](crate::invariants::record_committed) count also counts
+    /// as a violation.
+    ///
+    /// Use it for tests of a component with a seeded bug, or of FIRST
+    /// itself, that must keep being caught.
+    ///
+    /// # Example
+    ///
+    /// ```ignore
+    /// first::test()
+    ///     .expect_violation()
+    ///     .run(|env| { /* workload with a known ordering bug */ })
+    ///     .verify(|env, crash_info| { /* invariants the bug breaks */ })
+    ///     .execute();
+    /// ```
+    pub fn expect_violation(mut self) -> Self {
+        self.options.expect_violation = true;
+        self
+    }
+
+    /// Name the `#[test]` this builder runs in, as `cargo test` lists it
+    /// (e.g. `wal::tests::commit_survives_crash`).
+    ///
+    /// Every child process re-runs the test binary filtered to this one
+    /// test. By default the orchestrator takes the name of the thread the
+    /// test harness runs the test on, and failing that, the filter on its
+    /// command line, which is wrong if that filter is a substring of the
+    /// name. A filter matching no test leaves the children running
+    /// nothing; the sweep fails instead of passing with no crash point.
+    /// Set the name when the test runs outside the harness's thread, e.g.
+    /// on a runtime worker. The `#[first::crash_test]` and `#[first::run]`
+    /// attributes set it for the test they generate.
+    pub fn test_name(mut self, name: &str) -> Self {
+        self.options.test_name = Some(name.to_string());
+        self
+    }
+
+    /// Name the test after the function `name` in the module
+    /// `module_path` (`module_path!()`), for the FIRST attributes.
+    #[doc(hidden)]
+    pub fn __test_fn(self, module_path: &str, name: &str) -> Self {
+        // The harness names tests by their path within the crate
+        let name = match module_path.split_once("::") {
+            Some((_, module)) => format!("{}::{}", module, name),
+            None => name.to_string(),
+        };
+        self.test_name(&name)
+    }
+
+    /// Make every instrumented barrier take at least `latency`.
+    ///
+    /// [`Env::fsync()`] and [`Env::fdatasync()`] sleep for `latency` after
+    /// the sync completes and before returning, modelling a slow disk.
+    /// Use it to exercise engines with fsync timeouts that give up and
+    /// proceed as if the data were durable. The delay is fixed, so runs
+    /// stay reproducible.
+    ///
+    /// Applies to the workload only; verify barriers are not delayed.
+    pub fn fsync_latency(mut self, latency: Duration) -> Self {
+        self.options.fsync_latency = Some(latency);
+        self
+    }
+
+    /// Interrupt every instrumented barrier with `EINTR` `count` times.
+    ///
+    /// [`Env::fsync()`] and [`Env::fdatasync()`] return an
+    /// [`ErrorKind::Interrupted`](std::io::ErrorKind::Interrupted) error,
+    /// without syncing, on the first `count` attempts of each barrier, and
+    /// succeed on the next. POSIX allows `fsync` to be interrupted and the
+    /// call must simply be retried; engines that treat `EINTR` as a hard
+    /// failure, or ignore it and assume the data is durable, are caught
+    /// here. The sequence is fixed, so every run behaves the same.
+    ///
+    /// Applies to the workload only. The number of injected errors is
+    /// reported as [`CrashInfo::eintr_count`].
+    pub fn fsync_eintr(mut self, count: usize) -> Self {
+        self.options.fsync_eintr = count;
+        self
+    }
+
+    /// Make the `nth` [`Env::fsync_dir()`] call of the workload fail.
+    ///
+    /// Directory syncs are easy to forget, and when present their errors
+    /// are rarely handled: an engine that ignores a failed directory sync
+    /// may acknowledge a file creation or rename that a crash then undoes.
+    /// In every EXECUTION run, the `nth` call (1-indexed) applies `fault`
+    /// instead of syncing, either returning the given OS error or
+    /// pretending to succeed; all other calls sync normally. The crash
+    /// points after it show whether the engine noticed.
+    ///
+    /// The directory, fault and position are reported to verify as
+    /// [`CrashInfo::dir_fsync_fault`] at every crash point after the call.
+    ///
+    /// # Example
+    ///
+    /// ```ignore
+    /// first::test()
+    ///     .fail_dir_fsync(1, first::DirFsyncFault::Error(libc::EIO))
+    ///     .run(|env| { /* create files, then env.fsync_dir("") */ })
+    ///     .verify(|env, crash_info| {
+    ///         if crash_info.dir_fsync_fault.is_some() {
+    ///             // the engine must not have acknowledged the new file
+    ///         }
+    ///     })
+    ///     .execute();
+    /// ```
+    pub fn fail_dir_fsync(mut self, nth: usize, fault: DirFsyncFault) -> Self {
+        self.options.dir_fsync_fault = Some((nth.max(1), fault));
+        self
+    }
+
+    /// Crash in the middle of each [`fsync_latency()`](Self::fsync_latency)
+    /// window.
+    ///
+    /// Adds a crash point labelled `fsync_latency_window` halfway through
+    /// every delayed barrier. At that point the data is durable but the
+    /// barrier has not returned, and it is not yet counted in
+    /// [`CrashInfo::barriers()`]. Has no effect without `fsync_latency()`.
+    pub fn crash_during_fsync(mut self) -> Self {
+        self.options.crash_during_fsync = true;
+        self
+    }
+
+    /// Crash inside every barrier on one of `files`, with their pending
+    /// data only partly written.
+    ///
+    /// Each [`Env::fsync()`] or [`Env::fdatasync()`] on a listed file
+    /// (relative to the workspace) becomes a crash point labelled
+    /// `label`, reached before the data is synced. At that point a power
+    /// loss while the drive was flushing is simulated: of the bytes each
+    /// listed file gained since its last sync, a subset of 4 KiB pages
+    /// reaches the disk, the pages in between read back as zeroes, and
+    /// the file ends after the last persisted page. The subset is chosen
+    /// by the [`shuffle()`](Self::shuffle) seed (or 0) and the crash
+    /// point, so every run persists the same pages, and is reported in
+    /// [`CrashInfo::partial_flush()`].
+    ///
+    /// Unlike [`crash_during_fsync()`](Self::crash_during_fsync), which
+    /// crashes after the data is durable, this is the worst case for
+    /// engines that trust a half-written tail. Only data past a file's
+    /// synced length is tracked as pending; overwrites inside it survive.
+    ///
+    /// # Example
+    ///
+    /// ```ignore
+    /// first::test()
+    ///     .crash_mid_fsync("wal_fsync", &["wal.log"])
+    ///     .run(|env| { /* append records, env.fsync(&wal) */ })
+    ///     .verify(|env, crash_info| {
+    ///         // recovery must discard the torn records
+    ///     })
+    ///     .execute();
+    /// ```
+    pub fn crash_mid_fsync(mut self, label: &str, files: &[&str]) -> Self {
+        self.options.mid_fsync =
+            Some((label.to_string(), files.iter().map(PathBuf::from).collect()));
+        self
+    }
+
+    /// Lose every byte appended since a file's last sync at the crash.
+    ///
+    /// Just before the `SIGKILL`, each regular file in the workspace is
+    /// truncated to its length at its last [`Env::fsync()`],
+    /// [`Env::fdatasync()`] or [`atomic_write()`](crate::atomic_write);
+    /// files never synced that way are truncated to zero bytes. Syncs are
+    /// tracked per inode, across every handle and rename, so a crash
+    /// between the syncs of a multi-file commit leaves exactly the files
+    /// synced so far.
+    ///
+    /// Only unsynced tails are modelled: overwrites inside the synced
+    /// length survive, and so do directory entries. Files synced with
+    /// plain [`File::sync_all()`](std::fs::File::sync_all) count as never
+    /// synced.
+    pub fn lose_unsynced_writes(mut self) -> Self {
+        self.options.lose_unsynced_writes = true;
+        self
+    }
+
+    /// Lose every write not made durable by a sync at the crash, as a
+    /// power loss would.
+    ///
+    /// A `SIGKILL` leaves the page cache intact, so by default every
+    /// `write()` survives the crash and a missing fsync goes unnoticed.
+    /// With this option, FIRST snapshots a file's contents each time it is
+    /// synced through [`Env::fsync()`], [`Env::fdatasync()`] or
+    /// [`atomic_write()`](crate::atomic_write), keyed by inode so the
+    /// snapshot follows renames. Just before the `SIGKILL`, every regular
+    /// file in the workspace is rolled back to its snapshot, and files
+    /// never synced that way are truncated to zero bytes.
+    ///
+    /// This supersedes [`lose_unsynced_writes()`](Self::lose_unsynced_writes),
+    /// which only cuts unsynced tails: here unsynced overwrites inside a
+    /// file are undone too. Limits of the interception:
+    ///
+    /// - Only the instrumented calls count as syncs; plain
+    ///   [`File::sync_all()`](std::fs::File::sync_all) and `sync_data()`
+    ///   are invisible, so their files are treated as never synced.
+    /// - Directory entries are not rolled back: a created or renamed file
+    ///   keeps its name even if its directory was never synced.
+    /// - Everything unsynced is lost, which is the worst case; a real
+    ///   crash may keep some of it (see
+    ///   [`crash_mid_fsync()`](Self::crash_mid_fsync) for partial flushes).
+    /// - Each sync copies the whole file into memory, so keep synced files
+    ///   small.
+    pub fn drop_unsynced(mut self) -> Self {
+        self.options.drop_unsynced = true;
+        self
+    }
+
+    /// Tear the most recent unsynced write at the crash, as a power loss
+    /// in the middle of a sector would.
+    ///
+    /// FIRST logs the offset and length of the last write to each file made
+    /// through [`Env::write_all()`] or [`Env::write_all_at()`], until the
+    /// file is synced through [`Env::fsync()`] or [`Env::fdatasync()`].
+    /// Just before the `SIGKILL`, the most recent logged write keeps only
+    /// its first half: a write at the end of the file is truncated there,
+    /// and one inside the file has its second half zeroed. Verify then
+    /// finds a partial record, which recovery must detect and discard.
+    ///
+    /// The tear is reported as [`CrashInfo::partial_write`]. Writes made
+    /// any other way are not logged and never torn, and a crash point
+    /// reached with no unsynced logged write leaves the files as they are.
+    pub fn torn_last_write(mut self) -> Self {
+        self.options.torn_last_write = true;
+        self
+    }
+
+    /// Lose every memory-mapped page that was not `msync`ed at the crash.
+    ///
+    /// Just before the `SIGKILL`, each page of a live [`Env::mmap()`]
+    /// mapping that changed since its last [`MappedFile::msync()`] is
+    /// rewritten with its last synced contents, as if the kernel never got
+    /// to write it back. Without this option dirty pages are left to the
+    /// page cache and usually survive the crash, hiding missing `msync`
+    /// calls.
+    ///
+    /// [`MappedFile::msync()`]: crate::MappedFile::msync
+    pub fn lose_unsynced_mmap(mut self) -> Self {
+        self.options.lose_unsynced_mmap = true;
+        self
+    }
+
+    /// Define the verification logic, and call it in the orchestrator
+    /// process itself at each crash point instead of spawning a VERIFY
+    /// child.
+    ///
+    /// Replaces [`verify()`](Self::verify). After the EXECUTION child is
+    /// killed, the orchestrator calls `f` directly on the crashed
+    /// workspace, so `f` must be `Fn`. This saves starting the test binary
+    /// once per crash point, which dominates the run time of small
+    /// workloads. A panic fails the crash point as in a VERIFY child; only
+    /// the sweep's verification runs in process, while replays and the
+    /// other modes keep their children. Crash points matched by a
+    /// [`verify_at()`](Self::verify_at) closure, which is called at most
+    /// once, are still verified in a VERIFY child.
+    ///
+    /// Verification is no longer isolated:
+    ///
+    /// - Global state (statics, thread-locals, caches, open handles leaked
+    ///   by the closure) persists from one crash point to the next, and
+    ///   is shared with the orchestrator.
+    /// - A verify closure that aborts, loops forever or exhausts memory
+    ///   takes the whole test down, and `resource_limits()` does not
+    ///   apply to it.
+    /// - Helpers that take no [`Env`] and read the crash from the VERIFY
+    ///   child's environment, namely [`checked()`](crate::checked) and
+    ///   [`record_committed()`](crate::invariants::record_committed), see
+    ///   no crash and record nothing. Those that take the `Env`, such as
+    ///   [`Env::expected()`], [`memory_checkpoint()`](crate::memory_checkpoint())
+    ///   and [`Env::persist()`], work as in a VERIFY child.
+    ///
+    /// Opt in for speed-sensitive suites whose verify only reads the
+    /// workspace.
+    ///
+    /// # Example
+    ///
+    /// ```ignore
+    /// first::test()
+    ///     .run(|env| { /* workload */ })
+    ///     .verify_in_process(|env, _| assert!(open_db(env).is_consistent()))
+    ///     .execute();
+    /// ```
+    pub fn verify_in_process(mut self, f: impl Fn(&Env, &CrashInfo) + 'static) -> Self {
+        self.options.verify_in_process = true;
+        self.verify_fn = None;
+        self.shared_verify = Some(Box::new(f));
+        self
+    }
+
+    /// Fail the test if recovery after any crash point takes longer than
+    /// `limit`.
+    ///
+    /// The verify closure is timed at every crash point, and the first
+    /// point whose recovery exceeds `limit` fails with its measured time.
+    /// To time only part of the closure, such as opening the database but
+    /// not the checks that follow, wrap that part in
+    /// [`recovery_timer()`](crate::recovery_timer). With `FIRST_VERBOSE=1`
+    /// the recovery time of every crash point is printed.
+    ///
+    /// Wall-clock timing depends on the machine; leave generous headroom
+    /// so the limit catches pathological slowdowns, not noise.
+    pub fn max_recovery_time(mut self, limit: Duration) -> Self {
+        self.options.max_recovery_time = Some(limit);
+        self
+    }
+
+    /// Kill any child that runs longer than `limit`, failing its crash
+    /// point as timed out.
+    ///
+    /// Every EXECUTION, VERIFY and discovery child gets its own deadline.
+    /// A workload or recovery that deadlocks or spins forever is killed
+    /// with `SIGKILL` and reported with its crash label and work dir,
+    /// instead of hanging the test. A timed-out workload ends the sweep,
+    /// as later points would hang at the same place.
+    ///
+    /// `FIRST_TIMEOUT_SECS` (e.g. `FIRST_TIMEOUT_SECS=30` or `0.5`)
+    /// overrides `limit`, and sets a timeout for tests that configure
+    /// none. Verification run by
+    /// [`verify_in_process()`](Self::verify_in_process) has no child to
+    /// kill and is not timed out.
+    ///
+    /// # Example
+    ///
+    /// ```ignore
+    /// first::test()
+    ///     .timeout(Duration::from_secs(30))
+    ///     .run(|env| { /* workload */ })
+    ///     .verify(|env, crash_info| { /* recovery */ })
+    ///     .execute();
+    /// ```
+    pub fn timeout(mut self, limit: Duration) -> Self {
+        self.options.timeout = Some(limit);
+        self
+    }
+
+    /// Collect coverage profiles of every crash point into `dir`.
+    ///
+    /// Each EXECUTION and VERIFY child is started with `LLVM_PROFILE_FILE`
+    /// pointing into `dir` (e.g. `verify_point_3_<pid>.profraw`), so a
+    /// build instrumented for coverage leaves one profile per child. As a
+    /// killed process never writes its profile, EXECUTION children exit
+    /// with status 137 at the crash point instead of raising `SIGKILL`;
+    /// like `SIGKILL`, this runs no destructors and flushes no user
+    /// buffers.
+    ///
+    /// Merging the profiles shows how much of the recovery code the sweep
+    /// exercised:
+    ///
+    /// ```text
+    /// RUSTFLAGS="-C instrument-coverage" cargo test --test my_test
+    /// llvm-profdata merge -sparse <dir>/*.profraw -o sweep.profdata
+    /// llvm-cov report --instr-profile sweep.profdata <test binary>
+    /// ```
+    ///
+    /// Without `-C instrument-coverage` no profiles are written.
+    pub fn coverage_dir(mut self, dir: impl Into<PathBuf>) -> Self {
+        self.options.coverage_dir = Some(dir.into());
+        self
+    }
+
+    /// Sweep the crash points in an order shuffled by `seed`.
+    ///
+    /// Every crash point gets a fresh workspace, so the order must not
+    /// matter; a result that depends on it means state leaks between runs,
+    /// e.g. through [`Env::metadata_path()`] or files outside the
+    /// workspace. The points are found by a DISCOVER run first, and the
+    /// order is printed at the start of the sweep; the same seed always
+    /// gives the same order.
+    ///
+    /// When a point fails, it is re-run on its own with a fresh workspace
+    /// and metadata directory. If it passes there, the failure is reported
+    /// as an isolation violation, listing the points that ran before it.
+    pub fn shuffle(mut self, seed: u64) -> Self {
+        self.options.shuffle = Some(seed);
+        self
+    }
+
+    /// Explore at most the first `n` crash points.
+    ///
+    /// For a quick smoke test of a workload with many crash points, e.g.
+    /// in a pre-commit hook, next to a full sweep run less often. Once `n`
+    /// points are swept the sweep stops and reports "explored first `n`
+    /// crash points (capped)", which passes like a complete sweep but is
+    /// not one: the points after the cap were never crashed at. A
+    /// workload with at most `n` points is swept completely as usual.
+    ///
+    /// The cap applies to the sweep order, so with
+    /// [`shuffle()`](Self::shuffle) it keeps the first `n` points of the
+    /// shuffled order.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `n` is 0.
+    pub fn max_crash_points(mut self, n: usize) -> Self {
+        assert!(
+            n > 0,
+            "max_crash_points() must allow at least 1 crash point"
+        );
+        self.options.max_crash_points = Some(n);
+        self
+    }
+
+    /// Declare that the workload calls crash points from threads it
+    /// spawns, such as background flush or compaction threads.
+    ///
+    /// Crash points on any thread draw distinct IDs from one counter, in
+    /// scheduling order, so which operation a given ID lands on can vary
+    /// between runs. Exactly one thread crashes the process; any other
+    /// thread that reaches a crash point meanwhile blocks there until the
+    /// SIGKILL. Without this, crashing at a crash point off the workload
+    /// thread prints a warning.
+    ///
+    /// Prefer joining threads before sweeping where possible: a fixed order
+    /// keeps every failure reproducible.
+    pub fn allow_threads(mut self) -> Self {
+        self.options.allow_threads = true;
+        self
+    }
+
+    /// Fix the seed returned by [`crate::seed()`] in the workload and verify.
+    ///
+    /// Without it, a seed is generated for each sweep. Either way it is
+    /// passed to every child as `FIRST_SEED`, so all crash points see the
+    /// same value, and the reproduction command of a failure includes it.
+    /// A `FIRST_SEED` set in the environment takes precedence.
+    pub fn seed(mut self, seed: u64) -> Self {
+        self.options.seed = Some(seed);
+        self
+    }
+
+    /// Sweep crash points in recently changed files exhaustively, and only
+    /// `sample_percent` percent of the others.
+    ///
+    /// The changed files are read from `FIRST_CHANGED_FILES`, separated by
+    /// commas or newlines, so a CI job can pass the diff straight through:
+    ///
+    /// ```text
+    /// FIRST_CHANGED_FILES="$(git diff --name-only origin/main)" cargo test
+    /// ```
+    ///
+    /// Points are mapped to files by their [`crash_point!`](crate::crash_point!)
+    /// call site; plain [`crash_point()`](crate::crash_point) points have
+    /// none and are always sampled. The points are found by a DISCOVER run,
+    /// points in changed files are swept first, and the sample is chosen
+    /// with the [`shuffle()`](Self::shuffle) seed (or 0), so it is the same
+    /// on every run. The start of the sweep lists which points are swept
+    /// exhaustively and which were sampled. Without `FIRST_CHANGED_FILES`,
+    /// every point is swept.
+    ///
+    /// Works with [`target_sites()`](Self::target_sites), where it selects
+    /// call sites instead of points, and with
+    /// [`target_labels()`](Self::target_labels), where a label lies in the
+    /// file of its first crash point.
+    pub fn prioritize_changed(mut self, sample_percent: u32) -> Self {
+        self.options.prioritize_changed = Some(sample_percent);
+        self
+    }
+
+    /// Sweep `count` crash points picked at random with `seed`, instead of
+    /// every one.
+    ///
+    /// For workloads with too many crash points to sweep exhaustively: the
+    /// points are counted by a DISCOVER run first, and each run crashes at
+    /// a different random sample when the seed changes, e.g. per CI run,
+    /// covering the crash space over time in bounded time per run. The
+    /// start of the sweep lists the sampled points, which are swept in
+    /// ascending order (or in the [`shuffle()`](Self::shuffle) order); the
+    /// same seed always gives the same sample, so a failing sample is
+    /// reproduced by re-running with its seed. With `count` at least the
+    /// number of points, every point is swept.
+    ///
+    /// Works with [`target_sites()`](Self::target_sites) and
+    /// [`target_labels()`](Self::target_labels), where it samples call
+    /// sites or labels. Cannot be combined with
+    /// [`prioritize_changed()`](Self::prioritize_changed), which samples
+    /// on its own.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `count` is 0.
+    pub fn sample(mut self, count: usize, seed: u64) -> Self {
+        assert!(count > 0, "sample() must pick at least 1 crash point");
+        self.options.sample = Some((count, seed));
+        self
+    }
+
+    /// Tag the run with a correlation id, e.g. a CI pipeline or commit.
+    ///
+    /// The tag is added as a `"run_tag"` field to every JSON event (the
+    /// libtest events of [`libtest_json()`](Self::libtest_json) and the
+    /// crash events of EXECUTION children) and to the summary line, so
+    /// telemetry can join crash test results with whatever triggered
+    /// them. Without this method the tag is read from `FIRST_RUN_TAG`,
+    /// which child processes inherit.
+    pub fn run_tag(mut self, tag: impl Into<String>) -> Self {
+        self.options.run_tag = Some(tag.into());
+        self
+    }
+
+    /// Name this invocation, for tests with several `first::test()`
+    /// invocations in one `#[test]` function.
+    ///
+    /// Each invocation is swept on its own, in the order the function
+    /// reaches them, with crash point IDs counted from 1 per invocation.
+    /// Children run only the invocation being swept, so independent crash
+    /// sequences can be built in a loop:
+    ///
+    /// ```ignore
+    /// #[test]
+    /// fn every_format_recovers() {
+    ///     for format in ["v1", "v2"] {
+    ///         first::test()
+    ///             .label(format)
+    ///             .run(move |env| write_log(env, format))
+    ///             .verify(move |env, _| assert_recovers(env, format))
+    ///             .execute();
+    ///     }
+    /// }
+    /// ```
+    ///
+    /// The label appears in the reproduction command as
+    /// `FIRST_INVOCATION="<label>"` (unlabelled invocations use their
+    /// 1-indexed position) and names the work dir root of the invocation,
+    /// `/tmp/first/invocation_<label>/`. Labels must be unique within the
+    /// function. The sequence of invocations must be deterministic, since
+    /// every child runs the function again to find its invocation.
+    /// Each invocation applies its own `resource_limits()` and
+    /// `loopback_fs()`; invocations with the same `loopback_fs()` share
+    /// one image.
+    pub fn label(mut self, name: impl Into<String>) -> Self {
+        self.options.label = Some(name.into());
+        self
+    }
+
+    /// Export the crash point timeline as a Graphviz DOT file.
+    ///
+    /// Before the sweep, the orchestrator runs the workload once in a
+    /// DISCOVER phase and records the instrumented filesystem operations
+    /// ([`Env::write_then_crash()`], [`Env::fsync()`],
+    /// [`Env::fdatasync()`] and the steps of [`atomic_write()`](crate::atomic_write))
+    /// interleaved with every crash point. The result is written to `path`
+    /// as a chain of nodes, showing which writes and barriers precede each
+    /// crash point. Render it with e.g. `dot -Tsvg`.
+    ///
+    /// Plain `std::fs` calls are not instrumented and do not appear.
+    pub fn export_graph(mut self, path: impl Into<PathBuf>) -> Self {
+        self.options.export_graph = Some(path.into());
+        self
+    }
+
     /// Execute the test based on current phase.
     ///
     /// - Orchestrator: runs the supervisor loop
@@ -104,32 +1760,175 @@ where
     /// | Execution   | Test fails    | Schedule exhausted | Crash (expected)  |
     /// | Verify      | Test fails    | Verification OK    | Test fails        |
     pub fn execute(self) {
+        assert!(
+            self.async_run_fn.is_none(),
+            "run_async() workloads must be run with execute_async().await, e.g. in a #[tokio::test]"
+        );
+        if self.begin_invocation() {
+            self.execute_phase();
+        }
+    }
+
+    /// Start this invocation, returning whether this process runs it.
+    fn begin_invocation(&self) -> bool {
+        crate::invocation::begin(
+            self.options.label.as_deref(),
+            runtime().phase == Phase::Orchestrator,
+        )
+    }
+
+    /// Run the phase of this process for a synchronous workload.
+    fn execute_phase(self) {
         let config = runtime();
-        let work_dir = std::env::var("FIRST_WORK_DIR")
-            .map(PathBuf::from)
-            .unwrap_or_else(|_| std::env::temp_dir().join("first").join("default"));
+        let (work_dir, metadata_dir) = phase_dirs();
 
         match config.phase {
             Phase::Orchestrator => {
-                // Step 3 will implement this
-                crate::orchestrator::run(self.run_fn, self.verify_fn);
+                let verify =
+                    |env: &Env, crash_info: &CrashInfo| self.verify_shared(env, crash_info);
+                let in_process: crate::orchestrator::VerifyRef<'_> = &verify;
+                crate::orchestrator::run(
+                    self.options.verify_in_process.then_some(in_process),
+                    &self.options,
+                );
             }
-            Phase::Execution => {
+            Phase::Execution | Phase::Discover => {
+                start_workload(self.options, config.phase, &work_dir, &metadata_dir);
                 if let Some(run_fn) = self.run_fn {
-                    let env = Env::new(work_dir);
+                    let env = Env::new(work_dir, metadata_dir);
                     run_fn(&env);
                 }
+                if config.phase == Phase::Execution {
+                    crate::fault::finish_workload();
+                }
+            }
+            Phase::Recover => {
+                crate::rt::install_options(self.options);
+                if let Some(recover_fn) = self.recover_fn {
+                    crate::crash::mark_durable(&work_dir);
+                    let env = Env::new(work_dir, metadata_dir);
+                    recover_fn(&env);
+                }
+            }
+            Phase::Read => {
+                if let Some(reader_fn) = self.reader_fn {
+                    let env = Env::new(work_dir, metadata_dir);
+                    crate::reader::run(&env, reader_fn);
+                }
             }
             Phase::Verify => {
-                if let Some(verify_fn) = self.verify_fn {
-                    let env = Env::new(work_dir);
+                if self.verify_fn.is_some()
+                    || self.shared_verify.is_some()
+                    || !self.point_verifiers.is_empty()
+                {
+                    let env = Env::new(work_dir, metadata_dir);
                     // Parse crash info from env var
                     let crash_info = parse_crash_info();
-                    verify_fn(&env, &crash_info);
+                    self.verify_crash(&env, &crash_info);
                 }
             }
         }
     }
+
+    /// Execute the test phase of this process, awaiting the
+    /// [`run_async()`](Self::run_async) workload.
+    ///
+    /// Like [`execute()`](Self::execute), for use in an async test such as
+    /// `#[tokio::test]`. Every phase but EXECUTION runs synchronously, so
+    /// the orchestrator blocks the runtime's thread for the whole sweep.
+    pub async fn execute_async(mut self) {
+        if !self.begin_invocation() {
+            return;
+        }
+        let config = runtime();
+        let Some(async_run_fn) = self.async_run_fn.take() else {
+            return self.execute_phase();
+        };
+        if !matches!(config.phase, Phase::Execution | Phase::Discover) {
+            return self.execute_phase();
+        }
+        let (work_dir, metadata_dir) = phase_dirs();
+        start_workload(self.options, config.phase, &work_dir, &metadata_dir);
+        async_run_fn(Env::new(work_dir, metadata_dir)).await;
+        if config.phase == Phase::Execution {
+            crate::fault::finish_workload();
+        }
+    }
+
+    /// Call the verify closures that apply to `crash_info`, timing
+    /// recovery.
+    fn verify_crash(self, env: &Env, crash_info: &CrashInfo) {
+        let (matching, _): (Vec<_>, Vec<_>) = self
+            .point_verifiers
+            .into_iter()
+            .partition(|v| v.selector.matches(crash_info));
+        let replaced = matching.iter().any(|v| v.replaces);
+        let verify_fn = self.verify_fn.filter(|_| !replaced);
+        let shared_verify = self.shared_verify.filter(|_| !replaced);
+        let elapsed = crate::recovery::time(|| {
+            crate::diagnose::run_verify(crash_info, || {
+                if let Some(verify_fn) = verify_fn {
+                    verify_fn(env, crash_info);
+                }
+                if let Some(verify_fn) = shared_verify {
+                    verify_fn(env, crash_info);
+                }
+                for verifier in matching {
+                    (verifier.f)(env, crash_info);
+                }
+            })
+        });
+        crate::recovery::check(crash_info, elapsed, self.options.max_recovery_time);
+    }
+
+    /// Call the [`verify_in_process()`](Self::verify_in_process) closure
+    /// at `crash_info`, timing recovery, unless a `verify_at()` closure
+    /// applies and needs a VERIFY child.
+    fn verify_shared(&self, env: &Env, crash_info: &CrashInfo) -> bool {
+        if self
+            .point_verifiers
+            .iter()
+            .any(|v| v.selector.matches(crash_info))
+        {
+            return false;
+        }
+        let elapsed = crate::recovery::time(|| {
+            crate::diagnose::run_verify_in_process(crash_info, || {
+                if let Some(verify_fn) = &self.shared_verify {
+                    verify_fn(env, crash_info);
+                }
+            })
+        });
+        crate::recovery::check(crash_info, elapsed, self.options.max_recovery_time);
+        true
+    }
+}
+
+/// Work and metadata directories of this child, from its environment.
+fn phase_dirs() -> (PathBuf, PathBuf) {
+    let work_dir = std::env::var("FIRST_WORK_DIR")
+        .map(PathBuf::from)
+        .unwrap_or_else(|_| std::env::temp_dir().join("first").join("default"));
+    let metadata_dir = std::env::var("FIRST_METADATA_DIR")
+        .map(PathBuf::from)
+        .unwrap_or_else(|_| work_dir.with_file_name("meta"));
+    (work_dir, metadata_dir)
+}
+
+/// Install the options of a workload child, arming the crash timer of
+/// `crash_after_duration()` and restoring a `snapshot_after()` snapshot in
+/// the EXECUTION phase.
+fn start_workload(options: Options, phase: Phase, work_dir: &Path, metadata_dir: &Path) {
+    if phase == Phase::Execution {
+        crate::orchestrator::mark_started(metadata_dir);
+    }
+    crate::rt::install_options(options);
+    crate::rt::resume_from_snapshot(work_dir, metadata_dir);
+    if phase == Phase::Execution
+        && let Some(after) = crate::rt::options().crash_after
+    {
+        crate::rt::arm_crash_timer(after);
+    }
 }
 
 /// Parse crash info from environment variable.
@@ -139,5 +1938,49 @@ fn parse_crash_info() -> CrashInfo {
         .and_then(|s| s.parse().ok())
         .unwrap_or(0);
     let label = std::env::var("FIRST_CRASH_LABEL").unwrap_or_else(|_| "unknown".to_string());
-    CrashInfo::new(point_id, label)
+    let mut info = CrashInfo::new(point_id, label);
+    info.work_dir = std::env::var_os("FIRST_WORK_DIR")
+        .map(PathBuf::from)
+        .unwrap_or_default();
+    info.max_fds = std::env::var("FIRST_CRASH_MAX_FDS")
+        .ok()
+        .and_then(|s| s.parse().ok());
+    info.partial_write = std::env::var("FIRST_CRASH_PARTIAL_WRITE")
+        .ok()
+        .and_then(|s| PartialWrite::from_env(&s));
+    info.eintr_count = std::env::var("FIRST_CRASH_EINTR_COUNT")
+        .ok()
+        .and_then(|s| s.parse().ok())
+        .unwrap_or(0);
+    info.dir_fsync_fault = std::env::var("FIRST_CRASH_DIR_FSYNC")
+        .ok()
+        .and_then(|s| InjectedDirFsync::from_env(&s));
+    info.fault = std::env::var("FIRST_CRASH_FAULT")
+        .ok()
+        .and_then(|s| crate::fault::InjectedFault::from_env(&s));
+    info.elapsed = std::env::var("FIRST_CRASH_ELAPSED_US")
+        .ok()
+        .and_then(|s| s.parse().ok())
+        .map(Duration::from_micros);
+    info.fsync_count = std::env::var("FIRST_CRASH_FSYNC_COUNT")
+        .ok()
+        .and_then(|s| s.parse().ok())
+        .unwrap_or(0);
+    info.total_points = std::env::var("FIRST_TOTAL_POINTS")
+        .ok()
+        .and_then(|s| s.parse().ok());
+    if let Ok(site) = std::env::var("FIRST_CRASH_SITE") {
+        info.set_site(&site);
+    }
+    info.history = std::env::var("FIRST_CRASH_HISTORY")
+        .ok()
+        .and_then(|s| s.split(',').map(|id| id.parse().ok()).collect())
+        .unwrap_or_else(|| vec![point_id]);
+    info.barriers = std::env::var("FIRST_CRASH_BARRIERS")
+        .map(|s| s.lines().filter_map(BarrierRecord::from_env).collect())
+        .unwrap_or_default();
+    info.partial_flush = std::env::var("FIRST_CRASH_PARTIAL_FLUSH")
+        .map(|s| s.lines().filter_map(PartialFlush::from_env).collect())
+        .unwrap_or_default();
+    info
 }
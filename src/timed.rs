@@ -0,0 +1,120 @@
+//! Crashes fired by a timer.
+//!
+//! With `TestBuilder::crash_after_duration()`, the orchestrator does not
+//! sweep. It runs one EXECUTION child that never crashes at a crash point
+//! but arms a timer when the workload starts and SIGKILLs itself when it
+//! fires, then verifies the workspace left behind. Where the crash lands
+//! depends on scheduling, so this complements the deterministic sweep
+//! rather than being part of it.
+
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+use crate::orchestrator::{self, ChildResult, FIRST_BASE_DIR, cleanup_work_dir, io_failure};
+use crate::test::Options;
+
+/// Crash target meaning "never crash at a crash point".
+const NO_TARGET: usize = usize::MAX;
+
+/// Crash the workload `after` into its run and verify the result.
+///
+/// Exits the process with status 1 if the workload finishes first or
+/// verify fails.
+pub(crate) fn run(
+    exe: &Path,
+    test_name: &Option<String>,
+    metadata_dir: &Path,
+    after: Duration,
+    options: &Options,
+) {
+    let work_dir = PathBuf::from(FIRST_BASE_DIR).join("timed");
+    cleanup_work_dir(&work_dir);
+    if let Err(e) = orchestrator::create_work_dir(&work_dir, options) {
+        eprintln!("[first] error: {}", io_failure("create", &work_dir, &e));
+        std::process::exit(1);
+    }
+
+    let crash_info = match orchestrator::spawn_child(
+        exe,
+        test_name,
+        "EXECUTION",
+        NO_TARGET,
+        None,
+        &work_dir,
+        metadata_dir,
+        options.coverage_dir.as_deref(),
+        options.resource_limits.as_ref(),
+    ) {
+        ChildResult::Crashed(info) => info,
+        ChildResult::Success | ChildResult::Skipped(_) => {
+            eprintln!(
+                "[first] crash after {:?}: FAILED (the workload completed before the timer fired)",
+                after
+            );
+            std::process::exit(1);
+        }
+        ChildResult::Failed(code) => {
+            eprintln!(
+                "[first] crash after {:?}: FAILED (execution failed with exit code {}; see {})",
+                after,
+                code,
+                work_dir.display()
+            );
+            std::process::exit(1);
+        }
+        ChildResult::TimedOut(limit) => {
+            eprintln!(
+                "[first] crash after {:?}: FAILED (workload timed out after {:?}; see {})",
+                after,
+                limit,
+                work_dir.display()
+            );
+            std::process::exit(1);
+        }
+    };
+    let fired = format!(
+        "crash after {:?} (fired at {:?}, after {} crash points)",
+        after,
+        crash_info.elapsed.unwrap_or(after),
+        crash_info.point_id
+    );
+
+    if options.drop_caches {
+        orchestrator::evict_work_dir(&work_dir);
+    }
+
+    let reason = match orchestrator::spawn_child_with_crash_info(
+        exe,
+        test_name,
+        NO_TARGET,
+        &work_dir,
+        metadata_dir,
+        &crash_info,
+        None,
+        options.coverage_dir.as_deref(),
+        options.resource_limits.as_ref(),
+        None,
+    ) {
+        ChildResult::Success => None,
+        ChildResult::Failed(code) => Some(format!("verification failed with exit code {}", code)),
+        ChildResult::Crashed(_) | ChildResult::Skipped(_) => {
+            Some("verify phase crashed unexpectedly".to_string())
+        }
+        ChildResult::TimedOut(limit) => Some(format!("verify phase timed out after {:?}", limit)),
+    };
+    if let Some(reason) = reason {
+        eprintln!("[first] {}: FAILED (see {})", fired, work_dir.display());
+        eprintln!("[first] reason: {}", reason);
+        crate::verify_log::print_tail(&work_dir);
+        std::process::exit(1);
+    }
+
+    eprintln!(
+        "[first] {}: OK{}",
+        fired,
+        crate::report::tag_suffix(crate::report::run_tag(options).as_deref())
+    );
+    if std::env::var("FIRST_KEEP_ARTIFACTS").is_err() {
+        cleanup_work_dir(&work_dir);
+    }
+}
@@ -0,0 +1,103 @@
+//! Per-phase child timeout.
+//!
+//! With `TestBuilder::timeout()` or `FIRST_TIMEOUT_SECS`, every EXECUTION,
+//! VERIFY and DISCOVER child gets a deadline. A watchdog thread started
+//! next to the child kills it with `SIGKILL` once the deadline passes, so
+//! a deadlocked workload or recovery becomes a failure instead of a hang.
+//!
+//! The watchdog also covers the orchestrator reading the child's stderr,
+//! which blocks as long as the child runs. A child killed by the watchdog
+//! is reported as timed out, never as an injected crash.
+
+use std::sync::Mutex;
+use std::sync::mpsc::{self, RecvTimeoutError, Sender};
+use std::thread::JoinHandle;
+use std::time::Duration;
+
+/// Phase timeout in seconds, overriding `TestBuilder::timeout()`.
+pub(crate) const ENV_TIMEOUT_SECS: &str = "FIRST_TIMEOUT_SECS";
+
+/// The timeout of every child phase, set by the orchestrator per sweep.
+static LIMIT: Mutex<Option<Duration>> = Mutex::new(None);
+
+/// Set the phase timeout: `FIRST_TIMEOUT_SECS` if set, else `configured`.
+///
+/// Called by the orchestrator before it spawns any child.
+pub(crate) fn configure(configured: Option<Duration>) {
+    let limit = match std::env::var(ENV_TIMEOUT_SECS).map(|s| s.trim().parse::<f64>()) {
+        Ok(Ok(secs)) if secs > 0.0 && secs.is_finite() => Some(Duration::from_secs_f64(secs)),
+        Ok(_) => {
+            eprintln!(
+                "[first] warning: ignoring {}: expected a positive number of seconds",
+                ENV_TIMEOUT_SECS
+            );
+            configured
+        }
+        Err(_) => configured,
+    };
+    *LIMIT.lock().unwrap_or_else(|e| e.into_inner()) = limit;
+}
+
+/// The configured phase timeout, if any.
+pub(crate) fn limit() -> Option<Duration> {
+    *LIMIT.lock().unwrap_or_else(|e| e.into_inner())
+}
+
+/// Kills a child that outlives the phase timeout.
+pub(crate) struct Watchdog {
+    done: Sender<()>,
+    thread: JoinHandle<bool>,
+}
+
+impl Watchdog {
+    /// Watch the child `pid`, or return `None` without a timeout.
+    pub(crate) fn start(pid: u32, limit: Option<Duration>) -> Option<Self> {
+        let limit = limit?;
+        let (done, finished) = mpsc::channel::<()>();
+        let thread = std::thread::spawn(move || match finished.recv_timeout(limit) {
+            Err(RecvTimeoutError::Timeout) => {
+                // SAFETY: kill() has no memory-safety preconditions. The
+                // child is reaped only after its stderr closes or it exits,
+                // and finish() follows at once, so `pid` is still ours.
+                unsafe {
+                    libc::kill(pid as libc::pid_t, libc::SIGKILL);
+                }
+                true
+            }
+            Ok(()) | Err(RecvTimeoutError::Disconnected) => false,
+        });
+        Some(Self { done, thread })
+    }
+
+    /// Stop watching once the child has exited; returns whether the
+    /// watchdog killed it.
+    pub(crate) fn finish(self) -> bool {
+        let _ = self.done.send(());
+        self.thread.join().unwrap_or(false)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::process::Command;
+
+    #[test]
+    fn test_watchdog_kills_hung_child() {
+        let limit = Duration::from_millis(100);
+        let mut child = Command::new("sleep").arg("10").spawn().unwrap();
+        let watchdog = Watchdog::start(child.id(), Some(limit)).unwrap();
+        let start = std::time::Instant::now();
+        let status = child.wait().unwrap();
+        assert!(watchdog.finish());
+        assert!(!status.success());
+        assert!(start.elapsed() >= limit && start.elapsed() < Duration::from_secs(5));
+
+        let mut child = Command::new("true").spawn().unwrap();
+        let watchdog = Watchdog::start(child.id(), Some(limit)).unwrap();
+        assert!(child.wait().unwrap().success());
+        assert!(!watchdog.finish());
+
+        assert!(Watchdog::start(child.id(), None).is_none());
+    }
+}
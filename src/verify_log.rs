@@ -0,0 +1,95 @@
+//! Output of failed VERIFY children.
+//!
+//! The orchestrator captures the stdout and stderr of every VERIFY child.
+//! When verification fails, both are kept in `verify.log` in the crash
+//! point's work dir, and the failure report prints the end of the log:
+//! the panic message and any logging of the recovery, without re-running
+//! the point. A passing verify leaves no log behind.
+
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::Output;
+
+/// Name of the log file in the work dir.
+pub(crate) const FILE_NAME: &str = "verify.log";
+
+/// Number of trailing log lines printed with a failure (`0` prints none).
+pub(crate) const ENV_LOG_TAIL: &str = "FIRST_LOG_TAIL";
+
+/// Lines printed when `FIRST_LOG_TAIL` is unset.
+const DEFAULT_TAIL_LINES: usize = 20;
+
+/// Path of the verify log of `work_dir`.
+pub(crate) fn path(work_dir: &Path) -> PathBuf {
+    work_dir.join(FILE_NAME)
+}
+
+/// Keep the output of a failed VERIFY child in `work_dir`.
+///
+/// Stderr comes first, so the tail shows the test harness's report of
+/// the failure, which it prints on stdout.
+pub(crate) fn write(work_dir: &Path, output: &Output) {
+    let mut log = output.stderr.clone();
+    log.extend_from_slice(&output.stdout);
+    let path = path(work_dir);
+    if let Err(e) = fs::write(&path, log) {
+        eprintln!(
+            "[first] warning: cannot write verify log {}: {}",
+            path.display(),
+            e
+        );
+    }
+}
+
+/// Remove a stale log, once the point's verify passed.
+pub(crate) fn remove(work_dir: &Path) {
+    let _ = fs::remove_file(path(work_dir));
+}
+
+/// Print the last lines of the verify log of `work_dir`, if it has one.
+pub(crate) fn print_tail(work_dir: &Path) {
+    let lines = std::env::var(ENV_LOG_TAIL)
+        .ok()
+        .and_then(|s| s.parse().ok())
+        .unwrap_or(DEFAULT_TAIL_LINES);
+    let path = path(work_dir);
+    let Ok(log) = fs::read(&path) else {
+        return;
+    };
+    let log = String::from_utf8_lossy(&log);
+    let tail = tail(&log, lines);
+    if tail.is_empty() {
+        return;
+    }
+    eprintln!("[first] verify output ({}):", path.display());
+    for line in tail.lines() {
+        eprintln!("  | {}", line);
+    }
+}
+
+/// The last `n` lines of `text`.
+fn tail(text: &str, n: usize) -> &str {
+    let text = text.trim_end();
+    if n == 0 {
+        return "";
+    }
+    match text.match_indices('\n').nth_back(n - 1) {
+        Some((i, _)) => &text[i + 1..],
+        None => text,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_tail() {
+        let text = "one\ntwo\nthree\n\n";
+        assert_eq!(tail(text, 2), "two\nthree");
+        assert_eq!(tail(text, 3), "one\ntwo\nthree");
+        assert_eq!(tail(text, 10), "one\ntwo\nthree");
+        assert_eq!(tail(text, 0), "");
+        assert_eq!(tail("", 5), "");
+    }
+}
@@ -0,0 +1,51 @@
+//! Crash points on two threads draw distinct IDs from one counter, and
+//! exactly one of them crashes the process.
+
+use std::fs::OpenOptions;
+use std::io::Write;
+
+const PER_THREAD: usize = 10;
+
+#[test]
+fn two_threads_share_the_counter() {
+    first::test()
+        .discover()
+        .allow_threads()
+        .run(|env| {
+            std::thread::scope(|scope| {
+                for name in ["ids_a", "ids_b"] {
+                    let path = env.path(name);
+                    scope.spawn(move || {
+                        let mut log = OpenOptions::new()
+                            .create(true)
+                            .append(true)
+                            .open(path)
+                            .unwrap();
+                        for _ in 0..PER_THREAD {
+                            let id = first::crash_point("background");
+                            writeln!(log, "{}", id).unwrap();
+                        }
+                    });
+                }
+            });
+        })
+        .verify(|env, crash_info| {
+            assert_eq!(crash_info.total_points, Some(2 * PER_THREAD));
+            let mut ids: Vec<usize> = ["ids_a", "ids_b"]
+                .iter()
+                .flat_map(|name| {
+                    std::fs::read_to_string(env.path(name))
+                        .unwrap_or_default()
+                        .lines()
+                        .map(|l| l.parse().unwrap())
+                        .collect::<Vec<_>>()
+                })
+                .collect();
+            ids.sort();
+            let count = ids.len();
+            ids.dedup();
+            assert_eq!(ids.len(), count, "an ID was assigned twice");
+            assert!(ids.iter().all(|&id| id < crash_info.point_id));
+        })
+        .execute();
+}
@@ -0,0 +1,24 @@
+//! With `assert_deterministic()`, every EXECUTION child checks the crash
+//! points it reaches against the schedule found by discovery.
+
+#[test]
+fn deterministic_schedule_passes() {
+    first::test()
+        .assert_deterministic()
+        .run(|env| {
+            env.write("header", "v1").unwrap();
+            first::crash_point("after_header");
+            for i in 0..3 {
+                env.write(format!("record_{}", i), i.to_string()).unwrap();
+                first::crash_point("after_record");
+            }
+            env.fsync_dir(".").unwrap();
+            first::crash_point("after_sync");
+        })
+        .verify(|env, _crash_info| {
+            if let Ok(header) = env.read("header") {
+                assert_eq!(header, b"v1");
+            }
+        })
+        .execute();
+}
@@ -0,0 +1,26 @@
+//! Crash points that must never be reached once shutdown completes.
+
+use std::fs;
+
+#[test]
+fn unreachable_label_is_never_hit() {
+    first::test()
+        .discover()
+        .assert_unreachable("write_after_shutdown")
+        .run(|env| {
+            let mut shut_down = false;
+            for i in 0..3 {
+                if shut_down {
+                    first::crash_point("write_after_shutdown");
+                }
+                fs::write(env.path(format!("segment_{}", i)), b"data").unwrap();
+                first::crash_point("after_segment_write");
+                shut_down = i == 2;
+            }
+        })
+        .verify(|env, crash_info| {
+            let segments = fs::read_dir(env.path("")).unwrap().count();
+            assert_eq!(segments, crash_info.point_id);
+        })
+        .execute();
+}
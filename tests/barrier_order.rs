@@ -0,0 +1,36 @@
+//! Verify can check the order in which files were made durable.
+
+use std::fs::File;
+use std::io::Write;
+
+use first::invariants::assert_barrier_order;
+
+#[test]
+fn data_is_synced_before_manifest() {
+    first::test()
+        .run(|env| {
+            let mut data = File::create(env.path("segment")).unwrap();
+            data.write_all(b"rows").unwrap();
+            env.fdatasync(&data).unwrap();
+            first::crash_point("after_data_sync");
+
+            let mut manifest = File::create(env.path("MANIFEST")).unwrap();
+            manifest.write_all(b"segment").unwrap();
+            env.fsync(&manifest).unwrap();
+            first::crash_point("after_manifest_sync");
+        })
+        .verify(|_env, crash_info| {
+            let files: Vec<_> = crash_info
+                .barriers()
+                .iter()
+                .map(|b| b.file.as_ref().unwrap().to_str().unwrap())
+                .collect();
+            match crash_info.label.as_str() {
+                "after_data_sync" => assert_eq!(files, ["segment"]),
+                _ => assert_eq!(files, ["segment", "MANIFEST"]),
+            }
+            assert_eq!(crash_info.barriers()[0].after_point, 0);
+            assert_barrier_order(crash_info, "segment", "MANIFEST");
+        })
+        .execute();
+}
@@ -0,0 +1,45 @@
+//! Crash info reported through binary metadata matches what the workload
+//! did before the crash.
+
+use std::fs::OpenOptions;
+use std::io::Write;
+
+#[test]
+fn binary_metadata_reaches_verify() {
+    first::test()
+        .binary_metadata()
+        .run(|env| {
+            let mut wal = OpenOptions::new()
+                .create(true)
+                .append(true)
+                .open(env.path("wal"))
+                .unwrap();
+            wal.write_all(b"one\n").unwrap();
+            env.fsync(&wal).unwrap();
+            first::crash_point!("after_sync");
+            wal.write_all(b"two\n").unwrap();
+            env.fdatasync(&wal).unwrap();
+            first::crash_point("after_datasync");
+        })
+        .verify(|_env, crash_info| match crash_info.label.as_str() {
+            "after_sync" => {
+                assert_eq!(crash_info.point_id, 1);
+                assert_eq!(crash_info.fsync_count, 1);
+                assert_eq!(crash_info.site.map(|(_, line)| line), Some(19));
+                assert_eq!(crash_info.barriers().len(), 1);
+            }
+            "after_datasync" => {
+                assert_eq!(crash_info.point_id, 2);
+                assert_eq!(crash_info.fsync_count, 2);
+                assert!(crash_info.site.is_none());
+                let files: Vec<_> = crash_info
+                    .barriers()
+                    .iter()
+                    .map(|b| b.file.as_ref().unwrap().to_str().unwrap())
+                    .collect();
+                assert_eq!(files, ["wal", "wal"]);
+            }
+            label => panic!("unexpected crash point {:?}", label),
+        })
+        .execute();
+}
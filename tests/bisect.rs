@@ -0,0 +1,45 @@
+//! With `bisect()`, only the points on the binary search path to the
+//! first failing crash point are run.
+
+use std::fs::{self, OpenOptions};
+use std::io::Write;
+
+#[test]
+fn bisect_finds_first_failing_point() {
+    first::test()
+        .bisect()
+        .expect_violation()
+        .run(|env| {
+            for step in 1..=8 {
+                env.write("step", step.to_string()).unwrap();
+                first::crash_point("after_step");
+            }
+        })
+        .verify(|env, crash_info| {
+            let notes = env.metadata_path("verified");
+            let earlier = fs::read_to_string(&notes).unwrap_or_default();
+            // The last point, then halving: 4 passes, 6 fails, 5 passes
+            let expected = match crash_info.point_id {
+                8 => "",
+                4 => "8\n",
+                6 => "8\n4\n",
+                5 => "8\n4\n6\n",
+                id => panic!("crash point {} is not on the search path", id),
+            };
+            assert_eq!(earlier, expected);
+            let mut file = OpenOptions::new()
+                .create(true)
+                .append(true)
+                .open(&notes)
+                .unwrap();
+            writeln!(file, "{}", crash_info.point_id).unwrap();
+
+            // Seeded bug: every step from 6 on leaves a bad state behind
+            let step: u32 = env
+                .read("step")
+                .map(|s| String::from_utf8(s).unwrap().parse().unwrap())
+                .unwrap();
+            assert!(step < 6, "seeded failure");
+        })
+        .execute();
+}
@@ -0,0 +1,42 @@
+//! Recovery in the presence of block padding left by a crash.
+
+use std::fs::{self, File};
+use std::io::Write;
+
+#[test]
+fn recovery_ignores_block_padding() {
+    first::test()
+        .block_padding(0)
+        .run(|env| {
+            let mut file = File::create(env.path("append.log")).unwrap();
+            file.write_all(b"RECORD1\n").unwrap();
+            first::crash_point("after_write_1");
+
+            file.write_all(b"RECORD2\n").unwrap();
+            first::crash_point("after_write_2");
+        })
+        .verify(|env, crash_info| {
+            let contents = fs::read(env.path("append.log")).unwrap();
+
+            // The crash layer rounds the file up to a whole block.
+            assert!(
+                contents.len() >= 4096 && contents.len() % 512 == 0,
+                "expected block-padded file, got {} bytes",
+                contents.len()
+            );
+
+            // Recovery: the logical end is the last non-fill byte.
+            let end = contents.iter().rposition(|&b| b != 0).map_or(0, |i| i + 1);
+            let records: Vec<_> = contents[..end]
+                .split(|&b| b == b'\n')
+                .filter(|r| !r.is_empty())
+                .collect();
+
+            let expected: &[&[u8]] = match crash_info.label.as_str() {
+                "after_write_1" => &[b"RECORD1"],
+                _ => &[b"RECORD1", b"RECORD2"],
+            };
+            assert_eq!(records, expected);
+        })
+        .execute();
+}
@@ -0,0 +1,23 @@
+//! Verify can record which named invariants it actually checked.
+
+use std::fs;
+
+#[test]
+fn conditional_checks_are_recorded() {
+    first::test()
+        .run(|env| {
+            fs::write(env.path("a"), b"1").unwrap();
+            first::crash_point("after_a");
+            fs::write(env.path("b"), b"2").unwrap();
+            first::crash_point("after_b");
+        })
+        .verify(|env, crash_info| {
+            first::checked("a_written");
+            assert!(env.path("a").exists());
+            if crash_info.label == "after_b" {
+                first::checked("b_written");
+                assert!(env.path("b").exists());
+            }
+        })
+        .execute();
+}
@@ -0,0 +1,44 @@
+//! Recovery of a key-value log reconstructs the in-memory state of its last
+//! commit.
+
+use std::collections::BTreeMap;
+use std::fs::{self, OpenOptions};
+use std::io::Write;
+
+fn dump(map: &BTreeMap<String, String>) -> String {
+    map.iter().map(|(k, v)| format!("{}={}\n", k, v)).collect()
+}
+
+#[test]
+fn recovered_state_matches_checkpoint() {
+    first::test()
+        .run(|env| {
+            let mut log = OpenOptions::new()
+                .create(true)
+                .append(true)
+                .open(env.path("log"))
+                .unwrap();
+            let mut map = BTreeMap::new();
+            for (k, v) in [("a", "1"), ("b", "2"), ("a", "3")] {
+                log.write_all(format!("{}={}\n", k, v).as_bytes()).unwrap();
+                first::crash_point("before_commit");
+                env.fsync(&log).unwrap();
+                map.insert(k.to_string(), v.to_string());
+                first::checkpoint_memory(dump(&map));
+                first::crash_point("after_commit");
+            }
+        })
+        .verify(|env, crash_info| {
+            let mut map = BTreeMap::new();
+            let log = fs::read_to_string(env.path("log")).unwrap_or_default();
+            for (k, v) in log.lines().filter_map(|l| l.split_once('=')) {
+                map.insert(k.to_string(), v.to_string());
+            }
+            if crash_info.label == "after_commit" {
+                let checkpoint = first::memory_checkpoint(env).unwrap();
+                assert_eq!(checkpoint.after_point, crash_info.point_id - 1);
+                checkpoint.assert_matches(dump(&map));
+            }
+        })
+        .execute();
+}
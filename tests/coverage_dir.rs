@@ -0,0 +1,26 @@
+//! Children write coverage profiles into the configured directory.
+
+use std::fs;
+
+const COVERAGE_DIR: &str = "/tmp/first_coverage_test";
+
+#[test]
+fn children_get_profile_paths() {
+    first::test()
+        .coverage_dir(COVERAGE_DIR)
+        .run(|env| {
+            fs::write(env.path("data"), b"value").unwrap();
+            first::crash_point("after_write");
+        })
+        .verify(|_env, crash_info| {
+            let profile = std::env::var("LLVM_PROFILE_FILE").unwrap();
+            assert_eq!(
+                profile,
+                format!(
+                    "{}/verify_point_{}_%p.profraw",
+                    COVERAGE_DIR, crash_info.point_id
+                )
+            );
+        })
+        .execute();
+}
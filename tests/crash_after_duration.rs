@@ -0,0 +1,38 @@
+//! A timer can crash the workload wherever it is, between crash points.
+
+use std::fs::{self, OpenOptions};
+use std::io::Write;
+use std::time::{Duration, Instant};
+
+#[test]
+fn timer_crash_is_verified() {
+    first::test()
+        .crash_after_duration(Duration::from_millis(50))
+        .run(|env| {
+            let mut log = OpenOptions::new()
+                .create(true)
+                .append(true)
+                .open(env.path("log"))
+                .unwrap();
+            // Flushes on a clock, like a background flusher; never finishes
+            // before the timer fires
+            let start = Instant::now();
+            while start.elapsed() < Duration::from_secs(10) {
+                log.write_all(b"record\n").unwrap();
+                first::crash_point("after_record");
+                std::thread::sleep(Duration::from_millis(5));
+            }
+        })
+        .verify(|env, crash_info| {
+            assert_eq!(crash_info.label, "crash_after_duration");
+            let elapsed = crash_info
+                .elapsed
+                .expect("timer crash records elapsed time");
+            assert!(elapsed >= Duration::from_millis(50));
+
+            let log = fs::read_to_string(env.path("log")).unwrap();
+            assert!(log.lines().all(|l| l == "record"));
+            assert!(log.lines().count() >= crash_info.point_id);
+        })
+        .execute();
+}
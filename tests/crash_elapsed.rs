@@ -0,0 +1,22 @@
+//! `CrashInfo::elapsed` is how long the workload ran before its crash,
+//! for crashes at crash points as well as timer crashes.
+
+use std::time::Duration;
+
+#[test]
+fn elapsed_grows_with_workload_time() {
+    first::test()
+        .run(|env| {
+            env.write("data", "v1").unwrap();
+            first::crash_point("fast");
+            std::thread::sleep(Duration::from_millis(30));
+            first::crash_point("after_sleep");
+        })
+        .verify(|_env, crash_info| {
+            let elapsed = crash_info.elapsed.expect("crash records elapsed time");
+            if crash_info.label == "after_sleep" {
+                assert!(elapsed >= Duration::from_millis(30));
+            }
+        })
+        .execute();
+}
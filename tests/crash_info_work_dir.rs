@@ -0,0 +1,16 @@
+//! `CrashInfo::work_dir` names the workspace the crash happened in, the
+//! one verify sees through `Env`.
+
+#[test]
+fn crash_info_names_workspace() {
+    first::test()
+        .run(|env| {
+            env.write("data", "v1").unwrap();
+            first::crash_point("after_write");
+        })
+        .verify(|env, crash_info| {
+            assert_eq!(env.path(""), crash_info.work_dir.join(""));
+            assert!(crash_info.work_dir.join("data").exists());
+        })
+        .execute();
+}
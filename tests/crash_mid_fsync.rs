@@ -0,0 +1,54 @@
+//! A crash inside a barrier on a listed file persists only some of its
+//! pending pages, as reported in the crash info.
+
+use std::fs::{self, File};
+use std::io::Write;
+
+/// Byte at `offset` of the fully written WAL.
+fn expected(offset: u64) -> u8 {
+    if offset < 100 { b'a' } else { b'b' }
+}
+
+#[test]
+fn torn_wal_flush() {
+    first::test()
+        .crash_mid_fsync("wal_fsync", &["wal"])
+        .lose_unsynced_writes()
+        // Seeds the persisted pages; with 2, a zeroed page is left between
+        // two persisted ones
+        .shuffle(2)
+        .run(|env| {
+            let mut wal = File::create(env.path("wal")).unwrap();
+            wal.write_all(&[b'a'; 100]).unwrap();
+            env.fsync(&wal).unwrap();
+
+            wal.write_all(&[b'b'; 3 * 4096]).unwrap();
+            first::crash_point("after_append");
+            env.fsync(&wal).unwrap();
+
+            // Not listed: synced without a crash point
+            let index = File::create(env.path("index")).unwrap();
+            env.fsync(&index).unwrap();
+        })
+        .verify(|env, crash_info| {
+            let data = fs::read(env.path("wal")).unwrap_or_default();
+            if crash_info.label != "wal_fsync" {
+                assert!(crash_info.partial_flush().is_empty());
+                return;
+            }
+
+            let [flush] = crash_info.partial_flush() else {
+                panic!("expected one partial flush: {:?}", crash_info);
+            };
+            assert_eq!(flush.file, std::path::Path::new("wal"));
+            let end = flush.persisted.last().map_or(flush.synced_len, |r| r.end);
+            assert_eq!(data.len() as u64, end);
+            for (offset, &byte) in (0..).zip(&data) {
+                let persisted = offset < flush.synced_len
+                    || flush.persisted.iter().any(|r| r.contains(&offset));
+                let want = if persisted { expected(offset) } else { 0 };
+                assert_eq!(byte, want, "byte {} of wal", offset);
+            }
+        })
+        .execute();
+}
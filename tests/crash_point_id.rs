@@ -0,0 +1,30 @@
+//! `crash_point()` returns the ID it was assigned, matching the numbering
+//! verify sees in `CrashInfo::point_id`.
+
+use std::fs::OpenOptions;
+use std::io::Write;
+
+#[test]
+fn returned_ids_match_crash_info() {
+    first::test()
+        .run(|env| {
+            let mut log = OpenOptions::new()
+                .create(true)
+                .append(true)
+                .open(env.path("ids"))
+                .unwrap();
+            for label in ["a", "b", "c"] {
+                let id = first::crash_point(label);
+                writeln!(log, "{}", id).unwrap();
+            }
+        })
+        .verify(|env, crash_info| {
+            assert_eq!(first::crash_point("in_verify"), 0);
+            let ids = std::fs::read_to_string(env.path("ids")).unwrap_or_default();
+            let expected: String = (1..crash_info.point_id)
+                .map(|id| format!("{}\n", id))
+                .collect();
+            assert_eq!(ids, expected);
+        })
+        .execute();
+}
@@ -0,0 +1,41 @@
+//! `crash_point_if()` with a false condition consumes no ID: only live
+//! calls advance the crash point counter.
+
+use std::fs::OpenOptions;
+use std::io::Write;
+
+#[test]
+fn skipped_calls_consume_no_id() {
+    first::test()
+        .run(|env| {
+            let mut log = OpenOptions::new()
+                .create(true)
+                .append(true)
+                .open(env.path("ids"))
+                .unwrap();
+            for i in 0..6 {
+                let id = first::crash_point_if("even_iteration", i % 2 == 0);
+                writeln!(log, "{} {}", i, id).unwrap();
+            }
+            first::crash_point("done");
+        })
+        .verify(|env, crash_info| {
+            assert_eq!(first::crash_point_if("in_verify", true), 0);
+            // Iterations 0, 2 and 4 get IDs 1 to 3, "done" gets 4
+            assert!(crash_info.point_id <= 4);
+            let expected_label = if crash_info.point_id == 4 {
+                "done"
+            } else {
+                "even_iteration"
+            };
+            assert_eq!(crash_info.label, expected_label);
+            // Crash point N < 4 is iteration 2N - 2, before its line
+            let logged = (2 * crash_info.point_id - 2).min(6);
+            let ids = std::fs::read_to_string(env.path("ids")).unwrap_or_default();
+            let expected: String = (0..logged)
+                .map(|i| format!("{} {}\n", i, if i % 2 == 0 { i / 2 + 1 } else { 0 }))
+                .collect();
+            assert_eq!(ids, expected);
+        })
+        .execute();
+}
@@ -0,0 +1,36 @@
+//! A crash schedule crashes the same workspace several times in a row,
+//! and verify sees the crashes so far in `CrashInfo::history`.
+
+use std::fs::{self, OpenOptions};
+use std::io::Write;
+
+#[test]
+fn schedule_crashes_in_sequence() {
+    first::test()
+        .crash_schedule(vec![1, 2])
+        .run(|env| {
+            // Each restart appends to whatever the previous crash left behind.
+            let mut log = OpenOptions::new()
+                .create(true)
+                .append(true)
+                .open(env.path("log"))
+                .unwrap();
+            log.write_all(b"A\n").unwrap();
+            first::crash_point("after_a");
+            log.write_all(b"B\n").unwrap();
+            first::crash_point("after_b");
+        })
+        .verify(|env, crash_info| {
+            let log = fs::read_to_string(env.path("log")).unwrap();
+            let records: Vec<_> = log.lines().collect();
+
+            let expected: &[&str] = match crash_info.history.as_slice() {
+                [1] => &["A"],
+                [1, 2] => &["A", "A", "B"],
+                history => panic!("unexpected crash history {:?}", history),
+            };
+            assert_eq!(records, expected);
+            assert_eq!(crash_info.history.last(), Some(&crash_info.point_id));
+        })
+        .execute();
+}
@@ -0,0 +1,20 @@
+//! `#[first::crash_test]` generates a test running one function as both
+//! the workload and verify.
+
+use std::fs;
+
+#[first::crash_test]
+fn single_function(env: &first::Env, crash: Option<&first::CrashInfo>) {
+    match crash {
+        None => {
+            fs::write(env.path("a"), b"1").unwrap();
+            first::crash_point("after_a");
+            fs::write(env.path("b"), b"2").unwrap();
+            first::crash_point("after_b");
+        }
+        Some(crash) => {
+            assert_eq!(fs::read(env.path("a")).unwrap(), b"1");
+            assert_eq!(env.path("b").exists(), crash.label == "after_b");
+        }
+    }
+}
@@ -0,0 +1,38 @@
+//! Partial writes through O_DIRECT handles stay block aligned.
+
+use std::fs::{self, OpenOptions};
+
+const BLOCK: usize = 4096;
+
+/// A buffer aligned for direct I/O.
+#[repr(align(4096))]
+struct Aligned([u8; 2 * BLOCK]);
+
+#[test]
+fn direct_partial_write_is_block_aligned() {
+    first::test()
+        .run(|env| {
+            assert_eq!(env.direct_io_alignment().unwrap() % 512, 0);
+            let file = env
+                .open_direct(
+                    "data.db",
+                    OpenOptions::new().read(true).write(true).create(true),
+                )
+                .unwrap();
+            let buf = Aligned([b'x'; 2 * BLOCK]);
+            // Cut mid-block: the torn write keeps only whole blocks
+            env.write_at_then_crash(&file, 0, &buf.0, BLOCK + 100)
+                .unwrap();
+        })
+        .verify(|env, crash_info| {
+            let partial = crash_info.partial_write.as_ref().unwrap();
+            assert_eq!(partial.file.to_str(), Some("data.db"));
+            assert_eq!(partial.offset, 0);
+            assert_eq!(partial.len, 2 * BLOCK);
+            assert_eq!(partial.written % 512, 0);
+            assert!(partial.written <= BLOCK + 100);
+            let len = fs::metadata(env.path("data.db")).unwrap().len();
+            assert_eq!(len, partial.written as u64);
+        })
+        .execute();
+}
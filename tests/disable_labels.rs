@@ -0,0 +1,30 @@
+//! Disabled crash points are skipped without shifting the IDs of the rest.
+
+use std::fs::OpenOptions;
+use std::io::Write;
+
+#[test]
+fn disabled_points_are_skipped() {
+    first::test()
+        .disable_labels(&["known_bug"])
+        .run(|env| {
+            let mut log = OpenOptions::new()
+                .create(true)
+                .append(true)
+                .open(env.path("log"))
+                .unwrap();
+            for i in 0..3 {
+                log.write_all(format!("{}\n", i).as_bytes()).unwrap();
+                env.fsync(&log).unwrap();
+                first::crash_point("known_bug");
+                first::crash_point("after_append");
+            }
+        })
+        .verify(|env, crash_info| {
+            assert_eq!(crash_info.label, "after_append");
+            assert_eq!(crash_info.point_id % 2, 0);
+            let log = std::fs::read_to_string(env.path("log")).unwrap();
+            assert_eq!(log.lines().count(), crash_info.point_id / 2);
+        })
+        .execute();
+}
@@ -0,0 +1,26 @@
+//! Crash point discovery before the sweep.
+
+use std::fs::File;
+use std::io::Write;
+
+#[test]
+fn discover_enumerates_crash_points() {
+    first::test()
+        .discover()
+        .run(|env| {
+            let mut file = File::create(env.path("data")).unwrap();
+            for i in 0..3 {
+                writeln!(file, "{}", i).unwrap();
+                first::crash_point("after_write");
+            }
+        })
+        .verify(|env, crash_info| {
+            let contents = std::fs::read_to_string(env.path("data")).unwrap();
+            assert_eq!(contents.lines().count(), crash_info.point_id);
+
+            assert_eq!(crash_info.total_points, Some(3));
+            assert_eq!(crash_info.is_first_point(), crash_info.point_id == 1);
+            assert_eq!(crash_info.is_last_point(), crash_info.point_id == 3);
+        })
+        .execute();
+}
@@ -0,0 +1,20 @@
+//! Verify still sees synced data after the page cache is dropped.
+
+use std::fs::{self, File};
+use std::io::Write;
+
+#[test]
+fn synced_data_survives_cache_drop() {
+    first::test()
+        .drop_caches()
+        .run(|env| {
+            let mut file = File::create(env.path("data")).unwrap();
+            file.write_all(b"committed").unwrap();
+            env.fsync(&file).unwrap();
+            first::crash_point("after_sync");
+        })
+        .verify(|env, _crash_info| {
+            assert_eq!(fs::read(env.path("data")).unwrap(), b"committed");
+        })
+        .execute();
+}
@@ -0,0 +1,31 @@
+//! With `drop_unsynced()`, writes that were never synced are gone after
+//! the crash, including overwrites inside a synced file.
+
+use std::fs::{self, File};
+use std::io::Write;
+use std::os::unix::fs::FileExt;
+
+#[test]
+fn unsynced_writes_are_lost() {
+    first::test()
+        .drop_unsynced()
+        .run(|env| {
+            let mut file = File::create(env.path("data")).unwrap();
+            file.write_all(b"old").unwrap();
+            env.fsync(&file).unwrap();
+            file.write_all_at(b"new", 0).unwrap();
+            fs::write(env.path("never_synced"), b"lost").unwrap();
+            first::crash_point("after_overwrite");
+            env.fsync(&file).unwrap();
+            first::crash_point("after_sync");
+        })
+        .verify(|env, crash_info| {
+            let expected: &[u8] = match crash_info.label.as_str() {
+                "after_overwrite" => b"old",
+                _ => b"new",
+            };
+            assert_eq!(fs::read(env.path("data")).unwrap(), expected);
+            assert_eq!(fs::read(env.path("never_synced")).unwrap(), b"");
+        })
+        .execute();
+}
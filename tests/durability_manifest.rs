@@ -0,0 +1,29 @@
+//! Files present after a crash are always a committed version.
+
+use first::DurabilityManifest;
+
+/// FNV-1a, standing in for an engine's own checksum.
+fn checksum(data: &[u8]) -> u64 {
+    data.iter().fold(0xcbf2_9ce4_8422_2325, |hash, &byte| {
+        (hash ^ u64::from(byte)).wrapping_mul(0x0100_0000_01b3)
+    })
+}
+
+#[test]
+fn segment_is_always_committed_version() {
+    first::test()
+        .run(|env| {
+            let manifest = DurabilityManifest::open(env);
+            for version in [&b"segment v1"[..], b"segment v2"] {
+                manifest.commit("segment", checksum(version)).unwrap();
+                first::atomic_write(env, "segment", version).unwrap();
+                first::crash_point("after_commit");
+            }
+        })
+        .verify(|env, _crash_info| {
+            let manifest = DurabilityManifest::open(env);
+            assert!(!manifest.committed("segment").is_empty());
+            manifest.assert_files(env, checksum);
+        })
+        .execute();
+}
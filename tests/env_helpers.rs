@@ -0,0 +1,27 @@
+//! `Env::create_dir()`, `Env::write()` and `Env::read()` work on paths
+//! relative to the workspace.
+
+#[test]
+fn helpers_resolve_in_workspace() {
+    first::test()
+        .run(|env| {
+            let dir = env.create_dir("db/segments").unwrap();
+            assert_eq!(dir, env.path("db/segments"));
+            assert!(dir.is_dir());
+            // Creating it again is not an error
+            env.create_dir("db/segments").unwrap();
+
+            env.write("db/segments/0001", b"first").unwrap();
+            first::crash_point("after_segment");
+            env.write("db/manifest", "0001\n").unwrap();
+            first::crash_point("after_manifest");
+        })
+        .verify(|env, crash_info| {
+            assert_eq!(env.read("db/segments/0001").unwrap(), b"first");
+            match crash_info.label.as_str() {
+                "after_segment" => assert!(env.read("db/manifest").is_err()),
+                _ => assert_eq!(env.read("db/manifest").unwrap(), b"0001\n"),
+            }
+        })
+        .execute();
+}
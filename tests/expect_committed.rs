@@ -0,0 +1,34 @@
+//! Keys recorded with `Env::expect()` after their commit is durable are
+//! present after recovery.
+
+use std::fs::OpenOptions;
+
+#[test]
+fn expected_keys_survive() {
+    first::test()
+        .drop_unsynced()
+        .run(|env| {
+            let mut log = OpenOptions::new()
+                .create(true)
+                .append(true)
+                .open(env.path("log"))
+                .unwrap();
+            for key in ["a", "b", "c"] {
+                env.write_all(&mut log, format!("{}\n", key).as_bytes())
+                    .unwrap();
+                first::crash_point("before_fsync");
+                env.fsync(&log).unwrap();
+                env.expect(&[key]).unwrap();
+                first::crash_point("after_commit");
+            }
+        })
+        .verify(|env, crash_info| {
+            let log = std::fs::read_to_string(env.path("log")).unwrap_or_default();
+            let expected = env.expected();
+            expected.assert_present(|key| log.lines().any(|line| line == key));
+            if crash_info.label == "after_commit" {
+                assert_eq!(expected.after_point, crash_info.point_id - 1);
+            }
+        })
+        .execute();
+}
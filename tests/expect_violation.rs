@@ -0,0 +1,23 @@
+//! A seeded torn-write bug must be caught: the sweep passes because a
+//! crash point fails.
+
+use std::fs;
+
+#[test]
+fn torn_write_is_caught() {
+    first::test()
+        .expect_violation()
+        .run(|env| {
+            // BUG: the record is not written atomically
+            env.write_then_crash("value", b"12345678", 4).unwrap();
+        })
+        .verify(|env, _crash_info| {
+            let value = fs::read(env.path("value")).unwrap_or_default();
+            assert!(
+                value.is_empty() || value == b"12345678",
+                "torn value {:?}",
+                value
+            );
+        })
+        .execute();
+}
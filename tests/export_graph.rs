@@ -0,0 +1,36 @@
+//! The crash point timeline can be exported as a DOT graph.
+
+use std::fs::{self, File};
+use std::io::Write;
+
+#[test]
+fn graph_shows_operations_between_crash_points() {
+    // Children re-run this function too; only the orchestrator (no
+    // FIRST_PHASE) writes the graph and checks it.
+    let orchestrator = std::env::var_os("FIRST_PHASE").is_none();
+    let graph = std::env::temp_dir().join("first-export-graph-test.dot");
+    if orchestrator {
+        let _ = fs::remove_file(&graph);
+    }
+
+    first::test()
+        .export_graph(&graph)
+        .run(|env| {
+            let mut wal = File::create(env.path("wal")).unwrap();
+            wal.write_all(b"PUT a\n").unwrap();
+            env.fsync(&wal).unwrap();
+            first::crash_point("after_wal_sync");
+            first::atomic_write(env, "MANIFEST", b"v1").unwrap();
+        })
+        .verify(|_env, _crash_info| {})
+        .execute();
+
+    if orchestrator {
+        let dot = fs::read_to_string(&graph).unwrap();
+        assert!(dot.starts_with("digraph first {"));
+        assert!(dot.contains("label=\"fsync wal\""));
+        assert!(dot.contains("label=\"#1 after_wal_sync\""));
+        assert!(dot.contains("label=\"rename MANIFEST.tmp -> MANIFEST\""));
+        let _ = fs::remove_file(&graph);
+    }
+}
@@ -0,0 +1,42 @@
+//! A failed directory sync is reported to verify, and the workload sees the
+//! injected error.
+
+use std::fs;
+use std::path::PathBuf;
+
+use first::DirFsyncFault;
+
+#[test]
+fn failed_dir_fsync_is_reported() {
+    first::test()
+        .fail_dir_fsync(2, DirFsyncFault::Error(libc::EIO))
+        .run(|env| {
+            fs::create_dir(env.path("db")).unwrap();
+            env.fsync_dir("").unwrap();
+            first::crash_point("after_mkdir");
+
+            fs::write(env.path("db/manifest"), b"v1").unwrap();
+            let err = env.fsync_dir("db").unwrap_err();
+            assert_eq!(err.raw_os_error(), Some(libc::EIO));
+            first::crash_point("after_failed_sync");
+
+            env.fsync_dir("db").unwrap();
+            first::crash_point("after_retry");
+        })
+        .verify(|_env, crash_info| match crash_info.label.as_str() {
+            "after_mkdir" => assert!(crash_info.dir_fsync_fault.is_none()),
+            _ => {
+                let injected = crash_info.dir_fsync_fault.as_ref().unwrap();
+                assert_eq!(injected.dir, PathBuf::from("db"));
+                assert_eq!(injected.fault, DirFsyncFault::Error(libc::EIO));
+                assert_eq!(injected.after_point, 1);
+                let syncs = if crash_info.label == "after_retry" {
+                    2
+                } else {
+                    1
+                };
+                assert_eq!(crash_info.fsync_count, syncs);
+            }
+        })
+        .execute();
+}
@@ -0,0 +1,38 @@
+//! A targeted `fault_point()` fails the next write with `ENOSPC` instead of
+//! crashing; verify sees what the workload's error handling left behind.
+
+use std::fs::OpenOptions;
+
+use first::FaultKind;
+
+#[test]
+fn enospc_reaches_error_handling() {
+    first::test()
+        .run(|env| {
+            let mut log = OpenOptions::new()
+                .create(true)
+                .append(true)
+                .open(env.path("log"))
+                .unwrap();
+            first::fault_point("before_append", FaultKind::NoSpace);
+            if let Err(e) = env.write_all(&mut log, b"record\n") {
+                assert_eq!(e.raw_os_error(), Some(libc::ENOSPC));
+                env.write("error_handled", "").unwrap();
+            }
+            first::crash_point("after_append");
+        })
+        .verify(|env, crash_info| match &crash_info.fault {
+            Some(fault) => {
+                assert_eq!(crash_info.label, "before_append");
+                assert_eq!(fault.kind, FaultKind::NoSpace);
+                assert_eq!(fault.operation.as_deref(), Some("write"));
+                assert!(env.path("error_handled").exists());
+                assert_eq!(std::fs::read(env.path("log")).unwrap(), b"");
+            }
+            None => {
+                assert_eq!(crash_info.label, "after_append");
+                assert!(!env.path("error_handled").exists());
+            }
+        })
+        .execute();
+}
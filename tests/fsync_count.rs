@@ -0,0 +1,31 @@
+//! Counting durability barriers issued before each crash point.
+
+use std::fs::File;
+use std::io::Write;
+
+#[test]
+fn fsync_count_tracks_barriers() {
+    first::test()
+        .run(|env| {
+            let mut file = File::create(env.path("log")).unwrap();
+            file.write_all(b"RECORD1\n").unwrap();
+            first::crash_point("before_any_fsync");
+
+            env.fsync(&file).unwrap();
+            first::crash_point("after_fsync");
+
+            file.write_all(b"RECORD2\n").unwrap();
+            env.fdatasync(&file).unwrap();
+            first::crash_point("after_fdatasync");
+        })
+        .verify(|_env, crash_info| {
+            let expected = match crash_info.label.as_str() {
+                "before_any_fsync" => 0,
+                "after_fsync" => 1,
+                "after_fdatasync" => 2,
+                label => panic!("unexpected crash label {}", label),
+            };
+            assert_eq!(crash_info.fsync_count, expected);
+        })
+        .execute();
+}
@@ -0,0 +1,34 @@
+//! A WAL that retries interrupted fsyncs keeps every acknowledged record.
+
+use std::fs::{self, OpenOptions};
+use std::io::{ErrorKind, Write};
+
+#[test]
+fn wal_retries_interrupted_fsync() {
+    first::test()
+        .fsync_eintr(2)
+        .run(|env| {
+            let mut wal = OpenOptions::new()
+                .create(true)
+                .append(true)
+                .open(env.path("wal"))
+                .unwrap();
+            for record in [b"one\n", b"two\n"] {
+                wal.write_all(record).unwrap();
+                loop {
+                    match env.fsync(&wal) {
+                        Err(e) if e.kind() == ErrorKind::Interrupted => continue,
+                        result => break result.unwrap(),
+                    }
+                }
+                first::crash_point("acked");
+            }
+        })
+        .verify(|env, crash_info| {
+            // Two interrupted attempts precede each completed fsync.
+            assert_eq!(crash_info.eintr_count, 2 * crash_info.fsync_count);
+            let wal = fs::read_to_string(env.path("wal")).unwrap();
+            assert!(wal.lines().count() >= crash_info.fsync_count);
+        })
+        .execute();
+}
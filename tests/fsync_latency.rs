@@ -0,0 +1,30 @@
+//! Slow barriers, with a crash while a barrier is still in flight.
+
+use std::fs::{self, File};
+use std::io::Write;
+use std::time::{Duration, Instant};
+
+#[test]
+fn crash_while_fsync_is_in_flight() {
+    first::test()
+        .fsync_latency(Duration::from_millis(20))
+        .crash_during_fsync()
+        .run(|env| {
+            let mut file = File::create(env.path("data")).unwrap();
+            file.write_all(b"committed").unwrap();
+            let start = Instant::now();
+            env.fsync(&file).unwrap();
+            assert!(start.elapsed() >= Duration::from_millis(20));
+            first::crash_point("after_fsync");
+        })
+        .verify(|env, crash_info| {
+            // Durable in both cases; only the acknowledgement differs.
+            assert_eq!(fs::read(env.path("data")).unwrap(), b"committed");
+            match crash_info.label.as_str() {
+                "fsync_latency_window" => assert!(crash_info.barriers().is_empty()),
+                "after_fsync" => assert_eq!(crash_info.barriers().len(), 1),
+                other => panic!("unexpected crash point {:?}", other),
+            }
+        })
+        .execute();
+}
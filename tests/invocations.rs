@@ -0,0 +1,26 @@
+//! Several `first::test()` invocations in one test function are swept
+//! one after the other, each counting its crash points from 1.
+
+#[test]
+fn invocations_in_a_loop() {
+    for (name, records) in [("short", 2), ("long", 4)] {
+        first::test()
+            .label(name)
+            .run(move |env| {
+                for i in 1..=records {
+                    env.write(format!("{}_{}", name, i), b"ok").unwrap();
+                    first::crash_point("after_record");
+                }
+            })
+            .verify(move |env, crash_info| {
+                assert!(crash_info.point_id <= records);
+                // Only this invocation's workload ran in its children
+                let files = env.list_files();
+                assert_eq!(files.len(), crash_info.point_id, "{:?}", files);
+                for file in files {
+                    assert!(file.to_string_lossy().starts_with(name));
+                }
+            })
+            .execute();
+    }
+}
@@ -0,0 +1,27 @@
+//! Global setup guarded by `is_orchestrator()` runs once and reaches the
+//! children through a file.
+
+use std::fs;
+
+#[test]
+fn setup_runs_only_in_orchestrator() {
+    let dataset = std::env::temp_dir().join("first_is_orchestrator_dataset");
+    if first::is_orchestrator() {
+        fs::write(&dataset, std::process::id().to_string()).unwrap();
+    }
+
+    let check = move || {
+        let owner = fs::read_to_string(&dataset).expect("dataset written by the orchestrator");
+        assert_ne!(owner, std::process::id().to_string());
+    };
+    let verify_check = check.clone();
+
+    first::test()
+        .run(move |env| {
+            check();
+            fs::write(env.path("data"), b"1").unwrap();
+            first::crash_point("after_write");
+        })
+        .verify(move |_env, _crash_info| verify_check())
+        .execute();
+}
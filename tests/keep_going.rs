@@ -0,0 +1,32 @@
+//! With `keep_going()`, the sweep moves past failing crash points and
+//! verifies every point.
+
+use std::fs::{self, OpenOptions};
+use std::io::Write;
+
+#[test]
+fn sweep_continues_past_failures() {
+    first::test()
+        .keep_going()
+        .expect_violation()
+        .run(|_env| {
+            first::crash_point("bad");
+            first::crash_point("bad");
+            first::crash_point("good");
+        })
+        .verify(|env, crash_info| {
+            let notes = env.metadata_path("verified");
+            if crash_info.label == "good" {
+                // Both failing points were verified before this one
+                assert_eq!(fs::read_to_string(&notes).unwrap(), "1\n2\n");
+            }
+            let mut file = OpenOptions::new()
+                .create(true)
+                .append(true)
+                .open(&notes)
+                .unwrap();
+            writeln!(file, "{}", crash_info.point_id).unwrap();
+            assert_ne!(crash_info.label, "bad", "seeded failure");
+        })
+        .execute();
+}
@@ -0,0 +1,39 @@
+//! With `FIRST_COVERAGE`, the sweep reports every crash point label its
+//! workload reached and how often each was crashed at.
+
+use std::fs;
+use std::path::Path;
+
+#[test]
+fn label_coverage_report() {
+    let path = Path::new(env!("CARGO_TARGET_TMPDIR")).join("first_label_coverage.json");
+    if first::is_orchestrator() {
+        let _ = fs::remove_file(&path);
+        // SAFETY: the only test in this binary, set before any child runs
+        unsafe { std::env::set_var("FIRST_COVERAGE", &path) };
+    }
+
+    first::test()
+        .disable_labels(&["never_crashed"])
+        .run(|env| {
+            for i in 0..3 {
+                env.write("log", i.to_string()).unwrap();
+                first::crash_point("after_append");
+            }
+            first::crash_point("never_crashed");
+        })
+        .verify(|_env, _crash_info| {})
+        .execute();
+
+    if !first::is_orchestrator() {
+        return;
+    }
+    let report = fs::read_to_string(&path).unwrap();
+    fs::remove_file(&path).unwrap();
+    assert_eq!(
+        report,
+        "{\"version\":1,\"labels\":[\
+         {\"label\":\"after_append\",\"reached\":3,\"crashed\":3},\
+         {\"label\":\"never_crashed\",\"reached\":1,\"crashed\":0}]}\n"
+    );
+}
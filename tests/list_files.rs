@@ -0,0 +1,24 @@
+//! The exact set of files surviving each crash of an atomic replace: the
+//! temporary file is gone once the rename happened.
+
+use std::path::PathBuf;
+
+#[test]
+fn temp_file_does_not_survive_rename() {
+    first::test()
+        .run(|env| {
+            std::fs::create_dir(env.path("db")).unwrap();
+            first::atomic_write(env, "db/MANIFEST", b"version=1\n").unwrap();
+        })
+        .verify(|env, crash_info| {
+            let expected: &[&str] = match crash_info.label.as_str() {
+                "atomic_write_after_temp_write" | "atomic_write_after_temp_fsync" => {
+                    &["db/MANIFEST.tmp"]
+                }
+                _ => &["db/MANIFEST"],
+            };
+            let expected: Vec<PathBuf> = expected.iter().map(PathBuf::from).collect();
+            assert_eq!(env.list_files(), expected, "at '{}'", crash_info.label);
+        })
+        .execute();
+}
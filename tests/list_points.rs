@@ -0,0 +1,41 @@
+//! `list_points()` runs the workload once without crashing and lists its
+//! crash points instead of sweeping them, past libtest's output capture.
+
+use std::process::Command;
+
+/// Set in the re-run of this test whose listing is checked.
+const ENV_LISTING: &str = "LIST_POINTS_LISTING";
+
+#[test]
+fn lists_without_verifying() {
+    if std::env::var_os(ENV_LISTING).is_none() {
+        // Re-run this test with its output captured, as without --nocapture
+        let output = Command::new(std::env::current_exe().unwrap())
+            .args(["--exact", "lists_without_verifying"])
+            .env(ENV_LISTING, "1")
+            .output()
+            .unwrap();
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        assert!(output.status.success(), "listing failed: {}", stderr);
+        assert!(stderr.contains("[first] 2 crash points:"), "{}", stderr);
+        assert!(stderr.contains("  1 \"after_write\"\n"), "{}", stderr);
+        assert!(
+            stderr.contains("  2 \"after_commit\" tests/list_points.rs:"),
+            "{}",
+            stderr
+        );
+        return;
+    }
+
+    first::test()
+        .list_points()
+        .run(|env| {
+            env.write("data", "v1").unwrap();
+            first::crash_point("after_write");
+            first::crash_point!("after_commit");
+        })
+        .verify(|_env, crash_info| {
+            panic!("verify ran for crash point {}", crash_info.point_id);
+        })
+        .execute();
+}
@@ -0,0 +1,32 @@
+//! Workspaces can live on a loopback filesystem image of a chosen type.
+
+use std::fs;
+
+const MOUNT_DIR: &str = "/tmp/first/loop/ext4_33554432/mnt";
+
+/// Filesystem type mounted at `dir`, per `/proc/self/mounts`.
+fn mounted_type(dir: &str) -> Option<String> {
+    let mounts = fs::read_to_string("/proc/self/mounts").ok()?;
+    mounts.lines().rev().find_map(|line| {
+        let fields: Vec<&str> = line.split_whitespace().collect();
+        (fields.get(1) == Some(&dir)).then(|| fields[2].to_string())
+    })
+}
+
+#[test]
+fn workspaces_on_ext4_image() {
+    first::test()
+        .loopback_fs(first::LoopbackFs::new("ext4").size(32 * 1024 * 1024))
+        .run(|env| {
+            fs::write(env.path("data"), b"v1").unwrap();
+            first::crash_point("after_write");
+        })
+        .verify(|env, _crash_info| {
+            // Without privileges the sweep falls back to the host filesystem
+            if env.path("data").starts_with(MOUNT_DIR) {
+                assert_eq!(mounted_type(MOUNT_DIR).as_deref(), Some("ext4"));
+            }
+            assert_eq!(fs::read(env.path("data")).unwrap(), b"v1");
+        })
+        .execute();
+}
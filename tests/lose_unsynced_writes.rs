@@ -0,0 +1,48 @@
+//! A crash between the syncs of a multi-file commit keeps only the files
+//! synced so far.
+
+use std::fs::{self, File, OpenOptions};
+use std::io::Write;
+
+#[test]
+fn commit_across_two_files() {
+    first::test()
+        .lose_unsynced_writes()
+        .run(|env| {
+            first::atomic_write(env, "MANIFEST", b"v1").unwrap();
+
+            let mut wal = File::create(env.path("wal")).unwrap();
+            wal.write_all(b"record").unwrap();
+            env.fsync(&wal).unwrap();
+            first::crash_point("after_wal_sync");
+
+            let mut index = File::create(env.path("index")).unwrap();
+            index.write_all(b"offset").unwrap();
+            first::crash_point("before_index_sync");
+            env.fsync(&index).unwrap();
+            first::crash_point("after_index_sync");
+
+            // A second handle on the synced WAL, appending without a sync
+            let mut append = OpenOptions::new()
+                .append(true)
+                .open(env.path("wal"))
+                .unwrap();
+            append.write_all(b"unsynced").unwrap();
+            first::crash_point("after_wal_append");
+        })
+        .verify(|env, crash_info| {
+            let read = |name: &str| fs::read(env.path(name)).unwrap_or_default();
+            if env.path("MANIFEST").exists() {
+                assert_eq!(read("MANIFEST"), b"v1");
+            }
+            if !crash_info.label.starts_with("atomic_write") {
+                assert_eq!(read("wal"), b"record");
+            }
+            match crash_info.label.as_str() {
+                "before_index_sync" => assert_eq!(read("index"), b""),
+                "after_index_sync" | "after_wal_append" => assert_eq!(read("index"), b"offset"),
+                _ => {}
+            }
+        })
+        .execute();
+}
@@ -0,0 +1,21 @@
+//! `max_crash_points()` stops the sweep after the first `n` crash points.
+
+#[test]
+fn sweep_stops_at_cap() {
+    first::test()
+        .max_crash_points(2)
+        .run(|env| {
+            for i in 0..5 {
+                env.write(format!("file_{}", i), "data").unwrap();
+                first::crash_point("after_write");
+            }
+        })
+        .verify(|env, crash_info| {
+            assert!(crash_info.point_id <= 2, "swept past the cap");
+            assert!(
+                env.path(format!("file_{}", crash_info.point_id - 1))
+                    .exists()
+            );
+        })
+        .execute();
+}
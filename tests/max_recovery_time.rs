@@ -0,0 +1,22 @@
+//! Recovery that stays within max_recovery_time passes.
+
+use std::fs;
+use std::time::Duration;
+
+#[test]
+fn recovery_within_limit() {
+    first::test()
+        .max_recovery_time(Duration::from_secs(5))
+        .run(|env| {
+            fs::write(env.path("log"), b"entry").unwrap();
+            first::crash_point("after_write");
+        })
+        .verify(|env, _crash_info| {
+            let log = {
+                let _timer = first::recovery_timer();
+                fs::read(env.path("log")).unwrap_or_default()
+            };
+            assert!(log.is_empty() || log == b"entry");
+        })
+        .execute();
+}
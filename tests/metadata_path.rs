@@ -0,0 +1,32 @@
+//! Scratch files that persist across crash-restart iterations.
+
+use std::fs::{self, OpenOptions};
+use std::io::Write;
+
+#[test]
+fn metadata_path_survives_iterations() {
+    first::test()
+        .run(|env| {
+            fs::write(env.path("data"), b"a").unwrap();
+            first::crash_point("first");
+            fs::write(env.path("data"), b"ab").unwrap();
+            first::crash_point("second");
+            fs::write(env.path("data"), b"abc").unwrap();
+            first::crash_point("third");
+        })
+        .verify(|env, crash_info| {
+            let notes_path = env.metadata_path("notes.txt");
+
+            // Every earlier verify run left exactly one line behind.
+            let previous = fs::read_to_string(&notes_path).unwrap_or_default();
+            assert_eq!(previous.lines().count(), crash_info.point_id - 1);
+
+            let mut notes = OpenOptions::new()
+                .create(true)
+                .append(true)
+                .open(&notes_path)
+                .unwrap();
+            writeln!(notes, "{}: ok", crash_info.label).unwrap();
+        })
+        .execute();
+}
@@ -0,0 +1,28 @@
+//! Pages of a mapped file that were never msynced are lost at the crash.
+
+#[test]
+fn unsynced_mmap_pages_are_lost() {
+    first::test()
+        .lose_unsynced_mmap()
+        .run(|env| {
+            let mut map = env.mmap("table", 8192).unwrap();
+            map[..6].copy_from_slice(b"synced");
+            map.msync(0..6).unwrap();
+            first::crash_point("after_msync");
+
+            map[4096..4103].copy_from_slice(b"pending");
+            first::crash_point("after_store");
+        })
+        .verify(|env, crash_info| {
+            let map = env.mmap("table", 8192).unwrap();
+            assert_eq!(&map[..6], b"synced");
+            assert!(map[4096..].iter().all(|&b| b == 0));
+
+            let barrier = &crash_info.barriers()[0];
+            assert_eq!(barrier.kind, first::BarrierKind::Msync);
+            assert_eq!(barrier.range.as_ref().map(|r| r.start), Some(0));
+            assert_eq!(barrier.file.as_deref(), Some("table".as_ref()));
+            assert_eq!(crash_info.fsync_count, 0);
+        })
+        .execute();
+}
@@ -0,0 +1,29 @@
+//! A log that syncs every record before acknowledging it leaves nothing
+//! past its last barrier at the acknowledgement points.
+
+use std::fs::OpenOptions;
+use std::io::Write;
+
+#[test]
+fn nothing_written_past_commit_barrier() {
+    first::test()
+        .run(|env| {
+            let mut log = OpenOptions::new()
+                .create(true)
+                .append(true)
+                .open(env.path("log"))
+                .unwrap();
+            for record in [b"one\n", b"two\n"] {
+                log.write_all(record).unwrap();
+                first::crash_point("before_sync");
+                env.fdatasync(&log).unwrap();
+                first::crash_point("acked");
+            }
+        })
+        .verify(|env, crash_info| {
+            if crash_info.label == "acked" {
+                first::assert_not_durable_before_barrier(env, &["log", "missing"]);
+            }
+        })
+        .execute();
+}
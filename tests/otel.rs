@@ -0,0 +1,83 @@
+//! With the `otel` feature, each crash point is exported as a span to the
+//! configured OTLP/HTTP endpoint.
+#![cfg(feature = "otel")]
+
+use std::fs;
+use std::io::{BufRead, BufReader, Read, Write};
+use std::net::TcpListener;
+use std::sync::mpsc;
+use std::time::Duration;
+
+/// Accept one OTLP request, answer 200 and send its body to the test.
+fn collector() -> (String, mpsc::Receiver<String>) {
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let endpoint = format!("http://{}", listener.local_addr().unwrap());
+    let (tx, rx) = mpsc::channel();
+    std::thread::spawn(move || {
+        let (stream, _) = listener.accept().unwrap();
+        let mut reader = BufReader::new(stream);
+        let mut request_line = String::new();
+        reader.read_line(&mut request_line).unwrap();
+        let mut len = 0;
+        loop {
+            let mut header = String::new();
+            reader.read_line(&mut header).unwrap();
+            if header.trim().is_empty() {
+                break;
+            }
+            if let Some(value) = header.to_lowercase().strip_prefix("content-length:") {
+                len = value.trim().parse().unwrap();
+            }
+        }
+        let mut body = vec![0; len];
+        reader.read_exact(&mut body).unwrap();
+        reader
+            .get_mut()
+            .write_all(b"HTTP/1.1 200 OK\r\nContent-Length: 0\r\n\r\n")
+            .unwrap();
+        let body = String::from_utf8(body).unwrap();
+        tx.send(format!("{}{}", request_line.trim(), body)).unwrap();
+    });
+    (endpoint, rx)
+}
+
+#[test]
+fn span_per_crash_point() {
+    let collected = first::is_orchestrator().then(|| {
+        let (endpoint, rx) = collector();
+        // SAFETY: the only test in this binary, set before any child runs
+        unsafe { std::env::set_var("OTEL_EXPORTER_OTLP_ENDPOINT", endpoint) };
+        rx
+    });
+
+    first::test()
+        .run_tag("otel-test")
+        .run(|env| {
+            fs::write(env.path("a"), b"1").unwrap();
+            first::crash_point("after_a");
+            fs::write(env.path("b"), b"2").unwrap();
+            first::crash_point("after_b");
+        })
+        .verify(|_env, _crash_info| {})
+        .execute();
+
+    let Some(rx) = collected else {
+        return;
+    };
+    let request = rx.recv_timeout(Duration::from_secs(10)).unwrap();
+    assert!(request.starts_with("POST /v1/traces HTTP/1.1{"));
+    assert_eq!(request.matches(r#""spanId""#).count(), 2);
+    for label in ["after_a", "after_b"] {
+        assert!(request.contains(&format!(
+            r#"{{"key":"first.crash_point.label","value":{{"stringValue":"{}"}}}}"#,
+            label
+        )));
+    }
+    assert_eq!(
+        request
+            .matches(r#"{"key":"first.result","value":{"stringValue":"ok"}}"#)
+            .count(),
+        2
+    );
+    assert!(request.contains(r#"{"key":"first.run_tag","value":{"stringValue":"otel-test"}}"#));
+}
@@ -0,0 +1,32 @@
+//! `Env::persist()` snapshots the crashed workspace under a stable path,
+//! named after the crash point.
+
+use std::fs;
+use std::path::Path;
+
+#[test]
+fn persist_copies_workspace() {
+    let dest = Path::new(env!("CARGO_TARGET_TMPDIR")).join("first-persist");
+    if first::is_orchestrator() {
+        let _ = fs::remove_dir_all(&dest);
+    }
+
+    first::test()
+        .run(|env| {
+            env.create_dir("db").unwrap();
+            env.write("db/wal", "PUT a").unwrap();
+            first::crash_point("after wal/write");
+        })
+        .verify(|env, _crash_info| {
+            let kept = env.persist(&dest).unwrap();
+            assert_eq!(kept, dest.join("point_1_after_wal_write"));
+        })
+        .execute();
+
+    if !first::is_orchestrator() {
+        return;
+    }
+    let kept = dest.join("point_1_after_wal_write");
+    assert_eq!(fs::read(kept.join("db/wal")).unwrap(), b"PUT a");
+    fs::remove_dir_all(&dest).unwrap();
+}
@@ -0,0 +1,32 @@
+//! Crash points in changed files are swept; the others are sampled.
+
+use std::fs;
+
+#[test]
+fn only_changed_points_swept_without_sample() {
+    if std::env::var_os("FIRST_CHANGED_FILES").is_none() {
+        // SAFETY: the only test in this binary, set before any child runs
+        unsafe { std::env::set_var("FIRST_CHANGED_FILES", "tests/prioritize_changed.rs") };
+    }
+
+    first::test()
+        .prioritize_changed(0)
+        .run(|env| {
+            fs::write(env.path("a"), b"1").unwrap();
+            first::crash_point("unsited");
+            fs::write(env.path("b"), b"2").unwrap();
+            first::crash_point!("after_b");
+            fs::write(env.path("c"), b"3").unwrap();
+            first::crash_point!("after_c");
+        })
+        .verify(|env, crash_info| {
+            // "unsited" has no call site, so it is never in a changed file
+            let expected = match crash_info.label.as_str() {
+                "after_b" => 2,
+                "after_c" => 3,
+                other => panic!("unchanged crash point {:?} was swept", other),
+            };
+            assert_eq!(fs::read_dir(env.path(".")).unwrap().count(), expected);
+        })
+        .execute();
+}
@@ -0,0 +1,29 @@
+//! A concurrent reader never sees a half-applied transfer.
+
+use std::fs;
+
+#[test]
+fn reader_sees_whole_transfers() {
+    first::test()
+        .run(|env| {
+            for moved in 0..=5u32 {
+                let state = format!("{} {}", 100 - moved * 10, moved * 10);
+                first::atomic_write(env, "accounts", state.as_bytes()).unwrap();
+                first::crash_point("after_transfer");
+            }
+        })
+        .reader(|env| {
+            // atomic_write replaces the file whole, so any version adds up
+            if let Ok(state) = fs::read_to_string(env.path("accounts")) {
+                let total: u32 = state.split(' ').map(|n| n.parse::<u32>().unwrap()).sum();
+                assert_eq!(total, 100, "torn read: {:?}", state);
+            }
+        })
+        .verify(|env, _crash_info| {
+            if let Ok(state) = fs::read_to_string(env.path("accounts")) {
+                let total: u32 = state.split(' ').map(|n| n.parse::<u32>().unwrap()).sum();
+                assert_eq!(total, 100);
+            }
+        })
+        .execute();
+}
@@ -0,0 +1,25 @@
+//! Recovered commits never decrease as the crash point moves later.
+
+use std::fs::OpenOptions;
+use std::io::Write;
+
+#[test]
+fn committed_count_is_monotonic() {
+    first::test()
+        .run(|env| {
+            let mut log = OpenOptions::new()
+                .create(true)
+                .append(true)
+                .open(env.path("log"))
+                .unwrap();
+            for _ in 0..3 {
+                log.write_all(b"commit\n").unwrap();
+                first::crash_point("after_commit");
+            }
+        })
+        .verify(|env, _crash_info| {
+            let log = std::fs::read_to_string(env.path("log")).unwrap_or_default();
+            first::invariants::record_committed(log.lines().count() as u64);
+        })
+        .execute();
+}
@@ -0,0 +1,50 @@
+//! Log replay that crashes midway and restarts ends in the same state as a
+//! single replay.
+
+use std::collections::BTreeMap;
+use std::fs::{self, File, OpenOptions};
+use std::io::Write;
+
+fn parse(text: &str) -> BTreeMap<String, String> {
+    text.lines()
+        .filter_map(|l| l.split_once('='))
+        .map(|(k, v)| (k.to_string(), v.to_string()))
+        .collect()
+}
+
+#[test]
+fn replay_is_idempotent() {
+    first::test()
+        .run(|env| {
+            let mut wal = OpenOptions::new()
+                .create(true)
+                .append(true)
+                .open(env.path("wal"))
+                .unwrap();
+            for record in ["a=1\n", "b=2\n", "a=3\n"] {
+                wal.write_all(record.as_bytes()).unwrap();
+                env.fsync(&wal).unwrap();
+                first::crash_point("after_append");
+            }
+        })
+        .recover_idempotent(8, |env| {
+            let wal = fs::read_to_string(env.path("wal")).unwrap_or_default();
+            if wal.is_empty() {
+                return;
+            }
+            let mut data = parse(&fs::read_to_string(env.path("data")).unwrap_or_default());
+            data.extend(parse(&wal));
+            let text: String = data.iter().map(|(k, v)| format!("{}={}\n", k, v)).collect();
+            first::atomic_write(env, "data", text.as_bytes()).unwrap();
+            first::crash_point("after_apply");
+
+            let wal = File::create(env.path("wal")).unwrap();
+            env.fsync(&wal).unwrap();
+            first::crash_point("after_truncate");
+        })
+        .verify(|env, _| {
+            let wal = fs::read_to_string(env.path("wal")).unwrap();
+            assert!(!parse(&wal).is_empty());
+        })
+        .execute();
+}
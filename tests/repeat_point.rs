@@ -0,0 +1,24 @@
+//! Repeated crashes at one point leave identical state.
+
+use std::fs::OpenOptions;
+use std::io::Write;
+
+#[test]
+fn repeated_runs_are_identical() {
+    first::test()
+        .repeat_point("after_commit", 3)
+        .run(|env| {
+            let mut log = OpenOptions::new()
+                .create(true)
+                .append(true)
+                .open(env.path("log"))
+                .unwrap();
+            for i in 0..3 {
+                log.write_all(format!("record {}\n", i).as_bytes()).unwrap();
+                env.fsync(&log).unwrap();
+                first::crash_point("after_commit");
+            }
+        })
+        .verify(|_env, _crash_info| unreachable!("repeat_point() does not verify"))
+        .execute();
+}
@@ -0,0 +1,26 @@
+//! A failure bundle is re-verified without running the workload.
+
+use first::DurabilityManifest;
+
+/// Big-endian packing of the bytes, as the bundled workload checksummed.
+fn checksum(data: &[u8]) -> u64 {
+    data.iter().fold(0, |sum, &b| (sum << 8) | u64::from(b))
+}
+
+#[test]
+fn bundled_failure_replays() {
+    first::test()
+        .replay_bundle("tests/data/replay_bundle")
+        .run(|_| panic!("the workload must not run"))
+        .verify(|env, crash_info| {
+            assert_eq!(
+                (crash_info.point_id, crash_info.label.as_str()),
+                (1, "after_commit")
+            );
+            assert_eq!(std::fs::read(env.path("wal")).unwrap(), b"v1");
+            let manifest = DurabilityManifest::open(env);
+            assert_eq!(manifest.committed("wal"), [checksum(b"v1")]);
+            manifest.assert_files(env, checksum);
+        })
+        .execute();
+}
@@ -0,0 +1,39 @@
+//! Replaying a multi-crash history from a script file.
+
+use std::fs::{self, OpenOptions};
+use std::io::Write;
+
+#[test]
+fn replay_script_crashes_in_sequence() {
+    first::test()
+        .replay_script(concat!(
+            env!("CARGO_MANIFEST_DIR"),
+            "/tests/data/double_crash.txt"
+        ))
+        .run(|env| {
+            // Each restart appends to whatever the previous crash left behind.
+            let mut log = OpenOptions::new()
+                .create(true)
+                .append(true)
+                .open(env.path("log"))
+                .unwrap();
+            log.write_all(b"A\n").unwrap();
+            first::crash_point("after_a");
+            log.write_all(b"B\n").unwrap();
+            first::crash_point("after_b");
+        })
+        .verify(|env, crash_info| {
+            let log = fs::read_to_string(env.path("log")).unwrap();
+            let records: Vec<_> = log.lines().collect();
+
+            // Step 1 crashes at "after_a" on an empty workspace; step 2
+            // restarts on top of it and crashes at "after_b".
+            let expected: &[&str] = match crash_info.label.as_str() {
+                "after_a" => &["A"],
+                "after_b" => &["A", "A", "B"],
+                label => panic!("unexpected crash label {}", label),
+            };
+            assert_eq!(records, expected);
+        })
+        .execute();
+}
@@ -0,0 +1,37 @@
+//! `report_json()` writes one JSON object listing every crash point of
+//! the sweep.
+
+use std::fs;
+
+#[test]
+fn report_lists_every_point() {
+    // Only the orchestrator writes the report, so a per-process path is fine
+    let path = std::env::temp_dir().join(format!("first_report_{}.json", std::process::id()));
+
+    first::test()
+        .seed(9)
+        .report_json(&path)
+        .run(|env| {
+            fs::write(env.path("a"), b"1").unwrap();
+            first::crash_point("after_a");
+            fs::write(env.path("b"), b"2").unwrap();
+            first::crash_point("after_b");
+        })
+        .verify(|_env, _crash_info| {})
+        .execute();
+
+    if !first::is_orchestrator() {
+        return;
+    }
+    let report = fs::read_to_string(&path).unwrap();
+    fs::remove_file(&path).unwrap();
+    assert!(report.starts_with(r#"{"version":1,"test":"#));
+    assert!(report.contains(r#""seed":9,"complete":true,"points":["#));
+    for (id, label) in [(1, "after_a"), (2, "after_b")] {
+        assert!(report.contains(&format!(
+            r#"{{"id":{},"label":"{}","result":"pass","reason":null,"work_dir":"/tmp/first/run_{}","duration_ms":"#,
+            id, label, id
+        )));
+    }
+    assert_eq!(report.matches(r#""id":"#).count(), 2);
+}
@@ -0,0 +1,23 @@
+//! A workload runs under resource limits, or without them where cgroups
+//! are unavailable.
+
+use std::fs;
+
+#[test]
+fn recovery_under_resource_limits() {
+    first::test()
+        .resource_limits(
+            first::ResourceLimits::new()
+                .memory_max(512 * 1024 * 1024)
+                .cpu_percent(100),
+        )
+        .run(|env| {
+            fs::write(env.path("data"), vec![7u8; 1 << 20]).unwrap();
+            first::crash_point("after_write");
+        })
+        .verify(|env, _| {
+            let data = fs::read(env.path("data")).unwrap();
+            assert!(data.iter().all(|&b| b == 7));
+        })
+        .execute();
+}
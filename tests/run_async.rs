@@ -0,0 +1,51 @@
+//! `run_async()` awaits an async workload, with crash points between
+//! `.await`s counted as in synchronous code.
+
+use std::future::Future;
+use std::pin::pin;
+use std::task::{Context, Poll, Waker};
+
+/// Drive `future` on this thread, like a current-thread runtime.
+fn block_on(future: impl Future<Output = ()>) {
+    let mut future = pin!(future);
+    let mut cx = Context::from_waker(Waker::noop());
+    while future.as_mut().poll(&mut cx).is_pending() {}
+}
+
+/// Yield to the executor once, like an I/O future that is not ready yet.
+async fn yield_now() {
+    let mut yielded = false;
+    std::future::poll_fn(|_| {
+        if yielded {
+            Poll::Ready(())
+        } else {
+            yielded = true;
+            Poll::Pending
+        }
+    })
+    .await
+}
+
+#[test]
+fn async_workload_crashes_between_awaits() {
+    block_on(
+        first::test()
+            .run_async(|env| async move {
+                for i in 1..=3 {
+                    env.write(format!("record_{}", i), b"ok").unwrap();
+                    yield_now().await;
+                    first::crash_point("after_record");
+                }
+            })
+            .verify(|env, crash_info| {
+                for i in 1..=crash_info.point_id {
+                    assert_eq!(env.read(format!("record_{}", i)).unwrap(), b"ok");
+                }
+                assert!(
+                    env.read(format!("record_{}", crash_info.point_id + 1))
+                        .is_err()
+                );
+            })
+            .execute_async(),
+    );
+}
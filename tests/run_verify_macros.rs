@@ -0,0 +1,18 @@
+//! `#[first::run]` and `#[first::verify]` on functions of the same name
+//! generate one test with that workload and verify.
+
+use std::fs;
+
+#[first::run]
+fn paired_functions(env: &first::Env) {
+    fs::write(env.path("a"), b"1").unwrap();
+    first::crash_point("after_a");
+    fs::write(env.path("b"), b"2").unwrap();
+    first::crash_point("after_b");
+}
+
+#[first::verify]
+fn paired_functions(env: &first::Env, crash: &first::CrashInfo) {
+    assert_eq!(fs::read(env.path("a")).unwrap(), b"1");
+    assert_eq!(env.path("b").exists(), crash.label == "after_b");
+}
@@ -0,0 +1,24 @@
+//! `sample()` sweeps a seeded random subset of the crash points.
+
+#[test]
+fn sweeps_seeded_sample() {
+    first::test()
+        .sample(3, 42)
+        .run(|env| {
+            for i in 0..20 {
+                env.write(format!("file_{}", i), "data").unwrap();
+                first::crash_point("after_write");
+            }
+        })
+        .verify(|env, crash_info| {
+            let count = std::fs::read_dir(env.path("")).unwrap().count();
+            assert_eq!(count, crash_info.point_id);
+            // Each sampled point is verified once, three in all
+            let seen = env.metadata_path("seen");
+            let mut log = std::fs::read_to_string(&seen).unwrap_or_default();
+            log.push_str(&format!("{}\n", crash_info.point_id));
+            std::fs::write(&seen, &log).unwrap();
+            assert!(log.lines().count() <= 3);
+        })
+        .execute();
+}
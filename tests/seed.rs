@@ -0,0 +1,22 @@
+//! The seed set with `seed()` is returned by `first::seed()` in the
+//! workload and in every verify.
+
+use std::fs;
+
+#[test]
+fn seed_reaches_every_child() {
+    first::test()
+        .seed(42)
+        .run(|env| {
+            assert_eq!(first::seed(), 42);
+            fs::write(env.path("seed"), first::seed().to_string()).unwrap();
+            first::crash_point("after_write");
+        })
+        .verify(|env, _crash_info| {
+            assert_eq!(first::seed(), 42);
+            if let Ok(written) = fs::read_to_string(env.path("seed")) {
+                assert_eq!(written, "42");
+            }
+        })
+        .execute();
+}
@@ -0,0 +1,31 @@
+//! With the `serde` feature, values checkpointed by the workload before
+//! the crash reach verify through `Env::restore()`.
+#![cfg(feature = "serde")]
+
+#[test]
+fn checkpointed_values_reach_verify() {
+    first::test()
+        .run(|env| {
+            let mut committed = Vec::new();
+            for txid in 1..=3u64 {
+                env.write(format!("tx_{}", txid), b"done").unwrap();
+                committed.push(txid);
+                env.checkpoint("committed", &committed).unwrap();
+                first::crash_point("after_commit");
+            }
+            env.checkpoint("finished", &true).unwrap();
+            first::crash_point("after_finish");
+        })
+        .verify(|env, crash_info| {
+            let committed: Vec<u64> = env.restore("committed").unwrap();
+            let expected: Vec<u64> = (1..=crash_info.point_id.min(3) as u64).collect();
+            assert_eq!(committed, expected);
+            for txid in committed {
+                assert_eq!(env.read(format!("tx_{}", txid)).unwrap(), b"done");
+            }
+            // Only what was checkpointed before the crash is restored
+            let finished: Option<bool> = env.restore("finished");
+            assert_eq!(finished.is_some(), crash_info.label == "after_finish");
+        })
+        .execute();
+}
@@ -0,0 +1,19 @@
+//! With the `serde` feature, crash metadata survives labels that break a
+//! hand-rolled JSON parser.
+#![cfg(feature = "serde")]
+
+#[test]
+fn label_with_quotes_and_braces() {
+    const LABEL: &str = r#"commit "a,{b}" \ done"#;
+
+    first::test()
+        .run(|env| {
+            env.write("log", b"a").unwrap();
+            first::crash_point(LABEL);
+        })
+        .verify(|_env, crash_info| {
+            assert_eq!(crash_info.label, LABEL);
+            assert_eq!(crash_info.history, [1]);
+        })
+        .execute();
+}
@@ -0,0 +1,27 @@
+//! Crash points can be swept in a seeded shuffled order.
+
+use std::fs;
+
+#[test]
+fn shuffled_sweep_passes() {
+    first::test()
+        .shuffle(7)
+        .run(|env| {
+            fs::write(env.path("a"), b"1").unwrap();
+            first::crash_point("after_a");
+            fs::write(env.path("b"), b"2").unwrap();
+            first::crash_point("after_b");
+            fs::write(env.path("c"), b"3").unwrap();
+            first::crash_point("after_c");
+        })
+        .verify(|env, crash_info| {
+            // Each point starts from a fresh workspace, whatever ran before
+            let expected = match crash_info.label.as_str() {
+                "after_a" => 1,
+                "after_b" => 2,
+                _ => 3,
+            };
+            assert_eq!(fs::read_dir(env.path(".")).unwrap().count(), expected);
+        })
+        .execute();
+}
@@ -0,0 +1,54 @@
+//! With `snapshot_after()`, crash points after the snapshot start from a
+//! copy of the workspace instead of re-running the workload's setup.
+
+use std::fs::{self, OpenOptions};
+use std::io::Write;
+use std::path::Path;
+
+#[test]
+fn later_points_skip_setup() {
+    let setups = Path::new(env!("CARGO_TARGET_TMPDIR")).join("first_snapshot_after_setups");
+    if first::is_orchestrator() {
+        let _ = fs::remove_file(&setups);
+    }
+
+    let setups_log = setups.clone();
+    first::test()
+        .snapshot_after("loaded")
+        .run(move |env| {
+            if !env.is_resumed() {
+                let mut log = OpenOptions::new()
+                    .create(true)
+                    .append(true)
+                    .open(&setups_log)
+                    .unwrap();
+                writeln!(log, "setup").unwrap();
+                env.write("dataset", "0123456789").unwrap();
+                first::crash_point("loaded");
+            }
+            let dataset = env.read("dataset").unwrap();
+            for i in 0..3 {
+                env.write(format!("derived_{}", i), &dataset[..=i]).unwrap();
+                first::crash_point("derived");
+            }
+        })
+        .verify(|env, crash_info| {
+            assert_eq!(env.read("dataset").unwrap(), b"0123456789");
+            for i in 0..3 {
+                let derived = env.read(format!("derived_{}", i));
+                if i + 2 <= crash_info.point_id {
+                    assert_eq!(derived.unwrap(), &b"0123456789"[..=i]);
+                } else {
+                    assert!(derived.is_err());
+                }
+            }
+        })
+        .execute();
+
+    if !first::is_orchestrator() {
+        return;
+    }
+    // Crash point 1 and the run that took the snapshot; the rest resumed
+    let setups = fs::read_to_string(&setups).unwrap();
+    assert_eq!(setups.lines().count(), 2);
+}
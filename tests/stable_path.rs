@@ -0,0 +1,23 @@
+//! Engines that embed absolute paths see the same workspace path every time.
+
+use std::fs;
+
+#[test]
+fn stable_path_is_identical_across_phases() {
+    first::test()
+        .stable_path("stable_path_test")
+        .run(|env| {
+            // The engine records its own absolute location on disk.
+            let path = env.path("data");
+            fs::write(&path, path.to_string_lossy().as_bytes()).unwrap();
+            first::crash_point("after_write");
+            fs::write(env.path("more"), b"x").unwrap();
+            first::crash_point("after_more");
+        })
+        .verify(|env, _crash_info| {
+            let recorded = fs::read_to_string(env.path("data")).unwrap();
+            assert_eq!(recorded, env.path("data").to_string_lossy());
+            assert!(recorded.contains("first-stable-stable_path_test"));
+        })
+        .execute();
+}
@@ -0,0 +1,34 @@
+//! A catalog shared by crash tests is checked once the binary's tests are
+//! done.
+
+use std::fs::{self, OpenOptions};
+use std::io::Write;
+
+#[test]
+fn catalog_is_consistent_after_suite() {
+    let catalog = std::env::temp_dir().join("first_suite_catalog");
+    let checked = catalog.clone();
+    first::suite_invariant(&catalog, move || {
+        let entries = fs::read_to_string(&checked).unwrap();
+        assert!(entries.lines().all(|l| l.starts_with("table ")));
+        fs::remove_file(&checked).unwrap();
+    });
+
+    first::test()
+        .run(|env| {
+            fs::write(env.path("t1"), b"rows").unwrap();
+            first::crash_point("after_create");
+        })
+        .verify(|env, _| {
+            let rows = fs::read(env.path("t1")).unwrap_or_default();
+            assert!(rows.is_empty() || rows == b"rows");
+        })
+        .execute();
+
+    let mut entries = OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(&catalog)
+        .unwrap();
+    writeln!(entries, "table t1").unwrap();
+}
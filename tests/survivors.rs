@@ -0,0 +1,27 @@
+//! `Env::survivors()` lists the files left after each crash, without
+//! following a symlink out of the workspace.
+
+use std::fs;
+use std::path::PathBuf;
+
+#[test]
+fn survivors_of_each_crash() {
+    first::test()
+        .run(|env| {
+            fs::create_dir(env.path("segments")).unwrap();
+            fs::write(env.path("segments/1"), b"a").unwrap();
+            first::crash_point("after_segment_1");
+            std::os::unix::fs::symlink("/tmp", env.path("outside")).unwrap();
+            fs::write(env.path("segments/2"), b"b").unwrap();
+            first::crash_point("after_segment_2");
+        })
+        .verify(|env, crash_info| {
+            let expected: &[&str] = match crash_info.label.as_str() {
+                "after_segment_1" => &["segments/1"],
+                _ => &["outside", "segments/1", "segments/2"],
+            };
+            let expected: Vec<PathBuf> = expected.iter().map(PathBuf::from).collect();
+            assert_eq!(env.survivors().unwrap(), expected);
+        })
+        .execute();
+}
@@ -0,0 +1,34 @@
+//! With `FIRST_CRASH_LABEL_OCCURRENCE`, label targeting crashes at the nth
+//! crash point with each label instead of the first.
+
+use std::fs;
+
+#[test]
+fn crashes_at_second_occurrence() {
+    if first::is_orchestrator() {
+        // SAFETY: the only test in this binary, set before any child runs
+        unsafe { std::env::set_var("FIRST_CRASH_LABEL_OCCURRENCE", "2") };
+    }
+
+    first::test()
+        .target_labels()
+        .run(|env| {
+            for i in 0..3 {
+                fs::write(env.path(format!("put_{}", i)), "v").unwrap();
+                first::crash_point("after_put");
+                fs::write(env.path(format!("del_{}", i)), "v").unwrap();
+                first::crash_point("after_delete");
+            }
+        })
+        .verify(|env, crash_info| {
+            let expected_id = match crash_info.label.as_str() {
+                "after_put" => 3,
+                "after_delete" => 4,
+                other => panic!("unexpected crash point {:?}", other),
+            };
+            assert_eq!(crash_info.point_id, expected_id);
+            assert!(env.path("put_1").exists());
+            assert!(!env.path("put_2").exists());
+        })
+        .execute();
+}
@@ -0,0 +1,44 @@
+//! Crash points can be targeted by call site instead of by counter.
+
+use std::fs;
+
+fn append(env: &first::Env, line: &str) {
+    let mut log = fs::read_to_string(env.path("log")).unwrap_or_default();
+    log.push_str(line);
+    fs::write(env.path("log"), log).unwrap();
+}
+
+#[test]
+fn sweep_visits_each_call_site_once() {
+    first::test()
+        .target_sites()
+        .run(|env| {
+            for i in 0..3 {
+                append(env, &format!("{}\n", i));
+                // Hit three times, but swept once: at its first hit.
+                first::crash_point!("after_append");
+            }
+            // Counter-only points are never targeted in site mode.
+            first::crash_point("unsited");
+            append(env, "done\n");
+            first::crash_point!("after_done");
+        })
+        .verify(|env, crash_info| {
+            let (file, _line) = crash_info.site.expect("site-targeted crash");
+            assert!(file.ends_with("target_sites.rs"));
+
+            let log = fs::read_to_string(env.path("log")).unwrap();
+            match crash_info.label.as_str() {
+                "after_append" => {
+                    assert_eq!(crash_info.point_id, 1);
+                    assert_eq!(log, "0\n");
+                }
+                "after_done" => {
+                    assert_eq!(crash_info.point_id, 5);
+                    assert!(log.ends_with("done\n"));
+                }
+                other => panic!("unexpected crash point {:?}", other),
+            }
+        })
+        .execute();
+}
@@ -0,0 +1,19 @@
+//! Children are filtered on the name given with `test_name()`, here a test
+//! in a nested module.
+
+mod nested {
+    #[test]
+    fn explicit_test_name() {
+        first::test()
+            .test_name("nested::explicit_test_name")
+            .run(|env| {
+                env.write("data", "v1").unwrap();
+                first::crash_point("after_write");
+            })
+            .verify(|env, crash_info| {
+                assert_eq!(crash_info.label, "after_write");
+                assert!(env.path("data").exists());
+            })
+            .execute();
+    }
+}
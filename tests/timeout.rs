@@ -0,0 +1,26 @@
+//! With `timeout()`, a verify that hangs is killed and its crash point
+//! fails instead of hanging the test.
+
+use std::time::Duration;
+
+#[test]
+fn timeout_kills_hung_verify() {
+    first::test()
+        .timeout(Duration::from_millis(500))
+        .expect_violation()
+        .run(|env| {
+            env.write("state", "before").unwrap();
+            first::crash_point("before_lock");
+            env.write("state", "locked").unwrap();
+            first::crash_point("while_locked");
+        })
+        .verify(|env, _crash_info| {
+            // Seeded bug: recovery waits forever on a lock left held
+            if env.read("state").unwrap() == b"locked" {
+                loop {
+                    std::thread::sleep(Duration::from_secs(1));
+                }
+            }
+        })
+        .execute();
+}
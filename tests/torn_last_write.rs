@@ -0,0 +1,38 @@
+//! With `torn_last_write()`, the last unsynced write logged through
+//! `Env::write_all()` keeps only its first half after the crash.
+
+use std::fs::{self, OpenOptions};
+
+#[test]
+fn last_append_is_torn() {
+    first::test()
+        .torn_last_write()
+        .run(|env| {
+            let mut log = OpenOptions::new()
+                .create(true)
+                .append(true)
+                .open(env.path("log"))
+                .unwrap();
+            env.write_all(&mut log, b"RECORD1\n").unwrap();
+            env.write_all(&mut log, b"RECORD2\n").unwrap();
+            first::crash_point("after_record2");
+            env.fsync(&log).unwrap();
+            first::crash_point("after_sync");
+        })
+        .verify(|env, crash_info| {
+            let log = fs::read_to_string(env.path("log")).unwrap();
+            match crash_info.label.as_str() {
+                "after_record2" => {
+                    assert_eq!(log, "RECORD1\nRECO");
+                    let torn = crash_info.partial_write.as_ref().expect("torn write");
+                    assert_eq!(torn.file, std::path::Path::new("log"));
+                    assert_eq!((torn.offset, torn.written, torn.len), (8, 4, 8));
+                }
+                _ => {
+                    assert_eq!(log, "RECORD1\nRECORD2\n");
+                    assert!(crash_info.partial_write.is_none());
+                }
+            }
+        })
+        .execute();
+}
@@ -0,0 +1,29 @@
+//! File descriptor tracking across the crash sweep.
+
+use std::fs::File;
+
+#[test]
+fn track_fds_reports_open_descriptors() {
+    first::test()
+        .track_fds()
+        .run(|env| {
+            let _a = File::create(env.path("a")).unwrap();
+            first::crash_point("one_file_open");
+            let _b = File::create(env.path("b")).unwrap();
+            first::crash_point("two_files_open");
+        })
+        .verify(|_env, crash_info| {
+            let fds = crash_info
+                .max_fds
+                .expect("max_fds must be reported when track_fds() is set");
+
+            // stdin/stdout/stderr plus at least one workload file per point.
+            assert!(
+                fds > 3 + crash_info.point_id - 1,
+                "expected more descriptors at '{}', got {}",
+                crash_info.label,
+                fds
+            );
+        })
+        .execute();
+}
@@ -0,0 +1,46 @@
+//! Recovery of a length-prefixed record log is the same at every read
+//! chunk size.
+
+use std::fs::OpenOptions;
+use std::io::{self, Read, Write};
+
+/// Read records until a torn or missing one, handling short reads.
+fn recover(mut input: impl Read) -> Vec<Vec<u8>> {
+    let mut records = Vec::new();
+    loop {
+        let mut len = [0u8; 4];
+        if input.read_exact(&mut len).is_err() {
+            return records;
+        }
+        let mut record = vec![0u8; u32::from_le_bytes(len) as usize];
+        match input.read_exact(&mut record) {
+            Ok(()) => records.push(record),
+            Err(e) if e.kind() == io::ErrorKind::UnexpectedEof => return records,
+            Err(e) => panic!("read failed: {}", e),
+        }
+    }
+}
+
+#[test]
+fn recovery_is_independent_of_read_chunking() {
+    first::test()
+        .run(|env| {
+            let mut log = OpenOptions::new()
+                .create(true)
+                .append(true)
+                .open(env.path("log"))
+                .unwrap();
+            for record in ["first", "second record", "3"] {
+                log.write_all(&(record.len() as u32).to_le_bytes()).unwrap();
+                log.write_all(record.as_bytes()).unwrap();
+                env.fsync(&log).unwrap();
+                first::crash_point("after_append");
+            }
+        })
+        .vary_read_chunking([1, 3, 7])
+        .verify(|env, crash_info| {
+            let records = recover(env.open_chunked("log").unwrap());
+            assert_eq!(records.len(), crash_info.point_id);
+        })
+        .execute();
+}
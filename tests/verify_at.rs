@@ -0,0 +1,26 @@
+//! Point-specific verify closures run alongside or instead of verify.
+
+use std::fs;
+
+#[test]
+fn verify_at_selects_by_label_and_id() {
+    first::test()
+        .run(|env| {
+            fs::write(env.path("state"), b"written").unwrap();
+            first::crash_point("after_write");
+            fs::remove_file(env.path("state")).unwrap();
+            first::crash_point("after_remove");
+        })
+        .verify(|env, crash_info| {
+            assert_eq!(crash_info.label, "after_write");
+            assert!(env.path("state").exists());
+        })
+        .verify_at("after_write", |env, _crash_info| {
+            assert_eq!(fs::read(env.path("state")).unwrap(), b"written");
+        })
+        .verify_only_at(2, |env, crash_info| {
+            assert_eq!(crash_info.label, "after_remove");
+            assert!(!env.path("state").exists());
+        })
+        .execute();
+}
@@ -0,0 +1,30 @@
+//! With `verify_in_process()`, verify runs in the orchestrator, once per
+//! crash point, and its global state carries over between points.
+
+use std::fs;
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+static VERIFIED: AtomicUsize = AtomicUsize::new(0);
+
+#[test]
+fn verify_runs_in_orchestrator() {
+    first::test()
+        .run(|env| {
+            env.write("a", b"1").unwrap();
+            first::crash_point("after_a");
+            env.write("b", b"2").unwrap();
+            first::crash_point("after_b");
+        })
+        .verify_in_process(|env, crash_info| {
+            assert!(first::is_orchestrator());
+            // Not isolated: the counter saw every earlier point
+            let earlier = VERIFIED.fetch_add(1, Ordering::SeqCst);
+            assert_eq!(earlier + 1, crash_info.point_id);
+
+            assert_eq!(fs::read(env.path("a")).unwrap(), b"1");
+            if crash_info.label == "after_b" {
+                assert_eq!(env.read("b").unwrap(), b"2");
+            }
+        })
+        .execute();
+}
@@ -0,0 +1,25 @@
+//! `memory_checkpoint()` in a `verify_in_process()` closure loads the
+//! checkpoint taken before the crash being verified.
+
+#[test]
+fn memory_checkpoint_reaches_in_process_verify() {
+    first::test()
+        .run(|env| {
+            env.write("k1", b"v1").unwrap();
+            first::checkpoint_memory("k1");
+            first::crash_point("after_k1");
+            env.write("k2", b"v2").unwrap();
+            first::checkpoint_memory("k1,k2");
+            first::crash_point("after_k2");
+        })
+        .verify_in_process(|env, crash_info| {
+            assert!(first::is_orchestrator());
+            let checkpoint =
+                first::memory_checkpoint(env).expect("checkpoint taken before the crash");
+            match crash_info.label.as_str() {
+                "after_k1" => checkpoint.assert_matches("k1"),
+                _ => checkpoint.assert_matches("k1,k2"),
+            }
+        })
+        .execute();
+}
@@ -0,0 +1,25 @@
+//! `Env::expected()` in a `verify_in_process()` closure sees the keys the
+//! workload recorded before the crash being verified.
+
+#[test]
+fn expected_keys_reach_in_process_verify() {
+    first::test()
+        .run(|env| {
+            env.write("k1", b"v1").unwrap();
+            env.expect(&["k1"]).unwrap();
+            first::crash_point("after_k1");
+            env.write("k2", b"v2").unwrap();
+            env.expect(&["k2"]).unwrap();
+            first::crash_point("after_k2");
+        })
+        .verify_in_process(|env, crash_info| {
+            assert!(first::is_orchestrator());
+            let expected = env.expected();
+            match crash_info.label.as_str() {
+                "after_k1" => assert_eq!(expected.committed, ["k1"]),
+                _ => assert_eq!(expected.committed, ["k1", "k2"]),
+            }
+            expected.assert_present(|key| env.path(key).exists());
+        })
+        .execute();
+}
@@ -0,0 +1,27 @@
+//! A failing verify leaves its output in `verify.log` in its work dir.
+
+use std::fs;
+
+#[test]
+fn verify_log_keeps_failing_output() {
+    first::test()
+        .expect_violation()
+        .run(|env| {
+            env.write("state", "ok").unwrap();
+            first::crash_point("ok");
+            env.write("state", "bad").unwrap();
+            first::crash_point("bad");
+        })
+        .verify(|env, crash_info| {
+            println!("recovering from {}", crash_info.label);
+            assert_eq!(env.read("state").unwrap(), b"ok", "seeded failure");
+        })
+        .execute();
+
+    if !first::is_orchestrator() {
+        return;
+    }
+    let log = fs::read_to_string("/tmp/first/run_2/verify.log").unwrap();
+    assert!(log.contains("recovering from bad"), "{}", log);
+    assert!(log.contains("seeded failure"), "{}", log);
+}
@@ -0,0 +1,30 @@
+//! Workspaces are created with the configured mode and subdirectory layout.
+
+use std::fs;
+use std::os::unix::fs::PermissionsExt;
+
+#[test]
+fn workspace_layout_exists_in_every_phase() {
+    first::test()
+        .workspace_mode(0o750)
+        .workspace_dirs(&["data", "log", "tmp"])
+        .run(|env| {
+            let mode = fs::metadata(env.path("")).unwrap().permissions().mode();
+            assert_eq!(mode & 0o777, 0o750);
+
+            // The engine writes straight into its expected subdirectories.
+            fs::write(env.path("data/table"), b"rows").unwrap();
+            first::crash_point("after_data");
+            fs::write(env.path("log/wal"), b"entry").unwrap();
+            first::crash_point("after_log");
+        })
+        .verify(|env, _crash_info| {
+            for dir in ["data", "log", "tmp"] {
+                let meta = fs::metadata(env.path(dir)).unwrap();
+                assert!(meta.is_dir());
+                assert_eq!(meta.permissions().mode() & 0o777, 0o750);
+            }
+            assert_eq!(fs::read(env.path("data/table")).unwrap(), b"rows");
+        })
+        .execute();
+}
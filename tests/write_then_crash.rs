@@ -0,0 +1,32 @@
+//! Crashing in the middle of a write leaves a genuinely partial record.
+
+use std::fs;
+
+#[test]
+fn write_then_crash_leaves_partial_record() {
+    first::test()
+        .run(|env| {
+            env.write_then_crash("wal.log", b"RECORD1\n", 4).unwrap();
+            env.write_then_crash("wal.log", b"RECORD2\n", 4).unwrap();
+        })
+        .verify(|env, crash_info| {
+            let contents = fs::read(env.path("wal.log")).unwrap();
+            let partial = crash_info
+                .partial_write
+                .as_ref()
+                .expect("write_then_crash must report the partial write");
+
+            assert_eq!(partial.file, std::path::Path::new("wal.log"));
+            assert_eq!(partial.written, 4);
+            assert_eq!(partial.len, 8);
+            assert_eq!(contents.len() as u64, partial.offset + 4);
+
+            let expected: &[u8] = match crash_info.point_id {
+                1 => b"RECO",
+                2 => b"RECORD1\nRECO",
+                id => panic!("unexpected crash point {}", id),
+            };
+            assert_eq!(contents, expected);
+        })
+        .execute();
+}